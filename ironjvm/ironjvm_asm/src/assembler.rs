@@ -0,0 +1,1363 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use std::collections::HashMap;
+use std::slice::Iter;
+
+use ironjvm_cfparser::code::CodeParser;
+use ironjvm_cfparser::frames;
+use ironjvm_cfparser::frames::FrameMethod;
+use ironjvm_cfparser::frames::SuperclassResolver;
+use ironjvm_cfparser::writer;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::descriptor::method::MethodDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::error::AsmError;
+use crate::error::AsmResult;
+use crate::keywords;
+use crate::opcodes;
+use crate::opcodes::Operands;
+use crate::pool;
+use crate::pool::Constant;
+use crate::pool::ConstantPool;
+use crate::token;
+use crate::token::Cursor;
+use crate::token::Line;
+use crate::token::Token;
+
+/// Assembles the source of one class, merging reference types in frames to `java/lang/Object`.
+pub fn assemble(source: &str) -> AsmResult<Vec<u8>> {
+    assemble_with(source, &ObjectResolver)
+}
+
+/// Assembles the source of one class, merging reference types in frames with `resolver`.
+pub fn assemble_with<R: SuperclassResolver>(source: &str, resolver: &R) -> AsmResult<Vec<u8>> {
+    let lines = token::tokenize(source)?;
+
+    let mut assembler = Assembler::default();
+    let mut lines = lines.iter();
+    while let Some(line) = lines.next() {
+        assembler.statement(line, &mut lines)?;
+    }
+
+    let mut class = assembler.finish()?;
+    class.compute_frames(resolver)?;
+
+    Ok(class.encode())
+}
+
+/// Merges any two classes to `java/lang/Object`, as the assembler cannot load the classes a source
+/// refers to.
+pub struct ObjectResolver;
+
+impl SuperclassResolver for ObjectResolver {
+    fn common_superclass(&self, _: &JStr, _: &JStr) -> JString {
+        JString::from("java/lang/Object")
+    }
+}
+
+struct Attribute {
+    name_index: u16,
+    info: Vec<u8>,
+}
+
+impl Attribute {
+    fn new(pool: &mut ConstantPool, name: &str, info: Vec<u8>) -> AsmResult<Self> {
+        Ok(Self {
+            name_index: pool.utf8(name)?,
+            info,
+        })
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.name_index.to_be_bytes());
+        output.extend_from_slice(&(self.info.len() as u32).to_be_bytes());
+        output.extend_from_slice(&self.info);
+    }
+}
+
+struct Code {
+    name_index: u16,
+    max_stack: Option<u16>,
+    max_locals: Option<u16>,
+    bytes: Vec<u8>,
+    exception_table: Vec<[u16; 4]>,
+    attributes: Vec<Attribute>,
+    // whether the source gives its own StackMapTable
+    has_stack_map_table: bool,
+}
+
+struct Member {
+    // for diagnostics
+    name: String,
+    access_flags: u16,
+    name_index: u16,
+    descriptor_index: u16,
+    code: Option<Code>,
+    attributes: Vec<Attribute>,
+}
+
+impl Member {
+    fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.access_flags.to_be_bytes());
+        output.extend_from_slice(&self.name_index.to_be_bytes());
+        output.extend_from_slice(&self.descriptor_index.to_be_bytes());
+
+        let attributes_count = self.attributes.len() + self.code.is_some() as usize;
+        output.extend_from_slice(&(attributes_count as u16).to_be_bytes());
+
+        if let Some(code) = &self.code {
+            let mut info = Vec::new();
+            info.extend_from_slice(&code.max_stack.unwrap_or_default().to_be_bytes());
+            info.extend_from_slice(&code.max_locals.unwrap_or_default().to_be_bytes());
+            info.extend_from_slice(&(code.bytes.len() as u32).to_be_bytes());
+            info.extend_from_slice(&code.bytes);
+
+            info.extend_from_slice(&(code.exception_table.len() as u16).to_be_bytes());
+            for entry in code.exception_table.iter().flatten() {
+                info.extend_from_slice(&entry.to_be_bytes());
+            }
+
+            info.extend_from_slice(&(code.attributes.len() as u16).to_be_bytes());
+            for attribute in &code.attributes {
+                attribute.encode(&mut info);
+            }
+
+            Attribute {
+                name_index: code.name_index,
+                info,
+            }
+            .encode(output);
+        }
+
+        for attribute in &self.attributes {
+            attribute.encode(output);
+        }
+    }
+}
+
+struct Class {
+    pool: ConstantPool,
+    minor_version: u16,
+    major_version: u16,
+    access_flags: u16,
+    this_class: u16,
+    super_class: u16,
+    interfaces: Vec<u16>,
+    fields: Vec<Member>,
+    methods: Vec<Member>,
+    attributes: Vec<Attribute>,
+}
+
+impl Class {
+    // fills in the limits the source leaves out and adds a StackMapTable to methods without one,
+    // by analysing the class as assembled so far; methods with explicit limits keep assembling
+    // if their code cannot be analysed, so that malformed code can still be written
+    fn compute_frames<R: SuperclassResolver>(&mut self, resolver: &R) -> AsmResult<()> {
+        let needs_limits = self.methods.iter().any(|method| {
+            matches!(&method.code, Some(code) if code.max_stack.is_none() || code.max_locals.is_none())
+        });
+        let needs_frames =
+            self.major_version >= 50 && self.methods.iter().any(|method| method.code.is_some());
+        if !needs_limits && !needs_frames {
+            return Ok(());
+        }
+
+        let bytes = self.encode();
+        let classfile = match ClassFileParser::new(&bytes).parse() {
+            Ok(classfile) => classfile,
+            Err(_) if !needs_limits => return Ok(()),
+            Err(error) => return Err(AsmError::Parse(error)),
+        };
+
+        let constant_pool = &classfile.constant_pool;
+        // the assembler wrote these constants itself
+        let this_class = match constant_pool[self.this_class as usize - 1].info {
+            CpInfoType::ConstantClass { name_index } => utf8(constant_pool, name_index),
+            _ => unreachable!(),
+        };
+
+        for (member, method) in self.methods.iter_mut().zip(&classfile.methods) {
+            let Some(code) = &mut member.code else {
+                continue;
+            };
+            let Some((bytes, exception_table)) =
+                method
+                    .attributes
+                    .iter()
+                    .find_map(|attribute| match &attribute.info {
+                        AttributeInfoType::CodeAttribute {
+                            code,
+                            exception_table,
+                            ..
+                        } => Some((code, exception_table)),
+                        _ => None,
+                    })
+            else {
+                continue;
+            };
+
+            let has_limits = code.max_stack.is_some() && code.max_locals.is_some();
+            let frame_method = FrameMethod {
+                constant_pool,
+                this_class,
+                access_flags: member.access_flags,
+                name: utf8(constant_pool, member.name_index),
+                descriptor: utf8(constant_pool, member.descriptor_index),
+            };
+
+            let pool = &mut self.pool;
+            let mut pool_error = None;
+            let computed = CodeParser::new(bytes)
+                .parse()
+                .map_err(AsmError::Parse)
+                .and_then(|instructions| {
+                    frames::compute_frames(
+                        &frame_method,
+                        &instructions,
+                        exception_table,
+                        resolver,
+                        |name| match pool
+                            .jutf8(name)
+                            .and_then(|name_index| pool.add(Constant::Class(name_index)))
+                        {
+                            Ok(index) => index,
+                            Err(error) => {
+                                pool_error = Some(error);
+                                0
+                            }
+                        },
+                    )
+                    .map_err(|error| AsmError::Frames {
+                        method: member.name.clone(),
+                        error,
+                    })
+                });
+            if let Some(error) = pool_error {
+                return Err(error);
+            }
+
+            let computed = match computed {
+                Ok(computed) => computed,
+                Err(_) if has_limits => continue,
+                Err(error) => return Err(error),
+            };
+
+            code.max_stack.get_or_insert(computed.max_stack);
+            code.max_locals.get_or_insert(computed.max_locals);
+
+            if self.major_version >= 50
+                && !code.has_stack_map_table
+                && !computed.stack_map_table.is_empty()
+            {
+                code.exception_table = frames::replace_unreachable(
+                    &mut code.bytes,
+                    exception_table,
+                    &computed.unreachable,
+                )
+                .iter()
+                .map(|handler| {
+                    [
+                        handler.start_pc,
+                        handler.end_pc,
+                        handler.handler_pc,
+                        handler.catch_type,
+                    ]
+                })
+                .collect();
+
+                let mut info = Vec::new();
+                writer::encode_stack_map_table(&computed.stack_map_table, &mut info);
+                code.attributes
+                    .push(Attribute::new(&mut self.pool, "StackMapTable", info)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut output = Vec::new();
+        output.extend_from_slice(&0xCAFEBABE_u32.to_be_bytes());
+        output.extend_from_slice(&self.minor_version.to_be_bytes());
+        output.extend_from_slice(&self.major_version.to_be_bytes());
+        self.pool.encode(&mut output);
+        output.extend_from_slice(&self.access_flags.to_be_bytes());
+        output.extend_from_slice(&self.this_class.to_be_bytes());
+        output.extend_from_slice(&self.super_class.to_be_bytes());
+
+        output.extend_from_slice(&(self.interfaces.len() as u16).to_be_bytes());
+        for interface in &self.interfaces {
+            output.extend_from_slice(&interface.to_be_bytes());
+        }
+
+        for members in [&self.fields, &self.methods] {
+            output.extend_from_slice(&(members.len() as u16).to_be_bytes());
+            for member in members {
+                member.encode(&mut output);
+            }
+        }
+
+        output.extend_from_slice(&(self.attributes.len() as u16).to_be_bytes());
+        for attribute in &self.attributes {
+            attribute.encode(&mut output);
+        }
+
+        output
+    }
+}
+
+#[derive(Default)]
+struct Assembler {
+    pool: ConstantPool,
+    // the major and minor version
+    version: Option<(u16, u16)>,
+    // the access flags, index and name of this class
+    class: Option<(u16, u16, String)>,
+    super_class: Option<u16>,
+    interfaces: Vec<u16>,
+    fields: Vec<Member>,
+    methods: Vec<Member>,
+    attributes: Vec<Attribute>,
+    bootstrap_methods: Vec<(u16, Vec<u16>)>,
+    // whether attribute directives outside a method belong to the last field
+    in_field: bool,
+    method: Option<MethodAssembler>,
+}
+
+impl Assembler {
+    fn statement<'a>(&mut self, line: &'a Line, lines: &mut Iter<'a, Line>) -> AsmResult<()> {
+        let mut cursor = Cursor::new(&line.tokens, line.number);
+        let first = cursor
+            .word()
+            .map_err(|_| AsmError::UnknownInstruction { line: line.number })?;
+        if let Some(directive) = first.strip_prefix('.') {
+            return self.directive(directive, cursor);
+        }
+
+        let Self {
+            pool,
+            bootstrap_methods,
+            method,
+            ..
+        } = self;
+        let method = method
+            .as_mut()
+            .ok_or(AsmError::UnexpectedInstruction { line: line.number })?;
+
+        // a label may be followed by an instruction on the same line
+        let mnemonic = match first.strip_suffix(':') {
+            Some(label) => {
+                method.label(label, line.number)?;
+                if cursor.is_empty() {
+                    return Ok(());
+                }
+
+                cursor.word()?
+            }
+            None => first,
+        };
+
+        method.instruction(mnemonic, cursor, pool, bootstrap_methods, lines)
+    }
+
+    fn directive(&mut self, directive: &str, mut cursor: Cursor) -> AsmResult<()> {
+        let line = cursor.line;
+
+        match directive {
+            "version" => {
+                self.outside_method(line)?;
+                let major_version = cursor.number()?;
+                let minor_version = if cursor.is_empty() {
+                    0
+                } else {
+                    cursor.number()?
+                };
+
+                self.version = Some((major_version, minor_version));
+            }
+            "constant" => {
+                self.outside_method(line)?;
+                if cursor.number::<u16>()? != self.pool.next_index() {
+                    return Err(AsmError::InvalidConstant { line });
+                }
+
+                let constant = pool_entry(&mut cursor)?;
+                self.pool.push(constant)?;
+            }
+            "class" => {
+                self.outside_method(line)?;
+                let access_flags = flags(&keywords::CLASS_FLAGS, &mut cursor, 1)?;
+                let name = cursor.word()?;
+
+                self.class = Some((access_flags, self.pool.class(name)?, name.to_string()));
+            }
+            "super" => {
+                self.outside_method(line)?;
+                self.super_class = Some(self.pool.class(cursor.word()?)?);
+            }
+            "implements" => {
+                self.outside_method(line)?;
+                let interface = self.pool.class(cursor.word()?)?;
+                self.interfaces.push(interface);
+            }
+            "sourcefile" => {
+                self.outside_method(line)?;
+                let sourcefile = self.string(&mut cursor)?;
+                let attribute = Attribute::new(
+                    &mut self.pool,
+                    "SourceFile",
+                    sourcefile.to_be_bytes().to_vec(),
+                )?;
+                self.attributes.push(attribute);
+            }
+            "signature" => {
+                let signature = self.string(&mut cursor)?;
+                let attribute = Attribute::new(
+                    &mut self.pool,
+                    "Signature",
+                    signature.to_be_bytes().to_vec(),
+                )?;
+                self.attributes_mut().push(attribute);
+            }
+            "attribute" => {
+                let name = cursor.word()?;
+                let info = hex(&mut cursor)?;
+
+                // the bootstrap methods are kept in order for invokedynamic and dynamic
+                // constants to find, and written out again when the class is finished
+                if name == "BootstrapMethods" && self.method.is_none() && !self.in_field {
+                    let bootstrap_methods = pool::bootstrap_methods(&info).ok_or_else(|| {
+                        AsmError::MalformedAttribute {
+                            name: name.to_string(),
+                        }
+                    })?;
+                    for bootstrap_method in bootstrap_methods {
+                        if !self.bootstrap_methods.contains(&bootstrap_method) {
+                            self.bootstrap_methods.push(bootstrap_method);
+                        }
+                    }
+
+                    return cursor.end();
+                }
+
+                let attribute = Attribute::new(&mut self.pool, name, info)?;
+                self.attributes_mut().push(attribute);
+            }
+            "codeattribute" => {
+                let name = cursor.word()?;
+                let info = hex(&mut cursor)?;
+                let attribute = Attribute::new(&mut self.pool, name, info)?;
+
+                let method = self.inside_method(line)?;
+                method.has_stack_map_table |= name == "StackMapTable";
+                method.code_attributes.push(attribute);
+            }
+            "field" => {
+                self.outside_method(line)?;
+                let has_value = cursor.len() >= 4 && cursor.peek_back(1) == Some("=");
+                let access_flags = flags(
+                    &keywords::FIELD_FLAGS,
+                    &mut cursor,
+                    if has_value { 4 } else { 2 },
+                )?;
+                let name = cursor.word()?;
+                let descriptor = cursor.word()?;
+
+                let mut field = Member {
+                    name: name.to_string(),
+                    access_flags,
+                    name_index: self.pool.utf8(name)?,
+                    descriptor_index: self.pool.utf8(descriptor)?,
+                    code: None,
+                    attributes: Vec::new(),
+                };
+                if has_value {
+                    cursor.word()?;
+                    let value = constant(&mut cursor, &mut self.pool, &mut self.bootstrap_methods)?;
+                    field.attributes.push(Attribute::new(
+                        &mut self.pool,
+                        "ConstantValue",
+                        value.to_be_bytes().to_vec(),
+                    )?);
+                }
+
+                self.fields.push(field);
+                self.in_field = true;
+            }
+            "method" => {
+                self.outside_method(line)?;
+                let access_flags = flags(&keywords::METHOD_FLAGS, &mut cursor, 1)?;
+                let (name, descriptor) = split_method(cursor.word()?, line)?;
+
+                self.method = Some(MethodAssembler::new(Member {
+                    name: name.to_string(),
+                    access_flags,
+                    name_index: self.pool.utf8(name)?,
+                    descriptor_index: self.pool.utf8(descriptor)?,
+                    code: None,
+                    attributes: Vec::new(),
+                }));
+            }
+            "limit" => {
+                let kind = cursor.word()?;
+                let limit = cursor.number()?;
+
+                let method = self.inside_method(line)?;
+                match kind {
+                    "stack" => method.max_stack = Some(limit),
+                    "locals" => method.max_locals = Some(limit),
+                    _ => return Err(AsmError::UnknownDirective { line }),
+                }
+                method.has_code = true;
+            }
+            "throws" => {
+                let exception = self.pool.class(cursor.word()?)?;
+                self.inside_method(line)?.exceptions.push(exception);
+            }
+            "catch" => {
+                let catch_type = match cursor.word()? {
+                    "all" => 0,
+                    name => self.pool.class(name)?,
+                };
+
+                let mut label = |keyword| {
+                    if cursor.word()? != keyword {
+                        return Err(AsmError::ExpectedOperand { line });
+                    }
+
+                    cursor.word().map(str::to_string)
+                };
+                let catch = Catch {
+                    start: label("from")?,
+                    end: label("to")?,
+                    handler: label("using")?,
+                    catch_type,
+                    line,
+                };
+
+                let method = self.inside_method(line)?;
+                method.catches.push(catch);
+                method.has_code = true;
+            }
+            "line" => {
+                let line_number = cursor.number()?;
+
+                let method = self.inside_method(line)?;
+                method
+                    .line_numbers
+                    .push([method.code.len() as u16, line_number]);
+                method.has_code = true;
+            }
+            "end" => match cursor.word()? {
+                "method" => {
+                    let method = self
+                        .method
+                        .take()
+                        .ok_or(AsmError::UnexpectedDirective { line })?;
+                    let method = method.finish(&mut self.pool)?;
+                    self.methods.push(method);
+                }
+                "field" => {
+                    self.outside_method(line)?;
+                    if !self.in_field {
+                        return Err(AsmError::UnexpectedDirective { line });
+                    }
+                }
+                _ => return Err(AsmError::UnknownDirective { line }),
+            },
+            _ => return Err(AsmError::UnknownDirective { line }),
+        }
+
+        cursor.end()?;
+        if !matches!(directive, "signature" | "attribute" | "field") {
+            self.in_field = false;
+        }
+
+        Ok(())
+    }
+
+    fn outside_method(&self, line: usize) -> AsmResult<()> {
+        if self.method.is_some() {
+            return Err(AsmError::UnexpectedDirective { line });
+        }
+
+        Ok(())
+    }
+
+    fn inside_method(&mut self, line: usize) -> AsmResult<&mut MethodAssembler> {
+        self.method
+            .as_mut()
+            .ok_or(AsmError::UnexpectedDirective { line })
+    }
+
+    // the attributes an attribute directive adds to
+    fn attributes_mut(&mut self) -> &mut Vec<Attribute> {
+        match (&mut self.method, self.fields.last_mut()) {
+            (Some(method), _) => &mut method.member.attributes,
+            (None, Some(field)) if self.in_field => &mut field.attributes,
+            _ => &mut self.attributes,
+        }
+    }
+
+    fn string(&mut self, cursor: &mut Cursor) -> AsmResult<u16> {
+        let string = JString::from_utf16(cursor.string()?);
+        self.pool.add(Constant::Utf8(string))
+    }
+
+    fn finish(mut self) -> AsmResult<Class> {
+        if self.method.is_some() {
+            return Err(AsmError::UnterminatedMethod);
+        }
+
+        let (access_flags, this_class, name) = self.class.ok_or(AsmError::MissingClassDirective)?;
+        let (major_version, minor_version) = self.version.unwrap_or((62, 0));
+
+        // java/lang/Object and modules have no superclass
+        let super_class = match self.super_class {
+            Some(super_class) => super_class,
+            None if name == "java/lang/Object" || access_flags & 0x8000 != 0 => 0,
+            None => self.pool.class("java/lang/Object")?,
+        };
+
+        if !self.bootstrap_methods.is_empty() {
+            let mut info = Vec::new();
+            info.extend_from_slice(&(self.bootstrap_methods.len() as u16).to_be_bytes());
+            for (method_handle, arguments) in &self.bootstrap_methods {
+                info.extend_from_slice(&method_handle.to_be_bytes());
+                info.extend_from_slice(&(arguments.len() as u16).to_be_bytes());
+                for argument in arguments {
+                    info.extend_from_slice(&argument.to_be_bytes());
+                }
+            }
+
+            let attribute = Attribute::new(&mut self.pool, "BootstrapMethods", info)?;
+            self.attributes.push(attribute);
+        }
+
+        Ok(Class {
+            pool: self.pool,
+            minor_version,
+            major_version,
+            access_flags,
+            this_class,
+            super_class,
+            interfaces: self.interfaces,
+            fields: self.fields,
+            methods: self.methods,
+            attributes: self.attributes,
+        })
+    }
+}
+
+// a branch offset to fill in once the method's labels are known
+struct Fixup {
+    position: usize,
+    // the offset is relative to the instruction
+    pc: usize,
+    label: String,
+    wide: bool,
+    line: usize,
+}
+
+struct Catch {
+    start: String,
+    end: String,
+    handler: String,
+    catch_type: u16,
+    line: usize,
+}
+
+struct MethodAssembler {
+    member: Member,
+    max_stack: Option<u16>,
+    max_locals: Option<u16>,
+    code: Vec<u8>,
+    // whether the method has a Code attribute, even if empty
+    has_code: bool,
+    labels: HashMap<String, usize>,
+    fixups: Vec<Fixup>,
+    catches: Vec<Catch>,
+    line_numbers: Vec<[u16; 2]>,
+    exceptions: Vec<u16>,
+    code_attributes: Vec<Attribute>,
+    has_stack_map_table: bool,
+}
+
+impl MethodAssembler {
+    fn new(member: Member) -> Self {
+        Self {
+            member,
+            max_stack: None,
+            max_locals: None,
+            code: Vec::new(),
+            has_code: false,
+            labels: HashMap::new(),
+            fixups: Vec::new(),
+            catches: Vec::new(),
+            line_numbers: Vec::new(),
+            exceptions: Vec::new(),
+            code_attributes: Vec::new(),
+            has_stack_map_table: false,
+        }
+    }
+
+    fn label(&mut self, label: &str, line: usize) -> AsmResult<()> {
+        if self
+            .labels
+            .insert(label.to_string(), self.code.len())
+            .is_some()
+        {
+            return Err(AsmError::DuplicateLabel { line });
+        }
+        self.has_code = true;
+
+        Ok(())
+    }
+
+    fn fixup(&mut self, label: &str, pc: usize, wide: bool, line: usize) {
+        self.fixups.push(Fixup {
+            position: self.code.len(),
+            pc,
+            label: label.to_string(),
+            wide,
+            line,
+        });
+
+        let width = if wide { 4 } else { 2 };
+        self.code.resize(self.code.len() + width, 0);
+    }
+
+    fn push_u2(&mut self, value: u16) {
+        self.code.extend_from_slice(&value.to_be_bytes());
+    }
+
+    fn instruction<'a>(
+        &mut self,
+        mnemonic: &str,
+        mut cursor: Cursor,
+        pool: &mut ConstantPool,
+        bootstrap_methods: &mut Vec<(u16, Vec<u16>)>,
+        lines: &mut Iter<'a, Line>,
+    ) -> AsmResult<()> {
+        let line = cursor.line;
+        let opcode = opcodes::opcode(mnemonic).ok_or(AsmError::UnknownInstruction { line })?;
+        let pc = self.code.len();
+        self.has_code = true;
+
+        match opcodes::operands(opcode) {
+            Operands::None => self.code.push(opcode),
+            Operands::Byte => {
+                let value = cursor.number::<i8>()?;
+                self.code.extend_from_slice(&[opcode, value as u8]);
+            }
+            Operands::Short => {
+                let value = cursor.number::<i16>()?;
+                self.code.push(opcode);
+                self.push_u2(value as u16);
+            }
+            Operands::Constant | Operands::WideConstant => {
+                let (index, is_wide) = loadable_constant(&mut cursor, pool, bootstrap_methods)?;
+
+                // ldc2_w only loads longs and doubles, and ldc and ldc_w everything else
+                if is_wide != (opcode == 0x14) {
+                    return Err(AsmError::MismatchedConstant { line });
+                }
+
+                // like Jasmin, ldc becomes ldc_w when the constant does not fit
+                match u8::try_from(index) {
+                    Ok(index) if opcode == 0x12 => self.code.extend_from_slice(&[opcode, index]),
+                    _ => {
+                        self.code.push(if opcode == 0x12 { 0x13 } else { opcode });
+                        self.push_u2(index);
+                    }
+                }
+            }
+            Operands::Local => {
+                let index = cursor.number::<u16>()?;
+                match u8::try_from(index) {
+                    Ok(index) => self.code.extend_from_slice(&[opcode, index]),
+                    Err(_) => {
+                        self.code.extend_from_slice(&[opcodes::WIDE, opcode]);
+                        self.push_u2(index);
+                    }
+                }
+            }
+            Operands::Increment => {
+                let index = cursor.number::<u16>()?;
+                let value = cursor.number::<i16>()?;
+                match (u8::try_from(index), i8::try_from(value)) {
+                    (Ok(index), Ok(value)) => {
+                        self.code.extend_from_slice(&[opcode, index, value as u8])
+                    }
+                    _ => {
+                        self.code.extend_from_slice(&[opcodes::WIDE, opcode]);
+                        self.push_u2(index);
+                        self.push_u2(value as u16);
+                    }
+                }
+            }
+            Operands::Branch | Operands::WideBranch => {
+                let label = cursor.word()?;
+                self.code.push(opcode);
+                self.fixup(
+                    label,
+                    pc,
+                    opcodes::operands(opcode) == Operands::WideBranch,
+                    line,
+                );
+            }
+            Operands::TableSwitch => {
+                let low = cursor.number::<i32>()?;
+                let high = if cursor.is_empty() {
+                    None
+                } else {
+                    Some(cursor.number::<i32>()?)
+                };
+                cursor.end()?;
+
+                let (cases, default) = switch_cases(lines, line)?;
+                let count = cases.len() as i64;
+                let high = match high {
+                    Some(high) if high as i64 - low as i64 + 1 == count => high,
+                    None if count > 0 => (low as i64 + count - 1) as i32,
+                    _ => return Err(AsmError::InvalidSwitch { line }),
+                };
+
+                self.switch(opcode, pc, default);
+                self.code.extend_from_slice(&low.to_be_bytes());
+                self.code.extend_from_slice(&high.to_be_bytes());
+                for (key, label, line) in cases {
+                    if key.is_some() {
+                        return Err(AsmError::InvalidSwitch { line });
+                    }
+
+                    self.fixup(label, pc, true, line);
+                }
+            }
+            Operands::LookupSwitch => {
+                cursor.end()?;
+
+                let (cases, default) = switch_cases(lines, line)?;
+                self.switch(opcode, pc, default);
+                self.code
+                    .extend_from_slice(&(cases.len() as u32).to_be_bytes());
+                for (key, label, line) in cases {
+                    let key = key
+                        .and_then(token::parse_integer)
+                        .and_then(|key| i32::try_from(key).ok())
+                        .ok_or(AsmError::InvalidSwitch { line })?;
+
+                    self.code.extend_from_slice(&key.to_be_bytes());
+                    self.fixup(label, pc, true, line);
+                }
+            }
+            Operands::Field => {
+                let index = field_ref(&mut cursor, pool)?;
+                self.code.push(opcode);
+                self.push_u2(index);
+            }
+            Operands::Method => {
+                let interface = cursor.peek_word() == Some("interface");
+                if interface {
+                    cursor.word()?;
+                }
+
+                let index = method_ref(cursor.word()?, interface, line, pool)?;
+                self.code.push(opcode);
+                self.push_u2(index);
+            }
+            Operands::InterfaceMethod => {
+                let reference = cursor.word()?;
+                let count = if cursor.is_empty() {
+                    let (_, descriptor) = split_method(reference, line)?;
+                    let descriptor = JString::from(descriptor);
+                    let descriptor = MethodDescriptor::from_jstr(&descriptor)
+                        .map_err(|_| AsmError::InvalidDescriptor { line })?;
+
+                    u8::try_from(descriptor.parameter_slots() + 1)
+                        .map_err(|_| AsmError::InvalidDescriptor { line })?
+                } else {
+                    cursor.number()?
+                };
+
+                let index = method_ref(reference, true, line, pool)?;
+                self.code.push(opcode);
+                self.push_u2(index);
+                self.code.extend_from_slice(&[count, 0]);
+            }
+            Operands::Dynamic => {
+                let (name, descriptor) = split_method(cursor.word()?, line)?;
+                let name_and_type = pool.name_and_type(name, descriptor)?;
+                let bootstrap = bootstrap_method(&mut cursor, pool, bootstrap_methods)?;
+                let index = pool.add(Constant::InvokeDynamic(bootstrap, name_and_type))?;
+
+                self.code.push(opcode);
+                self.push_u2(index);
+                self.code.extend_from_slice(&[0, 0]);
+            }
+            Operands::Class => {
+                let index = pool.class(cursor.word()?)?;
+                self.code.push(opcode);
+                self.push_u2(index);
+            }
+            Operands::ArrayType => {
+                let atype = match opcodes::ARRAY_TYPES
+                    .iter()
+                    .find(|(name, _)| cursor.peek_word() == Some(*name))
+                {
+                    Some((_, atype)) => {
+                        cursor.word()?;
+                        *atype
+                    }
+                    None => cursor.number()?,
+                };
+
+                self.code.extend_from_slice(&[opcode, atype]);
+            }
+            Operands::MultiArray => {
+                let index = pool.class(cursor.word()?)?;
+                let dimensions = cursor.number()?;
+
+                self.code.push(opcode);
+                self.push_u2(index);
+                self.code.push(dimensions);
+            }
+        }
+
+        cursor.end()
+    }
+
+    // the opcode, padding and default target shared by both switches
+    fn switch(&mut self, opcode: u8, pc: usize, (label, line): (&str, usize)) {
+        self.code.push(opcode);
+        while self.code.len() & 3 != 0 {
+            self.code.push(0);
+        }
+
+        self.fixup(label, pc, true, line);
+    }
+
+    fn finish(mut self, pool: &mut ConstantPool) -> AsmResult<Member> {
+        for fixup in &self.fixups {
+            let target = self
+                .labels
+                .get(&fixup.label)
+                .ok_or(AsmError::UndefinedLabel { line: fixup.line })?;
+            let offset = *target as i64 - fixup.pc as i64;
+
+            let position = fixup.position;
+            if fixup.wide {
+                self.code[position..position + 4].copy_from_slice(&(offset as i32).to_be_bytes());
+            } else {
+                let offset = i16::try_from(offset)
+                    .map_err(|_| AsmError::BranchOutOfRange { line: fixup.line })?;
+                self.code[position..position + 2].copy_from_slice(&offset.to_be_bytes());
+            }
+        }
+
+        let mut exception_table = Vec::with_capacity(self.catches.len());
+        for catch in &self.catches {
+            let label = |label: &String| {
+                self.labels
+                    .get(label)
+                    .map(|pc| *pc as u16)
+                    .ok_or(AsmError::UndefinedLabel { line: catch.line })
+            };
+
+            exception_table.push([
+                label(&catch.start)?,
+                label(&catch.end)?,
+                label(&catch.handler)?,
+                catch.catch_type,
+            ]);
+        }
+
+        if !self.line_numbers.is_empty() {
+            let mut info = Vec::new();
+            info.extend_from_slice(&(self.line_numbers.len() as u16).to_be_bytes());
+            for entry in self.line_numbers.iter().flatten() {
+                info.extend_from_slice(&entry.to_be_bytes());
+            }
+
+            self.code_attributes
+                .insert(0, Attribute::new(pool, "LineNumberTable", info)?);
+        }
+
+        if !self.exceptions.is_empty() {
+            let mut info = Vec::new();
+            info.extend_from_slice(&(self.exceptions.len() as u16).to_be_bytes());
+            for exception in &self.exceptions {
+                info.extend_from_slice(&exception.to_be_bytes());
+            }
+
+            self.member
+                .attributes
+                .push(Attribute::new(pool, "Exceptions", info)?);
+        }
+
+        if self.has_code {
+            self.member.code = Some(Code {
+                name_index: pool.utf8("Code")?,
+                max_stack: self.max_stack,
+                max_locals: self.max_locals,
+                bytes: self.code,
+                exception_table,
+                attributes: self.code_attributes,
+                has_stack_map_table: self.has_stack_map_table,
+            });
+        }
+
+        Ok(self.member)
+    }
+}
+
+// the cases of a switch, each with its key, label and line, up to its default case, which ends it
+#[allow(clippy::type_complexity)]
+fn switch_cases<'a>(
+    lines: &mut Iter<'a, Line>,
+    line: usize,
+) -> AsmResult<(Vec<(Option<&'a str>, &'a str, usize)>, (&'a str, usize))> {
+    let mut cases = Vec::new();
+
+    for case in lines.by_ref() {
+        let invalid = AsmError::InvalidSwitch { line: case.number };
+        let words = case
+            .tokens
+            .iter()
+            .map(|token| match token {
+                Token::Word(word) => Some(word.as_str()),
+                Token::String(_) => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(invalid)?;
+
+        // `key: label`, `key : label` or just `label`
+        let (key, label) = match *words.as_slice() {
+            [label] => (None, label),
+            [key, ":", label] => (Some(key), label),
+            [key, label] if key.ends_with(':') => (Some(&key[..key.len() - 1]), label),
+            _ => return Err(AsmError::InvalidSwitch { line: case.number }),
+        };
+
+        if key == Some("default") {
+            return Ok((cases, (label, case.number)));
+        }
+
+        cases.push((key, label, case.number));
+    }
+
+    Err(AsmError::UnterminatedSwitch { line })
+}
+
+// access flag keywords or numbers, leaving the last `keep` operands
+fn flags(table: &[(&str, u16)], cursor: &mut Cursor, keep: usize) -> AsmResult<u16> {
+    let line = cursor.line;
+    let mut flags = 0;
+
+    while cursor.len() > keep {
+        let word = cursor.word()?;
+        flags |= keywords::flag(table, word)
+            .or_else(|| {
+                token::parse_integer(word)
+                    .filter(|_| word.starts_with("0x"))
+                    .and_then(|flag| u16::try_from(flag).ok())
+            })
+            .ok_or(AsmError::InvalidFlag { line })?;
+    }
+
+    Ok(flags)
+}
+
+// the bytes of a raw attribute, which may be left out for an empty one
+fn hex(cursor: &mut Cursor) -> AsmResult<Vec<u8>> {
+    let line = cursor.line;
+    if cursor.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let digits = cursor.word()?.as_bytes();
+    if digits.len() % 2 != 0 {
+        return Err(AsmError::InvalidHex { line });
+    }
+
+    digits
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or(AsmError::InvalidHex { line })
+        })
+        .collect()
+}
+
+// splits `name(descriptor)` or `owner/name(descriptor)` before the descriptor
+fn split_method(word: &str, line: usize) -> AsmResult<(&str, &str)> {
+    word.find('(')
+        .filter(|index| *index > 0)
+        .map(|index| word.split_at(index))
+        .ok_or(AsmError::InvalidReference { line })
+}
+
+// splits `owner/name` before the name
+fn split_member(word: &str, line: usize) -> AsmResult<(&str, &str)> {
+    word.rsplit_once('/')
+        .filter(|(owner, name)| !owner.is_empty() && !name.is_empty())
+        .ok_or(AsmError::InvalidReference { line })
+}
+
+// `owner/name descriptor`
+fn field_ref(cursor: &mut Cursor, pool: &mut ConstantPool) -> AsmResult<u16> {
+    let (owner, name) = split_member(cursor.word()?, cursor.line)?;
+    let descriptor = cursor.word()?;
+
+    let class = pool.class(owner)?;
+    let name_and_type = pool.name_and_type(name, descriptor)?;
+    pool.add(Constant::FieldRef(class, name_and_type))
+}
+
+// `owner/name(descriptor)`
+fn method_ref(word: &str, interface: bool, line: usize, pool: &mut ConstantPool) -> AsmResult<u16> {
+    let (member, descriptor) = split_method(word, line)?;
+    let (owner, name) = split_member(member, line)?;
+
+    let class = pool.class(owner)?;
+    let name_and_type = pool.name_and_type(name, descriptor)?;
+    pool.add(if interface {
+        Constant::InterfaceMethodRef(class, name_and_type)
+    } else {
+        Constant::MethodRef(class, name_and_type)
+    })
+}
+
+// `kind [interface] reference`, where field references are followed by their descriptor
+fn method_handle(cursor: &mut Cursor, pool: &mut ConstantPool) -> AsmResult<u16> {
+    let line = cursor.line;
+    let reference_kind =
+        keywords::reference_kind(cursor.word()?).ok_or(AsmError::InvalidConstant { line })?;
+
+    let reference_index = if reference_kind <= 4 {
+        field_ref(cursor, pool)?
+    } else {
+        let interface = cursor.peek_word() == Some("interface");
+        if interface {
+            cursor.word()?;
+        }
+
+        method_ref(cursor.word()?, interface || reference_kind == 9, line, pool)?
+    };
+
+    pool.add(Constant::MethodHandle(reference_kind, reference_index))
+}
+
+// a method handle followed by its static arguments in brackets, as an index into the
+// BootstrapMethods attribute
+fn bootstrap_method(
+    cursor: &mut Cursor,
+    pool: &mut ConstantPool,
+    bootstrap_methods: &mut Vec<(u16, Vec<u16>)>,
+) -> AsmResult<u16> {
+    let method_handle = method_handle(cursor, pool)?;
+    if cursor.word()? != "[" {
+        return Err(AsmError::ExpectedOperand { line: cursor.line });
+    }
+
+    let mut arguments = Vec::new();
+    while cursor.peek_word() != Some("]") {
+        arguments.push(constant(cursor, pool, bootstrap_methods)?);
+    }
+    cursor.word()?;
+
+    let bootstrap_method = (method_handle, arguments);
+    let index = match bootstrap_methods
+        .iter()
+        .position(|candidate| *candidate == bootstrap_method)
+    {
+        Some(index) => index,
+        None => {
+            bootstrap_methods.push(bootstrap_method);
+            bootstrap_methods.len() - 1
+        }
+    };
+
+    Ok(index as u16)
+}
+
+// an entry of a `.constant` directive, referring to other entries by their `#index`
+fn pool_entry(cursor: &mut Cursor) -> AsmResult<Constant> {
+    let line = cursor.line;
+    let kind = cursor.word()?;
+    let constant = match kind {
+        "utf8" => Constant::Utf8(JString::from_utf16(cursor.string()?)),
+        "integer" | "float" | "long" | "double" => {
+            let constant = numeric_constant(cursor.word()?);
+            match (kind, constant) {
+                ("integer", Some(constant @ Constant::Integer(_)))
+                | ("float", Some(constant @ Constant::Float(_)))
+                | ("long", Some(constant @ Constant::Long(_)))
+                | ("double", Some(constant @ Constant::Double(_))) => constant,
+                _ => return Err(AsmError::InvalidConstant { line }),
+            }
+        }
+        "class" => Constant::Class(pool_index(cursor)?),
+        "string" => Constant::String(pool_index(cursor)?),
+        "fieldref" => Constant::FieldRef(pool_index(cursor)?, pool_index(cursor)?),
+        "methodref" => Constant::MethodRef(pool_index(cursor)?, pool_index(cursor)?),
+        "interfacemethodref" => {
+            Constant::InterfaceMethodRef(pool_index(cursor)?, pool_index(cursor)?)
+        }
+        "nameandtype" => Constant::NameAndType(pool_index(cursor)?, pool_index(cursor)?),
+        "methodhandle" => {
+            let reference_kind = cursor.number()?;
+            Constant::MethodHandle(reference_kind, pool_index(cursor)?)
+        }
+        "methodtype" => Constant::MethodType(pool_index(cursor)?),
+        "dynamic" => {
+            let bootstrap = cursor.number()?;
+            Constant::Dynamic(bootstrap, pool_index(cursor)?)
+        }
+        "invokedynamic" => {
+            let bootstrap = cursor.number()?;
+            Constant::InvokeDynamic(bootstrap, pool_index(cursor)?)
+        }
+        "module" => Constant::Module(pool_index(cursor)?),
+        "package" => Constant::Package(pool_index(cursor)?),
+        _ => return Err(AsmError::InvalidConstant { line }),
+    };
+
+    Ok(constant)
+}
+
+fn pool_index(cursor: &mut Cursor) -> AsmResult<u16> {
+    let line = cursor.line;
+
+    cursor
+        .word()?
+        .strip_prefix('#')
+        .and_then(|index| index.parse().ok())
+        .ok_or(AsmError::InvalidConstant { line })
+}
+
+// a loadable constant, as ldc and bootstrap arguments take
+fn constant(
+    cursor: &mut Cursor,
+    pool: &mut ConstantPool,
+    bootstrap_methods: &mut Vec<(u16, Vec<u16>)>,
+) -> AsmResult<u16> {
+    loadable_constant(cursor, pool, bootstrap_methods).map(|(index, _)| index)
+}
+
+// a loadable constant, along with whether it is a long or a double, including dynamic ones
+fn loadable_constant(
+    cursor: &mut Cursor,
+    pool: &mut ConstantPool,
+    bootstrap_methods: &mut Vec<(u16, Vec<u16>)>,
+) -> AsmResult<(u16, bool)> {
+    let line = cursor.line;
+
+    match cursor.next() {
+        Some(Token::String(units)) => {
+            let string_index = pool.add(Constant::Utf8(JString::from_utf16(units)))?;
+            Ok((pool.add(Constant::String(string_index))?, false))
+        }
+        Some(Token::Word(word)) => match word.as_str() {
+            "class" => Ok((pool.class(cursor.word()?)?, false)),
+            "methodtype" => {
+                let descriptor_index = pool.utf8(cursor.word()?)?;
+                Ok((pool.add(Constant::MethodType(descriptor_index))?, false))
+            }
+            "methodhandle" => Ok((method_handle(cursor, pool)?, false)),
+            "dynamic" => {
+                let name = cursor.word()?;
+                let descriptor = cursor.word()?;
+                let name_and_type = pool.name_and_type(name, descriptor)?;
+                let bootstrap = bootstrap_method(cursor, pool, bootstrap_methods)?;
+
+                let is_wide = matches!(descriptor, "J" | "D");
+                Ok((
+                    pool.add(Constant::Dynamic(bootstrap, name_and_type))?,
+                    is_wide,
+                ))
+            }
+            _ => {
+                let constant = numeric_constant(word).ok_or(AsmError::InvalidConstant { line })?;
+                let is_wide = matches!(constant, Constant::Long(_) | Constant::Double(_));
+                Ok((pool.add(constant)?, is_wide))
+            }
+        },
+        None => Err(AsmError::ExpectedOperand { line }),
+    }
+}
+
+// `1`, `1L`, `1.0f` or `1.0`, typed by the literal alone, with NaN and Infinity spelt out
+fn numeric_constant(word: &str) -> Option<Constant> {
+    let is_hex = word.trim_start_matches('-').starts_with("0x");
+
+    if let Some(digits) = word.strip_suffix(['L', 'l']) {
+        return Some(Constant::Long(token::parse_integer(digits)? as u64));
+    }
+
+    if is_hex {
+        let value = token::parse_integer(word)?;
+        return (i32::MIN as i64..=u32::MAX as i64)
+            .contains(&value)
+            .then_some(Constant::Integer(value as u32));
+    }
+
+    if let Some(digits) = word.strip_suffix(['F', 'f']) {
+        return Some(Constant::Float(parse_float::<f32>(digits)?.to_bits()));
+    }
+
+    let digits = word.strip_suffix(['D', 'd']);
+    if digits.is_some()
+        || word.contains(['.', 'e', 'E'])
+        || word.ends_with("NaN")
+        || word.ends_with("Infinity")
+    {
+        return Some(Constant::Double(
+            parse_float::<f64>(digits.unwrap_or(word))?.to_bits(),
+        ));
+    }
+
+    let value = i32::try_from(token::parse_integer(word)?).ok()?;
+    Some(Constant::Integer(value as u32))
+}
+
+fn parse_float<T: std::str::FromStr>(digits: &str) -> Option<T> {
+    match digits {
+        "NaN" => "NaN".parse().ok(),
+        "Infinity" => "inf".parse().ok(),
+        "-Infinity" => "-inf".parse().ok(),
+        _ => digits.parse().ok(),
+    }
+}
+
+fn utf8<'clazz>(constant_pool: &[CpInfo<'clazz>], index: u16) -> &'clazz JStr {
+    match constant_pool[index as usize - 1].info {
+        CpInfoType::ConstantUtf8 { string, .. } => string,
+        _ => unreachable!(),
+    }
+}
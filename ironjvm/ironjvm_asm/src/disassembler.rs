@@ -0,0 +1,751 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use std::collections::BTreeSet;
+
+use ironjvm_cfparser::code::CodeParser;
+use ironjvm_cfparser::error::ParseError;
+use ironjvm_cfparser::lazy::LazyAttribute;
+use ironjvm_cfparser::lazy::LazyClassFile;
+use ironjvm_cfparser::lazy::LazyMember;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+
+use crate::error::AsmError;
+use crate::error::AsmResult;
+use crate::keywords;
+use crate::opcodes;
+use crate::opcodes::Operands;
+use crate::pool;
+
+/// Disassembles a class file into source the assembler accepts. The constant pool is written out
+/// entry by entry, so that attributes without a directive of their own can be kept as raw bytes,
+/// constant pool indices included.
+pub fn disassemble(bytes: &[u8]) -> AsmResult<String> {
+    let class = ClassFileParser::new(bytes).parse_lazy()?;
+
+    let mut disassembler = Disassembler {
+        class: &class,
+        bootstrap_methods: Vec::new(),
+        output: String::new(),
+    };
+    if let Some(attribute) = disassembler.find_attribute(&class.attributes, "BootstrapMethods")? {
+        disassembler.bootstrap_methods = pool::bootstrap_methods(attribute.info())
+            .ok_or_else(|| malformed("BootstrapMethods"))?;
+    }
+
+    disassembler.class()?;
+    Ok(disassembler.output)
+}
+
+struct Disassembler<'a, 'clazz> {
+    class: &'a LazyClassFile<'clazz>,
+    bootstrap_methods: Vec<(u16, Vec<u16>)>,
+    output: String,
+}
+
+impl<'a, 'clazz> Disassembler<'a, 'clazz> {
+    fn class(&mut self) -> AsmResult<()> {
+        let class = self.class;
+
+        self.line(format!(
+            ".version {} {}",
+            class.major_version, class.minor_version
+        ));
+        self.constant_pool()?;
+        self.line(format!(
+            ".class {}{}",
+            flags(&keywords::CLASS_FLAGS, class.access_flags),
+            class.this_class_name()?.display()
+        ));
+        if let Some(super_class) = class.super_class_name()? {
+            self.line(format!(".super {}", super_class.display()));
+        }
+        for interface in class.interface_names() {
+            self.line(format!(".implements {}", interface?.display()));
+        }
+
+        for attribute in &class.attributes {
+            match self.attribute_name(attribute)? {
+                "SourceFile" => {
+                    let sourcefile = self.utf8(u2(attribute.info(), 0, "SourceFile")?)?;
+                    self.line(format!(".sourcefile {}", quote(sourcefile)));
+                }
+                name => self.attribute("", name, attribute)?,
+            }
+        }
+
+        for field in &class.fields {
+            self.field(field)?;
+        }
+
+        for method in &class.methods {
+            self.method(method)?;
+        }
+
+        Ok(())
+    }
+
+    fn field(&mut self, field: &LazyMember<'clazz>) -> AsmResult<()> {
+        let constant_value = match self.find_attribute(&field.attributes, "ConstantValue")? {
+            Some(attribute) => {
+                let value = u2(attribute.info(), 0, "ConstantValue")?;
+                format!(" = {}", self.constant(value)?)
+            }
+            None => String::new(),
+        };
+
+        self.line(String::new());
+        self.line(format!(
+            ".field {}{} {}{}",
+            flags(&keywords::FIELD_FLAGS, field.access_flags),
+            self.utf8(field.name_index)?.display(),
+            self.utf8(field.descriptor_index)?.display(),
+            constant_value
+        ));
+
+        for attribute in &field.attributes {
+            match self.attribute_name(attribute)? {
+                "ConstantValue" if !constant_value.is_empty() => {}
+                name => self.attribute("    ", name, attribute)?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn method(&mut self, method: &LazyMember<'clazz>) -> AsmResult<()> {
+        self.line(String::new());
+        self.line(format!(
+            ".method {}{}{}",
+            flags(&keywords::METHOD_FLAGS, method.access_flags),
+            self.utf8(method.name_index)?.display(),
+            self.utf8(method.descriptor_index)?.display()
+        ));
+
+        let mut code = None;
+        for attribute in &method.attributes {
+            match self.attribute_name(attribute)? {
+                "Code" => code = Some(attribute),
+                "Exceptions" => {
+                    let info = attribute.info();
+                    let count = u2(info, 0, "Exceptions")?;
+                    for index in 0..count as usize {
+                        let exception = self.class_name(u2(info, 2 + index * 2, "Exceptions")?)?;
+                        self.line(format!("    .throws {}", exception.display()));
+                    }
+                }
+                name => self.attribute("    ", name, attribute)?,
+            }
+        }
+
+        if let Some(code) = code {
+            self.code(code.info())?;
+        }
+
+        self.line(".end method".to_string());
+
+        Ok(())
+    }
+
+    fn code(&mut self, info: &'clazz [u8]) -> AsmResult<()> {
+        let max_stack = u2(info, 0, "Code")?;
+        let max_locals = u2(info, 2, "Code")?;
+        let code_length = u4(info, 4, "Code")? as usize;
+        let code = info
+            .get(8..8 + code_length)
+            .ok_or_else(|| malformed("Code"))?;
+
+        let mut position = 8 + code_length;
+        let exception_table_length = u2(info, position, "Code")?;
+        let mut exception_table = Vec::with_capacity(exception_table_length as usize);
+        for index in 0..exception_table_length as usize {
+            let entry = position + 2 + index * 8;
+            exception_table.push([
+                u2(info, entry, "Code")?,
+                u2(info, entry + 2, "Code")?,
+                u2(info, entry + 4, "Code")?,
+                u2(info, entry + 6, "Code")?,
+            ]);
+        }
+
+        position += 2 + exception_table_length as usize * 8;
+        let attributes_count = u2(info, position, "Code")?;
+        position += 2;
+        let mut attributes = Vec::with_capacity(attributes_count as usize);
+        for _ in 0..attributes_count {
+            let length = u4(info, position + 2, "Code")? as usize;
+            let attribute = info
+                .get(position..position + 6 + length)
+                .and_then(LazyAttribute::from_bytes)
+                .ok_or_else(|| malformed("Code"))?;
+
+            attributes.push(attribute);
+            position += 6 + length;
+        }
+
+        let instructions = if code.is_empty() {
+            Vec::new()
+        } else {
+            CodeParser::new(code).parse()?
+        };
+
+        let mut line_numbers = Vec::new();
+        for attribute in &attributes {
+            if self.attribute_name(attribute)? == "LineNumberTable" {
+                let info = attribute.info();
+                for index in 0..u2(info, 0, "LineNumberTable")? as usize {
+                    line_numbers.push((
+                        u2(info, 2 + index * 4, "LineNumberTable")?,
+                        u2(info, 4 + index * 4, "LineNumberTable")?,
+                    ));
+                }
+            }
+        }
+
+        let mut labels = BTreeSet::new();
+        for entry in &exception_table {
+            labels.extend(&entry[..3]);
+        }
+        for (pc, instruction) in &instructions {
+            labels.extend(targets(*pc, instruction));
+        }
+
+        self.line(format!("    .limit stack {max_stack}"));
+        self.line(format!("    .limit locals {max_locals}"));
+        for [start_pc, end_pc, handler_pc, catch_type] in exception_table {
+            let catch_type = match catch_type {
+                0 => "all".to_string(),
+                index => self.class_name(index)?.display().to_string(),
+            };
+            self.line(format!(
+                "    .catch {catch_type} from L{start_pc} to L{end_pc} using L{handler_pc}"
+            ));
+        }
+
+        for (pc, instruction) in &instructions {
+            if labels.contains(pc) {
+                self.line(format!("L{pc}:"));
+            }
+
+            for (_, line_number) in line_numbers.iter().filter(|(start_pc, _)| start_pc == pc) {
+                self.line(format!("    .line {line_number}"));
+            }
+
+            let text = self.instruction(code, *pc, instruction)?;
+            self.line(format!("    {text}"));
+        }
+
+        // a label may follow the last instruction, such as the end of an exception handler range
+        if labels.contains(&(code.len() as u16)) {
+            self.line(format!("L{}:", code.len()));
+        }
+
+        for attribute in &attributes {
+            match self.attribute_name(attribute)? {
+                "LineNumberTable" => {}
+                name => {
+                    self.line(format!(
+                        "    .codeattribute {} {}",
+                        name,
+                        hex(attribute.info())
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn instruction(&self, code: &[u8], pc: u16, instruction: &Instruction) -> AsmResult<String> {
+        let mut position = pc as usize;
+        let wide = code[position] == opcodes::WIDE;
+        if wide {
+            position += 1;
+        }
+
+        let opcode = code[position];
+        let mnemonic = opcodes::MNEMONICS[opcode as usize];
+        let operands = &code[position + 1..];
+        let u2 = |offset: usize| u16::from_be_bytes([operands[offset], operands[offset + 1]]);
+        let label = |offset: i64| format!("L{}", pc as i64 + offset);
+
+        Ok(match opcodes::operands(opcode) {
+            Operands::None => mnemonic.to_string(),
+            Operands::Byte => format!("{mnemonic} {}", operands[0] as i8),
+            Operands::Short => format!("{mnemonic} {}", u2(0) as i16),
+            Operands::Constant => format!("{mnemonic} {}", self.constant(operands[0] as u16)?),
+            Operands::WideConstant => format!("{mnemonic} {}", self.constant(u2(0))?),
+            Operands::Local if wide => format!("{mnemonic} {}", u2(0)),
+            Operands::Local => format!("{mnemonic} {}", operands[0]),
+            Operands::Increment if wide => format!("{mnemonic} {} {}", u2(0), u2(2) as i16),
+            Operands::Increment => format!("{mnemonic} {} {}", operands[0], operands[1] as i8),
+            Operands::Branch => format!("{mnemonic} {}", label(u2(0) as i16 as i64)),
+            Operands::WideBranch => {
+                let offset = i32::from_be_bytes(operands[..4].try_into().unwrap());
+                format!("{mnemonic} {}", label(offset as i64))
+            }
+            Operands::TableSwitch => {
+                let Instruction::TableSwitch {
+                    default,
+                    low,
+                    high,
+                    ref jump_offsets,
+                } = *instruction
+                else {
+                    unreachable!()
+                };
+
+                let mut text = format!("{mnemonic} {low} {high}");
+                for offset in jump_offsets {
+                    text.push_str(&format!("\n        {}", label(*offset as i64)));
+                }
+                text.push_str(&format!("\n        default: {}", label(default as i64)));
+
+                text
+            }
+            Operands::LookupSwitch => {
+                let Instruction::LookupSwitch {
+                    default,
+                    ref match_offset_pairs,
+                    ..
+                } = *instruction
+                else {
+                    unreachable!()
+                };
+
+                let mut text = mnemonic.to_string();
+                for (key, offset) in match_offset_pairs {
+                    text.push_str(&format!("\n        {key}: {}", label(*offset as i64)));
+                }
+                text.push_str(&format!("\n        default: {}", label(default as i64)));
+
+                text
+            }
+            Operands::Field | Operands::Method => {
+                format!("{mnemonic} {}", self.member_ref(u2(0), opcode != 0xb9)?)
+            }
+            Operands::InterfaceMethod => {
+                format!(
+                    "{mnemonic} {} {}",
+                    self.member_ref(u2(0), false)?,
+                    operands[2]
+                )
+            }
+            Operands::Dynamic => {
+                let index = u2(0);
+                let CpInfoType::ConstantInvokeDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                } = self.constant_info(index)?
+                else {
+                    return Err(ParseError::InvalidConstantPoolIndex.into());
+                };
+                let (name, descriptor) = self.name_and_type(name_and_type_index)?;
+
+                format!(
+                    "{mnemonic} {}{} {}",
+                    name.display(),
+                    descriptor.display(),
+                    self.bootstrap_method(bootstrap_method_attr_index)?
+                )
+            }
+            Operands::Class => format!("{mnemonic} {}", self.class_name(u2(0))?.display()),
+            Operands::ArrayType => match opcodes::ARRAY_TYPES
+                .iter()
+                .find(|(_, atype)| *atype == operands[0])
+            {
+                Some((name, _)) => format!("{mnemonic} {name}"),
+                None => format!("{mnemonic} {}", operands[0]),
+            },
+            Operands::MultiArray => format!(
+                "{mnemonic} {} {}",
+                self.class_name(u2(0))?.display(),
+                operands[2]
+            ),
+        })
+    }
+
+    fn constant_pool(&mut self) -> AsmResult<()> {
+        let constant_pool = &self.class.constant_pool;
+
+        for index in 1..=constant_pool.len() as u16 {
+            let entry = match constant_pool.get(index)?.info {
+                CpInfoType::ConstantUtf8 { string, .. } => format!("utf8 {}", quote(string)),
+                CpInfoType::ConstantInteger { .. } => format!("integer {}", self.constant(index)?),
+                CpInfoType::ConstantFloat { .. } => format!("float {}", self.constant(index)?),
+                CpInfoType::ConstantLong { .. } => format!("long {}", self.constant(index)?),
+                CpInfoType::ConstantDouble { .. } => format!("double {}", self.constant(index)?),
+                CpInfoType::ConstantClass { name_index } => format!("class #{name_index}"),
+                CpInfoType::ConstantString { string_index } => format!("string #{string_index}"),
+                CpInfoType::ConstantFieldRef {
+                    class_index,
+                    name_and_type_index,
+                } => format!("fieldref #{class_index} #{name_and_type_index}"),
+                CpInfoType::ConstantMethodRef {
+                    class_index,
+                    name_and_type_index,
+                } => format!("methodref #{class_index} #{name_and_type_index}"),
+                CpInfoType::ConstantInterfaceMethodRef {
+                    class_index,
+                    name_and_type_index,
+                } => format!("interfacemethodref #{class_index} #{name_and_type_index}"),
+                CpInfoType::ConstantNameAndType {
+                    name_index,
+                    descriptor_index,
+                } => format!("nameandtype #{name_index} #{descriptor_index}"),
+                CpInfoType::ConstantMethodHandle {
+                    reference_kind,
+                    reference_index,
+                } => format!("methodhandle {reference_kind} #{reference_index}"),
+                CpInfoType::ConstantMethodType { descriptor_index } => {
+                    format!("methodtype #{descriptor_index}")
+                }
+                CpInfoType::ConstantDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                } => format!("dynamic {bootstrap_method_attr_index} #{name_and_type_index}"),
+                CpInfoType::ConstantInvokeDynamic {
+                    bootstrap_method_attr_index,
+                    name_and_type_index,
+                } => format!("invokedynamic {bootstrap_method_attr_index} #{name_and_type_index}"),
+                CpInfoType::ConstantModule { name_index } => format!("module #{name_index}"),
+                CpInfoType::ConstantPackage { name_index } => format!("package #{name_index}"),
+                // the second slot of a long or double
+                CpInfoType::ConstantUnusable => continue,
+            };
+
+            self.line(format!(".constant {index} {entry}"));
+        }
+
+        Ok(())
+    }
+
+    // a loadable constant, in the form the assembler takes for ldc
+    fn constant(&self, index: u16) -> AsmResult<String> {
+        Ok(match self.constant_info(index)? {
+            CpInfoType::ConstantInteger { bytes } => (bytes as i32).to_string(),
+            CpInfoType::ConstantFloat { bytes } => {
+                let value = f32::from_bits(bytes);
+                format!("{}f", float(value as f64, format!("{value:?}")))
+            }
+            CpInfoType::ConstantLong {
+                high_bytes,
+                low_bytes,
+            } => format!("{}L", ((high_bytes as u64) << 32 | low_bytes as u64) as i64),
+            CpInfoType::ConstantDouble {
+                high_bytes,
+                low_bytes,
+            } => {
+                let value = f64::from_bits((high_bytes as u64) << 32 | low_bytes as u64);
+                float(value, format!("{value:?}"))
+            }
+            CpInfoType::ConstantString { string_index } => quote(self.utf8(string_index)?),
+            CpInfoType::ConstantClass { name_index } => {
+                format!("class {}", self.utf8(name_index)?.display())
+            }
+            CpInfoType::ConstantMethodType { descriptor_index } => {
+                format!("methodtype {}", self.utf8(descriptor_index)?.display())
+            }
+            CpInfoType::ConstantMethodHandle {
+                reference_kind,
+                reference_index,
+            } => format!(
+                "methodhandle {}",
+                self.method_handle(reference_kind, reference_index)?
+            ),
+            CpInfoType::ConstantDynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => {
+                let (name, descriptor) = self.name_and_type(name_and_type_index)?;
+                format!(
+                    "dynamic {} {} {}",
+                    name.display(),
+                    descriptor.display(),
+                    self.bootstrap_method(bootstrap_method_attr_index)?
+                )
+            }
+            _ => return Err(ParseError::InvalidConstantPoolIndex.into()),
+        })
+    }
+
+    fn method_handle(&self, reference_kind: u8, reference_index: u16) -> AsmResult<String> {
+        let (kind, _) = keywords::REFERENCE_KINDS
+            .iter()
+            .find(|(_, kind)| *kind == reference_kind)
+            .ok_or(ParseError::InvalidConstantPoolIndex)?;
+
+        Ok(format!(
+            "{kind} {}",
+            self.member_ref(reference_index, reference_kind != 9)?
+        ))
+    }
+
+    fn bootstrap_method(&self, index: u16) -> AsmResult<String> {
+        let (method_handle, arguments) = self
+            .bootstrap_methods
+            .get(index as usize)
+            .ok_or_else(|| malformed("BootstrapMethods"))?;
+
+        let CpInfoType::ConstantMethodHandle {
+            reference_kind,
+            reference_index,
+        } = self.constant_info(*method_handle)?
+        else {
+            return Err(ParseError::InvalidConstantPoolIndex.into());
+        };
+
+        let mut text = self.method_handle(reference_kind, reference_index)?;
+        text.push_str(" [");
+        for argument in arguments {
+            text.push(' ');
+            text.push_str(&self.constant(*argument)?);
+        }
+        text.push_str(" ]");
+
+        Ok(text)
+    }
+
+    // `owner/name descriptor` for fields and `owner/name(descriptor)` for methods, with interface
+    // methods marked as such when `mark_interface` is set
+    fn member_ref(&self, index: u16, mark_interface: bool) -> AsmResult<String> {
+        let (class_index, name_and_type_index, separator, interface) =
+            match self.constant_info(index)? {
+                CpInfoType::ConstantFieldRef {
+                    class_index,
+                    name_and_type_index,
+                } => (class_index, name_and_type_index, " ", ""),
+                CpInfoType::ConstantMethodRef {
+                    class_index,
+                    name_and_type_index,
+                } => (class_index, name_and_type_index, "", ""),
+                CpInfoType::ConstantInterfaceMethodRef {
+                    class_index,
+                    name_and_type_index,
+                } => (
+                    class_index,
+                    name_and_type_index,
+                    "",
+                    if mark_interface { "interface " } else { "" },
+                ),
+                _ => return Err(ParseError::InvalidConstantPoolIndex.into()),
+            };
+        let (name, descriptor) = self.name_and_type(name_and_type_index)?;
+
+        Ok(format!(
+            "{interface}{}/{}{separator}{}",
+            self.class_name(class_index)?.display(),
+            name.display(),
+            descriptor.display()
+        ))
+    }
+
+    // an attribute with no directive of its own
+    fn attribute(&mut self, indent: &str, name: &str, attribute: &LazyAttribute) -> AsmResult<()> {
+        match name {
+            "Signature" => {
+                let signature = self.utf8(u2(attribute.info(), 0, "Signature")?)?;
+                self.line(format!("{indent}.signature {}", quote(signature)));
+            }
+            _ => self.line(format!(
+                "{indent}.attribute {name} {}",
+                hex(attribute.info())
+            )),
+        }
+
+        Ok(())
+    }
+
+    fn find_attribute(
+        &self,
+        attributes: &[LazyAttribute<'clazz>],
+        name: &str,
+    ) -> AsmResult<Option<LazyAttribute<'clazz>>> {
+        for attribute in attributes {
+            if self.attribute_name(attribute)? == name {
+                return Ok(Some(*attribute));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn attribute_name(&self, attribute: &LazyAttribute<'clazz>) -> AsmResult<&'clazz str> {
+        attribute
+            .name(&self.class.constant_pool)?
+            .to_str()
+            .ok_or_else(|| malformed("attribute name"))
+    }
+
+    fn constant_info(&self, index: u16) -> AsmResult<CpInfoType<'clazz>> {
+        Ok(self.class.constant_pool.get(index)?.info)
+    }
+
+    fn utf8(&self, index: u16) -> AsmResult<&'clazz JStr> {
+        Ok(self.class.constant_pool.utf8(index)?)
+    }
+
+    fn class_name(&self, index: u16) -> AsmResult<&'clazz JStr> {
+        Ok(self.class.constant_pool.class_name(index)?)
+    }
+
+    fn name_and_type(&self, index: u16) -> AsmResult<(&'clazz JStr, &'clazz JStr)> {
+        let CpInfoType::ConstantNameAndType {
+            name_index,
+            descriptor_index,
+        } = self.constant_info(index)?
+        else {
+            return Err(ParseError::InvalidConstantPoolIndex.into());
+        };
+
+        Ok((self.utf8(name_index)?, self.utf8(descriptor_index)?))
+    }
+
+    fn line(&mut self, line: String) {
+        self.output.push_str(&line);
+        self.output.push('\n');
+    }
+}
+
+// the pcs an instruction branches to
+fn targets(pc: u16, instruction: &Instruction) -> Vec<u16> {
+    let offsets = match *instruction {
+        Instruction::IfEq { offset }
+        | Instruction::IfNe { offset }
+        | Instruction::IfLt { offset }
+        | Instruction::IfGe { offset }
+        | Instruction::IfGt { offset }
+        | Instruction::IfLe { offset }
+        | Instruction::IfICmpEq { offset }
+        | Instruction::IfICmpNe { offset }
+        | Instruction::IfICmpLt { offset }
+        | Instruction::IfICmpGe { offset }
+        | Instruction::IfICmpGt { offset }
+        | Instruction::IfICmpLe { offset }
+        | Instruction::IfACmpEq { offset }
+        | Instruction::IfACmpNe { offset }
+        | Instruction::Goto { offset }
+        | Instruction::Jsr { offset }
+        | Instruction::IfNull { offset }
+        | Instruction::IfNonNull { offset } => vec![offset as i64],
+        Instruction::GotoW { offset } | Instruction::JsrW { offset } => vec![offset as i64],
+        Instruction::TableSwitch {
+            default,
+            ref jump_offsets,
+            ..
+        } => [default]
+            .iter()
+            .chain(jump_offsets)
+            .map(|offset| *offset as i64)
+            .collect(),
+        Instruction::LookupSwitch {
+            default,
+            ref match_offset_pairs,
+            ..
+        } => [default]
+            .into_iter()
+            .chain(match_offset_pairs.iter().map(|(_, offset)| *offset))
+            .map(|offset| offset as i64)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    offsets
+        .into_iter()
+        .map(|offset| (pc as i64 + offset) as u16)
+        .collect()
+}
+
+// the keywords for the flags set, followed by a space, with any other flags as a number
+fn flags(table: &[(&str, u16)], access_flags: u16) -> String {
+    let mut text = String::new();
+    let mut rest = access_flags;
+
+    for (keyword, flag) in table {
+        if access_flags & flag != 0 {
+            text.push_str(keyword);
+            text.push(' ');
+            rest &= !flag;
+        }
+    }
+
+    if rest != 0 {
+        text.push_str(&format!("0x{rest:04x} "));
+    }
+
+    text
+}
+
+// a string literal, escaping everything outside printable ASCII by UTF-16 code unit
+fn quote(string: &JStr) -> String {
+    let mut text = String::from('"');
+
+    for unit in string.utf16_units() {
+        match unit {
+            0x22 => text.push_str("\\\""),
+            0x5c => text.push_str("\\\\"),
+            0x0a => text.push_str("\\n"),
+            0x09 => text.push_str("\\t"),
+            0x0d => text.push_str("\\r"),
+            0x20..=0x7e => text.push(unit as u8 as char),
+            _ => text.push_str(&format!("\\u{unit:04x}")),
+        }
+    }
+
+    text.push('"');
+    text
+}
+
+fn float(value: f64, digits: String) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else {
+        digits
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+fn u2(bytes: &[u8], position: usize, attribute: &str) -> AsmResult<u16> {
+    bytes
+        .get(position..position + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| malformed(attribute))
+}
+
+fn u4(bytes: &[u8], position: usize, attribute: &str) -> AsmResult<u32> {
+    bytes
+        .get(position..position + 4)
+        .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| malformed(attribute))
+}
+
+fn malformed(name: &str) -> AsmError {
+    AsmError::MalformedAttribute {
+        name: name.to_string(),
+    }
+}
@@ -0,0 +1,61 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use ironjvm_cfparser::error::ParseError;
+use ironjvm_cfparser::frames::error::FrameError;
+
+#[derive(Debug)]
+pub enum AsmError {
+    BranchOutOfRange { line: usize },
+    DuplicateLabel { line: usize },
+    ExpectedOperand { line: usize },
+    Frames { method: String, error: FrameError },
+    InvalidConstant { line: usize },
+    InvalidDescriptor { line: usize },
+    InvalidEscape { line: usize },
+    InvalidFlag { line: usize },
+    InvalidHex { line: usize },
+    InvalidNumber { line: usize },
+    InvalidReference { line: usize },
+    InvalidSwitch { line: usize },
+    MalformedAttribute { name: String },
+    MismatchedConstant { line: usize },
+    MissingClassDirective,
+    Parse(ParseError),
+    TooManyConstants,
+    TrailingOperand { line: usize },
+    UndefinedLabel { line: usize },
+    UnexpectedDirective { line: usize },
+    UnexpectedInstruction { line: usize },
+    UnknownDirective { line: usize },
+    UnknownInstruction { line: usize },
+    UnterminatedMethod,
+    UnterminatedString { line: usize },
+    UnterminatedSwitch { line: usize },
+    Utf8TooLong,
+}
+
+impl From<ParseError> for AsmError {
+    fn from(error: ParseError) -> Self {
+        Self::Parse(error)
+    }
+}
+
+pub type AsmResult<T> = Result<T, AsmError>;
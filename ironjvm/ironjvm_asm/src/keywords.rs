@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// The keywords for access flags and method handle kinds, shared by the assembler and the
+// disassembler.
+
+pub(crate) const CLASS_FLAGS: [(&str, u16); 9] = [
+    ("public", 0x0001),
+    ("final", 0x0010),
+    ("super", 0x0020),
+    ("interface", 0x0200),
+    ("abstract", 0x0400),
+    ("synthetic", 0x1000),
+    ("annotation", 0x2000),
+    ("enum", 0x4000),
+    ("module", 0x8000),
+];
+
+pub(crate) const FIELD_FLAGS: [(&str, u16); 9] = [
+    ("public", 0x0001),
+    ("private", 0x0002),
+    ("protected", 0x0004),
+    ("static", 0x0008),
+    ("final", 0x0010),
+    ("volatile", 0x0040),
+    ("transient", 0x0080),
+    ("synthetic", 0x1000),
+    ("enum", 0x4000),
+];
+
+pub(crate) const METHOD_FLAGS: [(&str, u16); 12] = [
+    ("public", 0x0001),
+    ("private", 0x0002),
+    ("protected", 0x0004),
+    ("static", 0x0008),
+    ("final", 0x0010),
+    ("synchronized", 0x0020),
+    ("bridge", 0x0040),
+    ("varargs", 0x0080),
+    ("native", 0x0100),
+    ("abstract", 0x0400),
+    ("strict", 0x0800),
+    ("synthetic", 0x1000),
+];
+
+// the mnemonics of the instructions each reference kind behaves like, with newinvokespecial for
+// constructors
+pub(crate) const REFERENCE_KINDS: [(&str, u8); 9] = [
+    ("getfield", 1),
+    ("getstatic", 2),
+    ("putfield", 3),
+    ("putstatic", 4),
+    ("invokevirtual", 5),
+    ("invokestatic", 6),
+    ("invokespecial", 7),
+    ("newinvokespecial", 8),
+    ("invokeinterface", 9),
+];
+
+pub(crate) fn flag(flags: &[(&str, u16)], keyword: &str) -> Option<u16> {
+    flags
+        .iter()
+        .find(|(name, _)| *name == keyword)
+        .map(|(_, flag)| *flag)
+}
+
+pub(crate) fn reference_kind(keyword: &str) -> Option<u8> {
+    REFERENCE_KINDS
+        .iter()
+        .find(|(name, _)| *name == keyword)
+        .map(|(_, kind)| *kind)
+}
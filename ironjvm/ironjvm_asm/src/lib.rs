@@ -0,0 +1,65 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+//! Assembler and disassembler for Java class files in a textual format modelled on Jasmin, for
+//! writing conformance tests, including deliberately malformed classes.
+//!
+//! ```text
+//! .version 62 0
+//! .class public super Hello
+//! .super java/lang/Object
+//!
+//! .field private static final GREETING Ljava/lang/String; = "Hello, World!"
+//!
+//! .method public static main([Ljava/lang/String;)V
+//!     getstatic java/lang/System/out Ljava/io/PrintStream;
+//!     ldc "Hello, World!"
+//!     invokevirtual java/io/PrintStream/println(Ljava/lang/String;)V
+//!     return
+//! .end method
+//! ```
+//!
+//! Constant pool entries are written symbolically and added as they are used. Branches refer to
+//! labels, and `.catch <class|all> from <label> to <label> using <label>` adds an exception
+//! handler. `.limit stack` and `.limit locals` are computed when left out, and a StackMapTable is
+//! computed for every method of a class version 50 or later that does not have one already.
+//! `.attribute <name> <hex>` adds an attribute with raw contents to the class, or to the field or
+//! method it follows, and `.codeattribute` adds one to the Code attribute of a method.
+//!
+//! A numeric constant takes its type from its literal alone: `1` is an int, `1L` a long, `1.0f` a
+//! float, and `1.0`, `1e3` or `1d` a double. Nothing is widened to fit the instruction, so
+//! `ldc 1L` and `ldc2_w 1` are errors, as ldc and ldc_w only load single-slot constants and
+//! ldc2_w only longs and doubles.
+//!
+//! Raw attributes refer to the constant pool by index, so `.constant <index> <entry>` lays out
+//! the pool ahead of the class, as in `.constant 7 class #23` or `.constant 23 utf8 "Hello"`.
+//! Symbolic constants reuse these entries, and a raw BootstrapMethods attribute likewise fixes
+//! the order of the bootstrap methods. The disassembler writes the constant pool this way.
+
+#![feature(let_else)]
+
+pub mod assembler;
+pub mod disassembler;
+pub mod error;
+
+mod keywords;
+mod opcodes;
+mod pool;
+mod token;
@@ -0,0 +1,122 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process;
+
+use ironjvm_asm::assembler;
+use ironjvm_asm::disassembler;
+use ironjvm_cfck::ClassFileChecker;
+use ironjvm_cfparser::ClassFileParser;
+
+const USAGE: &str = "usage: ironjvm-asm [-d <directory>] [--check] <file.j>...
+       ironjvm-asm --disassemble <file.class>...";
+
+fn main() {
+    let mut directory = PathBuf::from(".");
+    let mut check = false;
+    let mut disassemble = false;
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-d" => match args.next() {
+                Some(path) => directory = PathBuf::from(path),
+                None => usage(),
+            },
+            "--check" => check = true,
+            "--disassemble" => disassemble = true,
+            _ if arg.starts_with('-') => usage(),
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        usage();
+    }
+
+    let mut failed = false;
+    for file in &files {
+        let result = if disassemble {
+            disassemble_file(file)
+        } else {
+            assemble_file(file, &directory, check)
+        };
+
+        if let Err(error) = result {
+            eprintln!("{file}: {error}");
+            failed = true;
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(2);
+}
+
+// writes the class to its binary name under the directory, then checks it if asked to; classes
+// failing the check are still written, as they are often malformed on purpose
+fn assemble_file(file: &str, directory: &Path, check: bool) -> Result<(), String> {
+    let source = fs::read_to_string(file).map_err(|error| error.to_string())?;
+    let bytes = assembler::assemble(&source).map_err(|error| format!("{error:?}"))?;
+
+    let class = ClassFileParser::new(&bytes)
+        .parse_lazy()
+        .map_err(|error| format!("{error:?}"))?;
+    let name = class
+        .this_class_name()
+        .ok()
+        .and_then(|name| name.to_str())
+        .ok_or("this class has no valid name")?;
+
+    let path = directory.join(format!("{name}.class"));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| error.to_string())?;
+    }
+    fs::write(&path, &bytes).map_err(|error| error.to_string())?;
+
+    if check {
+        let classfile = ClassFileParser::new(&bytes)
+            .parse()
+            .map_err(|error| format!("{error:?}"))?;
+        ClassFileChecker::new(classfile)
+            .check()
+            .map_err(|error| format!("{error:?}"))?;
+    }
+
+    Ok(())
+}
+
+fn disassemble_file(file: &str) -> Result<(), String> {
+    let bytes = fs::read(file).map_err(|error| error.to_string())?;
+    let source = disassembler::disassemble(&bytes).map_err(|error| format!("{error:?}"))?;
+
+    print!("{source}");
+    Ok(())
+}
@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// The mnemonics of the instructions and the kinds of operands they take, shared by the assembler
+// and the disassembler.
+
+pub(crate) const MNEMONICS: [&str; 202] = [
+    "nop",
+    "aconst_null",
+    "iconst_m1",
+    "iconst_0",
+    "iconst_1",
+    "iconst_2",
+    "iconst_3",
+    "iconst_4",
+    "iconst_5",
+    "lconst_0",
+    "lconst_1",
+    "fconst_0",
+    "fconst_1",
+    "fconst_2",
+    "dconst_0",
+    "dconst_1",
+    "bipush",
+    "sipush",
+    "ldc",
+    "ldc_w",
+    "ldc2_w",
+    "iload",
+    "lload",
+    "fload",
+    "dload",
+    "aload",
+    "iload_0",
+    "iload_1",
+    "iload_2",
+    "iload_3",
+    "lload_0",
+    "lload_1",
+    "lload_2",
+    "lload_3",
+    "fload_0",
+    "fload_1",
+    "fload_2",
+    "fload_3",
+    "dload_0",
+    "dload_1",
+    "dload_2",
+    "dload_3",
+    "aload_0",
+    "aload_1",
+    "aload_2",
+    "aload_3",
+    "iaload",
+    "laload",
+    "faload",
+    "daload",
+    "aaload",
+    "baload",
+    "caload",
+    "saload",
+    "istore",
+    "lstore",
+    "fstore",
+    "dstore",
+    "astore",
+    "istore_0",
+    "istore_1",
+    "istore_2",
+    "istore_3",
+    "lstore_0",
+    "lstore_1",
+    "lstore_2",
+    "lstore_3",
+    "fstore_0",
+    "fstore_1",
+    "fstore_2",
+    "fstore_3",
+    "dstore_0",
+    "dstore_1",
+    "dstore_2",
+    "dstore_3",
+    "astore_0",
+    "astore_1",
+    "astore_2",
+    "astore_3",
+    "iastore",
+    "lastore",
+    "fastore",
+    "dastore",
+    "aastore",
+    "bastore",
+    "castore",
+    "sastore",
+    "pop",
+    "pop2",
+    "dup",
+    "dup_x1",
+    "dup_x2",
+    "dup2",
+    "dup2_x1",
+    "dup2_x2",
+    "swap",
+    "iadd",
+    "ladd",
+    "fadd",
+    "dadd",
+    "isub",
+    "lsub",
+    "fsub",
+    "dsub",
+    "imul",
+    "lmul",
+    "fmul",
+    "dmul",
+    "idiv",
+    "ldiv",
+    "fdiv",
+    "ddiv",
+    "irem",
+    "lrem",
+    "frem",
+    "drem",
+    "ineg",
+    "lneg",
+    "fneg",
+    "dneg",
+    "ishl",
+    "lshl",
+    "ishr",
+    "lshr",
+    "iushr",
+    "lushr",
+    "iand",
+    "land",
+    "ior",
+    "lor",
+    "ixor",
+    "lxor",
+    "iinc",
+    "i2l",
+    "i2f",
+    "i2d",
+    "l2i",
+    "l2f",
+    "l2d",
+    "f2i",
+    "f2l",
+    "f2d",
+    "d2i",
+    "d2l",
+    "d2f",
+    "i2b",
+    "i2c",
+    "i2s",
+    "lcmp",
+    "fcmpl",
+    "fcmpg",
+    "dcmpl",
+    "dcmpg",
+    "ifeq",
+    "ifne",
+    "iflt",
+    "ifge",
+    "ifgt",
+    "ifle",
+    "if_icmpeq",
+    "if_icmpne",
+    "if_icmplt",
+    "if_icmpge",
+    "if_icmpgt",
+    "if_icmple",
+    "if_acmpeq",
+    "if_acmpne",
+    "goto",
+    "jsr",
+    "ret",
+    "tableswitch",
+    "lookupswitch",
+    "ireturn",
+    "lreturn",
+    "freturn",
+    "dreturn",
+    "areturn",
+    "return",
+    "getstatic",
+    "putstatic",
+    "getfield",
+    "putfield",
+    "invokevirtual",
+    "invokespecial",
+    "invokestatic",
+    "invokeinterface",
+    "invokedynamic",
+    "new",
+    "newarray",
+    "anewarray",
+    "arraylength",
+    "athrow",
+    "checkcast",
+    "instanceof",
+    "monitorenter",
+    "monitorexit",
+    "wide",
+    "multianewarray",
+    "ifnull",
+    "ifnonnull",
+    "goto_w",
+    "jsr_w",
+];
+
+// the element types of newarray, by atype
+pub(crate) const ARRAY_TYPES: [(&str, u8); 8] = [
+    ("boolean", 4),
+    ("char", 5),
+    ("float", 6),
+    ("double", 7),
+    ("byte", 8),
+    ("short", 9),
+    ("int", 10),
+    ("long", 11),
+];
+
+pub(crate) const WIDE: u8 = 0xc4;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Operands {
+    None,
+    Byte,
+    Short,
+    // ldc, which is widened to ldc_w for constants past index 255
+    Constant,
+    WideConstant,
+    // a local variable index, which is widened past index 255
+    Local,
+    Increment,
+    Branch,
+    WideBranch,
+    TableSwitch,
+    LookupSwitch,
+    Field,
+    Method,
+    InterfaceMethod,
+    Dynamic,
+    Class,
+    ArrayType,
+    MultiArray,
+}
+
+pub(crate) fn opcode(mnemonic: &str) -> Option<u8> {
+    MNEMONICS
+        .iter()
+        .position(|candidate| *candidate == mnemonic)
+        .map(|opcode| opcode as u8)
+}
+
+pub(crate) fn operands(opcode: u8) -> Operands {
+    match opcode {
+        0x10 => Operands::Byte,
+        0x11 => Operands::Short,
+        0x12 => Operands::Constant,
+        0x13 | 0x14 => Operands::WideConstant,
+        0x15..=0x19 | 0x36..=0x3a | 0xa9 => Operands::Local,
+        0x84 => Operands::Increment,
+        0x99..=0xa8 | 0xc6 | 0xc7 => Operands::Branch,
+        0xc8 | 0xc9 => Operands::WideBranch,
+        0xaa => Operands::TableSwitch,
+        0xab => Operands::LookupSwitch,
+        0xb2..=0xb5 => Operands::Field,
+        0xb6..=0xb8 => Operands::Method,
+        0xb9 => Operands::InterfaceMethod,
+        0xba => Operands::Dynamic,
+        0xbb | 0xbd | 0xc0 | 0xc1 => Operands::Class,
+        0xbc => Operands::ArrayType,
+        0xc5 => Operands::MultiArray,
+        _ => Operands::None,
+    }
+}
@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use std::collections::HashMap;
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+
+use crate::error::AsmError;
+use crate::error::AsmResult;
+
+// the constants of a class being assembled, each added once
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) enum Constant {
+    Utf8(JString),
+    Integer(u32),
+    Float(u32),
+    Long(u64),
+    Double(u64),
+    Class(u16),
+    String(u16),
+    FieldRef(u16, u16),
+    MethodRef(u16, u16),
+    InterfaceMethodRef(u16, u16),
+    NameAndType(u16, u16),
+    MethodHandle(u8, u16),
+    MethodType(u16),
+    Dynamic(u16, u16),
+    InvokeDynamic(u16, u16),
+    Module(u16),
+    Package(u16),
+}
+
+#[derive(Default)]
+pub(crate) struct ConstantPool {
+    // the number of slots taken so far, longs and doubles taking two
+    count: u16,
+    bytes: Vec<u8>,
+    indices: HashMap<Constant, u16>,
+}
+
+impl ConstantPool {
+    pub(crate) fn add(&mut self, constant: Constant) -> AsmResult<u16> {
+        if let Some(index) = self.indices.get(&constant) {
+            return Ok(*index);
+        }
+
+        self.push(constant)
+    }
+
+    // adds `constant` at the next index even if it is already in the pool, as a `.constant`
+    // directive may repeat an entry of the class it was disassembled from
+    pub(crate) fn push(&mut self, constant: Constant) -> AsmResult<u16> {
+        let index = self.count + 1;
+        let slots = match constant {
+            Constant::Long(_) | Constant::Double(_) => 2,
+            _ => 1,
+        };
+        if index as u32 + slots > u16::MAX as u32 {
+            return Err(AsmError::TooManyConstants);
+        }
+
+        match &constant {
+            Constant::Utf8(string) => {
+                let bytes = string.as_bytes();
+                let length = u16::try_from(bytes.len()).map_err(|_| AsmError::Utf8TooLong)?;
+
+                self.bytes.push(1);
+                self.bytes.extend_from_slice(&length.to_be_bytes());
+                self.bytes.extend_from_slice(bytes);
+            }
+            Constant::Integer(bits) => self.write(3, &bits.to_be_bytes()),
+            Constant::Float(bits) => self.write(4, &bits.to_be_bytes()),
+            Constant::Long(bits) => self.write(5, &bits.to_be_bytes()),
+            Constant::Double(bits) => self.write(6, &bits.to_be_bytes()),
+            Constant::Class(name_index) => self.write(7, &name_index.to_be_bytes()),
+            Constant::String(string_index) => self.write(8, &string_index.to_be_bytes()),
+            Constant::FieldRef(class_index, name_and_type_index) => {
+                self.write_pair(9, *class_index, *name_and_type_index)
+            }
+            Constant::MethodRef(class_index, name_and_type_index) => {
+                self.write_pair(10, *class_index, *name_and_type_index)
+            }
+            Constant::InterfaceMethodRef(class_index, name_and_type_index) => {
+                self.write_pair(11, *class_index, *name_and_type_index)
+            }
+            Constant::NameAndType(name_index, descriptor_index) => {
+                self.write_pair(12, *name_index, *descriptor_index)
+            }
+            Constant::MethodHandle(reference_kind, reference_index) => {
+                self.write(15, &[*reference_kind]);
+                self.bytes.extend_from_slice(&reference_index.to_be_bytes());
+            }
+            Constant::MethodType(descriptor_index) => {
+                self.write(16, &descriptor_index.to_be_bytes())
+            }
+            Constant::Dynamic(bootstrap_method_attr_index, name_and_type_index) => {
+                self.write_pair(17, *bootstrap_method_attr_index, *name_and_type_index)
+            }
+            Constant::InvokeDynamic(bootstrap_method_attr_index, name_and_type_index) => {
+                self.write_pair(18, *bootstrap_method_attr_index, *name_and_type_index)
+            }
+            Constant::Module(name_index) => self.write(19, &name_index.to_be_bytes()),
+            Constant::Package(name_index) => self.write(20, &name_index.to_be_bytes()),
+        }
+
+        self.count += slots as u16;
+        self.indices.entry(constant).or_insert(index);
+
+        Ok(index)
+    }
+
+    pub(crate) fn next_index(&self) -> u16 {
+        self.count + 1
+    }
+
+    pub(crate) fn utf8(&mut self, string: &str) -> AsmResult<u16> {
+        self.add(Constant::Utf8(JString::from(string)))
+    }
+
+    pub(crate) fn jutf8(&mut self, string: &JStr) -> AsmResult<u16> {
+        self.add(Constant::Utf8(string.to_owned()))
+    }
+
+    pub(crate) fn class(&mut self, name: &str) -> AsmResult<u16> {
+        let name_index = self.utf8(name)?;
+        self.add(Constant::Class(name_index))
+    }
+
+    pub(crate) fn name_and_type(&mut self, name: &str, descriptor: &str) -> AsmResult<u16> {
+        let name_index = self.utf8(name)?;
+        let descriptor_index = self.utf8(descriptor)?;
+
+        self.add(Constant::NameAndType(name_index, descriptor_index))
+    }
+
+    /// The bytes of `constant_pool_count` and the `constant_pool` table.
+    pub(crate) fn encode(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&(self.count + 1).to_be_bytes());
+        output.extend_from_slice(&self.bytes);
+    }
+
+    fn write(&mut self, tag: u8, bytes: &[u8]) {
+        self.bytes.push(tag);
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn write_pair(&mut self, tag: u8, first: u16, second: u16) {
+        self.bytes.push(tag);
+        self.bytes.extend_from_slice(&first.to_be_bytes());
+        self.bytes.extend_from_slice(&second.to_be_bytes());
+    }
+}
+
+// the entries of a BootstrapMethods attribute
+pub(crate) fn bootstrap_methods(info: &[u8]) -> Option<Vec<(u16, Vec<u16>)>> {
+    let read = |position: usize| {
+        Some(u16::from_be_bytes(
+            info.get(position..position + 2)?.try_into().unwrap(),
+        ))
+    };
+
+    let mut bootstrap_methods = Vec::new();
+    let mut position = 2;
+    for _ in 0..read(0)? {
+        let method_handle = read(position)?;
+        let count = read(position + 2)?;
+        let arguments = (0..count as usize)
+            .map(|index| read(position + 4 + index * 2))
+            .collect::<Option<_>>()?;
+
+        bootstrap_methods.push((method_handle, arguments));
+        position += 4 + count as usize * 2;
+    }
+
+    Some(bootstrap_methods)
+}
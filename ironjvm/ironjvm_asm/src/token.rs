@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+use crate::error::AsmError;
+use crate::error::AsmResult;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) enum Token {
+    Word(String),
+    // kept as UTF-16 code units, so that unpaired surrogates survive a round trip
+    String(Vec<u16>),
+}
+
+#[derive(Debug)]
+pub(crate) struct Line {
+    pub(crate) number: usize,
+    pub(crate) tokens: Vec<Token>,
+}
+
+/// Splits the source into its non-empty lines of tokens. A `;` starting a token begins a comment,
+/// so descriptors such as `Ljava/lang/Object;` are left alone.
+pub(crate) fn tokenize(source: &str) -> AsmResult<Vec<Line>> {
+    let mut lines = Vec::new();
+
+    for (index, text) in source.lines().enumerate() {
+        let number = index + 1;
+        let mut tokens = Vec::new();
+
+        let mut chars = text.chars().peekable();
+        while let Some(&char) = chars.peek() {
+            match char {
+                ';' => break,
+                '"' => {
+                    chars.next();
+                    tokens.push(Token::String(string(&mut chars, number)?));
+                }
+                _ if char.is_whitespace() => {
+                    chars.next();
+                }
+                _ => {
+                    let mut word = String::new();
+                    while let Some(char) = chars.next_if(|char| !char.is_whitespace()) {
+                        word.push(char);
+                    }
+
+                    tokens.push(Token::Word(word));
+                }
+            }
+        }
+
+        if !tokens.is_empty() {
+            lines.push(Line { number, tokens });
+        }
+    }
+
+    Ok(lines)
+}
+
+fn string(chars: &mut Peekable<Chars>, line: usize) -> AsmResult<Vec<u16>> {
+    let mut units = Vec::new();
+    let mut buffer = [0; 2];
+
+    loop {
+        let char = match chars.next() {
+            Some('"') => return Ok(units),
+            Some('\\') => match chars.next() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some('b') => '\u{8}',
+                Some('f') => '\u{c}',
+                Some('0') => '\0',
+                Some('"') => '"',
+                Some('\'') => '\'',
+                Some('\\') => '\\',
+                Some('u') => {
+                    let digits = chars.by_ref().take(4).collect::<String>();
+                    let unit = (digits.len() == 4)
+                        .then(|| u16::from_str_radix(&digits, 16).ok())
+                        .flatten()
+                        .ok_or(AsmError::InvalidEscape { line })?;
+
+                    units.push(unit);
+                    continue;
+                }
+                _ => return Err(AsmError::InvalidEscape { line }),
+            },
+            Some(char) => char,
+            None => return Err(AsmError::UnterminatedString { line }),
+        };
+
+        units.extend_from_slice(char.encode_utf16(&mut buffer));
+    }
+}
+
+/// The operands of a line, consumed from left to right.
+pub(crate) struct Cursor<'a> {
+    tokens: &'a [Token],
+    pub(crate) line: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub(crate) fn new(tokens: &'a [Token], line: usize) -> Self {
+        Self { tokens, line }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.tokens.len()
+    }
+
+    pub(crate) fn next(&mut self) -> Option<&'a Token> {
+        let (token, rest) = self.tokens.split_first()?;
+        self.tokens = rest;
+
+        Some(token)
+    }
+
+    pub(crate) fn peek_word(&self) -> Option<&'a str> {
+        match self.tokens.first() {
+            Some(Token::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    // the word `index` operands before the last one
+    pub(crate) fn peek_back(&self, index: usize) -> Option<&'a str> {
+        match self.tokens.iter().rev().nth(index) {
+            Some(Token::Word(word)) => Some(word),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn word(&mut self) -> AsmResult<&'a str> {
+        match self.next() {
+            Some(Token::Word(word)) => Ok(word),
+            _ => Err(AsmError::ExpectedOperand { line: self.line }),
+        }
+    }
+
+    pub(crate) fn string(&mut self) -> AsmResult<&'a [u16]> {
+        match self.next() {
+            Some(Token::String(units)) => Ok(units),
+            _ => Err(AsmError::ExpectedOperand { line: self.line }),
+        }
+    }
+
+    pub(crate) fn number<T: TryFrom<i64>>(&mut self) -> AsmResult<T> {
+        let line = self.line;
+
+        parse_integer(self.word()?)
+            .and_then(|number| T::try_from(number).ok())
+            .ok_or(AsmError::InvalidNumber { line })
+    }
+
+    pub(crate) fn end(&self) -> AsmResult<()> {
+        if !self.tokens.is_empty() {
+            return Err(AsmError::TrailingOperand { line: self.line });
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a decimal or `0x`-prefixed hexadecimal integer, either of which may be negative.
+pub(crate) fn parse_integer(word: &str) -> Option<i64> {
+    let (negative, digits) = match word.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, word),
+    };
+
+    let magnitude = match digits.strip_prefix("0x") {
+        Some(digits) => i64::from_str_radix(digits, 16).ok()?,
+        None => digits.parse::<i64>().ok()?,
+    };
+
+    Some(if negative { -magnitude } else { magnitude })
+}
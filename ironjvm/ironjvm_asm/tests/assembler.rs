@@ -0,0 +1,97 @@
+use ironjvm_asm::assembler::assemble;
+use ironjvm_asm::error::AsmError;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+
+#[test]
+fn unknown_attributes() {
+    // the frames are computed from the assembled class, which has to parse with them in it
+    let bytes = assemble(
+        r#"
+.version 61 0
+.class public super Unknown
+.super java/lang/Object
+.attribute Foo 00
+
+.method public static choose(Z)I
+    .attribute Bar 0102
+    iload_0
+    ifeq Zero
+    iconst_1
+    ireturn
+Zero:
+    iconst_0
+    ireturn
+.end method
+"#,
+    )
+    .unwrap();
+
+    let lazy = ClassFileParser::new(&bytes).parse_lazy().unwrap();
+    let foo = lazy.attribute("Foo").unwrap().unwrap();
+    assert!(matches!(
+        foo.info,
+        AttributeInfoType::UnknownAttribute { info: [0x00] }
+    ));
+
+    let bar = lazy.methods[0]
+        .attribute(&lazy.constant_pool, "Bar")
+        .unwrap()
+        .unwrap();
+    assert!(matches!(
+        bar.info,
+        AttributeInfoType::UnknownAttribute { info: [0x01, 0x02] }
+    ));
+    assert!(lazy.methods[0]
+        .attribute(&lazy.constant_pool, "Code")
+        .unwrap()
+        .is_some());
+}
+
+#[test]
+fn constant_categories() {
+    let class = |instruction: &str| {
+        format!(
+            r#"
+.version 61 0
+.class public super Constants
+.super java/lang/Object
+
+.method public static load()V
+    .limit stack 2
+    {instruction}
+    return
+.end method
+"#
+        )
+    };
+
+    for valid in [
+        "ldc 1",
+        "ldc 1.5f",
+        "ldc \"one\"",
+        "ldc_w 1",
+        "ldc2_w 1L",
+        "ldc2_w 1.5",
+    ] {
+        assert!(assemble(&class(valid)).is_ok(), "{valid}");
+    }
+
+    // literals are never widened, so the constant has to match the instruction
+    for invalid in [
+        "ldc 1L",
+        "ldc 3.5",
+        "ldc_w 1e3",
+        "ldc2_w 1",
+        "ldc2_w 1.5f",
+        "ldc2_w \"one\"",
+    ] {
+        assert!(
+            matches!(
+                assemble(&class(invalid)),
+                Err(AsmError::MismatchedConstant { line: 8 })
+            ),
+            "{invalid}"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// every fixture starts with the line `; expect: <error>`, where the error is what `--check`
+// reports after the file name, or with `; expect: pass` for a class that passes the check
+#[test]
+fn fixtures() {
+    let directory = std::env::temp_dir().join("ironjvm-asm-check-test");
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/check");
+
+    let mut paths = fs::read_dir(&fixtures)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    for path in paths {
+        let source = fs::read_to_string(&path).unwrap();
+        let expected = source
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix("; expect: "))
+            .unwrap_or_else(|| panic!("{} has no expectation", path.display()));
+
+        let output = Command::new(env!("CARGO_BIN_EXE_ironjvm-asm"))
+            .arg("-d")
+            .arg(&directory)
+            .arg("--check")
+            .arg(&path)
+            .output()
+            .unwrap();
+
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        if expected == "pass" {
+            assert!(output.status.success(), "{}: {stderr}", path.display());
+            assert_eq!(stderr, "");
+        } else {
+            assert!(!output.status.success(), "{}", path.display());
+            assert_eq!(stderr, format!("{}: {expected}\n", path.display()));
+        }
+    }
+}
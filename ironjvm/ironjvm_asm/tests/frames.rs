@@ -0,0 +1,55 @@
+use std::fs;
+use std::process::Command;
+
+use ironjvm_asm::assembler::assemble;
+
+const DEAD_CODE: &str = r#"
+.version 52 0
+.class public super DeadCode
+.super java/lang/Object
+
+.method public static value()I
+TryStart:
+    iconst_1
+    ireturn
+    iconst_2
+    pop
+    goto TryStart
+TryEnd:
+Handler:
+    athrow
+    .catch java/lang/Throwable from TryStart to TryEnd using Handler
+.end method
+
+.method public static main([Ljava/lang/String;)V
+    getstatic java/lang/System/out Ljava/io/PrintStream;
+    invokestatic DeadCode/value()I
+    invokevirtual java/io/PrintStream/println(I)V
+    return
+.end method
+"#;
+
+#[test]
+fn unreachable_code() {
+    let classpath = std::env::temp_dir().join("ironjvm-asm-frames-test");
+    fs::create_dir_all(&classpath).unwrap();
+    fs::write(
+        classpath.join("DeadCode.class"),
+        assemble(DEAD_CODE).unwrap(),
+    )
+    .unwrap();
+
+    let output = Command::new("java")
+        .arg("-Xverify:all")
+        .arg("-cp")
+        .arg(&classpath)
+        .arg("DeadCode")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "1\n");
+}
@@ -22,21 +22,34 @@
 pub enum CheckError {
     AnnotationFlagWithoutInterfaceFlag,
     ClinitMethodWithoutStaticFlag,
+    DuplicatedClassAttribute,
     DuplicatedField,
     DuplicatedMethod,
     FieldNameIndexNotConstantUtf8,
     FinalAbstractFlagsSetSimultaneously,
     InterfaceFlagWithoutAbstractFlag,
+    InvalidClassName,
     InvalidConstantPoolIndex,
     InvalidFieldAttributes,
     InvalidFieldDescriptor,
     InvalidFieldFlags,
+    InvalidFieldName,
     InvalidFlagsWithInterfaceFlag,
     InvalidInterfaceFieldFlags,
     InvalidInterfaceMethodFlags,
     InvalidMethodFlags,
+    InvalidMethodHandleReference,
+    InvalidMethodName,
     InvalidMinor { minor: u16 },
+    InvalidModuleName,
+    InvalidNestAttributes,
+    InvalidPackageName,
+    InvalidPermittedSubclassesAttribute,
+    InvalidRecordAttribute,
+    InvalidReferenceKind { reference_kind: u8 },
+    MismatchedRecordComponent,
     NotOnlyModuleFlagSet,
+    PermittedSubclassesInFinalClass,
     SuperClassIndexNotConstantClass,
     ThisClassIndexNotConstantClass,
     UnsupportedMajor { major: u16 },
@@ -30,8 +30,13 @@ use ironjvm_javautil::be::JavaBeUtil;
 use ironjvm_javautil::descriptor::method::{MethodDescriptor, ReturnDescriptor};
 use ironjvm_javautil::descriptor::TypeDescriptor;
 use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::names::InternalName;
+use ironjvm_javautil::names::ModuleName;
+use ironjvm_javautil::names::UnqualifiedName;
+use ironjvm_specimpl::classfile::attrinfo::rattr::RecordComponentInfo;
 use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
 use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::cpinfo::ReferenceKind;
 use ironjvm_specimpl::classfile::flags::ClassAccessFlags;
 use ironjvm_specimpl::classfile::flags::FieldAccessFlags;
 use ironjvm_specimpl::classfile::flags::FlagsExt;
@@ -62,14 +67,16 @@ impl<'clazz> ClassFileChecker<'clazz> {
 
     pub fn check(&mut self) -> CheckResult<()> {
         self.check_classfile_version()?;
+        self.check_constant_pool()?;
         self.check_class_access_flags()?;
         self.check_this_class()?;
         self.check_super_class()?;
         self.check_interfaces()?;
+        self.check_class_attributes()?;
         self.check_fields()?;
         self.check_methods()?;
 
-        todo!()
+        Ok(())
     }
 
     fn check_classfile_version(&self) -> CheckResult<()> {
@@ -88,6 +95,131 @@ impl<'clazz> ClassFileChecker<'clazz> {
         Ok(())
     }
 
+    fn check_constant_pool(&self) -> CheckResult<()> {
+        for cp_info in &self.classfile.constant_pool {
+            match cp_info.info {
+                CpInfoType::ConstantClass { name_index }
+                    if self
+                        .constant_utf8(name_index)
+                        .and_then(|name| InternalName::from_class_constant(name).ok())
+                        .is_none() =>
+                {
+                    return Err(CheckError::InvalidClassName);
+                }
+                CpInfoType::ConstantMethodHandle {
+                    reference_kind,
+                    reference_index,
+                } => self.check_method_handle(reference_kind, reference_index)?,
+                CpInfoType::ConstantModule { name_index }
+                    if self
+                        .constant_utf8(name_index)
+                        .and_then(|name| ModuleName::from_jstr(name).ok())
+                        .is_none() =>
+                {
+                    return Err(CheckError::InvalidModuleName);
+                }
+                CpInfoType::ConstantPackage { name_index }
+                    if self
+                        .constant_utf8(name_index)
+                        .and_then(|name| InternalName::from_jstr(name).ok())
+                        .is_none() =>
+                {
+                    return Err(CheckError::InvalidPackageName);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_method_handle(&self, reference_kind: u8, reference_index: u16) -> CheckResult<()> {
+        let Some(reference_kind) = ReferenceKind::from_u8(reference_kind) else {
+            return Err(CheckError::InvalidReferenceKind { reference_kind });
+        };
+
+        let cp_info = |index: u16| {
+            (index as usize)
+                .checked_sub(1)
+                .and_then(|index| self.classfile.constant_pool.get(index))
+                .map(|cp_info| &cp_info.info)
+        };
+
+        let (name_and_type_index, is_valid_reference) = match cp_info(reference_index) {
+            Some(CpInfoType::ConstantFieldRef {
+                name_and_type_index,
+                ..
+            }) => (
+                *name_and_type_index,
+                matches!(
+                    reference_kind,
+                    ReferenceKind::GetField
+                        | ReferenceKind::GetStatic
+                        | ReferenceKind::PutField
+                        | ReferenceKind::PutStatic
+                ),
+            ),
+            Some(CpInfoType::ConstantMethodRef {
+                name_and_type_index,
+                ..
+            }) => (
+                *name_and_type_index,
+                matches!(
+                    reference_kind,
+                    ReferenceKind::InvokeVirtual
+                        | ReferenceKind::InvokeStatic
+                        | ReferenceKind::InvokeSpecial
+                        | ReferenceKind::NewInvokeSpecial
+                ),
+            ),
+            // static and private interface methods may only be referred to since version 52
+            Some(CpInfoType::ConstantInterfaceMethodRef {
+                name_and_type_index,
+                ..
+            }) => (
+                *name_and_type_index,
+                reference_kind == ReferenceKind::InvokeInterface
+                    || (self.state.major >= 52
+                        && matches!(
+                            reference_kind,
+                            ReferenceKind::InvokeStatic | ReferenceKind::InvokeSpecial
+                        )),
+            ),
+            _ => return Err(CheckError::InvalidConstantPoolIndex),
+        };
+
+        if !is_valid_reference {
+            return Err(CheckError::InvalidMethodHandleReference);
+        }
+
+        let Some(CpInfoType::ConstantNameAndType { name_index, .. }) = cp_info(name_and_type_index)
+        else {
+            return Err(CheckError::InvalidConstantPoolIndex);
+        };
+
+        let Some(CpInfoType::ConstantUtf8 { string, .. }) = cp_info(*name_index) else {
+            return Err(CheckError::InvalidConstantPoolIndex);
+        };
+
+        // only REF_newInvokeSpecial refers to constructors, and nothing refers to <clinit>
+        let is_init = string.as_bytes() == b"<init>";
+        let is_clinit = string.as_bytes() == b"<clinit>";
+        match reference_kind {
+            ReferenceKind::NewInvokeSpecial if !is_init => {
+                Err(CheckError::InvalidMethodHandleReference)
+            }
+            ReferenceKind::InvokeVirtual
+            | ReferenceKind::InvokeStatic
+            | ReferenceKind::InvokeSpecial
+            | ReferenceKind::InvokeInterface
+                if is_init || is_clinit =>
+            {
+                Err(CheckError::InvalidMethodHandleReference)
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn check_class_access_flags(&mut self) -> CheckResult<()> {
         let access_flags = self.classfile.access_flags;
 
@@ -106,7 +238,7 @@ impl<'clazz> ClassFileChecker<'clazz> {
         }
 
         if access_flags.flag_set(ClassAccessFlags::ACC_INTERFACE) {
-            if access_flags.flag_set(ClassAccessFlags::ACC_ABSTRACT) {
+            if !access_flags.flag_set(ClassAccessFlags::ACC_ABSTRACT) {
                 return Err(CheckError::InterfaceFlagWithoutAbstractFlag);
             }
 
@@ -195,13 +327,124 @@ impl<'clazz> ClassFileChecker<'clazz> {
         Ok(())
     }
 
+    // nestmates, records and sealed classes are only recognised from Java 11, 16 and 17
+    // respectively, and their attributes are ignored in older class files
+    fn check_class_attributes(&self) -> CheckResult<()> {
+        let mut nest_host = None;
+        let mut nest_members = None;
+        let mut permitted_subclasses = None;
+        let mut record_components = None;
+
+        for attribute in &self.classfile.attributes {
+            let is_duplicate = match &attribute.info {
+                AttributeInfoType::NestHostAttribute { host_class_index }
+                    if self.state.major >= 55 =>
+                {
+                    nest_host.replace(*host_class_index).is_some()
+                }
+                AttributeInfoType::NestMembersAttribute { classes, .. }
+                    if self.state.major >= 55 =>
+                {
+                    nest_members.replace(*classes).is_some()
+                }
+                AttributeInfoType::PermittedSubclassesAttribute { classes, .. }
+                    if self.state.major >= 61 =>
+                {
+                    permitted_subclasses.replace(*classes).is_some()
+                }
+                AttributeInfoType::RecordAttribute { components, .. } if self.state.major >= 60 => {
+                    record_components.replace(components).is_some()
+                }
+                _ => false,
+            };
+
+            if is_duplicate {
+                return Err(CheckError::DuplicatedClassAttribute);
+            }
+        }
+
+        if nest_host.is_some() && nest_members.is_some()
+            || matches!(nest_host, Some(index) if !self.is_constant_class(index))
+            || nest_members
+                .into_iter()
+                .flatten()
+                .any(|index| !self.is_constant_class(index.to_u16()))
+        {
+            return Err(CheckError::InvalidNestAttributes);
+        }
+
+        if let Some(classes) = permitted_subclasses {
+            if self
+                .classfile
+                .access_flags
+                .flag_set(ClassAccessFlags::ACC_FINAL)
+            {
+                return Err(CheckError::PermittedSubclassesInFinalClass);
+            }
+
+            if classes
+                .iter()
+                .any(|index| !self.is_constant_class(index.to_u16()))
+            {
+                return Err(CheckError::InvalidPermittedSubclassesAttribute);
+            }
+        }
+
+        if let Some(components) = record_components {
+            self.check_record_components(components)?;
+        }
+
+        Ok(())
+    }
+
+    fn check_record_components(&self, components: &[RecordComponentInfo]) -> CheckResult<()> {
+        let mut names = BTreeSet::new();
+
+        for component in components {
+            let (Some(name), Some(descriptor)) = (
+                self.constant_utf8(component.name_index),
+                self.constant_utf8(component.descriptor_index),
+            ) else {
+                return Err(CheckError::InvalidRecordAttribute);
+            };
+
+            if !names.insert(name) || !self.check_field_descriptor(descriptor) {
+                return Err(CheckError::InvalidRecordAttribute);
+            }
+
+            // every component is backed by an instance field and an accessor method of the same
+            // name and type
+            let has_field = self.classfile.fields.iter().any(|field| {
+                !field.access_flags.flag_set(FieldAccessFlags::ACC_STATIC)
+                    && self.constant_utf8(field.name_index) == Some(name)
+                    && self.constant_utf8(field.descriptor_index) == Some(descriptor)
+            });
+
+            let has_accessor = self.classfile.methods.iter().any(|method| {
+                let returns_component = self
+                    .constant_utf8(method.descriptor_index)
+                    .and_then(|method_descriptor| method_descriptor.as_bytes().strip_prefix(b"()"))
+                    == Some(descriptor.as_bytes());
+
+                !method.access_flags.flag_set(MethodAccessFlags::ACC_STATIC)
+                    && self.constant_utf8(method.name_index) == Some(name)
+                    && returns_component
+            });
+
+            if !has_field || !has_accessor {
+                return Err(CheckError::MismatchedRecordComponent);
+            }
+        }
+
+        Ok(())
+    }
+
     fn check_fields(&self) -> CheckResult<()> {
         self.check_field_duplicates()?;
+        self.check_field_names()?;
         self.check_field_access_flags()?;
 
-        let mut fields_iter = self.classfile.fields.iter();
-
-        if fields_iter.any(|field| {
+        if self.classfile.fields.iter().any(|field| {
             self.classfile
                 .constant_pool
                 .get((field.name_index - 1) as usize)
@@ -217,9 +460,9 @@ impl<'clazz> ClassFileChecker<'clazz> {
             return Err(CheckError::FieldNameIndexNotConstantUtf8);
         }
 
-        if fields_iter.any(|field| {
+        if self.classfile.fields.iter().any(|field| {
             let descriptor_index = field.descriptor_index;
-            let Some(CpInfoType::ConstantUtf8 { bytes, .. }) = self.classfile
+            let Some(CpInfoType::ConstantUtf8 { string, .. }) = self.classfile
                 .constant_pool
                 .get((descriptor_index - 1) as usize)
                 .filter(|some| {
@@ -233,33 +476,33 @@ impl<'clazz> ClassFileChecker<'clazz> {
                 return false;
             };
 
-            !self.check_field_descriptor(unsafe { JStr::from_jutf8_unchecked(bytes) })
+            !self.check_field_descriptor(string)
         }) {
             return Err(CheckError::InvalidFieldDescriptor);
         }
 
-        if fields_iter.any(|field| !self.check_field_attributes(field)) {
+        if self
+            .classfile
+            .fields
+            .iter()
+            .any(|field| !self.check_field_attributes(field))
+        {
             return Err(CheckError::InvalidFieldAttributes);
         }
 
         Ok(())
     }
 
-    // FIXME: optimize this function
+    // fields may share a name as long as their descriptors differ
     fn check_field_duplicates(&self) -> CheckResult<()> {
         let mut set = BTreeSet::new();
-        let mut fields_iter = self.classfile.fields.iter();
 
-        while let Some(field) = fields_iter.next() {
-            let opt = self
-                .classfile
-                .constant_pool
-                .get(field.name_index as usize - 1);
-            if let Some(name) = opt {
-                if let CpInfoType::ConstantUtf8 { bytes, .. } = name.info {
-                    if !set.insert(unsafe { JStr::from_jutf8_unchecked(bytes) }) {
-                        return Err(CheckError::DuplicatedField);
-                    }
+        for field in &self.classfile.fields {
+            let name = self.constant_utf8(field.name_index);
+            let descriptor = self.constant_utf8(field.descriptor_index);
+            if let (Some(name), Some(descriptor)) = (name, descriptor) {
+                if !set.insert((name, descriptor)) {
+                    return Err(CheckError::DuplicatedField);
                 }
             }
         }
@@ -267,6 +510,20 @@ impl<'clazz> ClassFileChecker<'clazz> {
         Ok(())
     }
 
+    fn check_field_names(&self) -> CheckResult<()> {
+        for field in &self.classfile.fields {
+            let Some(name) = self.constant_utf8(field.name_index) else {
+                return Err(CheckError::FieldNameIndexNotConstantUtf8);
+            };
+
+            if UnqualifiedName::from_jstr(name).is_err() {
+                return Err(CheckError::InvalidFieldName);
+            }
+        }
+
+        Ok(())
+    }
+
     fn check_field_access_flags(&self) -> CheckResult<()> {
         let mut fields_iter = self.classfile.fields.iter();
 
@@ -274,19 +531,13 @@ impl<'clazz> ClassFileChecker<'clazz> {
             if fields_iter.any(|field| {
                 let access_flags = field.access_flags;
 
-                !access_flags.flag_set(
-                    FieldAccessFlags::ACC_PUBLIC
-                        | FieldAccessFlags::ACC_STATIC
-                        | FieldAccessFlags::ACC_FINAL,
-                ) || access_flags
-                    != FieldAccessFlags::ACC_PUBLIC
-                        | FieldAccessFlags::ACC_STATIC
-                        | FieldAccessFlags::ACC_FINAL
-                    || access_flags
-                        != FieldAccessFlags::ACC_PUBLIC
-                            | FieldAccessFlags::ACC_STATIC
-                            | FieldAccessFlags::ACC_FINAL
-                            | FieldAccessFlags::ACC_SYNTHETIC
+                let required = FieldAccessFlags::ACC_PUBLIC
+                    | FieldAccessFlags::ACC_STATIC
+                    | FieldAccessFlags::ACC_FINAL;
+
+                // only ACC_SYNTHETIC may be set besides the required flags
+                !access_flags.flag_set(required)
+                    || access_flags & !(required | FieldAccessFlags::ACC_SYNTHETIC) != 0
             }) {
                 return Err(CheckError::InvalidInterfaceFieldFlags);
             }
@@ -352,26 +603,22 @@ impl<'clazz> ClassFileChecker<'clazz> {
 
     fn check_methods(&self) -> CheckResult<()> {
         self.check_method_duplicates()?;
+        self.check_method_names()?;
         self.check_method_access_flags()?;
 
         Ok(())
     }
 
-    // FIXME: optimize this function
+    // methods may share a name as long as their descriptors differ
     fn check_method_duplicates(&self) -> CheckResult<()> {
         let mut set = BTreeSet::new();
-        let mut methods_iter = self.classfile.fields.iter();
 
-        while let Some(method) = methods_iter.next() {
-            let opt = self
-                .classfile
-                .constant_pool
-                .get(method.name_index as usize - 1);
-            if let Some(name) = opt {
-                if let CpInfoType::ConstantUtf8 { bytes, .. } = name.info {
-                    if !set.insert(unsafe { JStr::from_jutf8_unchecked(bytes) }) {
-                        return Err(CheckError::DuplicatedMethod);
-                    }
+        for method in &self.classfile.methods {
+            let name = self.constant_utf8(method.name_index);
+            let descriptor = self.constant_utf8(method.descriptor_index);
+            if let (Some(name), Some(descriptor)) = (name, descriptor) {
+                if !set.insert((name, descriptor)) {
+                    return Err(CheckError::DuplicatedMethod);
                 }
             }
         }
@@ -379,11 +626,33 @@ impl<'clazz> ClassFileChecker<'clazz> {
         Ok(())
     }
 
+    fn check_method_names(&self) -> CheckResult<()> {
+        for method in &self.classfile.methods {
+            let Some(name) = self.constant_utf8(method.name_index) else {
+                return Err(CheckError::InvalidConstantPoolIndex);
+            };
+
+            if UnqualifiedName::from_method_name(name).is_err() {
+                return Err(CheckError::InvalidMethodName);
+            }
+        }
+
+        Ok(())
+    }
+
     fn check_method_access_flags(&self) -> CheckResult<()> {
-        let mut methods_iter = self.classfile.methods.iter();
+        // the flags of <clinit> are ignored, save for ACC_STATIC which is checked below
+        let methods = || {
+            self.classfile.methods.iter().filter(|method| {
+                !matches!(
+                    self.constant_utf8(method.name_index),
+                    Some(name) if name.as_bytes() == b"<clinit>"
+                )
+            })
+        };
 
         if self.state.is_interface {
-            if methods_iter.any(|method| {
+            if methods().any(|method| {
                 let access_flags = method.access_flags;
 
                 access_flags.flag_set(MethodAccessFlags::ACC_PROTECTED)
@@ -395,7 +664,7 @@ impl<'clazz> ClassFileChecker<'clazz> {
             }
 
             if self.state.major < 52 {
-                if methods_iter.any(|method| {
+                if methods().any(|method| {
                     let access_flags = method.access_flags;
 
                     !access_flags
@@ -404,53 +673,43 @@ impl<'clazz> ClassFileChecker<'clazz> {
                     return Err(CheckError::InvalidInterfaceMethodFlags);
                 }
             } else {
-                if methods_iter.any(|method| {
+                // exactly one of ACC_PUBLIC and ACC_PRIVATE
+                if methods().any(|method| {
                     let access_flags = method.access_flags;
 
-                    access_flags
-                        .flag_set(MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_PRIVATE)
+                    access_flags.flag_set(MethodAccessFlags::ACC_PUBLIC)
+                        == access_flags.flag_set(MethodAccessFlags::ACC_PRIVATE)
                 }) {
                     return Err(CheckError::InvalidInterfaceMethodFlags);
                 }
             }
         } else {
-            if methods_iter.any(|method| {
+            // at most one of ACC_PUBLIC, ACC_PRIVATE and ACC_PROTECTED
+            if methods().any(|method| {
                 let access_flags = method.access_flags;
 
-                access_flags.flag_set(
-                    FieldAccessFlags::ACC_PUBLIC
-                        | FieldAccessFlags::ACC_PRIVATE
-                        | FieldAccessFlags::ACC_PROTECTED,
-                ) || access_flags
-                    .flag_set(FieldAccessFlags::ACC_PUBLIC | FieldAccessFlags::ACC_PRIVATE)
+                access_flags
+                    .flag_set(MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_PRIVATE)
                     || access_flags
-                        .flag_set(FieldAccessFlags::ACC_PRIVATE | FieldAccessFlags::ACC_PROTECTED)
+                        .flag_set(MethodAccessFlags::ACC_PRIVATE | MethodAccessFlags::ACC_PROTECTED)
                     || access_flags
-                        .flag_set(FieldAccessFlags::ACC_PUBLIC | FieldAccessFlags::ACC_PROTECTED)
+                        .flag_set(MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_PROTECTED)
             }) {
                 return Err(CheckError::InvalidMethodFlags);
             }
         }
 
-        if methods_iter.any(|method| {
+        if methods().any(|method| {
             let access_flags = method.access_flags;
 
-            if access_flags.flag_set(MethodAccessFlags::ACC_ABSTRACT)
+            access_flags.flag_set(MethodAccessFlags::ACC_ABSTRACT)
                 && (access_flags.flag_set(MethodAccessFlags::ACC_PRIVATE)
                     || access_flags.flag_set(MethodAccessFlags::ACC_STATIC)
                     || access_flags.flag_set(MethodAccessFlags::ACC_FINAL)
                     || access_flags.flag_set(MethodAccessFlags::ACC_SYNCHRONIZED)
                     || access_flags.flag_set(MethodAccessFlags::ACC_NATIVE)
-                    || if (46u16..=60u16).contains(&self.state.major) {
-                        access_flags.flag_set(MethodAccessFlags::ACC_STRICT)
-                    } else {
-                        false
-                    })
-            {
-                true
-            } else {
-                false
-            }
+                    || (46u16..=60u16).contains(&self.state.major)
+                        && access_flags.flag_set(MethodAccessFlags::ACC_STRICT))
         }) {
             return Err(CheckError::InvalidMethodFlags);
         }
@@ -463,50 +722,52 @@ impl<'clazz> ClassFileChecker<'clazz> {
             }
         }
 
-        todo!()
+        Ok(())
+    }
+
+    fn constant_utf8(&self, index: u16) -> Option<&JStr> {
+        let CpInfoType::ConstantUtf8 { string, .. } = self
+            .classfile
+            .constant_pool
+            .get((index as usize).checked_sub(1)?)?
+            .info
+        else {
+            return None;
+        };
+
+        Some(string)
+    }
+
+    fn is_constant_class(&self, index: u16) -> bool {
+        matches!(
+            (index as usize)
+                .checked_sub(1)
+                .and_then(|index| self.classfile.constant_pool.get(index))
+                .map(|cp_info| &cp_info.info),
+            Some(CpInfoType::ConstantClass { .. })
+        )
     }
 
     fn check_methods_get_clinit(&self) -> Option<MethodInfo> {
-        let mut methods_iter = self.classfile.methods.iter();
-        methods_iter
+        self.classfile
+            .methods
+            .iter()
             .find(|method| {
-                let opt = &self
-                    .classfile
-                    .constant_pool
-                    .get(method.name_index as usize - 1);
-                if opt.is_none() {
+                let (Some(name), Some(descriptor)) = (
+                    self.constant_utf8(method.name_index),
+                    self.constant_utf8(method.descriptor_index),
+                ) else {
                     return false;
-                }
-
-                let name_cp_info = opt.unwrap();
-                let CpInfoType::ConstantUtf8 { bytes, .. } = &name_cp_info.info else {
-                    unreachable!()
                 };
-                let string = unsafe { JStr::from_jutf8_unchecked(bytes) };
-
-                let opt = &self
-                    .classfile
-                    .constant_pool
-                    .get(method.descriptor_index as usize - 1);
-                if opt.is_none() {
-                    return false;
-                }
 
-                let descriptor_cp_info = opt.unwrap();
-                let CpInfoType::ConstantUtf8 { bytes, .. } = &descriptor_cp_info.info else {
-                    unreachable!()
-                };
-                let descriptor_jstr = unsafe { JStr::from_jutf8_unchecked(bytes) };
-                let res = MethodDescriptor::from_jstr(descriptor_jstr);
-                if res.is_err() {
-                    return false;
-                }
-                let descriptor = res.unwrap();
+                let returns_void = matches!(
+                    MethodDescriptor::from_jstr(descriptor),
+                    Ok(descriptor) if descriptor.return_type() == ReturnDescriptor::VoidDescriptor
+                );
 
-                string.to_str().unwrap() == "<clinit>"
-                    && descriptor.return_type() == ReturnDescriptor::VoidDescriptor
+                name.as_bytes() == b"<clinit>" && returns_void
             })
-            .map(|method| method.clone())
+            .cloned()
     }
 }
 
@@ -0,0 +1,217 @@
+use ironjvm_cfck::ClassFileChecker;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_specimpl::classfile::attrinfo::rattr::RecordComponentInfo;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::AttributeInfo;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::FieldInfo;
+
+// checks a class of Sealed.java after changing it, returning the error
+fn check(class: &str, change: impl FnOnce(&mut ClassFile<'static>)) -> String {
+    let bytes = std::fs::read(format!(
+        "../test_classes/com/github/htgazurex1212/ironjvm/tests/{class}.class"
+    ))
+    .unwrap();
+    let mut classfile = ClassFileParser::new(Box::leak(bytes.into_boxed_slice()))
+        .parse()
+        .unwrap();
+    change(&mut classfile);
+
+    format!(
+        "{:?}",
+        ClassFileChecker::new(classfile).check().unwrap_err()
+    )
+}
+
+// finds an attribute by the name of its variant
+fn attribute<'a, 'clazz>(
+    classfile: &'a mut ClassFile<'clazz>,
+    name: &str,
+) -> &'a mut AttributeInfo<'clazz> {
+    classfile
+        .attributes
+        .iter_mut()
+        .find(|attribute| format!("{:?}", attribute.info).starts_with(name))
+        .unwrap()
+}
+
+fn components<'a, 'clazz>(
+    classfile: &'a mut ClassFile<'clazz>,
+) -> &'a mut Vec<RecordComponentInfo<'clazz>> {
+    match &mut attribute(classfile, "Record").info {
+        AttributeInfoType::RecordAttribute { components, .. } => components,
+        _ => unreachable!(),
+    }
+}
+
+fn duplicate(classfile: &mut ClassFile, name: &str) {
+    let attribute = attribute(classfile, name).clone();
+    classfile.attributes.push(attribute);
+}
+
+// the index of the first Utf8 entry, which is not a class
+fn utf8_index(classfile: &ClassFile) -> u16 {
+    classfile
+        .constant_pool
+        .iter()
+        .position(|cp_info| matches!(cp_info.info, CpInfoType::ConstantUtf8 { .. }))
+        .unwrap() as u16
+        + 1
+}
+
+// adds two fields of the same name, which are reported after the class attributes
+fn duplicate_field(classfile: &mut ClassFile) {
+    let name_index = utf8_index(classfile);
+    for _ in 0..2 {
+        classfile.fields.push(FieldInfo {
+            access_flags: 0,
+            name_index,
+            descriptor_index: name_index,
+            attributes_count: 0,
+            attributes: vec![],
+        });
+    }
+}
+
+#[test]
+fn duplicate_attributes() {
+    for (class, name) in [
+        ("Sealed", "NestMembers"),
+        ("Sealed$Vehicle", "PermittedSubclasses"),
+        ("Sealed$Car", "NestHost"),
+        ("Sealed$Point", "Record"),
+    ] {
+        assert_eq!(
+            check(class, |classfile| duplicate(classfile, name)),
+            "DuplicatedClassAttribute",
+            "{class} with two {name} attributes"
+        );
+    }
+}
+
+#[test]
+fn nest_attributes() {
+    // a class cannot be both a nest host with members and a member of another nest
+    assert_eq!(
+        check("Sealed", |classfile| {
+            let mut nest_host = attribute(classfile, "NestMembers").clone();
+            nest_host.info = AttributeInfoType::NestHostAttribute {
+                host_class_index: classfile.this_class,
+            };
+            classfile.attributes.push(nest_host);
+        }),
+        "InvalidNestAttributes"
+    );
+    assert_eq!(
+        check("Sealed$Car", |classfile| {
+            let utf8 = utf8_index(classfile);
+            if let AttributeInfoType::NestHostAttribute { host_class_index } =
+                &mut attribute(classfile, "NestHost").info
+            {
+                *host_class_index = utf8;
+            }
+        }),
+        "InvalidNestAttributes"
+    );
+    assert_eq!(
+        check("Sealed", |classfile| {
+            let utf8 = utf8_index(classfile).to_be_bytes();
+            if let AttributeInfoType::NestMembersAttribute { classes, .. } =
+                &mut attribute(classfile, "NestMembers").info
+            {
+                *classes = Box::leak(Box::new([utf8]));
+            }
+        }),
+        "InvalidNestAttributes"
+    );
+}
+
+#[test]
+fn permitted_subclasses() {
+    assert_eq!(
+        check("Sealed$Vehicle", |classfile| {
+            let utf8 = utf8_index(classfile).to_be_bytes();
+            if let AttributeInfoType::PermittedSubclassesAttribute { classes, .. } =
+                &mut attribute(classfile, "PermittedSubclasses").info
+            {
+                *classes = Box::leak(Box::new([utf8]));
+            }
+        }),
+        "InvalidPermittedSubclassesAttribute"
+    );
+}
+
+#[test]
+fn record_components() {
+    // each component needs an instance field and an accessor of the same name and type
+    assert_eq!(
+        check("Sealed$Point", |classfile| {
+            classfile.fields.pop();
+        }),
+        "MismatchedRecordComponent"
+    );
+    assert_eq!(
+        check("Sealed$Point", |classfile| {
+            let y = classfile.fields[1].name_index;
+            classfile.methods.retain(|method| method.name_index != y);
+        }),
+        "MismatchedRecordComponent"
+    );
+
+    assert_eq!(
+        check("Sealed$Point", |classfile| {
+            let components = components(classfile);
+            components.push(components[0].clone());
+        }),
+        "InvalidRecordAttribute"
+    );
+    assert_eq!(
+        check("Sealed$Point", |classfile| {
+            let components = components(classfile);
+            components[0].descriptor_index = components[0].name_index;
+        }),
+        "InvalidRecordAttribute"
+    );
+}
+
+#[test]
+fn class_file_versions() {
+    // nests are ignored before Java 11, records before Java 16 and sealed classes before
+    // Java 17, so only the duplicated field is found in older class files
+    for (info, major) in [
+        (
+            AttributeInfoType::NestHostAttribute {
+                host_class_index: 0,
+            },
+            55,
+        ),
+        (
+            AttributeInfoType::RecordAttribute {
+                components_count: 0,
+                components: vec![],
+            },
+            60,
+        ),
+        (
+            AttributeInfoType::PermittedSubclassesAttribute {
+                number_of_classes: 0,
+                classes: &[],
+            },
+            61,
+        ),
+    ] {
+        let vehicle = |major_version| {
+            check("Sealed$Vehicle", |classfile| {
+                classfile.major_version = major_version;
+                let mut attribute = classfile.attributes[0].clone();
+                attribute.info = info.clone();
+                classfile.attributes.extend([attribute.clone(), attribute]);
+                duplicate_field(classfile);
+            })
+        };
+
+        assert_eq!(vehicle(major - 1), "DuplicatedField");
+        assert_eq!(vehicle(major), "DuplicatedClassAttribute");
+    }
+}
@@ -0,0 +1,65 @@
+use ironjvm_cfck::ClassFileChecker;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+
+// checks Bootstraps.class after changing its first method handle, which refers to the
+// LambdaMetafactory.metafactory Methodref, optionally pointing it at a Utf8 entry instead
+fn check_with_handle(reference_kind: u8, refer_to_utf8: bool) -> String {
+    let bytes =
+        std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/Bootstraps.class")
+            .unwrap();
+    let mut classfile = ClassFileParser::new(&bytes).parse().unwrap();
+
+    let utf8 = classfile
+        .constant_pool
+        .iter()
+        .position(|cp_info| matches!(cp_info.info, CpInfoType::ConstantUtf8 { .. }))
+        .unwrap() as u16
+        + 1;
+    let handle = classfile
+        .constant_pool
+        .iter_mut()
+        .find(|cp_info| matches!(cp_info.info, CpInfoType::ConstantMethodHandle { .. }))
+        .unwrap();
+    if let CpInfoType::ConstantMethodHandle {
+        reference_kind: kind,
+        reference_index: index,
+    } = &mut handle.info
+    {
+        *kind = reference_kind;
+        if refer_to_utf8 {
+            *index = utf8;
+        }
+    }
+
+    format!(
+        "{:?}",
+        ClassFileChecker::new(classfile).check().unwrap_err()
+    )
+}
+
+#[test]
+fn reference_kinds() {
+    assert_eq!(
+        check_with_handle(0, false),
+        "InvalidReferenceKind { reference_kind: 0 }"
+    );
+    assert_eq!(
+        check_with_handle(10, false),
+        "InvalidReferenceKind { reference_kind: 10 }"
+    );
+}
+
+#[test]
+fn referenced_entries() {
+    // REF_getField and REF_invokeInterface cannot refer to a Methodref
+    assert_eq!(check_with_handle(1, false), "InvalidMethodHandleReference");
+    assert_eq!(check_with_handle(9, false), "InvalidMethodHandleReference");
+    assert_eq!(check_with_handle(6, true), "InvalidConstantPoolIndex");
+}
+
+#[test]
+fn constructors() {
+    // REF_newInvokeSpecial must name <init>, and metafactory is not a constructor
+    assert_eq!(check_with_handle(8, false), "InvalidMethodHandleReference");
+}
@@ -14,5 +14,23 @@ fn intentional_large(criterion: &mut Criterion) {
     });
 }
 
-criterion_group!(group, intentional_large);
+// what a build tool scanning classes reads: names, the superclass and annotations
+fn intentional_large_lazy(criterion: &mut Criterion) {
+    criterion.bench_function("Intentional Large (lazy)", |bencher| {
+        let file =
+            std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/Benchmark.class")
+                .unwrap();
+
+        bencher.iter(|| {
+            let mut parser = ClassFileParser::new(&file);
+            let classfile = parser.parse_lazy().unwrap();
+
+            classfile.this_class_name().unwrap();
+            classfile.super_class_name().unwrap();
+            classfile.attribute("RuntimeVisibleAnnotations").unwrap();
+        })
+    });
+}
+
+criterion_group!(group, intentional_large, intentional_large_lazy);
 criterion_main!(group);
@@ -0,0 +1,40 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::jstr::{validate_jstr, validate_jstr_scalar};
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+
+fn validate_constant_utf8(criterion: &mut Criterion) {
+    let file =
+        std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/Benchmark.class")
+            .unwrap();
+    let mut parser = ClassFileParser::new(&file);
+    let classfile = parser.parse().unwrap();
+
+    let constants = classfile
+        .constant_pool
+        .iter()
+        .filter_map(|constant| match constant.info {
+            CpInfoType::ConstantUtf8 { string, .. } => Some(string.as_bytes()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let joined = constants.concat();
+
+    let mut group = criterion.benchmark_group("Validate ConstantUtf8");
+    group.bench_function("Scalar", |bencher| {
+        bencher.iter(|| constants.iter().all(|bytes| validate_jstr_scalar(bytes)))
+    });
+    group.bench_function("SIMD", |bencher| {
+        bencher.iter(|| constants.iter().all(|bytes| validate_jstr(bytes)))
+    });
+    group.bench_function("Scalar (joined)", |bencher| {
+        bencher.iter(|| validate_jstr_scalar(&joined))
+    });
+    group.bench_function("SIMD (joined)", |bencher| {
+        bencher.iter(|| validate_jstr(&joined))
+    });
+    group.finish();
+}
+
+criterion_group!(group, validate_constant_utf8);
+criterion_main!(group);
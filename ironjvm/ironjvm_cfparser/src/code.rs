@@ -0,0 +1,802 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+
+use crate::error::ParseError;
+use crate::error::ParseResult;
+
+pub struct CodeParser<'clazz> {
+    code: &'clazz [u8],
+    pc: usize,
+}
+
+impl<'clazz> CodeParser<'clazz> {
+    pub fn new(code: &'clazz [u8]) -> Self {
+        Self { code, pc: 0 }
+    }
+
+    pub fn parse(&mut self) -> ParseResult<Vec<(u16, Instruction)>> {
+        if self.code.is_empty() || self.code.len() > u16::MAX as usize {
+            return Err(ParseError::InvalidCodeLength);
+        }
+
+        let mut vec = Vec::new();
+
+        while self.pc < self.code.len() {
+            let pc = self.pc as u16;
+            vec.push((pc, self.parse_instruction()?));
+        }
+
+        Ok(vec)
+    }
+
+    fn next_u1(&mut self) -> ParseResult<u8> {
+        let Some(&byte) = self.code.get(self.pc) else {
+            return Err(ParseError::UnexpectedEndOfCode);
+        };
+        self.pc += 1;
+
+        Ok(byte)
+    }
+
+    fn next_u2(&mut self) -> ParseResult<u16> {
+        Ok(u16::from_be_bytes([self.next_u1()?, self.next_u1()?]))
+    }
+
+    fn next_u4(&mut self) -> ParseResult<u32> {
+        Ok(u32::from_be_bytes([
+            self.next_u1()?,
+            self.next_u1()?,
+            self.next_u1()?,
+            self.next_u1()?,
+        ]))
+    }
+
+    fn parse_instruction(&mut self) -> ParseResult<Instruction> {
+        let opcode = self.next_u1()?;
+
+        Ok(match opcode {
+            0x00 => Instruction::Nop,
+            0x01 => Instruction::AConstNull,
+            0x02 => Instruction::IConstM1,
+            0x03 => Instruction::IConst0,
+            0x04 => Instruction::IConst1,
+            0x05 => Instruction::IConst2,
+            0x06 => Instruction::IConst3,
+            0x07 => Instruction::IConst4,
+            0x08 => Instruction::IConst5,
+            0x09 => Instruction::LConst0,
+            0x0a => Instruction::LConst1,
+            0x0b => Instruction::FConst0,
+            0x0c => Instruction::FConst1,
+            0x0d => Instruction::FConst2,
+            0x0e => Instruction::DConst0,
+            0x0f => Instruction::DConst1,
+            0x10 => Instruction::BIPush {
+                value: self.next_u1()? as i8,
+            },
+            0x11 => Instruction::SIPush {
+                value: self.next_u2()? as i16,
+            },
+            0x12 => Instruction::Ldc {
+                index: self.next_u1()?,
+            },
+            0x13 => Instruction::LdcW {
+                index: self.next_u2()?,
+            },
+            0x14 => Instruction::Ldc2W {
+                index: self.next_u2()?,
+            },
+            0x15 => Instruction::ILoad {
+                index: self.next_u1()?,
+            },
+            0x16 => Instruction::LLoad {
+                index: self.next_u1()?,
+            },
+            0x17 => Instruction::FLoad {
+                index: self.next_u1()?,
+            },
+            0x18 => Instruction::DLoad {
+                index: self.next_u1()?,
+            },
+            0x19 => Instruction::ALoad {
+                index: self.next_u1()?,
+            },
+            0x1a => Instruction::ILoad0,
+            0x1b => Instruction::ILoad1,
+            0x1c => Instruction::ILoad2,
+            0x1d => Instruction::ILoad3,
+            0x1e => Instruction::LLoad0,
+            0x1f => Instruction::LLoad1,
+            0x20 => Instruction::LLoad2,
+            0x21 => Instruction::LLoad3,
+            0x22 => Instruction::FLoad0,
+            0x23 => Instruction::FLoad1,
+            0x24 => Instruction::FLoad2,
+            0x25 => Instruction::FLoad3,
+            0x26 => Instruction::DLoad0,
+            0x27 => Instruction::DLoad1,
+            0x28 => Instruction::DLoad2,
+            0x29 => Instruction::DLoad3,
+            0x2a => Instruction::ALoad0,
+            0x2b => Instruction::ALoad1,
+            0x2c => Instruction::ALoad2,
+            0x2d => Instruction::ALoad3,
+            0x2e => Instruction::IALoad,
+            0x2f => Instruction::LALoad,
+            0x30 => Instruction::FALoad,
+            0x31 => Instruction::DALoad,
+            0x32 => Instruction::AALoad,
+            0x33 => Instruction::BALoad,
+            0x34 => Instruction::CALoad,
+            0x35 => Instruction::SALoad,
+            0x36 => Instruction::IStore {
+                index: self.next_u1()?,
+            },
+            0x37 => Instruction::LStore {
+                index: self.next_u1()?,
+            },
+            0x38 => Instruction::FStore {
+                index: self.next_u1()?,
+            },
+            0x39 => Instruction::DStore {
+                index: self.next_u1()?,
+            },
+            0x3a => Instruction::AStore {
+                index: self.next_u1()?,
+            },
+            0x3b => Instruction::IStore0,
+            0x3c => Instruction::IStore1,
+            0x3d => Instruction::IStore2,
+            0x3e => Instruction::IStore3,
+            0x3f => Instruction::LStore0,
+            0x40 => Instruction::LStore1,
+            0x41 => Instruction::LStore2,
+            0x42 => Instruction::LStore3,
+            0x43 => Instruction::FStore0,
+            0x44 => Instruction::FStore1,
+            0x45 => Instruction::FStore2,
+            0x46 => Instruction::FStore3,
+            0x47 => Instruction::DStore0,
+            0x48 => Instruction::DStore1,
+            0x49 => Instruction::DStore2,
+            0x4a => Instruction::DStore3,
+            0x4b => Instruction::AStore0,
+            0x4c => Instruction::AStore1,
+            0x4d => Instruction::AStore2,
+            0x4e => Instruction::AStore3,
+            0x4f => Instruction::IAStore,
+            0x50 => Instruction::LAStore,
+            0x51 => Instruction::FAStore,
+            0x52 => Instruction::DAStore,
+            0x53 => Instruction::AAStore,
+            0x54 => Instruction::BAStore,
+            0x55 => Instruction::CAStore,
+            0x56 => Instruction::SAStore,
+            0x57 => Instruction::Pop,
+            0x58 => Instruction::Pop2,
+            0x59 => Instruction::Dup,
+            0x5a => Instruction::DupX1,
+            0x5b => Instruction::DupX2,
+            0x5c => Instruction::Dup2,
+            0x5d => Instruction::Dup2X1,
+            0x5e => Instruction::Dup2X2,
+            0x5f => Instruction::Swap,
+            0x60 => Instruction::IAdd,
+            0x61 => Instruction::LAdd,
+            0x62 => Instruction::FAdd,
+            0x63 => Instruction::DAdd,
+            0x64 => Instruction::ISub,
+            0x65 => Instruction::LSub,
+            0x66 => Instruction::FSub,
+            0x67 => Instruction::DSub,
+            0x68 => Instruction::IMul,
+            0x69 => Instruction::LMul,
+            0x6a => Instruction::FMul,
+            0x6b => Instruction::DMul,
+            0x6c => Instruction::IDiv,
+            0x6d => Instruction::LDiv,
+            0x6e => Instruction::FDiv,
+            0x6f => Instruction::DDiv,
+            0x70 => Instruction::IRem,
+            0x71 => Instruction::LRem,
+            0x72 => Instruction::FRem,
+            0x73 => Instruction::DRem,
+            0x74 => Instruction::INeg,
+            0x75 => Instruction::LNeg,
+            0x76 => Instruction::FNeg,
+            0x77 => Instruction::DNeg,
+            0x78 => Instruction::IShl,
+            0x79 => Instruction::LShl,
+            0x7a => Instruction::IShr,
+            0x7b => Instruction::LShr,
+            0x7c => Instruction::IUShr,
+            0x7d => Instruction::LUShr,
+            0x7e => Instruction::IAnd,
+            0x7f => Instruction::LAnd,
+            0x80 => Instruction::IOr,
+            0x81 => Instruction::LOr,
+            0x82 => Instruction::IXor,
+            0x83 => Instruction::LXor,
+            0x84 => Instruction::IInc {
+                index: self.next_u1()?,
+                value: self.next_u1()? as i8,
+            },
+            0x85 => Instruction::I2L,
+            0x86 => Instruction::I2F,
+            0x87 => Instruction::I2D,
+            0x88 => Instruction::L2I,
+            0x89 => Instruction::L2F,
+            0x8a => Instruction::L2D,
+            0x8b => Instruction::F2I,
+            0x8c => Instruction::F2L,
+            0x8d => Instruction::F2D,
+            0x8e => Instruction::D2I,
+            0x8f => Instruction::D2L,
+            0x90 => Instruction::D2F,
+            0x91 => Instruction::I2B,
+            0x92 => Instruction::I2C,
+            0x93 => Instruction::I2S,
+            0x94 => Instruction::LCmp,
+            0x95 => Instruction::FCmpL,
+            0x96 => Instruction::FCmpG,
+            0x97 => Instruction::DCmpL,
+            0x98 => Instruction::DCmpG,
+            0x99 => Instruction::IfEq {
+                offset: self.next_u2()? as i16,
+            },
+            0x9a => Instruction::IfNe {
+                offset: self.next_u2()? as i16,
+            },
+            0x9b => Instruction::IfLt {
+                offset: self.next_u2()? as i16,
+            },
+            0x9c => Instruction::IfGe {
+                offset: self.next_u2()? as i16,
+            },
+            0x9d => Instruction::IfGt {
+                offset: self.next_u2()? as i16,
+            },
+            0x9e => Instruction::IfLe {
+                offset: self.next_u2()? as i16,
+            },
+            0x9f => Instruction::IfICmpEq {
+                offset: self.next_u2()? as i16,
+            },
+            0xa0 => Instruction::IfICmpNe {
+                offset: self.next_u2()? as i16,
+            },
+            0xa1 => Instruction::IfICmpLt {
+                offset: self.next_u2()? as i16,
+            },
+            0xa2 => Instruction::IfICmpGe {
+                offset: self.next_u2()? as i16,
+            },
+            0xa3 => Instruction::IfICmpGt {
+                offset: self.next_u2()? as i16,
+            },
+            0xa4 => Instruction::IfICmpLe {
+                offset: self.next_u2()? as i16,
+            },
+            0xa5 => Instruction::IfACmpEq {
+                offset: self.next_u2()? as i16,
+            },
+            0xa6 => Instruction::IfACmpNe {
+                offset: self.next_u2()? as i16,
+            },
+            0xa7 => Instruction::Goto {
+                offset: self.next_u2()? as i16,
+            },
+            0xa8 => Instruction::Jsr {
+                offset: self.next_u2()? as i16,
+            },
+            0xa9 => Instruction::Ret {
+                index: self.next_u1()?,
+            },
+            0xaa => self.parse_table_switch()?,
+            0xab => self.parse_lookup_switch()?,
+            0xac => Instruction::IReturn,
+            0xad => Instruction::LReturn,
+            0xae => Instruction::FReturn,
+            0xaf => Instruction::DReturn,
+            0xb0 => Instruction::AReturn,
+            0xb1 => Instruction::Return,
+            0xb2 => Instruction::GetStatic {
+                index: self.next_u2()?,
+            },
+            0xb3 => Instruction::PutStatic {
+                index: self.next_u2()?,
+            },
+            0xb4 => Instruction::GetField {
+                index: self.next_u2()?,
+            },
+            0xb5 => Instruction::PutField {
+                index: self.next_u2()?,
+            },
+            0xb6 => Instruction::InvokeVirtual {
+                index: self.next_u2()?,
+            },
+            0xb7 => Instruction::InvokeSpecial {
+                index: self.next_u2()?,
+            },
+            0xb8 => Instruction::InvokeStatic {
+                index: self.next_u2()?,
+            },
+            0xb9 => {
+                let index = self.next_u2()?;
+                let count = self.next_u1()?;
+                self.next_u1()?;
+
+                Instruction::InvokeInterface { index, count }
+            }
+            0xba => {
+                let index = self.next_u2()?;
+                self.next_u2()?;
+
+                Instruction::InvokeDynamic { index }
+            }
+            0xbb => Instruction::New {
+                index: self.next_u2()?,
+            },
+            0xbc => Instruction::NewArray {
+                atype: self.next_u1()?,
+            },
+            0xbd => Instruction::ANewArray {
+                index: self.next_u2()?,
+            },
+            0xbe => Instruction::ArrayLength,
+            0xbf => Instruction::AThrow,
+            0xc0 => Instruction::CheckCast {
+                index: self.next_u2()?,
+            },
+            0xc1 => Instruction::InstanceOf {
+                index: self.next_u2()?,
+            },
+            0xc2 => Instruction::MonitorEnter,
+            0xc3 => Instruction::MonitorExit,
+            0xc4 => self.parse_wide_instruction()?,
+            0xc5 => Instruction::MultiANewArray {
+                index: self.next_u2()?,
+                dimensions: self.next_u1()?,
+            },
+            0xc6 => Instruction::IfNull {
+                offset: self.next_u2()? as i16,
+            },
+            0xc7 => Instruction::IfNonNull {
+                offset: self.next_u2()? as i16,
+            },
+            0xc8 => Instruction::GotoW {
+                offset: self.next_u4()? as i32,
+            },
+            0xc9 => Instruction::JsrW {
+                offset: self.next_u4()? as i32,
+            },
+
+            _ => return Err(ParseError::InvalidOpcode { opcode }),
+        })
+    }
+
+    fn parse_table_switch(&mut self) -> ParseResult<Instruction> {
+        self.skip_switch_padding()?;
+
+        let default = self.next_u4()? as i32;
+        let low = self.next_u4()? as i32;
+        let high = self.next_u4()? as i32;
+        if low > high {
+            return Err(ParseError::InvalidSwitchInstruction);
+        }
+
+        let count = (high as i64 - low as i64 + 1) as usize;
+        if count > self.code.len() {
+            return Err(ParseError::UnexpectedEndOfCode);
+        }
+
+        let mut jump_offsets = Vec::with_capacity(count);
+        while jump_offsets.len() < count {
+            jump_offsets.push(self.next_u4()? as i32);
+        }
+
+        Ok(Instruction::TableSwitch {
+            default,
+            low,
+            high,
+            jump_offsets,
+        })
+    }
+
+    fn parse_lookup_switch(&mut self) -> ParseResult<Instruction> {
+        self.skip_switch_padding()?;
+
+        let default = self.next_u4()? as i32;
+        let npairs = self.next_u4()? as i32;
+        if npairs < 0 {
+            return Err(ParseError::InvalidSwitchInstruction);
+        }
+        if npairs as usize > self.code.len() {
+            return Err(ParseError::UnexpectedEndOfCode);
+        }
+
+        let mut match_offset_pairs = Vec::with_capacity(npairs as usize);
+        while match_offset_pairs.len() < npairs as usize {
+            match_offset_pairs.push((self.next_u4()? as i32, self.next_u4()? as i32));
+        }
+
+        Ok(Instruction::LookupSwitch {
+            default,
+            npairs,
+            match_offset_pairs,
+        })
+    }
+
+    fn parse_wide_instruction(&mut self) -> ParseResult<Instruction> {
+        let opcode = self.next_u1()?;
+
+        Ok(match opcode {
+            0x15 => Instruction::ILoadW {
+                index: self.next_u2()?,
+            },
+            0x16 => Instruction::LLoadW {
+                index: self.next_u2()?,
+            },
+            0x17 => Instruction::FLoadW {
+                index: self.next_u2()?,
+            },
+            0x18 => Instruction::DLoadW {
+                index: self.next_u2()?,
+            },
+            0x19 => Instruction::ALoadW {
+                index: self.next_u2()?,
+            },
+            0x36 => Instruction::IStoreW {
+                index: self.next_u2()?,
+            },
+            0x37 => Instruction::LStoreW {
+                index: self.next_u2()?,
+            },
+            0x38 => Instruction::FStoreW {
+                index: self.next_u2()?,
+            },
+            0x39 => Instruction::DStoreW {
+                index: self.next_u2()?,
+            },
+            0x3a => Instruction::AStoreW {
+                index: self.next_u2()?,
+            },
+            0x84 => Instruction::IIncW {
+                index: self.next_u2()?,
+                value: self.next_u2()? as i16,
+            },
+            0xa9 => Instruction::RetW {
+                index: self.next_u2()?,
+            },
+            _ => return Err(ParseError::InvalidOpcode { opcode }),
+        })
+    }
+
+    // tableswitch and lookupswitch operands start at the next multiple of 4 bytes from the
+    // start of the code
+    fn skip_switch_padding(&mut self) -> ParseResult<()> {
+        while self.pc & 3 != 0 {
+            self.next_u1()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Encodes instructions back into the bytes of a `Code` attribute.
+#[derive(Default)]
+pub struct CodeWriter {
+    code: Vec<u8>,
+}
+
+impl CodeWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The offset the next instruction is written at.
+    pub fn pc(&self) -> usize {
+        self.code.len()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.code
+    }
+
+    /// Writes `instruction` as it is, so branch offsets must already be relative to where it ends
+    /// up.
+    pub fn write(&mut self, instruction: &Instruction) {
+        match *instruction {
+            Instruction::Nop => self.opcode(0x00),
+            Instruction::AConstNull => self.opcode(0x01),
+            Instruction::IConstM1 => self.opcode(0x02),
+            Instruction::IConst0 => self.opcode(0x03),
+            Instruction::IConst1 => self.opcode(0x04),
+            Instruction::IConst2 => self.opcode(0x05),
+            Instruction::IConst3 => self.opcode(0x06),
+            Instruction::IConst4 => self.opcode(0x07),
+            Instruction::IConst5 => self.opcode(0x08),
+            Instruction::LConst0 => self.opcode(0x09),
+            Instruction::LConst1 => self.opcode(0x0a),
+            Instruction::FConst0 => self.opcode(0x0b),
+            Instruction::FConst1 => self.opcode(0x0c),
+            Instruction::FConst2 => self.opcode(0x0d),
+            Instruction::DConst0 => self.opcode(0x0e),
+            Instruction::DConst1 => self.opcode(0x0f),
+            Instruction::BIPush { value } => self.opcode(0x10).u1(value as u8),
+            Instruction::SIPush { value } => self.opcode(0x11).u2(value as u16),
+            Instruction::Ldc { index } => self.opcode(0x12).u1(index),
+            Instruction::LdcW { index } => self.opcode(0x13).u2(index),
+            Instruction::Ldc2W { index } => self.opcode(0x14).u2(index),
+            Instruction::ILoad { index } => self.opcode(0x15).u1(index),
+            Instruction::LLoad { index } => self.opcode(0x16).u1(index),
+            Instruction::FLoad { index } => self.opcode(0x17).u1(index),
+            Instruction::DLoad { index } => self.opcode(0x18).u1(index),
+            Instruction::ALoad { index } => self.opcode(0x19).u1(index),
+            Instruction::ILoad0 => self.opcode(0x1a),
+            Instruction::ILoad1 => self.opcode(0x1b),
+            Instruction::ILoad2 => self.opcode(0x1c),
+            Instruction::ILoad3 => self.opcode(0x1d),
+            Instruction::LLoad0 => self.opcode(0x1e),
+            Instruction::LLoad1 => self.opcode(0x1f),
+            Instruction::LLoad2 => self.opcode(0x20),
+            Instruction::LLoad3 => self.opcode(0x21),
+            Instruction::FLoad0 => self.opcode(0x22),
+            Instruction::FLoad1 => self.opcode(0x23),
+            Instruction::FLoad2 => self.opcode(0x24),
+            Instruction::FLoad3 => self.opcode(0x25),
+            Instruction::DLoad0 => self.opcode(0x26),
+            Instruction::DLoad1 => self.opcode(0x27),
+            Instruction::DLoad2 => self.opcode(0x28),
+            Instruction::DLoad3 => self.opcode(0x29),
+            Instruction::ALoad0 => self.opcode(0x2a),
+            Instruction::ALoad1 => self.opcode(0x2b),
+            Instruction::ALoad2 => self.opcode(0x2c),
+            Instruction::ALoad3 => self.opcode(0x2d),
+            Instruction::IALoad => self.opcode(0x2e),
+            Instruction::LALoad => self.opcode(0x2f),
+            Instruction::FALoad => self.opcode(0x30),
+            Instruction::DALoad => self.opcode(0x31),
+            Instruction::AALoad => self.opcode(0x32),
+            Instruction::BALoad => self.opcode(0x33),
+            Instruction::CALoad => self.opcode(0x34),
+            Instruction::SALoad => self.opcode(0x35),
+            Instruction::IStore { index } => self.opcode(0x36).u1(index),
+            Instruction::LStore { index } => self.opcode(0x37).u1(index),
+            Instruction::FStore { index } => self.opcode(0x38).u1(index),
+            Instruction::DStore { index } => self.opcode(0x39).u1(index),
+            Instruction::AStore { index } => self.opcode(0x3a).u1(index),
+            Instruction::IStore0 => self.opcode(0x3b),
+            Instruction::IStore1 => self.opcode(0x3c),
+            Instruction::IStore2 => self.opcode(0x3d),
+            Instruction::IStore3 => self.opcode(0x3e),
+            Instruction::LStore0 => self.opcode(0x3f),
+            Instruction::LStore1 => self.opcode(0x40),
+            Instruction::LStore2 => self.opcode(0x41),
+            Instruction::LStore3 => self.opcode(0x42),
+            Instruction::FStore0 => self.opcode(0x43),
+            Instruction::FStore1 => self.opcode(0x44),
+            Instruction::FStore2 => self.opcode(0x45),
+            Instruction::FStore3 => self.opcode(0x46),
+            Instruction::DStore0 => self.opcode(0x47),
+            Instruction::DStore1 => self.opcode(0x48),
+            Instruction::DStore2 => self.opcode(0x49),
+            Instruction::DStore3 => self.opcode(0x4a),
+            Instruction::AStore0 => self.opcode(0x4b),
+            Instruction::AStore1 => self.opcode(0x4c),
+            Instruction::AStore2 => self.opcode(0x4d),
+            Instruction::AStore3 => self.opcode(0x4e),
+            Instruction::IAStore => self.opcode(0x4f),
+            Instruction::LAStore => self.opcode(0x50),
+            Instruction::FAStore => self.opcode(0x51),
+            Instruction::DAStore => self.opcode(0x52),
+            Instruction::AAStore => self.opcode(0x53),
+            Instruction::BAStore => self.opcode(0x54),
+            Instruction::CAStore => self.opcode(0x55),
+            Instruction::SAStore => self.opcode(0x56),
+            Instruction::Pop => self.opcode(0x57),
+            Instruction::Pop2 => self.opcode(0x58),
+            Instruction::Dup => self.opcode(0x59),
+            Instruction::DupX1 => self.opcode(0x5a),
+            Instruction::DupX2 => self.opcode(0x5b),
+            Instruction::Dup2 => self.opcode(0x5c),
+            Instruction::Dup2X1 => self.opcode(0x5d),
+            Instruction::Dup2X2 => self.opcode(0x5e),
+            Instruction::Swap => self.opcode(0x5f),
+            Instruction::IAdd => self.opcode(0x60),
+            Instruction::LAdd => self.opcode(0x61),
+            Instruction::FAdd => self.opcode(0x62),
+            Instruction::DAdd => self.opcode(0x63),
+            Instruction::ISub => self.opcode(0x64),
+            Instruction::LSub => self.opcode(0x65),
+            Instruction::FSub => self.opcode(0x66),
+            Instruction::DSub => self.opcode(0x67),
+            Instruction::IMul => self.opcode(0x68),
+            Instruction::LMul => self.opcode(0x69),
+            Instruction::FMul => self.opcode(0x6a),
+            Instruction::DMul => self.opcode(0x6b),
+            Instruction::IDiv => self.opcode(0x6c),
+            Instruction::LDiv => self.opcode(0x6d),
+            Instruction::FDiv => self.opcode(0x6e),
+            Instruction::DDiv => self.opcode(0x6f),
+            Instruction::IRem => self.opcode(0x70),
+            Instruction::LRem => self.opcode(0x71),
+            Instruction::FRem => self.opcode(0x72),
+            Instruction::DRem => self.opcode(0x73),
+            Instruction::INeg => self.opcode(0x74),
+            Instruction::LNeg => self.opcode(0x75),
+            Instruction::FNeg => self.opcode(0x76),
+            Instruction::DNeg => self.opcode(0x77),
+            Instruction::IShl => self.opcode(0x78),
+            Instruction::LShl => self.opcode(0x79),
+            Instruction::IShr => self.opcode(0x7a),
+            Instruction::LShr => self.opcode(0x7b),
+            Instruction::IUShr => self.opcode(0x7c),
+            Instruction::LUShr => self.opcode(0x7d),
+            Instruction::IAnd => self.opcode(0x7e),
+            Instruction::LAnd => self.opcode(0x7f),
+            Instruction::IOr => self.opcode(0x80),
+            Instruction::LOr => self.opcode(0x81),
+            Instruction::IXor => self.opcode(0x82),
+            Instruction::LXor => self.opcode(0x83),
+            Instruction::IInc { index, value } => self.opcode(0x84).u1(index).u1(value as u8),
+            Instruction::I2L => self.opcode(0x85),
+            Instruction::I2F => self.opcode(0x86),
+            Instruction::I2D => self.opcode(0x87),
+            Instruction::L2I => self.opcode(0x88),
+            Instruction::L2F => self.opcode(0x89),
+            Instruction::L2D => self.opcode(0x8a),
+            Instruction::F2I => self.opcode(0x8b),
+            Instruction::F2L => self.opcode(0x8c),
+            Instruction::F2D => self.opcode(0x8d),
+            Instruction::D2I => self.opcode(0x8e),
+            Instruction::D2L => self.opcode(0x8f),
+            Instruction::D2F => self.opcode(0x90),
+            Instruction::I2B => self.opcode(0x91),
+            Instruction::I2C => self.opcode(0x92),
+            Instruction::I2S => self.opcode(0x93),
+            Instruction::LCmp => self.opcode(0x94),
+            Instruction::FCmpL => self.opcode(0x95),
+            Instruction::FCmpG => self.opcode(0x96),
+            Instruction::DCmpL => self.opcode(0x97),
+            Instruction::DCmpG => self.opcode(0x98),
+            Instruction::IfEq { offset } => self.opcode(0x99).u2(offset as u16),
+            Instruction::IfNe { offset } => self.opcode(0x9a).u2(offset as u16),
+            Instruction::IfLt { offset } => self.opcode(0x9b).u2(offset as u16),
+            Instruction::IfGe { offset } => self.opcode(0x9c).u2(offset as u16),
+            Instruction::IfGt { offset } => self.opcode(0x9d).u2(offset as u16),
+            Instruction::IfLe { offset } => self.opcode(0x9e).u2(offset as u16),
+            Instruction::IfICmpEq { offset } => self.opcode(0x9f).u2(offset as u16),
+            Instruction::IfICmpNe { offset } => self.opcode(0xa0).u2(offset as u16),
+            Instruction::IfICmpLt { offset } => self.opcode(0xa1).u2(offset as u16),
+            Instruction::IfICmpGe { offset } => self.opcode(0xa2).u2(offset as u16),
+            Instruction::IfICmpGt { offset } => self.opcode(0xa3).u2(offset as u16),
+            Instruction::IfICmpLe { offset } => self.opcode(0xa4).u2(offset as u16),
+            Instruction::IfACmpEq { offset } => self.opcode(0xa5).u2(offset as u16),
+            Instruction::IfACmpNe { offset } => self.opcode(0xa6).u2(offset as u16),
+            Instruction::Goto { offset } => self.opcode(0xa7).u2(offset as u16),
+            Instruction::Jsr { offset } => self.opcode(0xa8).u2(offset as u16),
+            Instruction::Ret { index } => self.opcode(0xa9).u1(index),
+            Instruction::TableSwitch {
+                default,
+                low,
+                high,
+                ref jump_offsets,
+            } => {
+                self.opcode(0xaa).switch_padding().u4(default as u32);
+                self.u4(low as u32).u4(high as u32);
+                for &offset in jump_offsets {
+                    self.u4(offset as u32);
+                }
+
+                self
+            }
+            Instruction::LookupSwitch {
+                default,
+                npairs,
+                ref match_offset_pairs,
+            } => {
+                self.opcode(0xab).switch_padding().u4(default as u32);
+                self.u4(npairs as u32);
+                for &(value, offset) in match_offset_pairs {
+                    self.u4(value as u32).u4(offset as u32);
+                }
+
+                self
+            }
+            Instruction::IReturn => self.opcode(0xac),
+            Instruction::LReturn => self.opcode(0xad),
+            Instruction::FReturn => self.opcode(0xae),
+            Instruction::DReturn => self.opcode(0xaf),
+            Instruction::AReturn => self.opcode(0xb0),
+            Instruction::Return => self.opcode(0xb1),
+            Instruction::GetStatic { index } => self.opcode(0xb2).u2(index),
+            Instruction::PutStatic { index } => self.opcode(0xb3).u2(index),
+            Instruction::GetField { index } => self.opcode(0xb4).u2(index),
+            Instruction::PutField { index } => self.opcode(0xb5).u2(index),
+            Instruction::InvokeVirtual { index } => self.opcode(0xb6).u2(index),
+            Instruction::InvokeSpecial { index } => self.opcode(0xb7).u2(index),
+            Instruction::InvokeStatic { index } => self.opcode(0xb8).u2(index),
+            Instruction::InvokeInterface { index, count } => {
+                self.opcode(0xb9).u2(index).u1(count).u1(0)
+            }
+            Instruction::InvokeDynamic { index } => self.opcode(0xba).u2(index).u2(0),
+            Instruction::New { index } => self.opcode(0xbb).u2(index),
+            Instruction::NewArray { atype } => self.opcode(0xbc).u1(atype),
+            Instruction::ANewArray { index } => self.opcode(0xbd).u2(index),
+            Instruction::ArrayLength => self.opcode(0xbe),
+            Instruction::AThrow => self.opcode(0xbf),
+            Instruction::CheckCast { index } => self.opcode(0xc0).u2(index),
+            Instruction::InstanceOf { index } => self.opcode(0xc1).u2(index),
+            Instruction::MonitorEnter => self.opcode(0xc2),
+            Instruction::MonitorExit => self.opcode(0xc3),
+            Instruction::MultiANewArray { index, dimensions } => {
+                self.opcode(0xc5).u2(index).u1(dimensions)
+            }
+            Instruction::IfNull { offset } => self.opcode(0xc6).u2(offset as u16),
+            Instruction::IfNonNull { offset } => self.opcode(0xc7).u2(offset as u16),
+            Instruction::GotoW { offset } => self.opcode(0xc8).u4(offset as u32),
+            Instruction::JsrW { offset } => self.opcode(0xc9).u4(offset as u32),
+            Instruction::ILoadW { index } => self.opcode(0xc4).u1(0x15).u2(index),
+            Instruction::LLoadW { index } => self.opcode(0xc4).u1(0x16).u2(index),
+            Instruction::FLoadW { index } => self.opcode(0xc4).u1(0x17).u2(index),
+            Instruction::DLoadW { index } => self.opcode(0xc4).u1(0x18).u2(index),
+            Instruction::ALoadW { index } => self.opcode(0xc4).u1(0x19).u2(index),
+            Instruction::IStoreW { index } => self.opcode(0xc4).u1(0x36).u2(index),
+            Instruction::LStoreW { index } => self.opcode(0xc4).u1(0x37).u2(index),
+            Instruction::FStoreW { index } => self.opcode(0xc4).u1(0x38).u2(index),
+            Instruction::DStoreW { index } => self.opcode(0xc4).u1(0x39).u2(index),
+            Instruction::AStoreW { index } => self.opcode(0xc4).u1(0x3a).u2(index),
+            Instruction::IIncW { index, value } => {
+                self.opcode(0xc4).u1(0x84).u2(index).u2(value as u16)
+            }
+            Instruction::RetW { index } => self.opcode(0xc4).u1(0xa9).u2(index),
+        };
+    }
+
+    fn opcode(&mut self, opcode: u8) -> &mut Self {
+        self.u1(opcode)
+    }
+
+    fn u1(&mut self, value: u8) -> &mut Self {
+        self.code.push(value);
+        self
+    }
+
+    fn u2(&mut self, value: u16) -> &mut Self {
+        self.code.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn u4(&mut self, value: u32) -> &mut Self {
+        self.code.extend_from_slice(&value.to_be_bytes());
+        self
+    }
+
+    fn switch_padding(&mut self) -> &mut Self {
+        while self.code.len() & 3 != 0 {
+            self.code.push(0);
+        }
+
+        self
+    }
+}
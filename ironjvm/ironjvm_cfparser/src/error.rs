@@ -20,11 +20,28 @@
 
 use std::io::Error;
 
+use crate::visitor::Label;
+
 #[derive(Debug)]
 pub enum ParseError {
+    DuplicateFrame { pc: u16 },
+    InvalidCodeLength,
+    InvalidCodeOffset,
     InvalidConstantPoolIndex,
+    InvalidConstantPoolTag { tag: u8 },
+    InvalidElementValueTag { tag: u8 },
     InvalidMagic,
+    InvalidModifiedUtf8 { cp_index: u16 },
+    InvalidOpcode { opcode: u8 },
+    InvalidStackMapFrameType { frame_type: u8 },
+    InvalidSwitchInstruction,
+    InvalidTargetType { target_type: u8 },
+    InvalidVerificationTypeTag { tag: u8 },
     IoError { src: Error },
+    MissingFrame { pc: u16 },
+    UnexpectedEndOfClassFile,
+    UnexpectedEndOfCode,
+    UnvisitedLabel { label: Label },
 }
 
 impl From<Error> for ParseError {
@@ -0,0 +1,1355 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Computes max_stack, max_locals and the StackMapTable of a method from its instructions, like
+// ASM's COMPUTE_FRAMES. The types of the local variables and operand stack are inferred by
+// data-flow over the instructions, merging references to their common superclass where control
+// flow joins.
+
+use ironjvm_javautil::descriptor::method::MethodDescriptor;
+use ironjvm_javautil::descriptor::method::ReturnDescriptor;
+use ironjvm_javautil::descriptor::BaseType;
+use ironjvm_javautil::descriptor::TypeDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_specimpl::classfile::attrinfo::cattr::CodeAttributeExceptionTableEntry;
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::attrinfo::smtattr::StackMapFrame;
+use ironjvm_specimpl::classfile::attrinfo::smtattr::VerificationTypeInfo;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::flags::MethodAccessFlags;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::code::CodeWriter;
+use crate::frames::error::FrameError;
+use crate::frames::error::FrameResult;
+
+pub mod error;
+
+/// Finds the class two reference types are merged to where control flow joins.
+pub trait SuperclassResolver {
+    /// The most specific common superclass of the classes `a` and `b`, given as internal names.
+    /// As in ASM, interfaces can simply be merged to `java/lang/Object`.
+    fn common_superclass(&self, a: &JStr, b: &JStr) -> JString;
+}
+
+/// The method whose frames are computed.
+pub struct FrameMethod<'a, 'clazz> {
+    pub constant_pool: &'a [CpInfo<'clazz>],
+    pub this_class: &'a JStr,
+    pub access_flags: u16,
+    pub name: &'a JStr,
+    pub descriptor: &'a JStr,
+}
+
+#[derive(Clone, Debug)]
+pub struct ComputedFrames {
+    pub max_stack: u16,
+    pub max_locals: u16,
+    pub stack_map_table: Vec<StackMapFrame>,
+    /// The code which cannot be reached, as `(start_pc, end_pc)` ranges. The stack map table is
+    /// only right once `replace_unreachable` has replaced it.
+    pub unreachable: Vec<(u16, u16)>,
+}
+
+/// The inferred type of a local variable or operand stack entry. Longs and doubles take two local
+/// variables, the second of which is `Top`, but only one operand stack entry.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FrameType {
+    Top,
+    Integer,
+    Float,
+    Long,
+    Double,
+    Null,
+    UninitializedThis,
+    // an internal name, or the descriptor of an array type
+    Object(JString),
+    // created by the `new` instruction at this offset
+    Uninitialized(u16),
+}
+
+impl FrameType {
+    fn from_descriptor(descriptor: &TypeDescriptor) -> Self {
+        if descriptor.dimensions > 0 {
+            return Self::Object(descriptor.to_jstring());
+        }
+
+        match descriptor.r#type {
+            BaseType::Boolean
+            | BaseType::Byte
+            | BaseType::Char
+            | BaseType::Int
+            | BaseType::Short => Self::Integer,
+            BaseType::Float => Self::Float,
+            BaseType::Long => Self::Long,
+            BaseType::Double => Self::Double,
+            BaseType::Object(name) => Self::Object(name.to_owned()),
+        }
+    }
+
+    fn object(name: &str) -> Self {
+        Self::Object(JString::from(name))
+    }
+
+    fn is_wide(&self) -> bool {
+        matches!(self, Self::Long | Self::Double)
+    }
+
+    fn size(&self) -> usize {
+        if self.is_wide() {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+/// Computes the frames of a method from its decoded instructions. `class_index` returns the
+/// constant pool index of a `ConstantClass` for the given name, adding one if needed, for the
+/// reference types in the frames. Code which cannot be reached is not an error, but is given in
+/// `unreachable` to be replaced as ASM does.
+pub fn compute_frames<R: SuperclassResolver>(
+    method: &FrameMethod,
+    instructions: &[(u16, Instruction)],
+    exception_table: &[CodeAttributeExceptionTableEntry],
+    resolver: &R,
+    mut class_index: impl FnMut(&JStr) -> u16,
+) -> FrameResult<ComputedFrames> {
+    let mut analyzer = Analyzer {
+        method,
+        instructions,
+        exception_table,
+        resolver,
+        frames: vec![None; instructions.len()],
+        worklist: Vec::new(),
+        max_stack: 0,
+        max_locals: 0,
+    };
+
+    let initial = analyzer.initial_frame()?;
+    analyzer.max_locals = initial.locals.len();
+    analyzer.run(initial.clone())?;
+
+    // As ASM does, each run of code which cannot be reached gets a frame with no locals and a
+    // Throwable on the stack, which is right once the code is replaced by nops ending in athrow.
+    let unreachable = unreachable_ranges(instructions, &analyzer.frames);
+    let unreachable_frame = Frame {
+        locals: Vec::new(),
+        stack: vec![FrameType::object("java/lang/Throwable")],
+    };
+    if !unreachable.is_empty() {
+        analyzer.max_stack = analyzer.max_stack.max(1);
+    }
+
+    let mut stack_map_table = Vec::new();
+    let mut previous_locals = values(&initial.locals);
+    let mut previous_pc = None;
+    for index in frame_targets(instructions, exception_table)? {
+        let pc = instructions[index].0;
+        let frame = match &analyzer.frames[index] {
+            Some(frame) => frame,
+            None if unreachable.iter().any(|&(start_pc, _)| start_pc == pc) => &unreachable_frame,
+            // the rest of the unreachable code becomes nops, which need no frames
+            None => continue,
+        };
+        let offset_delta = match previous_pc {
+            Some(previous_pc) => pc - previous_pc - 1,
+            None => pc,
+        };
+
+        let locals = values(&frame.locals);
+        stack_map_table.push(compress(
+            &previous_locals,
+            &locals,
+            &frame.stack,
+            offset_delta,
+            &mut class_index,
+        ));
+
+        previous_locals = locals;
+        previous_pc = Some(pc);
+    }
+
+    Ok(ComputedFrames {
+        max_stack: analyzer.max_stack as u16,
+        max_locals: analyzer.max_locals as u16,
+        stack_map_table,
+        unreachable,
+    })
+}
+
+/// Replaces each range of unreachable `code` with nops ending in athrow, returning the exception
+/// table without the replaced code in its ranges, as nothing there can throw to a handler.
+pub fn replace_unreachable(
+    code: &mut [u8],
+    exception_table: &[CodeAttributeExceptionTableEntry],
+    unreachable: &[(u16, u16)],
+) -> Vec<CodeAttributeExceptionTableEntry> {
+    for &(start_pc, end_pc) in unreachable {
+        code[start_pc as usize..end_pc as usize].fill(0x00);
+        code[end_pc as usize - 1] = 0xbf;
+    }
+
+    let mut replaced = Vec::with_capacity(exception_table.len());
+    for handler in exception_table {
+        let mut ranges = vec![(handler.start_pc, handler.end_pc)];
+        for &(start_pc, end_pc) in unreachable {
+            ranges = ranges
+                .into_iter()
+                .flat_map(|(start, end)| [(start, end.min(start_pc)), (start.max(end_pc), end)])
+                .filter(|(start, end)| start < end)
+                .collect();
+        }
+
+        replaced.extend(ranges.into_iter().map(|(start_pc, end_pc)| {
+            CodeAttributeExceptionTableEntry {
+                start_pc,
+                end_pc,
+                ..handler.clone()
+            }
+        }));
+    }
+
+    replaced
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Frame {
+    locals: Vec<FrameType>,
+    stack: Vec<FrameType>,
+}
+
+impl Frame {
+    fn push(&mut self, r#type: FrameType) {
+        self.stack.push(r#type);
+    }
+
+    fn pop(&mut self, pc: u16) -> FrameResult<FrameType> {
+        self.stack.pop().ok_or(FrameError::StackUnderflow { pc })
+    }
+
+    fn pop_many(&mut self, pc: u16, count: usize) -> FrameResult<()> {
+        if self.stack.len() < count {
+            return Err(FrameError::StackUnderflow { pc });
+        }
+
+        self.stack.truncate(self.stack.len() - count);
+        Ok(())
+    }
+
+    fn local(&self, index: usize) -> FrameType {
+        self.locals.get(index).cloned().unwrap_or(FrameType::Top)
+    }
+
+    fn set_local(&mut self, index: usize, r#type: FrameType) {
+        let size = r#type.size();
+        if self.locals.len() < index + size {
+            self.locals.resize(index + size, FrameType::Top);
+        }
+
+        // overwriting the second half of a long or double invalidates it
+        if index > 0 && self.locals[index - 1].is_wide() {
+            self.locals[index - 1] = FrameType::Top;
+        }
+
+        if size == 2 {
+            self.locals[index + 1] = FrameType::Top;
+        }
+        self.locals[index] = r#type;
+    }
+
+    fn stack_size(&self) -> usize {
+        self.stack.iter().map(FrameType::size).sum()
+    }
+}
+
+// where control goes after an instruction, besides exception handlers
+struct Successors {
+    targets: Vec<i64>,
+    falls_through: bool,
+}
+
+impl Successors {
+    fn next() -> Self {
+        Self {
+            targets: Vec::new(),
+            falls_through: true,
+        }
+    }
+
+    fn none() -> Self {
+        Self {
+            targets: Vec::new(),
+            falls_through: false,
+        }
+    }
+}
+
+struct Analyzer<'a, 'clazz, R> {
+    method: &'a FrameMethod<'a, 'clazz>,
+    instructions: &'a [(u16, Instruction)],
+    exception_table: &'a [CodeAttributeExceptionTableEntry],
+    resolver: &'a R,
+    // the frame before each instruction
+    frames: Vec<Option<Frame>>,
+    worklist: Vec<usize>,
+    max_stack: usize,
+    max_locals: usize,
+}
+
+impl<'a, 'clazz, R: SuperclassResolver> Analyzer<'a, 'clazz, R> {
+    fn initial_frame(&self) -> FrameResult<Frame> {
+        let descriptor = MethodDescriptor::from_jstr(self.method.descriptor)
+            .map_err(|_| FrameError::InvalidDescriptor)?;
+        let mut frame = Frame {
+            locals: Vec::new(),
+            stack: Vec::new(),
+        };
+
+        if self.method.access_flags & MethodAccessFlags::ACC_STATIC == 0 {
+            let this =
+                if self.method.name == "<init>" && self.method.this_class != "java/lang/Object" {
+                    FrameType::UninitializedThis
+                } else {
+                    FrameType::Object(self.method.this_class.to_owned())
+                };
+
+            frame.locals.push(this);
+        }
+
+        for parameter in descriptor.parameters_iter() {
+            let index = frame.locals.len();
+            frame.set_local(index, FrameType::from_descriptor(&parameter));
+        }
+
+        Ok(frame)
+    }
+
+    fn run(&mut self, initial: Frame) -> FrameResult<()> {
+        if self.instructions.is_empty() {
+            return Ok(());
+        }
+
+        self.frames[0] = Some(initial);
+        self.worklist.push(0);
+
+        while let Some(index) = self.worklist.pop() {
+            let (pc, instruction) = &self.instructions[index];
+            let before = self.frames[index].clone().unwrap();
+
+            let mut frame = before.clone();
+            let successors = self.execute(&mut frame, *pc, instruction)?;
+            self.max_stack = self
+                .max_stack
+                .max(before.stack_size())
+                .max(frame.stack_size());
+            self.max_locals = self.max_locals.max(frame.locals.len());
+
+            // a handler can be reached before or after the instruction changes the locals
+            for handler in self.exception_table {
+                if !(handler.start_pc..handler.end_pc).contains(pc) {
+                    continue;
+                }
+
+                let exception = if handler.catch_type == 0 {
+                    FrameType::object("java/lang/Throwable")
+                } else {
+                    FrameType::Object(self.class_name(handler.catch_type)?.to_owned())
+                };
+                let handler_index = self.index_of(handler.handler_pc as i64, *pc)?;
+
+                for locals in [&before.locals, &frame.locals] {
+                    self.propagate(
+                        handler_index,
+                        Frame {
+                            locals: locals.clone(),
+                            stack: vec![exception.clone()],
+                        },
+                    )?;
+                }
+            }
+
+            for target in &successors.targets {
+                let target_index = self.index_of(*pc as i64 + target, *pc)?;
+                self.propagate(target_index, frame.clone())?;
+            }
+
+            if successors.falls_through {
+                if index + 1 == self.instructions.len() {
+                    return Err(FrameError::InvalidBranchTarget { pc: *pc });
+                }
+
+                self.propagate(index + 1, frame)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn propagate(&mut self, index: usize, frame: Frame) -> FrameResult<()> {
+        let merged = match &self.frames[index] {
+            Some(existing) => {
+                let merged = self.merge(existing, &frame, self.instructions[index].0)?;
+                if merged == *existing {
+                    return Ok(());
+                }
+
+                merged
+            }
+            None => frame,
+        };
+
+        self.frames[index] = Some(merged);
+        self.worklist.push(index);
+
+        Ok(())
+    }
+
+    fn merge(&self, existing: &Frame, incoming: &Frame, pc: u16) -> FrameResult<Frame> {
+        if existing.stack.len() != incoming.stack.len() {
+            return Err(FrameError::InconsistentStackHeight { pc });
+        }
+
+        Ok(Frame {
+            locals: existing
+                .locals
+                .iter()
+                .zip(&incoming.locals)
+                .map(|(a, b)| self.merge_type(a, b))
+                .collect(),
+            stack: existing
+                .stack
+                .iter()
+                .zip(&incoming.stack)
+                .map(|(a, b)| self.merge_type(a, b))
+                .collect(),
+        })
+    }
+
+    fn merge_type(&self, a: &FrameType, b: &FrameType) -> FrameType {
+        match (a, b) {
+            _ if a == b => a.clone(),
+            (FrameType::Null, FrameType::Object(_)) => b.clone(),
+            (FrameType::Object(_), FrameType::Null) => a.clone(),
+            (FrameType::Object(a), FrameType::Object(b)) => {
+                FrameType::Object(self.merge_references(a, b))
+            }
+            _ => FrameType::Top,
+        }
+    }
+
+    fn merge_references(&self, a: &JStr, b: &JStr) -> JString {
+        let is_array = |name: &JStr| name.as_bytes().first() == Some(&b'[');
+        if !is_array(a) && !is_array(b) {
+            return self.resolver.common_superclass(a, b);
+        }
+
+        // arrays of references merge to arrays of the merged element type
+        let element = |name: &JStr| match name.as_bytes().get(1) {
+            Some(b'[') => Some(name[1..].to_owned()),
+            Some(b'L') => Some(name[2..name.len() - 1].to_owned()),
+            _ => None,
+        };
+        if let (true, true, Some(a), Some(b)) = (is_array(a), is_array(b), element(a), element(b)) {
+            return array_of(&self.merge_references(&a, &b));
+        }
+
+        JString::from("java/lang/Object")
+    }
+
+    fn index_of(&self, target: i64, pc: u16) -> FrameResult<usize> {
+        u16::try_from(target)
+            .ok()
+            .and_then(|target| {
+                self.instructions
+                    .binary_search_by_key(&target, |(pc, _)| *pc)
+                    .ok()
+            })
+            .ok_or(FrameError::InvalidBranchTarget { pc })
+    }
+
+    fn touch_local(&mut self, index: usize, r#type: &FrameType) {
+        self.max_locals = self.max_locals.max(index + r#type.size());
+    }
+
+    fn load(&mut self, frame: &mut Frame, index: usize, r#type: Option<FrameType>) {
+        // aload pushes whatever reference the local variable holds
+        let r#type = r#type.unwrap_or_else(|| frame.local(index));
+        self.touch_local(index, &r#type);
+        frame.push(r#type);
+    }
+
+    fn store(&mut self, frame: &mut Frame, pc: u16, index: usize) -> FrameResult<()> {
+        let value = frame.pop(pc)?;
+        self.touch_local(index, &value);
+        frame.set_local(index, value);
+
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        frame: &mut Frame,
+        pc: u16,
+        instruction: &Instruction,
+    ) -> FrameResult<Successors> {
+        match *instruction {
+            Instruction::Nop => {}
+            Instruction::AConstNull => frame.push(FrameType::Null),
+            Instruction::IConstM1
+            | Instruction::IConst0
+            | Instruction::IConst1
+            | Instruction::IConst2
+            | Instruction::IConst3
+            | Instruction::IConst4
+            | Instruction::IConst5
+            | Instruction::BIPush { .. }
+            | Instruction::SIPush { .. } => frame.push(FrameType::Integer),
+            Instruction::LConst0 | Instruction::LConst1 => frame.push(FrameType::Long),
+            Instruction::FConst0 | Instruction::FConst1 | Instruction::FConst2 => {
+                frame.push(FrameType::Float)
+            }
+            Instruction::DConst0 | Instruction::DConst1 => frame.push(FrameType::Double),
+            Instruction::Ldc { index } => frame.push(self.constant_type(index as u16)?),
+            Instruction::LdcW { index } | Instruction::Ldc2W { index } => {
+                frame.push(self.constant_type(index)?)
+            }
+            Instruction::ILoad { index } => {
+                self.load(frame, index as usize, Some(FrameType::Integer))
+            }
+            Instruction::LLoad { index } => self.load(frame, index as usize, Some(FrameType::Long)),
+            Instruction::FLoad { index } => {
+                self.load(frame, index as usize, Some(FrameType::Float))
+            }
+            Instruction::DLoad { index } => {
+                self.load(frame, index as usize, Some(FrameType::Double))
+            }
+            Instruction::ALoad { index } => self.load(frame, index as usize, None),
+            Instruction::ILoadW { index } => {
+                self.load(frame, index as usize, Some(FrameType::Integer))
+            }
+            Instruction::LLoadW { index } => {
+                self.load(frame, index as usize, Some(FrameType::Long))
+            }
+            Instruction::FLoadW { index } => {
+                self.load(frame, index as usize, Some(FrameType::Float))
+            }
+            Instruction::DLoadW { index } => {
+                self.load(frame, index as usize, Some(FrameType::Double))
+            }
+            Instruction::ALoadW { index } => self.load(frame, index as usize, None),
+            Instruction::ILoad0 => self.load(frame, 0, Some(FrameType::Integer)),
+            Instruction::ILoad1 => self.load(frame, 1, Some(FrameType::Integer)),
+            Instruction::ILoad2 => self.load(frame, 2, Some(FrameType::Integer)),
+            Instruction::ILoad3 => self.load(frame, 3, Some(FrameType::Integer)),
+            Instruction::LLoad0 => self.load(frame, 0, Some(FrameType::Long)),
+            Instruction::LLoad1 => self.load(frame, 1, Some(FrameType::Long)),
+            Instruction::LLoad2 => self.load(frame, 2, Some(FrameType::Long)),
+            Instruction::LLoad3 => self.load(frame, 3, Some(FrameType::Long)),
+            Instruction::FLoad0 => self.load(frame, 0, Some(FrameType::Float)),
+            Instruction::FLoad1 => self.load(frame, 1, Some(FrameType::Float)),
+            Instruction::FLoad2 => self.load(frame, 2, Some(FrameType::Float)),
+            Instruction::FLoad3 => self.load(frame, 3, Some(FrameType::Float)),
+            Instruction::DLoad0 => self.load(frame, 0, Some(FrameType::Double)),
+            Instruction::DLoad1 => self.load(frame, 1, Some(FrameType::Double)),
+            Instruction::DLoad2 => self.load(frame, 2, Some(FrameType::Double)),
+            Instruction::DLoad3 => self.load(frame, 3, Some(FrameType::Double)),
+            Instruction::ALoad0 => self.load(frame, 0, None),
+            Instruction::ALoad1 => self.load(frame, 1, None),
+            Instruction::ALoad2 => self.load(frame, 2, None),
+            Instruction::ALoad3 => self.load(frame, 3, None),
+            Instruction::IALoad
+            | Instruction::BALoad
+            | Instruction::CALoad
+            | Instruction::SALoad => {
+                frame.pop_many(pc, 2)?;
+                frame.push(FrameType::Integer);
+            }
+            Instruction::LALoad => {
+                frame.pop_many(pc, 2)?;
+                frame.push(FrameType::Long);
+            }
+            Instruction::FALoad => {
+                frame.pop_many(pc, 2)?;
+                frame.push(FrameType::Float);
+            }
+            Instruction::DALoad => {
+                frame.pop_many(pc, 2)?;
+                frame.push(FrameType::Double);
+            }
+            Instruction::AALoad => {
+                frame.pop(pc)?;
+                let array = frame.pop(pc)?;
+                frame.push(component_type(&array));
+            }
+            Instruction::IStore { index }
+            | Instruction::LStore { index }
+            | Instruction::FStore { index }
+            | Instruction::DStore { index }
+            | Instruction::AStore { index } => self.store(frame, pc, index as usize)?,
+            Instruction::IStoreW { index }
+            | Instruction::LStoreW { index }
+            | Instruction::FStoreW { index }
+            | Instruction::DStoreW { index }
+            | Instruction::AStoreW { index } => self.store(frame, pc, index as usize)?,
+            Instruction::IStore0
+            | Instruction::LStore0
+            | Instruction::FStore0
+            | Instruction::DStore0
+            | Instruction::AStore0 => self.store(frame, pc, 0)?,
+            Instruction::IStore1
+            | Instruction::LStore1
+            | Instruction::FStore1
+            | Instruction::DStore1
+            | Instruction::AStore1 => self.store(frame, pc, 1)?,
+            Instruction::IStore2
+            | Instruction::LStore2
+            | Instruction::FStore2
+            | Instruction::DStore2
+            | Instruction::AStore2 => self.store(frame, pc, 2)?,
+            Instruction::IStore3
+            | Instruction::LStore3
+            | Instruction::FStore3
+            | Instruction::DStore3
+            | Instruction::AStore3 => self.store(frame, pc, 3)?,
+            Instruction::IAStore
+            | Instruction::LAStore
+            | Instruction::FAStore
+            | Instruction::DAStore
+            | Instruction::AAStore
+            | Instruction::BAStore
+            | Instruction::CAStore
+            | Instruction::SAStore => frame.pop_many(pc, 3)?,
+            Instruction::Pop => frame.pop_many(pc, 1)?,
+            Instruction::Pop2 => {
+                if !frame.pop(pc)?.is_wide() {
+                    frame.pop(pc)?;
+                }
+            }
+            Instruction::Dup => {
+                let value = frame.pop(pc)?;
+                frame.push(value.clone());
+                frame.push(value);
+            }
+            Instruction::DupX1 => {
+                let value1 = frame.pop(pc)?;
+                let value2 = frame.pop(pc)?;
+                frame.stack.extend([value1.clone(), value2, value1]);
+            }
+            Instruction::DupX2 => {
+                let value1 = frame.pop(pc)?;
+                let value2 = frame.pop(pc)?;
+                if value2.is_wide() {
+                    frame.stack.extend([value1.clone(), value2, value1]);
+                } else {
+                    let value3 = frame.pop(pc)?;
+                    frame.stack.extend([value1.clone(), value3, value2, value1]);
+                }
+            }
+            Instruction::Dup2 => {
+                let value1 = frame.pop(pc)?;
+                if value1.is_wide() {
+                    frame.stack.extend([value1.clone(), value1]);
+                } else {
+                    let value2 = frame.pop(pc)?;
+                    frame
+                        .stack
+                        .extend([value2.clone(), value1.clone(), value2, value1]);
+                }
+            }
+            Instruction::Dup2X1 => {
+                let value1 = frame.pop(pc)?;
+                let value2 = frame.pop(pc)?;
+                if value1.is_wide() {
+                    frame.stack.extend([value1.clone(), value2, value1]);
+                } else {
+                    let value3 = frame.pop(pc)?;
+                    frame
+                        .stack
+                        .extend([value2.clone(), value1.clone(), value3, value2, value1]);
+                }
+            }
+            Instruction::Dup2X2 => {
+                let value1 = frame.pop(pc)?;
+                let value2 = frame.pop(pc)?;
+                match (value1.is_wide(), value2.is_wide()) {
+                    (true, true) => frame.stack.extend([value1.clone(), value2, value1]),
+                    (true, false) => {
+                        let value3 = frame.pop(pc)?;
+                        frame.stack.extend([value1.clone(), value3, value2, value1]);
+                    }
+                    (false, _) => {
+                        let value3 = frame.pop(pc)?;
+                        if value3.is_wide() {
+                            frame.stack.extend([
+                                value2.clone(),
+                                value1.clone(),
+                                value3,
+                                value2,
+                                value1,
+                            ]);
+                        } else {
+                            let value4 = frame.pop(pc)?;
+                            frame.stack.extend([
+                                value2.clone(),
+                                value1.clone(),
+                                value4,
+                                value3,
+                                value2,
+                                value1,
+                            ]);
+                        }
+                    }
+                }
+            }
+            Instruction::Swap => {
+                let value1 = frame.pop(pc)?;
+                let value2 = frame.pop(pc)?;
+                frame.stack.extend([value1, value2]);
+            }
+            Instruction::IAdd
+            | Instruction::ISub
+            | Instruction::IMul
+            | Instruction::IDiv
+            | Instruction::IRem
+            | Instruction::IShl
+            | Instruction::IShr
+            | Instruction::IUShr
+            | Instruction::IAnd
+            | Instruction::IOr
+            | Instruction::IXor
+            | Instruction::LCmp
+            | Instruction::FCmpL
+            | Instruction::FCmpG
+            | Instruction::DCmpL
+            | Instruction::DCmpG => {
+                frame.pop_many(pc, 2)?;
+                frame.push(FrameType::Integer);
+            }
+            Instruction::LAdd
+            | Instruction::LSub
+            | Instruction::LMul
+            | Instruction::LDiv
+            | Instruction::LRem
+            | Instruction::LShl
+            | Instruction::LShr
+            | Instruction::LUShr
+            | Instruction::LAnd
+            | Instruction::LOr
+            | Instruction::LXor => {
+                frame.pop_many(pc, 2)?;
+                frame.push(FrameType::Long);
+            }
+            Instruction::FAdd
+            | Instruction::FSub
+            | Instruction::FMul
+            | Instruction::FDiv
+            | Instruction::FRem => {
+                frame.pop_many(pc, 2)?;
+                frame.push(FrameType::Float);
+            }
+            Instruction::DAdd
+            | Instruction::DSub
+            | Instruction::DMul
+            | Instruction::DDiv
+            | Instruction::DRem => {
+                frame.pop_many(pc, 2)?;
+                frame.push(FrameType::Double);
+            }
+            Instruction::INeg
+            | Instruction::L2I
+            | Instruction::F2I
+            | Instruction::D2I
+            | Instruction::I2B
+            | Instruction::I2C
+            | Instruction::I2S
+            | Instruction::ArrayLength
+            | Instruction::InstanceOf { .. } => {
+                frame.pop(pc)?;
+                frame.push(FrameType::Integer);
+            }
+            Instruction::LNeg | Instruction::I2L | Instruction::F2L | Instruction::D2L => {
+                frame.pop(pc)?;
+                frame.push(FrameType::Long);
+            }
+            Instruction::FNeg | Instruction::I2F | Instruction::L2F | Instruction::D2F => {
+                frame.pop(pc)?;
+                frame.push(FrameType::Float);
+            }
+            Instruction::DNeg | Instruction::I2D | Instruction::L2D | Instruction::F2D => {
+                frame.pop(pc)?;
+                frame.push(FrameType::Double);
+            }
+            Instruction::IInc { index, .. } => {
+                self.touch_local(index as usize, &FrameType::Integer)
+            }
+            Instruction::IIncW { index, .. } => {
+                self.touch_local(index as usize, &FrameType::Integer)
+            }
+            Instruction::IfEq { offset }
+            | Instruction::IfNe { offset }
+            | Instruction::IfLt { offset }
+            | Instruction::IfGe { offset }
+            | Instruction::IfGt { offset }
+            | Instruction::IfLe { offset }
+            | Instruction::IfNull { offset }
+            | Instruction::IfNonNull { offset } => {
+                frame.pop(pc)?;
+                return Ok(Successors {
+                    targets: vec![offset as i64],
+                    falls_through: true,
+                });
+            }
+            Instruction::IfICmpEq { offset }
+            | Instruction::IfICmpNe { offset }
+            | Instruction::IfICmpLt { offset }
+            | Instruction::IfICmpGe { offset }
+            | Instruction::IfICmpGt { offset }
+            | Instruction::IfICmpLe { offset }
+            | Instruction::IfACmpEq { offset }
+            | Instruction::IfACmpNe { offset } => {
+                frame.pop_many(pc, 2)?;
+                return Ok(Successors {
+                    targets: vec![offset as i64],
+                    falls_through: true,
+                });
+            }
+            Instruction::Goto { offset } => {
+                return Ok(Successors {
+                    targets: vec![offset as i64],
+                    falls_through: false,
+                })
+            }
+            Instruction::GotoW { offset } => {
+                return Ok(Successors {
+                    targets: vec![offset as i64],
+                    falls_through: false,
+                })
+            }
+            Instruction::Jsr { .. }
+            | Instruction::JsrW { .. }
+            | Instruction::Ret { .. }
+            | Instruction::RetW { .. } => return Err(FrameError::UnsupportedSubroutine { pc }),
+            Instruction::TableSwitch {
+                default,
+                ref jump_offsets,
+                ..
+            } => {
+                frame.pop(pc)?;
+                return Ok(Successors {
+                    targets: [default]
+                        .iter()
+                        .chain(jump_offsets)
+                        .map(|offset| *offset as i64)
+                        .collect(),
+                    falls_through: false,
+                });
+            }
+            Instruction::LookupSwitch {
+                default,
+                ref match_offset_pairs,
+                ..
+            } => {
+                frame.pop(pc)?;
+                return Ok(Successors {
+                    targets: [default]
+                        .into_iter()
+                        .chain(match_offset_pairs.iter().map(|(_, offset)| *offset))
+                        .map(|offset| offset as i64)
+                        .collect(),
+                    falls_through: false,
+                });
+            }
+            Instruction::IReturn
+            | Instruction::LReturn
+            | Instruction::FReturn
+            | Instruction::DReturn
+            | Instruction::AReturn
+            | Instruction::AThrow => {
+                frame.pop(pc)?;
+                return Ok(Successors::none());
+            }
+            Instruction::Return => return Ok(Successors::none()),
+            Instruction::GetStatic { index } => frame.push(self.field_type(index)?),
+            Instruction::PutStatic { .. } => frame.pop_many(pc, 1)?,
+            Instruction::GetField { index } => {
+                frame.pop(pc)?;
+                frame.push(self.field_type(index)?);
+            }
+            Instruction::PutField { .. } => frame.pop_many(pc, 2)?,
+            Instruction::InvokeVirtual { index }
+            | Instruction::InvokeSpecial { index }
+            | Instruction::InvokeStatic { index }
+            | Instruction::InvokeInterface { index, .. } => {
+                let (name, descriptor) = self.member_ref(index)?;
+                let descriptor = MethodDescriptor::from_jstr(descriptor)
+                    .map_err(|_| FrameError::InvalidDescriptor)?;
+                frame.pop_many(pc, descriptor.parameter_count())?;
+
+                if !matches!(instruction, Instruction::InvokeStatic { .. }) {
+                    let receiver = frame.pop(pc)?;
+                    if matches!(instruction, Instruction::InvokeSpecial { .. }) && name == "<init>"
+                    {
+                        self.initialize(frame, &receiver)?;
+                    }
+                }
+
+                if let ReturnDescriptor::FieldType(r#type) = descriptor.return_type() {
+                    frame.push(FrameType::from_descriptor(&r#type));
+                }
+            }
+            Instruction::InvokeDynamic { index } => {
+                let CpInfoType::ConstantInvokeDynamic {
+                    name_and_type_index,
+                    ..
+                } = *self.constant(index)?
+                else {
+                    return Err(FrameError::InvalidConstantPoolEntry { index });
+                };
+                let (_, descriptor) = self.name_and_type(name_and_type_index)?;
+                let descriptor = MethodDescriptor::from_jstr(descriptor)
+                    .map_err(|_| FrameError::InvalidDescriptor)?;
+                frame.pop_many(pc, descriptor.parameter_count())?;
+
+                if let ReturnDescriptor::FieldType(r#type) = descriptor.return_type() {
+                    frame.push(FrameType::from_descriptor(&r#type));
+                }
+            }
+            Instruction::New { .. } => frame.push(FrameType::Uninitialized(pc)),
+            Instruction::NewArray { atype } => {
+                frame.pop(pc)?;
+                frame.push(match atype {
+                    4 => FrameType::object("[Z"),
+                    5 => FrameType::object("[C"),
+                    6 => FrameType::object("[F"),
+                    7 => FrameType::object("[D"),
+                    8 => FrameType::object("[B"),
+                    9 => FrameType::object("[S"),
+                    10 => FrameType::object("[I"),
+                    11 => FrameType::object("[J"),
+                    _ => FrameType::Top,
+                });
+            }
+            Instruction::ANewArray { index } => {
+                frame.pop(pc)?;
+                frame.push(FrameType::Object(array_of(self.class_name(index)?)));
+            }
+            Instruction::CheckCast { index } => {
+                frame.pop(pc)?;
+                frame.push(FrameType::Object(self.class_name(index)?.to_owned()));
+            }
+            Instruction::MonitorEnter | Instruction::MonitorExit => frame.pop_many(pc, 1)?,
+            Instruction::MultiANewArray { index, dimensions } => {
+                frame.pop_many(pc, dimensions as usize)?;
+                frame.push(FrameType::Object(self.class_name(index)?.to_owned()));
+            }
+        }
+
+        Ok(Successors::next())
+    }
+
+    // replaces the uninitialized object a constructor has been called on with the initialized one
+    fn initialize(&self, frame: &mut Frame, receiver: &FrameType) -> FrameResult<()> {
+        let initialized = match *receiver {
+            FrameType::UninitializedThis => FrameType::Object(self.method.this_class.to_owned()),
+            FrameType::Uninitialized(offset) => {
+                let index = self.index_of(offset as i64, offset)?;
+                let Instruction::New { index } = self.instructions[index].1 else {
+                    return Err(FrameError::InvalidBranchTarget { pc: offset });
+                };
+
+                FrameType::Object(self.class_name(index)?.to_owned())
+            }
+            _ => return Ok(()),
+        };
+
+        for r#type in frame.locals.iter_mut().chain(frame.stack.iter_mut()) {
+            if r#type == receiver {
+                *r#type = initialized.clone();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn constant(&self, index: u16) -> FrameResult<&'a CpInfoType<'clazz>> {
+        (index as usize)
+            .checked_sub(1)
+            .and_then(|index| self.method.constant_pool.get(index))
+            .map(|cp_info| &cp_info.info)
+            .ok_or(FrameError::InvalidConstantPoolEntry { index })
+    }
+
+    fn utf8(&self, index: u16) -> FrameResult<&'clazz JStr> {
+        match *self.constant(index)? {
+            CpInfoType::ConstantUtf8 { string, .. } => Ok(string),
+            _ => Err(FrameError::InvalidConstantPoolEntry { index }),
+        }
+    }
+
+    fn class_name(&self, index: u16) -> FrameResult<&'clazz JStr> {
+        match *self.constant(index)? {
+            CpInfoType::ConstantClass { name_index } => self.utf8(name_index),
+            _ => Err(FrameError::InvalidConstantPoolEntry { index }),
+        }
+    }
+
+    fn name_and_type(&self, index: u16) -> FrameResult<(&'clazz JStr, &'clazz JStr)> {
+        match *self.constant(index)? {
+            CpInfoType::ConstantNameAndType {
+                name_index,
+                descriptor_index,
+            } => Ok((self.utf8(name_index)?, self.utf8(descriptor_index)?)),
+            _ => Err(FrameError::InvalidConstantPoolEntry { index }),
+        }
+    }
+
+    fn member_ref(&self, index: u16) -> FrameResult<(&'clazz JStr, &'clazz JStr)> {
+        match *self.constant(index)? {
+            CpInfoType::ConstantFieldRef {
+                name_and_type_index,
+                ..
+            }
+            | CpInfoType::ConstantMethodRef {
+                name_and_type_index,
+                ..
+            }
+            | CpInfoType::ConstantInterfaceMethodRef {
+                name_and_type_index,
+                ..
+            } => self.name_and_type(name_and_type_index),
+            _ => Err(FrameError::InvalidConstantPoolEntry { index }),
+        }
+    }
+
+    fn field_type(&self, index: u16) -> FrameResult<FrameType> {
+        let (_, descriptor) = self.member_ref(index)?;
+        let descriptor =
+            TypeDescriptor::from_jstr(descriptor).map_err(|_| FrameError::InvalidDescriptor)?;
+
+        Ok(FrameType::from_descriptor(&descriptor))
+    }
+
+    fn constant_type(&self, index: u16) -> FrameResult<FrameType> {
+        Ok(match *self.constant(index)? {
+            CpInfoType::ConstantInteger { .. } => FrameType::Integer,
+            CpInfoType::ConstantFloat { .. } => FrameType::Float,
+            CpInfoType::ConstantLong { .. } => FrameType::Long,
+            CpInfoType::ConstantDouble { .. } => FrameType::Double,
+            CpInfoType::ConstantString { .. } => FrameType::object("java/lang/String"),
+            CpInfoType::ConstantClass { .. } => FrameType::object("java/lang/Class"),
+            CpInfoType::ConstantMethodType { .. } => {
+                FrameType::object("java/lang/invoke/MethodType")
+            }
+            CpInfoType::ConstantMethodHandle { .. } => {
+                FrameType::object("java/lang/invoke/MethodHandle")
+            }
+            CpInfoType::ConstantDynamic {
+                name_and_type_index,
+                ..
+            } => {
+                let (_, descriptor) = self.name_and_type(name_and_type_index)?;
+                let descriptor = TypeDescriptor::from_jstr(descriptor)
+                    .map_err(|_| FrameError::InvalidDescriptor)?;
+
+                FrameType::from_descriptor(&descriptor)
+            }
+            _ => return Err(FrameError::InvalidConstantPoolEntry { index }),
+        })
+    }
+}
+
+// the instructions which need an explicit frame: branch targets, exception handlers and
+// instructions only reachable by a jump
+fn frame_targets(
+    instructions: &[(u16, Instruction)],
+    exception_table: &[CodeAttributeExceptionTableEntry],
+) -> FrameResult<Vec<usize>> {
+    let mut is_target = vec![false; instructions.len()];
+    let index_of = |target: i64, pc: u16| {
+        u16::try_from(target)
+            .ok()
+            .and_then(|target| {
+                instructions
+                    .binary_search_by_key(&target, |(pc, _)| *pc)
+                    .ok()
+            })
+            .ok_or(FrameError::InvalidBranchTarget { pc })
+    };
+
+    for handler in exception_table {
+        is_target[index_of(handler.handler_pc as i64, handler.handler_pc)?] = true;
+    }
+
+    for (index, (pc, instruction)) in instructions.iter().enumerate() {
+        let (targets, falls_through) = match *instruction {
+            Instruction::IfEq { offset }
+            | Instruction::IfNe { offset }
+            | Instruction::IfLt { offset }
+            | Instruction::IfGe { offset }
+            | Instruction::IfGt { offset }
+            | Instruction::IfLe { offset }
+            | Instruction::IfICmpEq { offset }
+            | Instruction::IfICmpNe { offset }
+            | Instruction::IfICmpLt { offset }
+            | Instruction::IfICmpGe { offset }
+            | Instruction::IfICmpGt { offset }
+            | Instruction::IfICmpLe { offset }
+            | Instruction::IfACmpEq { offset }
+            | Instruction::IfACmpNe { offset }
+            | Instruction::IfNull { offset }
+            | Instruction::IfNonNull { offset } => (vec![offset as i64], true),
+            Instruction::Goto { offset } => (vec![offset as i64], false),
+            Instruction::GotoW { offset } => (vec![offset as i64], false),
+            Instruction::TableSwitch {
+                default,
+                ref jump_offsets,
+                ..
+            } => (
+                [default]
+                    .iter()
+                    .chain(jump_offsets)
+                    .map(|offset| *offset as i64)
+                    .collect(),
+                false,
+            ),
+            Instruction::LookupSwitch {
+                default,
+                ref match_offset_pairs,
+                ..
+            } => (
+                [default]
+                    .into_iter()
+                    .chain(match_offset_pairs.iter().map(|(_, offset)| *offset))
+                    .map(|offset| offset as i64)
+                    .collect(),
+                false,
+            ),
+            Instruction::IReturn
+            | Instruction::LReturn
+            | Instruction::FReturn
+            | Instruction::DReturn
+            | Instruction::AReturn
+            | Instruction::Return
+            | Instruction::AThrow => (Vec::new(), false),
+            _ => (Vec::new(), true),
+        };
+
+        for target in targets {
+            is_target[index_of(*pc as i64 + target, *pc)?] = true;
+        }
+
+        if !falls_through && index + 1 < instructions.len() {
+            is_target[index + 1] = true;
+        }
+    }
+
+    Ok((0..instructions.len())
+        .filter(|index| is_target[*index])
+        .collect())
+}
+
+// the runs of instructions no frame reached, up to the next instruction or the end of the code
+fn unreachable_ranges(
+    instructions: &[(u16, Instruction)],
+    frames: &[Option<Frame>],
+) -> Vec<(u16, u16)> {
+    let mut ranges: Vec<(u16, u16)> = Vec::new();
+
+    for (index, (pc, instruction)) in instructions.iter().enumerate() {
+        if frames[index].is_some() {
+            continue;
+        }
+
+        let end_pc = match instructions.get(index + 1) {
+            Some((next_pc, _)) => *next_pc,
+            None => {
+                // switches are padded from the start of the code, so the writer has to be at the
+                // same offset modulo 4
+                let mut writer = CodeWriter::new();
+                for _ in 0..*pc & 3 {
+                    writer.write(&Instruction::Nop);
+                }
+                writer.write(instruction);
+
+                pc + (writer.pc() as u16 - (pc & 3))
+            }
+        };
+
+        match ranges.last_mut() {
+            Some((_, last_end_pc)) if last_end_pc == pc => *last_end_pc = end_pc,
+            _ => ranges.push((*pc, end_pc)),
+        }
+    }
+
+    ranges
+}
+
+// the types of the local variables as a frame lists them, where longs and doubles take one entry
+// and trailing unusable locals are left out
+fn values(locals: &[FrameType]) -> Vec<FrameType> {
+    let mut values = Vec::with_capacity(locals.len());
+
+    let mut index = 0;
+    while index < locals.len() {
+        values.push(locals[index].clone());
+        index += locals[index].size();
+    }
+
+    while values.last() == Some(&FrameType::Top) {
+        values.pop();
+    }
+
+    values
+}
+
+fn compress(
+    previous: &[FrameType],
+    locals: &[FrameType],
+    stack: &[FrameType],
+    offset_delta: u16,
+    class_index: &mut impl FnMut(&JStr) -> u16,
+) -> StackMapFrame {
+    let mut verification_types = |types: &[FrameType]| {
+        types
+            .iter()
+            .map(|r#type| verification_type(r#type, class_index))
+            .collect::<Vec<_>>()
+    };
+
+    if locals == previous {
+        match (stack.len(), offset_delta) {
+            (0, 0..=63) => {
+                return StackMapFrame::SameFrame {
+                    frame_type: offset_delta as u8,
+                }
+            }
+            (0, _) => {
+                return StackMapFrame::SameFrameExtended {
+                    frame_type: 251,
+                    offset_delta,
+                }
+            }
+            (1, 0..=63) => {
+                return StackMapFrame::SameLocals1StackItemFrame {
+                    frame_type: 64 + offset_delta as u8,
+                    stack: verification_types(stack).remove(0),
+                }
+            }
+            (1, _) => {
+                return StackMapFrame::SameLocals1StackItemFrameExtended {
+                    frame_type: 247,
+                    offset_delta,
+                    stack: verification_types(stack).remove(0),
+                }
+            }
+            _ => {}
+        }
+    } else if stack.is_empty() {
+        if locals.len() > previous.len()
+            && locals.len() - previous.len() <= 3
+            && locals.starts_with(previous)
+        {
+            return StackMapFrame::AppendFrame {
+                frame_type: 251 + (locals.len() - previous.len()) as u8,
+                offset_delta,
+                locals: verification_types(&locals[previous.len()..]),
+            };
+        }
+
+        if previous.len() > locals.len()
+            && previous.len() - locals.len() <= 3
+            && previous.starts_with(locals)
+        {
+            return StackMapFrame::ChopFrame {
+                frame_type: 251 - (previous.len() - locals.len()) as u8,
+                offset_delta,
+            };
+        }
+    }
+
+    StackMapFrame::FullFrame {
+        frame_type: 255,
+        offset_delta,
+        number_of_locals: locals.len() as u16,
+        locals: verification_types(locals),
+        number_of_stack_items: stack.len() as u16,
+        stack: verification_types(stack),
+    }
+}
+
+fn verification_type(
+    r#type: &FrameType,
+    class_index: &mut impl FnMut(&JStr) -> u16,
+) -> VerificationTypeInfo {
+    match r#type {
+        FrameType::Top => VerificationTypeInfo::TopVariableInfo { tag: 0 },
+        FrameType::Integer => VerificationTypeInfo::IntegerVariableInfo { tag: 1 },
+        FrameType::Float => VerificationTypeInfo::FloatVariableInfo { tag: 2 },
+        FrameType::Double => VerificationTypeInfo::DoubleVariableInfo { tag: 3 },
+        FrameType::Long => VerificationTypeInfo::LongVariableInfo { tag: 4 },
+        FrameType::Null => VerificationTypeInfo::NullVariableInfo { tag: 5 },
+        FrameType::UninitializedThis => {
+            VerificationTypeInfo::UninitializedThisVariableInfo { tag: 6 }
+        }
+        FrameType::Object(name) => VerificationTypeInfo::ObjectVariableInfo {
+            tag: 7,
+            cpool_index: class_index(name),
+        },
+        FrameType::Uninitialized(offset) => VerificationTypeInfo::UninitializedVariableInfo {
+            tag: 8,
+            offset: *offset,
+        },
+    }
+}
+
+// the element type of an array loaded by aaload
+fn component_type(array: &FrameType) -> FrameType {
+    let FrameType::Object(descriptor) = array else {
+        return if *array == FrameType::Null {
+            FrameType::Null
+        } else {
+            FrameType::Top
+        };
+    };
+
+    match descriptor.as_bytes() {
+        [b'[', b'L', .., b';'] => FrameType::Object(descriptor[2..descriptor.len() - 1].to_owned()),
+        [b'[', b'[', ..] => FrameType::Object(descriptor[1..].to_owned()),
+        _ => FrameType::Top,
+    }
+}
+
+// the descriptor of an array whose elements are the class or array type `name`
+fn array_of(name: &JStr) -> JString {
+    let mut array = JString::new();
+    array.push('[');
+
+    if name.as_bytes().first() == Some(&b'[') {
+        array.push_jstr(name);
+    } else {
+        array.push('L');
+        array.push_jstr(name);
+        array.push(';');
+    }
+
+    array
+}
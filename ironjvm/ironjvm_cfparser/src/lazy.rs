@@ -0,0 +1,315 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// A view of a class file which indexes where the constant pool entries, members and attributes
+// are in one pass, and decodes them only when they are asked for.
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::AttributeInfo;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::error::ParseError;
+use crate::error::ParseResult;
+use crate::AttributeNames;
+use crate::ClassFileParser;
+
+pub struct LazyClassFile<'clazz> {
+    pub magic: u32,
+    pub minor_version: u16,
+    pub major_version: u16,
+    pub constant_pool: LazyConstantPool<'clazz>,
+    pub access_flags: u16,
+    pub this_class: u16,
+    pub super_class: u16,
+    pub interfaces: &'clazz [[u8; 2]],
+    pub fields: Vec<LazyMember<'clazz>>,
+    pub methods: Vec<LazyMember<'clazz>>,
+    pub attributes: Vec<LazyAttribute<'clazz>>,
+}
+
+impl<'clazz> LazyClassFile<'clazz> {
+    pub(crate) fn index(parser: &mut ClassFileParser<'clazz>) -> ParseResult<Self> {
+        let classfile = parser.classfile;
+
+        let magic = parser.parse_magic()?;
+        let minor_version = parser.try_u2()?;
+        let major_version = parser.try_u2()?;
+        let constant_pool_count = parser.try_u2()?;
+
+        let mut offsets = Vec::with_capacity((constant_pool_count as usize).saturating_sub(1));
+        while offsets.len() + 1 < constant_pool_count as usize {
+            offsets.push((classfile.len() - parser.classfile.len()) as u32);
+
+            let tag = parser.try_u1()?;
+            let length = match tag {
+                1 => parser.try_u2()? as usize,
+                3 | 4 | 9 | 10 | 11 | 12 | 17 | 18 => 4,
+                5 | 6 => 8,
+                7 | 8 | 16 | 19 | 20 => 2,
+                15 => 3,
+                _ => return Err(ParseError::InvalidConstantPoolTag { tag }),
+            };
+            parser.try_u1_many(length)?;
+
+            if matches!(tag, 5 | 6) {
+                offsets.push(UNUSABLE);
+            }
+        }
+
+        let constant_pool = LazyConstantPool { classfile, offsets };
+        let access_flags = parser.try_u2()?;
+        let this_class = parser.try_u2()?;
+        let super_class = parser.try_u2()?;
+        let interfaces_count = parser.try_u2()?;
+        if parser.classfile.len() < interfaces_count as usize * 2 {
+            return Err(ParseError::UnexpectedEndOfClassFile);
+        }
+        let interfaces = parser.parse_interfaces(interfaces_count);
+        let fields = index_members(parser)?;
+        let methods = index_members(parser)?;
+        let attributes = index_attributes(parser)?;
+
+        Ok(Self {
+            magic,
+            minor_version,
+            major_version,
+            constant_pool,
+            access_flags,
+            this_class,
+            super_class,
+            interfaces,
+            fields,
+            methods,
+            attributes,
+        })
+    }
+
+    pub fn this_class_name(&self) -> ParseResult<&'clazz JStr> {
+        self.constant_pool.class_name(self.this_class)
+    }
+
+    /// `None` for `java/lang/Object` and module descriptors, which have no superclass.
+    pub fn super_class_name(&self) -> ParseResult<Option<&'clazz JStr>> {
+        if self.super_class == 0 {
+            return Ok(None);
+        }
+
+        self.constant_pool.class_name(self.super_class).map(Some)
+    }
+
+    pub fn interface_names(&self) -> impl Iterator<Item = ParseResult<&'clazz JStr>> + '_ {
+        self.interfaces
+            .iter()
+            .map(|index| self.constant_pool.class_name(u16::from_be_bytes(*index)))
+    }
+
+    /// Decodes the class attribute called `name`, such as `RuntimeVisibleAnnotations`.
+    pub fn attribute(&self, name: &str) -> ParseResult<Option<AttributeInfo<'clazz>>> {
+        find_attribute(&self.constant_pool, &self.attributes, name)
+    }
+}
+
+// marks the slot after a long or double, as no entry can start at the beginning of the file
+const UNUSABLE: u32 = 0;
+
+pub struct LazyConstantPool<'clazz> {
+    classfile: &'clazz [u8],
+    // where each entry's tag is in the class file
+    offsets: Vec<u32>,
+}
+
+impl<'clazz> LazyConstantPool<'clazz> {
+    pub fn len(&self) -> usize {
+        self.offsets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.offsets.is_empty()
+    }
+
+    /// Decodes the entry at `index`, which starts from 1 as in the class file.
+    pub fn get(&self, index: u16) -> ParseResult<CpInfo<'clazz>> {
+        let offset = *(index as usize)
+            .checked_sub(1)
+            .and_then(|index| self.offsets.get(index))
+            .ok_or(ParseError::InvalidConstantPoolIndex)?;
+
+        if offset == UNUSABLE {
+            return Ok(CpInfo {
+                tag: 0,
+                info: CpInfoType::ConstantUnusable,
+            });
+        }
+
+        let mut parser = ClassFileParser::new(&self.classfile[offset as usize..]);
+        let tag = parser.next_u1();
+
+        Ok(CpInfo {
+            tag,
+            info: parser.parse_constant(tag, index)?,
+        })
+    }
+
+    pub fn utf8(&self, index: u16) -> ParseResult<&'clazz JStr> {
+        match self.get(index)?.info {
+            CpInfoType::ConstantUtf8 { string, .. } => Ok(string),
+            _ => Err(ParseError::InvalidConstantPoolIndex),
+        }
+    }
+
+    pub fn class_name(&self, index: u16) -> ParseResult<&'clazz JStr> {
+        match self.get(index)?.info {
+            CpInfoType::ConstantClass { name_index } => self.utf8(name_index),
+            _ => Err(ParseError::InvalidConstantPoolIndex),
+        }
+    }
+
+    /// Decodes every entry, as `ClassFileParser::parse` does.
+    pub fn to_vec(&self) -> ParseResult<Vec<CpInfo<'clazz>>> {
+        (1..=self.offsets.len() as u16)
+            .map(|index| self.get(index))
+            .collect()
+    }
+}
+
+impl<'clazz> AttributeNames<'clazz> for LazyConstantPool<'clazz> {
+    fn attribute_name(&self, index: u16) -> ParseResult<&'clazz JStr> {
+        self.utf8(index)
+    }
+}
+
+pub struct LazyMember<'clazz> {
+    pub access_flags: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub attributes: Vec<LazyAttribute<'clazz>>,
+}
+
+impl<'clazz> LazyMember<'clazz> {
+    pub fn attribute(
+        &self,
+        constant_pool: &LazyConstantPool<'clazz>,
+        name: &str,
+    ) -> ParseResult<Option<AttributeInfo<'clazz>>> {
+        find_attribute(constant_pool, &self.attributes, name)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct LazyAttribute<'clazz> {
+    pub attribute_name_index: u16,
+    pub attribute_length: u32,
+    // the whole attribute, including its name index and length
+    bytes: &'clazz [u8],
+}
+
+impl<'clazz> LazyAttribute<'clazz> {
+    /// Wraps an attribute as it appears in a class file, starting with its name index and length.
+    pub fn from_bytes(bytes: &'clazz [u8]) -> Option<Self> {
+        let attribute_length = u32::from_be_bytes(bytes.get(2..6)?.try_into().unwrap());
+        if bytes.len() != 6 + attribute_length as usize {
+            return None;
+        }
+
+        Some(Self {
+            attribute_name_index: u16::from_be_bytes([bytes[0], bytes[1]]),
+            attribute_length,
+            bytes,
+        })
+    }
+
+    /// The undecoded contents of the attribute.
+    pub fn info(&self) -> &'clazz [u8] {
+        &self.bytes[6..]
+    }
+
+    pub fn name(&self, constant_pool: &LazyConstantPool<'clazz>) -> ParseResult<&'clazz JStr> {
+        constant_pool.utf8(self.attribute_name_index)
+    }
+
+    /// The whole attribute, as `from_bytes` takes it.
+    pub fn as_bytes(&self) -> &'clazz [u8] {
+        self.bytes
+    }
+
+    pub fn decode<N: AttributeNames<'clazz> + ?Sized>(
+        &self,
+        constant_pool: &N,
+    ) -> ParseResult<AttributeInfo<'clazz>> {
+        let mut attributes = ClassFileParser::new(self.bytes).parse_attributes(1, constant_pool)?;
+
+        Ok(attributes.remove(0))
+    }
+}
+
+fn index_members<'clazz>(
+    parser: &mut ClassFileParser<'clazz>,
+) -> ParseResult<Vec<LazyMember<'clazz>>> {
+    let count = parser.try_u2()?;
+    let mut vec = Vec::with_capacity(count as usize);
+
+    while vec.len() < count as usize {
+        vec.push(LazyMember {
+            access_flags: parser.try_u2()?,
+            name_index: parser.try_u2()?,
+            descriptor_index: parser.try_u2()?,
+            attributes: index_attributes(parser)?,
+        });
+    }
+
+    Ok(vec)
+}
+
+pub(crate) fn index_attributes<'clazz>(
+    parser: &mut ClassFileParser<'clazz>,
+) -> ParseResult<Vec<LazyAttribute<'clazz>>> {
+    let count = parser.try_u2()?;
+    let mut vec = Vec::with_capacity(count as usize);
+
+    while vec.len() < count as usize {
+        let bytes = parser.classfile;
+        let attribute_name_index = parser.try_u2()?;
+        let attribute_length = parser.try_u4()?;
+        parser.try_u1_many(attribute_length as usize)?;
+
+        vec.push(LazyAttribute {
+            attribute_name_index,
+            attribute_length,
+            bytes: &bytes[..6 + attribute_length as usize],
+        });
+    }
+
+    Ok(vec)
+}
+
+fn find_attribute<'clazz>(
+    constant_pool: &LazyConstantPool<'clazz>,
+    attributes: &[LazyAttribute<'clazz>],
+    name: &str,
+) -> ParseResult<Option<AttributeInfo<'clazz>>> {
+    for attribute in attributes {
+        if attribute.name(constant_pool)?.as_bytes() == name.as_bytes() {
+            return attribute.decode(constant_pool).map(Some);
+        }
+    }
+
+    Ok(None)
+}
@@ -58,8 +58,15 @@ use ironjvm_specimpl::classfile::MethodInfo;
 
 use crate::error::ParseError;
 use crate::error::ParseResult;
+use crate::lazy::LazyClassFile;
+use crate::visitor::ClassVisitor;
 
-mod error;
+pub mod code;
+pub mod error;
+pub mod frames;
+pub mod lazy;
+pub mod visitor;
+pub mod writer;
 
 pub struct ClassFileParser<'clazz> {
     classfile: &'clazz [u8],
@@ -82,11 +89,11 @@ impl<'clazz> ClassFileParser<'clazz> {
         let interfaces_count = self.next_u2();
         let interfaces = self.parse_interfaces(interfaces_count);
         let fields_count = self.next_u2();
-        let fields = self.parse_fields(fields_count, &constant_pool)?;
+        let fields = self.parse_fields(fields_count, constant_pool.as_slice())?;
         let methods_count = self.next_u2();
-        let methods = self.parse_methods(methods_count, &constant_pool)?;
+        let methods = self.parse_methods(methods_count, constant_pool.as_slice())?;
         let attributes_count = self.next_u2();
-        let attributes = self.parse_attributes(attributes_count, &constant_pool)?;
+        let attributes = self.parse_attributes(attributes_count, constant_pool.as_slice())?;
 
         Ok(ClassFile {
             magic,
@@ -108,6 +115,17 @@ impl<'clazz> ClassFileParser<'clazz> {
         })
     }
 
+    /// Only finds where each part of the class file is, leaving the constant pool and attributes
+    /// to be decoded when they are needed.
+    pub fn parse_lazy(&mut self) -> ParseResult<LazyClassFile<'clazz>> {
+        LazyClassFile::index(self)
+    }
+
+    /// Walks through the class file, calling `visitor` for each part of it in order.
+    pub fn accept<V: ClassVisitor<'clazz>>(&mut self, visitor: &mut V) -> ParseResult<()> {
+        visitor::accept(&self.parse_lazy()?, visitor)
+    }
+
     // Credit: code referenced from https://github.com/TapVM/Aftermath
     fn next_u1(&mut self) -> u8 {
         let ret = self.classfile[0];
@@ -141,8 +159,39 @@ impl<'clazz> ClassFileParser<'clazz> {
         u32::from_be_bytes(self.next_u1_many(4).try_into().unwrap())
     }
 
+    // the checked readers are for the lazy indexer, which only reads lengths and counts and so
+    // cannot otherwise notice that the class file was cut short, and for attributes, which
+    // LazyAttribute::decode reads from a slice holding nothing past the attribute
+    fn try_u1_many(&mut self, len: usize) -> ParseResult<&'clazz [u8]> {
+        if self.classfile.len() < len {
+            return Err(ParseError::UnexpectedEndOfClassFile);
+        }
+
+        Ok(self.next_u1_many(len))
+    }
+
+    fn try_u1(&mut self) -> ParseResult<u8> {
+        Ok(self.try_u1_many(1)?[0])
+    }
+
+    fn try_u2(&mut self) -> ParseResult<u16> {
+        Ok(u16::from_be_bytes(self.try_u1_many(2)?.try_into().unwrap()))
+    }
+
+    fn try_u2_many(&mut self, length: usize) -> ParseResult<&'clazz [[u8; 2]]> {
+        if self.classfile.len() < length * 2 {
+            return Err(ParseError::UnexpectedEndOfClassFile);
+        }
+
+        Ok(self.next_u2_many(length))
+    }
+
+    fn try_u4(&mut self) -> ParseResult<u32> {
+        Ok(u32::from_be_bytes(self.try_u1_many(4)?.try_into().unwrap()))
+    }
+
     fn parse_magic(&mut self) -> ParseResult<u32> {
-        let output = self.next_u4();
+        let output = self.try_u4()?;
 
         if output != 0xCAFEBABE {
             return Err(ParseError::InvalidMagic);
@@ -156,89 +205,103 @@ impl<'clazz> ClassFileParser<'clazz> {
 
         while pool.len() + 1 < count as usize {
             let tag = self.next_u1();
-            let info = match tag {
-                1 => {
-                    let length = self.next_u2();
-
-                    CpInfoType::ConstantUtf8 {
-                        length,
-                        bytes: self.next_u1_many(length as usize),
-                    }
-                }
-                3 => CpInfoType::ConstantInteger {
-                    bytes: self.next_u4(),
-                },
-                4 => CpInfoType::ConstantFloat {
-                    bytes: self.next_u4(),
-                },
-                5 => CpInfoType::ConstantLong {
-                    high_bytes: self.next_u4(),
-                    low_bytes: self.next_u4(),
-                },
-                6 => CpInfoType::ConstantDouble {
-                    high_bytes: self.next_u4(),
-                    low_bytes: self.next_u4(),
-                },
-                7 => CpInfoType::ConstantClass {
-                    name_index: self.next_u2(),
-                },
-                8 => CpInfoType::ConstantString {
-                    string_index: self.next_u2(),
-                },
-                9 => CpInfoType::ConstantFieldRef {
-                    class_index: self.next_u2(),
-                    name_and_type_index: self.next_u2(),
-                },
-                10 => CpInfoType::ConstantMethodRef {
-                    class_index: self.next_u2(),
-                    name_and_type_index: self.next_u2(),
-                },
-                11 => CpInfoType::ConstantInterfaceMethodRef {
-                    class_index: self.next_u2(),
-                    name_and_type_index: self.next_u2(),
-                },
-                12 => CpInfoType::ConstantNameAndType {
-                    name_index: self.next_u2(),
-                    descriptor_index: self.next_u2(),
-                },
-                15 => CpInfoType::ConstantMethodHandle {
-                    reference_kind: self.next_u1(),
-                    reference_index: self.next_u2(),
-                },
-                16 => CpInfoType::ConstantMethodType {
-                    descriptor_index: self.next_u2(),
-                },
-                17 => CpInfoType::ConstantDynamic {
-                    bootstrap_method_attr_index: self.next_u2(),
-                    name_and_type_index: self.next_u2(),
-                },
-                18 => CpInfoType::ConstantInvokeDynamic {
-                    bootstrap_method_attr_index: self.next_u2(),
-                    name_and_type_index: self.next_u2(),
-                },
-                19 => CpInfoType::ConstantModule {
-                    name_index: self.next_u2(),
-                },
-                20 => CpInfoType::ConstantPackage {
-                    name_index: self.next_u2(),
-                },
-                _ => unreachable!(),
-            };
+            let info = self.parse_constant(tag, pool.len() as u16 + 1)?;
 
+            let is_wide = matches!(
+                info,
+                CpInfoType::ConstantLong { .. } | CpInfoType::ConstantDouble { .. }
+            );
             pool.push(CpInfo { tag, info });
+
+            if is_wide {
+                pool.push(CpInfo {
+                    tag: 0,
+                    info: CpInfoType::ConstantUnusable,
+                });
+            }
         }
 
         Ok(pool)
     }
 
+    fn parse_constant(&mut self, tag: u8, cp_index: u16) -> ParseResult<CpInfoType<'clazz>> {
+        Ok(match tag {
+            1 => {
+                let length = self.next_u2();
+                let string = JStr::from_jutf8(self.next_u1_many(length as usize))
+                    .map_err(|_| ParseError::InvalidModifiedUtf8 { cp_index })?;
+
+                CpInfoType::ConstantUtf8 { length, string }
+            }
+            3 => CpInfoType::ConstantInteger {
+                bytes: self.next_u4(),
+            },
+            4 => CpInfoType::ConstantFloat {
+                bytes: self.next_u4(),
+            },
+            5 => CpInfoType::ConstantLong {
+                high_bytes: self.next_u4(),
+                low_bytes: self.next_u4(),
+            },
+            6 => CpInfoType::ConstantDouble {
+                high_bytes: self.next_u4(),
+                low_bytes: self.next_u4(),
+            },
+            7 => CpInfoType::ConstantClass {
+                name_index: self.next_u2(),
+            },
+            8 => CpInfoType::ConstantString {
+                string_index: self.next_u2(),
+            },
+            9 => CpInfoType::ConstantFieldRef {
+                class_index: self.next_u2(),
+                name_and_type_index: self.next_u2(),
+            },
+            10 => CpInfoType::ConstantMethodRef {
+                class_index: self.next_u2(),
+                name_and_type_index: self.next_u2(),
+            },
+            11 => CpInfoType::ConstantInterfaceMethodRef {
+                class_index: self.next_u2(),
+                name_and_type_index: self.next_u2(),
+            },
+            12 => CpInfoType::ConstantNameAndType {
+                name_index: self.next_u2(),
+                descriptor_index: self.next_u2(),
+            },
+            15 => CpInfoType::ConstantMethodHandle {
+                reference_kind: self.next_u1(),
+                reference_index: self.next_u2(),
+            },
+            16 => CpInfoType::ConstantMethodType {
+                descriptor_index: self.next_u2(),
+            },
+            17 => CpInfoType::ConstantDynamic {
+                bootstrap_method_attr_index: self.next_u2(),
+                name_and_type_index: self.next_u2(),
+            },
+            18 => CpInfoType::ConstantInvokeDynamic {
+                bootstrap_method_attr_index: self.next_u2(),
+                name_and_type_index: self.next_u2(),
+            },
+            19 => CpInfoType::ConstantModule {
+                name_index: self.next_u2(),
+            },
+            20 => CpInfoType::ConstantPackage {
+                name_index: self.next_u2(),
+            },
+            _ => return Err(ParseError::InvalidConstantPoolTag { tag }),
+        })
+    }
+
     fn parse_interfaces(&mut self, count: u16) -> &'clazz [[u8; 2]] {
         self.next_u2_many(count as usize)
     }
 
-    fn parse_fields(
+    fn parse_fields<N: AttributeNames<'clazz> + ?Sized>(
         &mut self,
         count: u16,
-        constant_pool: &[CpInfo],
+        constant_pool: &N,
     ) -> ParseResult<Vec<FieldInfo<'clazz>>> {
         let mut vec = Vec::with_capacity(count as usize);
 
@@ -260,44 +323,33 @@ impl<'clazz> ClassFileParser<'clazz> {
         Ok(vec)
     }
 
-    fn parse_attributes(
+    fn parse_attributes<N: AttributeNames<'clazz> + ?Sized>(
         &mut self,
         count: u16,
-        constant_pool: &[CpInfo],
+        constant_pool: &N,
     ) -> ParseResult<Vec<AttributeInfo<'clazz>>> {
         let mut vec = Vec::with_capacity(count as usize);
 
         while vec.len() < count as usize {
-            let attribute_name_index = self.next_u2();
-            let attribute_length = self.next_u4();
-
-            let opt = &constant_pool.get(attribute_name_index as usize - 1);
-
-            if opt.is_none() {
-                return Err(ParseError::InvalidConstantPoolIndex);
-            };
-
-            let name_cp_info = opt.unwrap();
-            let CpInfoType::ConstantUtf8 { bytes, .. } = &name_cp_info.info else {
-                unreachable!()
-            };
+            let attribute_name_index = self.try_u2()?;
+            let attribute_length = self.try_u4()?;
 
-            let string = unsafe { JStr::from_jutf8_unchecked(bytes) };
-            let info = match string.to_str().unwrap() {
-                "ConstantValue" => AttributeInfoType::ConstantValueAttribute {
-                    constantvalue_index: self.next_u2(),
+            let string = constant_pool.attribute_name(attribute_name_index)?;
+            let info = match string.as_bytes() {
+                b"ConstantValue" => AttributeInfoType::ConstantValueAttribute {
+                    constantvalue_index: self.try_u2()?,
                 },
-                "Code" => {
-                    let max_stack = self.next_u2();
-                    let max_locals = self.next_u2();
-                    let code_length = self.next_u4();
+                b"Code" => {
+                    let max_stack = self.try_u2()?;
+                    let max_locals = self.try_u2()?;
+                    let code_length = self.try_u4()?;
 
-                    let code = self.next_u1_many(code_length as usize);
+                    let code = self.try_u1_many(code_length as usize)?;
 
-                    let exception_table_length = self.next_u2();
+                    let exception_table_length = self.try_u2()?;
                     let exception_table = self.parse_exception_table(exception_table_length)?;
 
-                    let attributes_count = self.next_u2();
+                    let attributes_count = self.try_u2()?;
 
                     AttributeInfoType::CodeAttribute {
                         max_stack,
@@ -310,8 +362,8 @@ impl<'clazz> ClassFileParser<'clazz> {
                         attributes: self.parse_attributes(attributes_count, constant_pool)?,
                     }
                 }
-                "StackMapTable" => {
-                    let number_of_entries = self.next_u2();
+                b"StackMapTable" => {
+                    let number_of_entries = self.try_u2()?;
                     let mut stack_map_table = Vec::with_capacity(number_of_entries as usize);
                     while stack_map_table.len() < number_of_entries as usize {
                         stack_map_table.push(self.parse_stack_map_frame()?);
@@ -322,23 +374,23 @@ impl<'clazz> ClassFileParser<'clazz> {
                         stack_map_table,
                     }
                 }
-                "Exceptions" => {
-                    let number_of_exceptions = self.next_u2();
+                b"Exceptions" => {
+                    let number_of_exceptions = self.try_u2()?;
 
                     AttributeInfoType::ExceptionsAttribute {
                         number_of_exceptions,
-                        exception_index_table: self.next_u2_many(number_of_exceptions as usize),
+                        exception_index_table: self.try_u2_many(number_of_exceptions as usize)?,
                     }
                 }
-                "InnerClasses" => {
-                    let number_of_classes = self.next_u2();
+                b"InnerClasses" => {
+                    let number_of_classes = self.try_u2()?;
                     let mut classes = Vec::with_capacity(number_of_classes as usize);
                     while classes.len() < number_of_classes as usize {
                         classes.push(InnerClass {
-                            inner_class_info_index: self.next_u2(),
-                            outer_class_info_index: self.next_u2(),
-                            inner_name_index: self.next_u2(),
-                            inner_class_access_flags: self.next_u2(),
+                            inner_class_info_index: self.try_u2()?,
+                            outer_class_info_index: self.try_u2()?,
+                            inner_name_index: self.try_u2()?,
+                            inner_class_access_flags: self.try_u2()?,
                         });
                     }
 
@@ -347,28 +399,28 @@ impl<'clazz> ClassFileParser<'clazz> {
                         classes,
                     }
                 }
-                "EnclosingMethod" => AttributeInfoType::EnclosingMethodAttribute {
-                    class_index: self.next_u2(),
-                    method_index: self.next_u2(),
+                b"EnclosingMethod" => AttributeInfoType::EnclosingMethodAttribute {
+                    class_index: self.try_u2()?,
+                    method_index: self.try_u2()?,
                 },
-                "Synthetic" => AttributeInfoType::SyntheticAttribute,
-                "Signature" => AttributeInfoType::SignatureAttribute {
-                    signature_index: self.next_u2(),
+                b"Synthetic" => AttributeInfoType::SyntheticAttribute,
+                b"Signature" => AttributeInfoType::SignatureAttribute {
+                    signature_index: self.try_u2()?,
                 },
-                "SourceFile" => AttributeInfoType::SourceFileAttribute {
-                    sourcefile_index: self.next_u2(),
+                b"SourceFile" => AttributeInfoType::SourceFileAttribute {
+                    sourcefile_index: self.try_u2()?,
                 },
-                "SourceDebugExtension" => AttributeInfoType::SourceDebugExtensionAttribute {
-                    debug_extension: self.next_u1_many(attribute_length as usize),
+                b"SourceDebugExtension" => AttributeInfoType::SourceDebugExtensionAttribute {
+                    debug_extension: self.try_u1_many(attribute_length as usize)?,
                 },
-                "LineNumberTable" => {
-                    let line_number_table_length = self.next_u2();
+                b"LineNumberTable" => {
+                    let line_number_table_length = self.try_u2()?;
                     let mut line_number_table =
                         Vec::with_capacity(line_number_table_length as usize);
                     while line_number_table.len() < line_number_table_length as usize {
                         line_number_table.push(LineNumber {
-                            start_pc: self.next_u2(),
-                            line_number: self.next_u2(),
+                            start_pc: self.try_u2()?,
+                            line_number: self.try_u2()?,
                         });
                     }
 
@@ -377,17 +429,17 @@ impl<'clazz> ClassFileParser<'clazz> {
                         line_number_table,
                     }
                 }
-                "LocalVariableTable" => {
-                    let local_variable_table_length = self.next_u2();
+                b"LocalVariableTable" => {
+                    let local_variable_table_length = self.try_u2()?;
                     let mut local_variable_table =
                         Vec::with_capacity(local_variable_table_length as usize);
                     while local_variable_table.len() < local_variable_table_length as usize {
                         local_variable_table.push(LocalVariable {
-                            start_pc: self.next_u2(),
-                            length: self.next_u2(),
-                            name_index: self.next_u2(),
-                            descriptor_index: self.next_u2(),
-                            index: self.next_u2(),
+                            start_pc: self.try_u2()?,
+                            length: self.try_u2()?,
+                            name_index: self.try_u2()?,
+                            descriptor_index: self.try_u2()?,
+                            index: self.try_u2()?,
                         });
                     }
 
@@ -396,19 +448,19 @@ impl<'clazz> ClassFileParser<'clazz> {
                         local_variable_table,
                     }
                 }
-                "LocalVariableTypeTable" => {
-                    let local_variable_type_table_length = self.next_u2();
+                b"LocalVariableTypeTable" => {
+                    let local_variable_type_table_length = self.try_u2()?;
                     let mut local_variable_type_table =
                         Vec::with_capacity(local_variable_type_table_length as usize);
                     while local_variable_type_table.len()
                         < local_variable_type_table_length as usize
                     {
                         local_variable_type_table.push(LocalVariableType {
-                            start_pc: self.next_u2(),
-                            length: self.next_u2(),
-                            name_index: self.next_u2(),
-                            signature_index: self.next_u2(),
-                            index: self.next_u2(),
+                            start_pc: self.try_u2()?,
+                            length: self.try_u2()?,
+                            name_index: self.try_u2()?,
+                            signature_index: self.try_u2()?,
+                            index: self.try_u2()?,
                         });
                     }
 
@@ -417,9 +469,9 @@ impl<'clazz> ClassFileParser<'clazz> {
                         local_variable_type_table,
                     }
                 }
-                "Deprecated" => AttributeInfoType::DeprecatedAttribute,
-                "RuntimeVisibleAnnotations" => {
-                    let num_annotations = self.next_u2();
+                b"Deprecated" => AttributeInfoType::DeprecatedAttribute,
+                b"RuntimeVisibleAnnotations" => {
+                    let num_annotations = self.try_u2()?;
                     let mut annotations = Vec::with_capacity(num_annotations as usize);
                     while annotations.len() < num_annotations as usize {
                         annotations.push(self.parse_annotation()?);
@@ -430,8 +482,8 @@ impl<'clazz> ClassFileParser<'clazz> {
                         annotations,
                     }
                 }
-                "RuntimeInvisibleAnnotations" => {
-                    let num_annotations = self.next_u2();
+                b"RuntimeInvisibleAnnotations" => {
+                    let num_annotations = self.try_u2()?;
                     let mut annotations = Vec::with_capacity(num_annotations as usize);
                     while annotations.len() < num_annotations as usize {
                         annotations.push(self.parse_annotation()?);
@@ -442,8 +494,8 @@ impl<'clazz> ClassFileParser<'clazz> {
                         annotations,
                     }
                 }
-                "RuntimeVisibleParameterAnnotations" => {
-                    let num_parameters = self.next_u2();
+                b"RuntimeVisibleParameterAnnotations" => {
+                    let num_parameters = self.try_u1()?;
                     let mut parameter_annotations = Vec::with_capacity(num_parameters as usize);
                     while parameter_annotations.len() < num_parameters as usize {
                         parameter_annotations.push(self.parse_parameter_annotation()?);
@@ -454,8 +506,8 @@ impl<'clazz> ClassFileParser<'clazz> {
                         parameter_annotations,
                     }
                 }
-                "RuntimeInvisibleParameterAnnotations" => {
-                    let num_parameters = self.next_u2();
+                b"RuntimeInvisibleParameterAnnotations" => {
+                    let num_parameters = self.try_u1()?;
                     let mut parameter_annotations = Vec::with_capacity(num_parameters as usize);
                     while parameter_annotations.len() < num_parameters as usize {
                         parameter_annotations.push(self.parse_parameter_annotation()?);
@@ -466,8 +518,8 @@ impl<'clazz> ClassFileParser<'clazz> {
                         parameter_annotations,
                     }
                 }
-                "RuntimeVisibleTypeAnnotations" => {
-                    let num_annotations = self.next_u2();
+                b"RuntimeVisibleTypeAnnotations" => {
+                    let num_annotations = self.try_u2()?;
                     let mut annotations = Vec::with_capacity(num_annotations as usize);
                     while annotations.len() < num_annotations as usize {
                         annotations.push(self.parse_type_annotation()?);
@@ -478,8 +530,8 @@ impl<'clazz> ClassFileParser<'clazz> {
                         annotations,
                     }
                 }
-                "RuntimeInvisibleTypeAnnotations" => {
-                    let num_annotations = self.next_u2();
+                b"RuntimeInvisibleTypeAnnotations" => {
+                    let num_annotations = self.try_u2()?;
                     let mut annotations = Vec::with_capacity(num_annotations as usize);
                     while annotations.len() < num_annotations as usize {
                         annotations.push(self.parse_type_annotation()?);
@@ -490,20 +542,21 @@ impl<'clazz> ClassFileParser<'clazz> {
                         annotations,
                     }
                 }
-                "AnnotationDefault" => AttributeInfoType::AnnotationDefaultAttribute {
+                b"AnnotationDefault" => AttributeInfoType::AnnotationDefaultAttribute {
                     default_value: self.parse_element_value()?,
                 },
-                "BootstrapMethods" => {
-                    let num_bootstrap_methods = self.next_u2();
+                b"BootstrapMethods" => {
+                    let num_bootstrap_methods = self.try_u2()?;
                     let mut bootstrap_methods = Vec::with_capacity(num_bootstrap_methods as usize);
                     while bootstrap_methods.len() < num_bootstrap_methods as usize {
-                        let num_bootstrap_arguments = self.next_u2();
+                        let bootstrap_method_ref = self.try_u2()?;
+                        let num_bootstrap_arguments = self.try_u2()?;
 
                         bootstrap_methods.push(BootstrapMethod {
-                            bootstrap_method_ref: self.next_u2(),
+                            bootstrap_method_ref,
                             num_bootstrap_arguments,
                             bootstrap_arguments: self
-                                .next_u2_many(num_bootstrap_arguments as usize),
+                                .try_u2_many(num_bootstrap_arguments as usize)?,
                         });
                     }
 
@@ -512,13 +565,13 @@ impl<'clazz> ClassFileParser<'clazz> {
                         bootstrap_methods,
                     }
                 }
-                "MethodParameters" => {
-                    let parameters_count = self.next_u1();
+                b"MethodParameters" => {
+                    let parameters_count = self.try_u1()?;
                     let mut parameters = Vec::with_capacity(parameters_count as usize);
                     while parameters.len() < parameters_count as usize {
                         parameters.push(MethodParameter {
-                            name_index: self.next_u2(),
-                            access_flags: self.next_u2(),
+                            name_index: self.try_u2()?,
+                            access_flags: self.try_u2()?,
                         });
                     }
 
@@ -527,64 +580,64 @@ impl<'clazz> ClassFileParser<'clazz> {
                         parameters,
                     }
                 }
-                "Module" => {
-                    let module_name_index = self.next_u2();
-                    let module_flags = self.next_u2();
-                    let module_version_index = self.next_u2();
+                b"Module" => {
+                    let module_name_index = self.try_u2()?;
+                    let module_flags = self.try_u2()?;
+                    let module_version_index = self.try_u2()?;
 
-                    let requires_count = self.next_u2();
+                    let requires_count = self.try_u2()?;
                     let mut requires = Vec::with_capacity(requires_count as usize);
                     while requires.len() < requires_count as usize {
                         requires.push(ModuleRequire {
-                            requires_index: self.next_u2(),
-                            requires_flags: self.next_u2(),
-                            requires_version_index: self.next_u2(),
+                            requires_index: self.try_u2()?,
+                            requires_flags: self.try_u2()?,
+                            requires_version_index: self.try_u2()?,
                         });
                     }
 
-                    let exports_count = self.next_u2();
+                    let exports_count = self.try_u2()?;
                     let mut exports = Vec::with_capacity(exports_count as usize);
                     while exports.len() < exports_count as usize {
-                        let exports_index = self.next_u2();
-                        let exports_flags = self.next_u2();
-                        let exports_to_count = self.next_u2();
+                        let exports_index = self.try_u2()?;
+                        let exports_flags = self.try_u2()?;
+                        let exports_to_count = self.try_u2()?;
 
                         exports.push(ModuleExport {
                             exports_index,
                             exports_flags,
                             exports_to_count,
-                            exports_to_index: self.next_u2_many(exports_to_count as usize),
+                            exports_to_index: self.try_u2_many(exports_to_count as usize)?,
                         });
                     }
 
-                    let opens_count = self.next_u2();
+                    let opens_count = self.try_u2()?;
                     let mut opens = Vec::with_capacity(opens_count as usize);
                     while opens.len() < opens_count as usize {
-                        let opens_index = self.next_u2();
-                        let opens_flags = self.next_u2();
-                        let opens_to_count = self.next_u2();
+                        let opens_index = self.try_u2()?;
+                        let opens_flags = self.try_u2()?;
+                        let opens_to_count = self.try_u2()?;
 
                         opens.push(ModuleOpen {
                             opens_index,
                             opens_flags,
                             opens_to_count,
-                            opens_to_index: self.next_u2_many(opens_to_count as usize),
+                            opens_to_index: self.try_u2_many(opens_to_count as usize)?,
                         });
                     }
 
-                    let uses_count = self.next_u2();
-                    let uses_index = self.next_u2_many(uses_count as usize);
+                    let uses_count = self.try_u2()?;
+                    let uses_index = self.try_u2_many(uses_count as usize)?;
 
-                    let provides_count = self.next_u2();
+                    let provides_count = self.try_u2()?;
                     let mut provides = Vec::with_capacity(provides_count as usize);
                     while provides.len() < provides_count as usize {
-                        let provides_index = self.next_u2();
-                        let provides_with_count = self.next_u2();
+                        let provides_index = self.try_u2()?;
+                        let provides_with_count = self.try_u2()?;
 
                         provides.push(ModuleProvide {
                             provides_index,
                             provides_with_count,
-                            provides_with_index: self.next_u2_many(provides_with_count as usize),
+                            provides_with_index: self.try_u2_many(provides_with_count as usize)?,
                         });
                     }
 
@@ -604,35 +657,35 @@ impl<'clazz> ClassFileParser<'clazz> {
                         provides,
                     }
                 }
-                "ModulePackages" => {
-                    let package_count = self.next_u2();
+                b"ModulePackages" => {
+                    let package_count = self.try_u2()?;
 
                     AttributeInfoType::ModulePackagesAttribute {
                         package_count,
-                        package_index: self.next_u2_many(package_count as usize),
+                        package_index: self.try_u2_many(package_count as usize)?,
                     }
                 }
-                "ModuleMainClass" => AttributeInfoType::ModuleMainClassAttribute {
-                    main_class_index: self.next_u2(),
+                b"ModuleMainClass" => AttributeInfoType::ModuleMainClassAttribute {
+                    main_class_index: self.try_u2()?,
                 },
-                "NestHost" => AttributeInfoType::NestHostAttribute {
-                    host_class_index: self.next_u2(),
+                b"NestHost" => AttributeInfoType::NestHostAttribute {
+                    host_class_index: self.try_u2()?,
                 },
-                "NestMembers" => {
-                    let number_of_classes = self.next_u2();
+                b"NestMembers" => {
+                    let number_of_classes = self.try_u2()?;
 
                     AttributeInfoType::NestMembersAttribute {
                         number_of_classes,
-                        classes: self.next_u2_many(number_of_classes as usize),
+                        classes: self.try_u2_many(number_of_classes as usize)?,
                     }
                 }
-                "Record" => {
-                    let components_count = self.next_u2();
+                b"Record" => {
+                    let components_count = self.try_u2()?;
                     let mut components = Vec::with_capacity(components_count as usize);
                     while components.len() < components_count as usize {
-                        let name_index = self.next_u2();
-                        let descriptor_index = self.next_u2();
-                        let attributes_count = self.next_u2();
+                        let name_index = self.try_u2()?;
+                        let descriptor_index = self.try_u2()?;
+                        let attributes_count = self.try_u2()?;
 
                         components.push(RecordComponentInfo {
                             name_index,
@@ -647,15 +700,17 @@ impl<'clazz> ClassFileParser<'clazz> {
                         components,
                     }
                 }
-                "PermittedSubclasses" => {
-                    let number_of_classes = self.next_u2();
+                b"PermittedSubclasses" => {
+                    let number_of_classes = self.try_u2()?;
 
                     AttributeInfoType::PermittedSubclassesAttribute {
                         number_of_classes,
-                        classes: self.next_u2_many(number_of_classes as usize),
+                        classes: self.try_u2_many(number_of_classes as usize)?,
                     }
                 }
-                _ => unreachable!(),
+                _ => AttributeInfoType::UnknownAttribute {
+                    info: self.try_u1_many(attribute_length as usize)?,
+                },
             };
 
             vec.push(AttributeInfo {
@@ -676,10 +731,10 @@ impl<'clazz> ClassFileParser<'clazz> {
 
         while vec.len() < count as usize {
             vec.push(CodeAttributeExceptionTableEntry {
-                start_pc: self.next_u2(),
-                end_pc: self.next_u2(),
-                handler_pc: self.next_u2(),
-                catch_type: self.next_u2(),
+                start_pc: self.try_u2()?,
+                end_pc: self.try_u2()?,
+                handler_pc: self.try_u2()?,
+                catch_type: self.try_u2()?,
             });
         }
 
@@ -687,18 +742,18 @@ impl<'clazz> ClassFileParser<'clazz> {
     }
 
     fn parse_stack_map_frame(&mut self) -> ParseResult<StackMapFrame> {
-        let frame_type = self.next_u1();
+        let frame_type = self.try_u1()?;
 
         Ok(match frame_type {
             0..=63 => StackMapFrame::SameFrame { frame_type },
             64..=127 => {
-                let stack = self.parse_verification_type_info();
+                let stack = self.parse_verification_type_info()?;
 
                 StackMapFrame::SameLocals1StackItemFrame { frame_type, stack }
             }
             247 => {
-                let offset_delta = self.next_u2();
-                let stack = self.parse_verification_type_info();
+                let offset_delta = self.try_u2()?;
+                let stack = self.parse_verification_type_info()?;
 
                 StackMapFrame::SameLocals1StackItemFrameExtended {
                     frame_type,
@@ -707,7 +762,7 @@ impl<'clazz> ClassFileParser<'clazz> {
                 }
             }
             248..=250 => {
-                let offset_delta = self.next_u2();
+                let offset_delta = self.try_u2()?;
 
                 StackMapFrame::ChopFrame {
                     frame_type,
@@ -715,7 +770,7 @@ impl<'clazz> ClassFileParser<'clazz> {
                 }
             }
             251 => {
-                let offset_delta = self.next_u2();
+                let offset_delta = self.try_u2()?;
 
                 StackMapFrame::SameFrameExtended {
                     frame_type,
@@ -723,12 +778,12 @@ impl<'clazz> ClassFileParser<'clazz> {
                 }
             }
             252..=254 => {
-                let offset_delta = self.next_u2();
+                let offset_delta = self.try_u2()?;
 
                 let locals_length = frame_type - 251;
                 let mut locals = Vec::with_capacity(locals_length as usize);
                 while locals.len() < locals_length as usize {
-                    locals.push(self.parse_verification_type_info());
+                    locals.push(self.parse_verification_type_info()?);
                 }
 
                 StackMapFrame::AppendFrame {
@@ -738,18 +793,18 @@ impl<'clazz> ClassFileParser<'clazz> {
                 }
             }
             255 => {
-                let offset_delta = self.next_u2();
+                let offset_delta = self.try_u2()?;
 
-                let number_of_locals = self.next_u2();
+                let number_of_locals = self.try_u2()?;
                 let mut locals = Vec::with_capacity(number_of_locals as usize);
                 while locals.len() < number_of_locals as usize {
-                    locals.push(self.parse_verification_type_info());
+                    locals.push(self.parse_verification_type_info()?);
                 }
 
-                let number_of_stack_items = self.next_u2();
+                let number_of_stack_items = self.try_u2()?;
                 let mut stack = Vec::with_capacity(number_of_stack_items as usize);
                 while stack.len() < number_of_stack_items as usize {
-                    stack.push(self.parse_verification_type_info());
+                    stack.push(self.parse_verification_type_info()?);
                 }
 
                 StackMapFrame::FullFrame {
@@ -761,14 +816,14 @@ impl<'clazz> ClassFileParser<'clazz> {
                     stack,
                 }
             }
-            _ => unreachable!(),
+            _ => return Err(ParseError::InvalidStackMapFrameType { frame_type }),
         })
     }
 
-    fn parse_verification_type_info(&mut self) -> VerificationTypeInfo {
-        let tag = self.next_u1();
+    fn parse_verification_type_info(&mut self) -> ParseResult<VerificationTypeInfo> {
+        let tag = self.try_u1()?;
 
-        match tag {
+        Ok(match tag {
             0 => VerificationTypeInfo::TopVariableInfo { tag },
             1 => VerificationTypeInfo::IntegerVariableInfo { tag },
             2 => VerificationTypeInfo::FloatVariableInfo { tag },
@@ -778,24 +833,24 @@ impl<'clazz> ClassFileParser<'clazz> {
             6 => VerificationTypeInfo::UninitializedThisVariableInfo { tag },
             7 => VerificationTypeInfo::ObjectVariableInfo {
                 tag,
-                cpool_index: self.next_u2(),
+                cpool_index: self.try_u2()?,
             },
             8 => VerificationTypeInfo::UninitializedVariableInfo {
                 tag,
-                offset: self.next_u2(),
+                offset: self.try_u2()?,
             },
-            _ => unreachable!(),
-        }
+            _ => return Err(ParseError::InvalidVerificationTypeTag { tag }),
+        })
     }
 
     fn parse_annotation(&mut self) -> ParseResult<Annotation> {
-        let type_index = self.next_u2();
+        let type_index = self.try_u2()?;
 
-        let num_element_value_pairs = self.next_u2();
+        let num_element_value_pairs = self.try_u2()?;
         let mut element_value_pairs = Vec::with_capacity(num_element_value_pairs as usize);
         while element_value_pairs.len() < num_element_value_pairs as usize {
             element_value_pairs.push(ElementValuePair {
-                element_name_index: self.next_u2(),
+                element_name_index: self.try_u2()?,
                 value: self.parse_element_value()?,
             });
         }
@@ -808,25 +863,25 @@ impl<'clazz> ClassFileParser<'clazz> {
     }
 
     fn parse_element_value(&mut self) -> ParseResult<ElementValue> {
-        let tag = self.next_u1();
+        let tag = self.try_u1()?;
         let value = match tag as char {
             'B' | 'C' | 'D' | 'F' | 'I' | 'J' | 'S' | 'Z' | 's' => {
                 ElementValueValue::ConstValueIndex {
-                    const_value_index: self.next_u2(),
+                    const_value_index: self.try_u2()?,
                 }
             }
             'e' => ElementValueValue::EnumConstValue {
-                type_name_index: self.next_u2(),
-                const_name_index: self.next_u2(),
+                type_name_index: self.try_u2()?,
+                const_name_index: self.try_u2()?,
             },
             'c' => ElementValueValue::ClassInfoIndex {
-                class_info_index: self.next_u2(),
+                class_info_index: self.try_u2()?,
             },
             '@' => ElementValueValue::AnnotationValue {
                 annotation_value: self.parse_annotation()?,
             },
             '[' => {
-                let num_values = self.next_u2();
+                let num_values = self.try_u2()?;
                 let mut values = Vec::with_capacity(num_values as usize);
                 while values.len() < num_values as usize {
                     values.push(self.parse_element_value()?);
@@ -834,14 +889,14 @@ impl<'clazz> ClassFileParser<'clazz> {
 
                 ElementValueValue::ArrayValue { num_values, values }
             }
-            _ => unreachable!(),
+            _ => return Err(ParseError::InvalidElementValueTag { tag }),
         };
 
         Ok(ElementValue { tag, value })
     }
 
     fn parse_parameter_annotation(&mut self) -> ParseResult<ParameterAnnotation> {
-        let num_annotations = self.next_u2();
+        let num_annotations = self.try_u2()?;
         let mut annotations = Vec::with_capacity(num_annotations as usize);
         while annotations.len() < num_annotations as usize {
             annotations.push(self.parse_annotation()?);
@@ -854,33 +909,33 @@ impl<'clazz> ClassFileParser<'clazz> {
     }
 
     fn parse_type_annotation(&mut self) -> ParseResult<TypeAnnotation> {
-        let target_type = self.next_u1();
+        let target_type = self.try_u1()?;
         let target_info = match target_type {
             0x00 | 0x01 => TypeAnnotationTargetInfo::TypeParameterTarget {
-                type_parameter_index: self.next_u1(),
+                type_parameter_index: self.try_u1()?,
             },
             0x10 => TypeAnnotationTargetInfo::SupertypeTarget {
-                supertype_index: self.next_u2(),
+                supertype_index: self.try_u2()?,
             },
             0x11 | 0x12 => TypeAnnotationTargetInfo::TypeParameterBoundTarget {
-                type_parameter_index: self.next_u1(),
-                bound_index: self.next_u1(),
+                type_parameter_index: self.try_u1()?,
+                bound_index: self.try_u1()?,
             },
             0x13..=0x15 => TypeAnnotationTargetInfo::EmptyTarget,
             0x16 => TypeAnnotationTargetInfo::FormalParameterTarget {
-                formal_parameter_index: self.next_u1(),
+                formal_parameter_index: self.try_u1()?,
             },
             0x17 => TypeAnnotationTargetInfo::ThrowsTarget {
-                throws_type_index: self.next_u2(),
+                throws_type_index: self.try_u2()?,
             },
             0x40 | 0x41 => {
-                let table_length = self.next_u2();
+                let table_length = self.try_u2()?;
                 let mut table = Vec::with_capacity(table_length as usize);
                 while table.len() < table_length as usize {
                     table.push(TypeAnnotationLocalVarTargetTableEntry {
-                        start_pc: self.next_u2(),
-                        length: self.next_u2(),
-                        index: self.next_u2(),
+                        start_pc: self.try_u2()?,
+                        length: self.try_u2()?,
+                        index: self.try_u2()?,
                     });
                 }
 
@@ -890,35 +945,35 @@ impl<'clazz> ClassFileParser<'clazz> {
                 }
             }
             0x42 => TypeAnnotationTargetInfo::CatchTarget {
-                catch_index: self.next_u2(),
+                catch_index: self.try_u2()?,
             },
             0x43..=0x46 => TypeAnnotationTargetInfo::OffsetTarget {
-                offset: self.next_u2(),
+                offset: self.try_u2()?,
             },
             0x47..=0x4B => TypeAnnotationTargetInfo::TypeArgumentTarget {
-                offset: self.next_u2(),
-                type_argument_index: self.next_u1(),
+                offset: self.try_u2()?,
+                type_argument_index: self.try_u1()?,
             },
-            _ => unreachable!(),
+            _ => return Err(ParseError::InvalidTargetType { target_type }),
         };
 
-        let path_length = self.next_u1();
+        let path_length = self.try_u1()?;
         let mut path = Vec::with_capacity(path_length as usize);
         while path.len() < path_length as usize {
             path.push(TypeAnnotationTypePathSegment {
-                type_path_kind: self.next_u1(),
-                type_argument_index: self.next_u1(),
+                type_path_kind: self.try_u1()?,
+                type_argument_index: self.try_u1()?,
             });
         }
         let target_path = TypeAnnotationTypePath { path_length, path };
 
-        let type_index = self.next_u2();
+        let type_index = self.try_u2()?;
 
-        let num_element_value_pairs = self.next_u2();
+        let num_element_value_pairs = self.try_u2()?;
         let mut element_value_pairs = Vec::with_capacity(num_element_value_pairs as usize);
         while element_value_pairs.len() < num_element_value_pairs as usize {
             element_value_pairs.push(ElementValuePair {
-                element_name_index: self.next_u2(),
+                element_name_index: self.try_u2()?,
                 value: self.parse_element_value()?,
             });
         }
@@ -933,10 +988,10 @@ impl<'clazz> ClassFileParser<'clazz> {
         })
     }
 
-    fn parse_methods(
+    fn parse_methods<N: AttributeNames<'clazz> + ?Sized>(
         &mut self,
         count: u16,
-        constant_pool: &[CpInfo],
+        constant_pool: &N,
     ) -> ParseResult<Vec<MethodInfo<'clazz>>> {
         let mut vec = Vec::with_capacity(count as usize);
 
@@ -959,3 +1014,22 @@ impl<'clazz> ClassFileParser<'clazz> {
         Ok(vec)
     }
 }
+
+/// Where attribute names are looked up when decoding attributes, which is either a decoded constant
+/// pool or a `LazyConstantPool`.
+pub trait AttributeNames<'clazz> {
+    fn attribute_name(&self, index: u16) -> ParseResult<&'clazz JStr>;
+}
+
+impl<'clazz> AttributeNames<'clazz> for [CpInfo<'clazz>] {
+    fn attribute_name(&self, index: u16) -> ParseResult<&'clazz JStr> {
+        match (index as usize)
+            .checked_sub(1)
+            .and_then(|index| self.get(index))
+            .map(|cp_info| &cp_info.info)
+        {
+            Some(CpInfoType::ConstantUtf8 { string, .. }) => Ok(string),
+            _ => Err(ParseError::InvalidConstantPoolIndex),
+        }
+    }
+}
@@ -0,0 +1,691 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// An event-based interface to class files in the style of ASM's ClassVisitor. The parser calls a
+// visitor for each part of a class file in the order they appear, and ClassWriter turns those calls
+// back into bytes, so transformations can sit between the two without building a ClassFile.
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::attrinfo::smtattr::StackMapFrame;
+use ironjvm_specimpl::classfile::attrinfo::smtattr::VerificationTypeInfo;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::code::CodeParser;
+use crate::error::ParseError;
+use crate::error::ParseResult;
+use crate::lazy;
+use crate::lazy::LazyAttribute;
+use crate::lazy::LazyClassFile;
+use crate::lazy::LazyConstantPool;
+use crate::lazy::LazyMember;
+use crate::ClassFileParser;
+
+pub struct ClassHeader<'a, 'clazz> {
+    pub minor_version: u16,
+    pub major_version: u16,
+    pub constant_pool: &'a [CpInfo<'clazz>],
+    pub access_flags: u16,
+    pub this_class: u16,
+    pub super_class: u16,
+    pub interfaces: &'clazz [[u8; 2]],
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MemberHeader {
+    pub access_flags: u16,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CodeHeader {
+    pub attribute_name_index: u16,
+    pub max_stack: u16,
+    pub max_locals: u16,
+}
+
+/// A position in the code of a method, which jumps, exception handlers, frames, line numbers and
+/// local variables refer to instead of offsets, so that the writer can work the offsets out again
+/// after instructions are added or removed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Label(u32);
+
+// labels of offsets in the code read are the offsets themselves, and new ones are numbered after
+// the largest offset a method can have
+static NEXT_LABEL: AtomicU32 = AtomicU32::new(1 << 16);
+
+impl Label {
+    /// A label different from every other, for code added by a transformation.
+    pub fn new() -> Self {
+        Self(NEXT_LABEL.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn at(pc: u16) -> Self {
+        Self(pc as u32)
+    }
+}
+
+impl Default for Label {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The instructions which jump to a label. `goto_w` and `jsr_w` are visited as `Goto` and `Jsr`,
+/// which the writer makes wide when their target is too far away. A conditional jump whose target
+/// is too far away is written as the opposite jump over a `goto_w` to the target, as in ASM.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Jump {
+    IfEq,
+    IfNe,
+    IfLt,
+    IfGe,
+    IfGt,
+    IfLe,
+    IfICmpEq,
+    IfICmpNe,
+    IfICmpLt,
+    IfICmpGe,
+    IfICmpGt,
+    IfICmpLe,
+    IfACmpEq,
+    IfACmpNe,
+    Goto,
+    Jsr,
+    IfNull,
+    IfNonNull,
+}
+
+impl Jump {
+    // the kind of jump `instruction` is, and its offset
+    fn of(instruction: &Instruction) -> Option<(Self, i32)> {
+        Some(match *instruction {
+            Instruction::IfEq { offset } => (Self::IfEq, offset as i32),
+            Instruction::IfNe { offset } => (Self::IfNe, offset as i32),
+            Instruction::IfLt { offset } => (Self::IfLt, offset as i32),
+            Instruction::IfGe { offset } => (Self::IfGe, offset as i32),
+            Instruction::IfGt { offset } => (Self::IfGt, offset as i32),
+            Instruction::IfLe { offset } => (Self::IfLe, offset as i32),
+            Instruction::IfICmpEq { offset } => (Self::IfICmpEq, offset as i32),
+            Instruction::IfICmpNe { offset } => (Self::IfICmpNe, offset as i32),
+            Instruction::IfICmpLt { offset } => (Self::IfICmpLt, offset as i32),
+            Instruction::IfICmpGe { offset } => (Self::IfICmpGe, offset as i32),
+            Instruction::IfICmpGt { offset } => (Self::IfICmpGt, offset as i32),
+            Instruction::IfICmpLe { offset } => (Self::IfICmpLe, offset as i32),
+            Instruction::IfACmpEq { offset } => (Self::IfACmpEq, offset as i32),
+            Instruction::IfACmpNe { offset } => (Self::IfACmpNe, offset as i32),
+            Instruction::Goto { offset } => (Self::Goto, offset as i32),
+            Instruction::Jsr { offset } => (Self::Jsr, offset as i32),
+            Instruction::IfNull { offset } => (Self::IfNull, offset as i32),
+            Instruction::IfNonNull { offset } => (Self::IfNonNull, offset as i32),
+            Instruction::GotoW { offset } => (Self::Goto, offset),
+            Instruction::JsrW { offset } => (Self::Jsr, offset),
+            _ => return None,
+        })
+    }
+
+    // the jump taken when this one is not, which goto and jsr have none of
+    pub(crate) fn opposite(self) -> Option<Self> {
+        Some(match self {
+            Self::IfEq => Self::IfNe,
+            Self::IfNe => Self::IfEq,
+            Self::IfLt => Self::IfGe,
+            Self::IfGe => Self::IfLt,
+            Self::IfGt => Self::IfLe,
+            Self::IfLe => Self::IfGt,
+            Self::IfICmpEq => Self::IfICmpNe,
+            Self::IfICmpNe => Self::IfICmpEq,
+            Self::IfICmpLt => Self::IfICmpGe,
+            Self::IfICmpGe => Self::IfICmpLt,
+            Self::IfICmpGt => Self::IfICmpLe,
+            Self::IfICmpLe => Self::IfICmpGt,
+            Self::IfACmpEq => Self::IfACmpNe,
+            Self::IfACmpNe => Self::IfACmpEq,
+            Self::IfNull => Self::IfNonNull,
+            Self::IfNonNull => Self::IfNull,
+            Self::Goto | Self::Jsr => return None,
+        })
+    }
+
+    // the instruction jumping by `offset`, which only goto and jsr have a wide form of
+    pub(crate) fn instruction(self, offset: i32, wide: bool) -> Instruction {
+        let short = offset as i16;
+
+        match self {
+            Self::IfEq => Instruction::IfEq { offset: short },
+            Self::IfNe => Instruction::IfNe { offset: short },
+            Self::IfLt => Instruction::IfLt { offset: short },
+            Self::IfGe => Instruction::IfGe { offset: short },
+            Self::IfGt => Instruction::IfGt { offset: short },
+            Self::IfLe => Instruction::IfLe { offset: short },
+            Self::IfICmpEq => Instruction::IfICmpEq { offset: short },
+            Self::IfICmpNe => Instruction::IfICmpNe { offset: short },
+            Self::IfICmpLt => Instruction::IfICmpLt { offset: short },
+            Self::IfICmpGe => Instruction::IfICmpGe { offset: short },
+            Self::IfICmpGt => Instruction::IfICmpGt { offset: short },
+            Self::IfICmpLe => Instruction::IfICmpLe { offset: short },
+            Self::IfACmpEq => Instruction::IfACmpEq { offset: short },
+            Self::IfACmpNe => Instruction::IfACmpNe { offset: short },
+            Self::Goto if wide => Instruction::GotoW { offset },
+            Self::Goto => Instruction::Goto { offset: short },
+            Self::Jsr if wide => Instruction::JsrW { offset },
+            Self::Jsr => Instruction::Jsr { offset: short },
+            Self::IfNull => Instruction::IfNull { offset: short },
+            Self::IfNonNull => Instruction::IfNonNull { offset: short },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TryCatchBlock {
+    pub start: Label,
+    pub end: Label,
+    pub handler: Label,
+    pub catch_type: u16,
+}
+
+/// An entry of a `LocalVariableTable`, or of a `LocalVariableTypeTable`, where `descriptor_index`
+/// is the index of the signature instead.
+#[derive(Clone, Copy, Debug)]
+pub struct LocalVariable {
+    pub start: Label,
+    pub end: Label,
+    pub name_index: u16,
+    pub descriptor_index: u16,
+    pub index: u16,
+}
+
+/// A local variable or operand stack entry of a frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameValue {
+    Top,
+    Integer,
+    Float,
+    Double,
+    Long,
+    Null,
+    UninitializedThis,
+    Object {
+        cpool_index: u16,
+    },
+    /// Created by the `new` instruction at `new`.
+    Uninitialized {
+        new: Label,
+    },
+}
+
+/// An entry of a `StackMapTable`, which is relative to the one before it as in the class file,
+/// without the offset.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Frame {
+    Same,
+    SameLocals1StackItem {
+        stack: FrameValue,
+    },
+    Chop {
+        count: u8,
+    },
+    Append {
+        locals: Vec<FrameValue>,
+    },
+    Full {
+        locals: Vec<FrameValue>,
+        stack: Vec<FrameValue>,
+    },
+}
+
+/// Receives the parts of a class file in order: the header, each field and each method with their
+/// attributes, and then the attributes of the class.
+///
+/// The `Code` attribute of a method is not passed to `visit_attribute`, but broken up between
+/// `visit_code` and `visit_code_end`, as in ASM: the try-catch blocks first, then the instructions
+/// with the labels, line numbers and frames of the positions they are at, then the local variables
+/// and the other attributes of the code. Jumps and switches are visited with labels rather than
+/// offsets, so the code can be changed without breaking them. `RuntimeVisibleTypeAnnotations` and
+/// `RuntimeInvisibleTypeAnnotations` of the code refer to offsets which are not kept track of, so
+/// they are dropped.
+pub trait ClassVisitor<'clazz> {
+    fn visit_header(&mut self, _header: &ClassHeader<'_, 'clazz>) {}
+
+    fn visit_field(&mut self, _field: &MemberHeader) {}
+
+    fn visit_field_end(&mut self) {}
+
+    fn visit_method(&mut self, _method: &MemberHeader) {}
+
+    fn visit_code(&mut self, _code: &CodeHeader) {}
+
+    fn visit_try_catch_block(&mut self, _block: &TryCatchBlock) {}
+
+    /// Marks where the next instruction is.
+    fn visit_label(&mut self, _label: Label) {}
+
+    /// The frame at the next instruction.
+    fn visit_frame(&mut self, _frame: &Frame) {}
+
+    fn visit_line_number(&mut self, _line: u16, _start: Label) {}
+
+    /// Any instruction other than a jump or switch, which the writer writes as it is.
+    fn visit_instruction(&mut self, _instruction: &Instruction) {}
+
+    fn visit_jump(&mut self, _jump: Jump, _target: Label) {}
+
+    fn visit_table_switch(&mut self, _low: i32, _high: i32, _default: Label, _labels: &[Label]) {}
+
+    fn visit_lookup_switch(&mut self, _default: Label, _pairs: &[(i32, Label)]) {}
+
+    fn visit_local_variable(&mut self, _variable: &LocalVariable) {}
+
+    fn visit_local_variable_type(&mut self, _variable: &LocalVariable) {}
+
+    /// The end of the code, where the writer works out its offsets, which fails if a label used by
+    /// the code was not visited in it.
+    fn visit_code_end(&mut self) -> ParseResult<()> {
+        Ok(())
+    }
+
+    fn visit_method_end(&mut self) {}
+
+    /// An attribute of the class, or of the field, method or `Code` attribute being visited.
+    fn visit_attribute(&mut self, _attribute: &LazyAttribute<'clazz>) {}
+
+    fn visit_end(&mut self) {}
+}
+
+// the code of a method as it is visited, apart from the tables before and after it
+#[derive(Debug)]
+pub(crate) enum CodeItem {
+    Label(Label),
+    Frame(Frame),
+    LineNumber(u16, Label),
+    Instruction(Instruction),
+    Jump(Jump, Label),
+    TableSwitch {
+        low: i32,
+        high: i32,
+        default: Label,
+        labels: Vec<Label>,
+    },
+    LookupSwitch {
+        default: Label,
+        pairs: Vec<(i32, Label)>,
+    },
+}
+
+impl CodeItem {
+    fn accept<'clazz, V: ClassVisitor<'clazz>>(&self, visitor: &mut V) {
+        match self {
+            Self::Label(label) => visitor.visit_label(*label),
+            Self::Frame(frame) => visitor.visit_frame(frame),
+            Self::LineNumber(line, start) => visitor.visit_line_number(*line, *start),
+            Self::Instruction(instruction) => visitor.visit_instruction(instruction),
+            Self::Jump(jump, target) => visitor.visit_jump(*jump, *target),
+            Self::TableSwitch {
+                low,
+                high,
+                default,
+                labels,
+            } => visitor.visit_table_switch(*low, *high, *default, labels),
+            Self::LookupSwitch { default, pairs } => visitor.visit_lookup_switch(*default, pairs),
+        }
+    }
+}
+
+pub(crate) fn accept<'clazz, V: ClassVisitor<'clazz>>(
+    classfile: &LazyClassFile<'clazz>,
+    visitor: &mut V,
+) -> ParseResult<()> {
+    let constant_pool = classfile.constant_pool.to_vec()?;
+    visitor.visit_header(&ClassHeader {
+        minor_version: classfile.minor_version,
+        major_version: classfile.major_version,
+        constant_pool: &constant_pool,
+        access_flags: classfile.access_flags,
+        this_class: classfile.this_class,
+        super_class: classfile.super_class,
+        interfaces: classfile.interfaces,
+    });
+
+    for field in &classfile.fields {
+        visitor.visit_field(&member_header(field));
+        for attribute in &field.attributes {
+            visitor.visit_attribute(attribute);
+        }
+        visitor.visit_field_end();
+    }
+
+    for method in &classfile.methods {
+        visitor.visit_method(&member_header(method));
+        for attribute in &method.attributes {
+            if attribute.name(&classfile.constant_pool)?.as_bytes() == b"Code" {
+                accept_code(attribute, &classfile.constant_pool, visitor)?;
+            } else {
+                visitor.visit_attribute(attribute);
+            }
+        }
+        visitor.visit_method_end();
+    }
+
+    for attribute in &classfile.attributes {
+        visitor.visit_attribute(attribute);
+    }
+    visitor.visit_end();
+
+    Ok(())
+}
+
+fn accept_code<'clazz, V: ClassVisitor<'clazz>>(
+    attribute: &LazyAttribute<'clazz>,
+    constant_pool: &LazyConstantPool<'clazz>,
+    visitor: &mut V,
+) -> ParseResult<()> {
+    let mut parser = ClassFileParser::new(attribute.info());
+    let max_stack = parser.try_u2()?;
+    let max_locals = parser.try_u2()?;
+    let code_length = parser.try_u4()?;
+    let code = parser.try_u1_many(code_length as usize)?;
+    let instructions = CodeParser::new(code).parse()?;
+
+    let exception_table_length = parser.try_u2()?;
+    let exception_table = parser.parse_exception_table(exception_table_length)?;
+    let attributes = lazy::index_attributes(&mut parser)?;
+
+    let mut labels = CodeLabels::new(&instructions, code.len());
+
+    let mut try_catch_blocks = Vec::with_capacity(exception_table.len());
+    for handler in &exception_table {
+        try_catch_blocks.push(TryCatchBlock {
+            start: labels.at(handler.start_pc as i64)?,
+            end: labels.at(handler.end_pc as i64)?,
+            handler: labels.at(handler.handler_pc as i64)?,
+            catch_type: handler.catch_type,
+        });
+    }
+
+    let mut items = Vec::with_capacity(instructions.len());
+    for (pc, instruction) in instructions {
+        let target = |labels: &mut CodeLabels, offset: i32| labels.at(pc as i64 + offset as i64);
+
+        let item = match instruction {
+            Instruction::TableSwitch {
+                default,
+                low,
+                high,
+                jump_offsets,
+            } => CodeItem::TableSwitch {
+                low,
+                high,
+                default: target(&mut labels, default)?,
+                labels: jump_offsets
+                    .into_iter()
+                    .map(|offset| target(&mut labels, offset))
+                    .collect::<ParseResult<_>>()?,
+            },
+            Instruction::LookupSwitch {
+                default,
+                match_offset_pairs,
+                ..
+            } => CodeItem::LookupSwitch {
+                default: target(&mut labels, default)?,
+                pairs: match_offset_pairs
+                    .into_iter()
+                    .map(|(value, offset)| Ok((value, target(&mut labels, offset)?)))
+                    .collect::<ParseResult<_>>()?,
+            },
+            instruction => match Jump::of(&instruction) {
+                Some((jump, offset)) => CodeItem::Jump(jump, target(&mut labels, offset)?),
+                None => CodeItem::Instruction(instruction),
+            },
+        };
+        items.push((pc, item));
+    }
+
+    let mut line_numbers = Vec::new();
+    let mut frames = Vec::new();
+    let mut local_variables = Vec::new();
+    let mut local_variable_types = Vec::new();
+    let mut other_attributes = Vec::new();
+    for attribute in &attributes {
+        match attribute.decode(constant_pool)?.info {
+            AttributeInfoType::LineNumberTableAttribute {
+                line_number_table, ..
+            } => {
+                for entry in line_number_table {
+                    labels.at(entry.start_pc as i64)?;
+                    line_numbers.push((entry.start_pc, entry.line_number));
+                }
+            }
+            AttributeInfoType::StackMapTableAttribute {
+                stack_map_table, ..
+            } => {
+                let mut pc = -1;
+                for frame in stack_map_table {
+                    let (offset_delta, frame) = frame_of(frame, &mut labels)?;
+                    pc += offset_delta as i64 + 1;
+
+                    labels.at(pc)?;
+                    frames.push((pc as u16, frame));
+                }
+            }
+            AttributeInfoType::LocalVariableTableAttribute {
+                local_variable_table,
+                ..
+            } => {
+                for entry in local_variable_table {
+                    local_variables.push(LocalVariable {
+                        start: labels.at(entry.start_pc as i64)?,
+                        end: labels.at(entry.start_pc as i64 + entry.length as i64)?,
+                        name_index: entry.name_index,
+                        descriptor_index: entry.descriptor_index,
+                        index: entry.index,
+                    });
+                }
+            }
+            AttributeInfoType::LocalVariableTypeTableAttribute {
+                local_variable_type_table,
+                ..
+            } => {
+                for entry in local_variable_type_table {
+                    local_variable_types.push(LocalVariable {
+                        start: labels.at(entry.start_pc as i64)?,
+                        end: labels.at(entry.start_pc as i64 + entry.length as i64)?,
+                        name_index: entry.name_index,
+                        descriptor_index: entry.signature_index,
+                        index: entry.index,
+                    });
+                }
+            }
+            AttributeInfoType::RuntimeVisibleTypeAnnotationsAttribute { .. }
+            | AttributeInfoType::RuntimeInvisibleTypeAnnotationsAttribute { .. } => (),
+            _ => other_attributes.push(attribute),
+        }
+    }
+    line_numbers.sort_by_key(|&(pc, _)| pc);
+
+    visitor.visit_code(&CodeHeader {
+        attribute_name_index: attribute.attribute_name_index,
+        max_stack,
+        max_locals,
+    });
+    for block in &try_catch_blocks {
+        visitor.visit_try_catch_block(block);
+    }
+
+    let mut line_numbers = line_numbers.into_iter().peekable();
+    let mut frames = frames.into_iter().peekable();
+    for (pc, item) in &items {
+        if labels.is_used(*pc) {
+            visitor.visit_label(Label::at(*pc));
+        }
+        while let Some((_, line)) = line_numbers.next_if(|(start_pc, _)| start_pc == pc) {
+            visitor.visit_line_number(line, Label::at(*pc));
+        }
+        if let Some((_, frame)) = frames.next_if(|(frame_pc, _)| frame_pc == pc) {
+            visitor.visit_frame(&frame);
+        }
+
+        item.accept(visitor);
+    }
+    if labels.is_used(code.len() as u16) {
+        visitor.visit_label(Label::at(code.len() as u16));
+    }
+
+    for variable in &local_variables {
+        visitor.visit_local_variable(variable);
+    }
+    for variable in &local_variable_types {
+        visitor.visit_local_variable_type(variable);
+    }
+    for attribute in other_attributes {
+        visitor.visit_attribute(attribute);
+    }
+    visitor.visit_code_end()?;
+
+    Ok(())
+}
+
+// the offsets in the code of a method which something refers to, and so are visited as labels
+struct CodeLabels {
+    // whether an instruction starts at each offset, or it is the end of the code
+    starts: Vec<bool>,
+    used: Vec<bool>,
+}
+
+impl CodeLabels {
+    fn new(instructions: &[(u16, Instruction)], code_length: usize) -> Self {
+        let mut starts = vec![false; code_length + 1];
+        for &(pc, _) in instructions {
+            starts[pc as usize] = true;
+        }
+        starts[code_length] = true;
+
+        Self {
+            used: vec![false; starts.len()],
+            starts,
+        }
+    }
+
+    fn at(&mut self, pc: i64) -> ParseResult<Label> {
+        if pc < 0 || !self.starts.get(pc as usize).copied().unwrap_or(false) {
+            return Err(ParseError::InvalidCodeOffset);
+        }
+        self.used[pc as usize] = true;
+
+        Ok(Label::at(pc as u16))
+    }
+
+    fn is_used(&self, pc: u16) -> bool {
+        self.used[pc as usize]
+    }
+}
+
+// the offset delta of a frame, and the frame with labels instead of offsets
+fn frame_of(frame: StackMapFrame, labels: &mut CodeLabels) -> ParseResult<(u16, Frame)> {
+    let mut values = |values: Vec<VerificationTypeInfo>| {
+        values
+            .into_iter()
+            .map(|value| frame_value(value, labels))
+            .collect::<ParseResult<Vec<_>>>()
+    };
+
+    Ok(match frame {
+        StackMapFrame::SameFrame { frame_type } => (frame_type as u16, Frame::Same),
+        StackMapFrame::SameLocals1StackItemFrame { frame_type, stack } => (
+            frame_type as u16 - 64,
+            Frame::SameLocals1StackItem {
+                stack: frame_value(stack, labels)?,
+            },
+        ),
+        StackMapFrame::SameLocals1StackItemFrameExtended {
+            offset_delta,
+            stack,
+            ..
+        } => (
+            offset_delta,
+            Frame::SameLocals1StackItem {
+                stack: frame_value(stack, labels)?,
+            },
+        ),
+        StackMapFrame::ChopFrame {
+            frame_type,
+            offset_delta,
+        } => (
+            offset_delta,
+            Frame::Chop {
+                count: 251 - frame_type,
+            },
+        ),
+        StackMapFrame::SameFrameExtended { offset_delta, .. } => (offset_delta, Frame::Same),
+        StackMapFrame::AppendFrame {
+            offset_delta,
+            locals,
+            ..
+        } => (
+            offset_delta,
+            Frame::Append {
+                locals: values(locals)?,
+            },
+        ),
+        StackMapFrame::FullFrame {
+            offset_delta,
+            locals,
+            stack,
+            ..
+        } => (
+            offset_delta,
+            Frame::Full {
+                locals: values(locals)?,
+                stack: values(stack)?,
+            },
+        ),
+    })
+}
+
+fn frame_value(value: VerificationTypeInfo, labels: &mut CodeLabels) -> ParseResult<FrameValue> {
+    Ok(match value {
+        VerificationTypeInfo::TopVariableInfo { .. } => FrameValue::Top,
+        VerificationTypeInfo::IntegerVariableInfo { .. } => FrameValue::Integer,
+        VerificationTypeInfo::FloatVariableInfo { .. } => FrameValue::Float,
+        VerificationTypeInfo::DoubleVariableInfo { .. } => FrameValue::Double,
+        VerificationTypeInfo::LongVariableInfo { .. } => FrameValue::Long,
+        VerificationTypeInfo::NullVariableInfo { .. } => FrameValue::Null,
+        VerificationTypeInfo::UninitializedThisVariableInfo { .. } => FrameValue::UninitializedThis,
+        VerificationTypeInfo::ObjectVariableInfo { cpool_index, .. } => {
+            FrameValue::Object { cpool_index }
+        }
+        VerificationTypeInfo::UninitializedVariableInfo { offset, .. } => {
+            FrameValue::Uninitialized {
+                new: labels.at(offset as i64)?,
+            }
+        }
+    })
+}
+
+fn member_header(member: &LazyMember) -> MemberHeader {
+    MemberHeader {
+        access_flags: member.access_flags,
+        name_index: member.name_index,
+        descriptor_index: member.descriptor_index,
+    }
+}
@@ -0,0 +1,826 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Writes the events of a ClassVisitor back into a class file.
+
+use std::collections::HashMap;
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::attrinfo::smtattr::StackMapFrame;
+use ironjvm_specimpl::classfile::attrinfo::smtattr::VerificationTypeInfo;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::code::CodeWriter;
+use crate::error::ParseError;
+use crate::error::ParseResult;
+use crate::lazy::LazyAttribute;
+use crate::visitor::ClassHeader;
+use crate::visitor::ClassVisitor;
+use crate::visitor::CodeHeader;
+use crate::visitor::CodeItem;
+use crate::visitor::Frame;
+use crate::visitor::FrameValue;
+use crate::visitor::Jump;
+use crate::visitor::Label;
+use crate::visitor::LocalVariable;
+use crate::visitor::MemberHeader;
+use crate::visitor::TryCatchBlock;
+
+/// Writes a class file from the events it is visited with. The offsets in the code of each method
+/// are worked out from its labels, making a `goto` or `jsr` wide if its target is too far away for
+/// the short form. A conditional jump whose target is too far away becomes the opposite jump over
+/// a `goto_w` to the target, as in ASM.
+///
+/// # Errors
+///
+/// `visit_code_end` fails if a label used by the code was not visited in it, or if two frames are
+/// at the same offset. It also fails if a conditional jump had to be rewritten in code which has
+/// frames, but none was visited for the instruction after it, as the writer does not work out
+/// frames.
+#[derive(Default)]
+pub struct ClassWriter<'clazz> {
+    minor_version: u16,
+    major_version: u16,
+    constant_pool: Vec<CpInfo<'clazz>>,
+    access_flags: u16,
+    this_class: u16,
+    super_class: u16,
+    interfaces: Vec<[u8; 2]>,
+    fields: Table,
+    methods: Table,
+    attributes: Table,
+    // the field or method being written
+    member: Option<(MemberHeader, Table)>,
+    code: Option<PendingCode>,
+}
+
+impl<'clazz> ClassWriter<'clazz> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an entry to the constant pool, returning its index.
+    pub fn add_constant(&mut self, info: CpInfoType<'clazz>) -> u16 {
+        let index = self.constant_pool.len() as u16 + 1;
+        let is_wide = matches!(
+            info,
+            CpInfoType::ConstantLong { .. } | CpInfoType::ConstantDouble { .. }
+        );
+
+        self.constant_pool.push(CpInfo {
+            tag: constant_tag(&info),
+            info,
+        });
+        if is_wide {
+            self.constant_pool.push(CpInfo {
+                tag: 0,
+                info: CpInfoType::ConstantUnusable,
+            });
+        }
+
+        index
+    }
+
+    // the index of the Utf8 constant `string`, which is added if there is none
+    fn utf8_constant(&mut self, string: &'static str) -> u16 {
+        let existing = self
+            .constant_pool
+            .iter()
+            .position(|cp_info| match cp_info.info {
+                CpInfoType::ConstantUtf8 {
+                    string: existing, ..
+                } => existing.as_bytes() == string.as_bytes(),
+                _ => false,
+            });
+        if let Some(index) = existing {
+            return index as u16 + 1;
+        }
+
+        let string = JStr::from_jutf8(string.as_bytes()).ok().unwrap();
+        self.add_constant(CpInfoType::ConstantUtf8 {
+            length: string.len() as u16,
+            string,
+        })
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        let mut output = Vec::new();
+
+        output.extend_from_slice(&0xCAFEBABEu32.to_be_bytes());
+        output.extend_from_slice(&self.minor_version.to_be_bytes());
+        output.extend_from_slice(&self.major_version.to_be_bytes());
+
+        output.extend_from_slice(&(self.constant_pool.len() as u16 + 1).to_be_bytes());
+        for cp_info in &self.constant_pool {
+            write_constant(&mut output, &cp_info.info);
+        }
+
+        output.extend_from_slice(&self.access_flags.to_be_bytes());
+        output.extend_from_slice(&self.this_class.to_be_bytes());
+        output.extend_from_slice(&self.super_class.to_be_bytes());
+        output.extend_from_slice(&(self.interfaces.len() as u16).to_be_bytes());
+        output.extend(self.interfaces.iter().flatten());
+
+        self.fields.write(&mut output);
+        self.methods.write(&mut output);
+        self.attributes.write(&mut output);
+
+        output
+    }
+
+    fn end_member(&mut self) -> Option<Vec<u8>> {
+        let (header, attributes) = self.member.take()?;
+
+        let mut output = Vec::new();
+        output.extend_from_slice(&header.access_flags.to_be_bytes());
+        output.extend_from_slice(&header.name_index.to_be_bytes());
+        output.extend_from_slice(&header.descriptor_index.to_be_bytes());
+        attributes.write(&mut output);
+
+        Some(output)
+    }
+}
+
+impl<'clazz> ClassVisitor<'clazz> for ClassWriter<'clazz> {
+    fn visit_header(&mut self, header: &ClassHeader<'_, 'clazz>) {
+        self.minor_version = header.minor_version;
+        self.major_version = header.major_version;
+        self.constant_pool = header.constant_pool.to_vec();
+        self.access_flags = header.access_flags;
+        self.this_class = header.this_class;
+        self.super_class = header.super_class;
+        self.interfaces = header.interfaces.to_vec();
+    }
+
+    fn visit_field(&mut self, field: &MemberHeader) {
+        self.member = Some((*field, Table::default()));
+    }
+
+    fn visit_field_end(&mut self) {
+        if let Some(field) = self.end_member() {
+            self.fields.push(&field);
+        }
+    }
+
+    fn visit_method(&mut self, method: &MemberHeader) {
+        self.member = Some((*method, Table::default()));
+    }
+
+    fn visit_code(&mut self, code: &CodeHeader) {
+        self.code = Some(PendingCode {
+            header: *code,
+            try_catch_blocks: Vec::new(),
+            items: Vec::new(),
+            local_variables: Vec::new(),
+            local_variable_types: Vec::new(),
+            attributes: Table::default(),
+        });
+    }
+
+    fn visit_try_catch_block(&mut self, block: &TryCatchBlock) {
+        if let Some(code) = &mut self.code {
+            code.try_catch_blocks.push(*block);
+        }
+    }
+
+    fn visit_label(&mut self, label: Label) {
+        if let Some(code) = &mut self.code {
+            code.items.push(CodeItem::Label(label));
+        }
+    }
+
+    fn visit_frame(&mut self, frame: &Frame) {
+        if let Some(code) = &mut self.code {
+            code.items.push(CodeItem::Frame(frame.clone()));
+        }
+    }
+
+    fn visit_line_number(&mut self, line: u16, start: Label) {
+        if let Some(code) = &mut self.code {
+            code.items.push(CodeItem::LineNumber(line, start));
+        }
+    }
+
+    fn visit_instruction(&mut self, instruction: &Instruction) {
+        if let Some(code) = &mut self.code {
+            code.items.push(CodeItem::Instruction(instruction.clone()));
+        }
+    }
+
+    fn visit_jump(&mut self, jump: Jump, target: Label) {
+        if let Some(code) = &mut self.code {
+            code.items.push(CodeItem::Jump(jump, target));
+        }
+    }
+
+    fn visit_table_switch(&mut self, low: i32, high: i32, default: Label, labels: &[Label]) {
+        if let Some(code) = &mut self.code {
+            code.items.push(CodeItem::TableSwitch {
+                low,
+                high,
+                default,
+                labels: labels.to_vec(),
+            });
+        }
+    }
+
+    fn visit_lookup_switch(&mut self, default: Label, pairs: &[(i32, Label)]) {
+        if let Some(code) = &mut self.code {
+            code.items.push(CodeItem::LookupSwitch {
+                default,
+                pairs: pairs.to_vec(),
+            });
+        }
+    }
+
+    fn visit_local_variable(&mut self, variable: &LocalVariable) {
+        if let Some(code) = &mut self.code {
+            code.local_variables.push(*variable);
+        }
+    }
+
+    fn visit_local_variable_type(&mut self, variable: &LocalVariable) {
+        if let Some(code) = &mut self.code {
+            code.local_variable_types.push(*variable);
+        }
+    }
+
+    fn visit_code_end(&mut self) -> ParseResult<()> {
+        let Some(code) = self.code.take() else {
+            return Ok(());
+        };
+        let assembled = assemble(&code.items)?;
+
+        let mut line_numbers = Vec::new();
+        for item in &code.items {
+            if let CodeItem::LineNumber(line, start) = *item {
+                line_numbers.push((line, assembled.pc(start)?));
+            }
+        }
+        let local_variables = local_variable_table(&code.local_variables, &assembled)?;
+        let local_variable_types = local_variable_table(&code.local_variable_types, &assembled)?;
+        let stack_map_table = stack_map_table(&assembled)?;
+
+        let mut exception_table = Vec::with_capacity(code.try_catch_blocks.len() * 8);
+        for block in &code.try_catch_blocks {
+            exception_table.extend_from_slice(&assembled.pc(block.start)?.to_be_bytes());
+            exception_table.extend_from_slice(&assembled.pc(block.end)?.to_be_bytes());
+            exception_table.extend_from_slice(&assembled.pc(block.handler)?.to_be_bytes());
+            exception_table.extend_from_slice(&block.catch_type.to_be_bytes());
+        }
+
+        // the attributes which refer to offsets are written again from their labels, before the
+        // ones passed through
+        let mut attributes = Table::default();
+
+        if !line_numbers.is_empty() {
+            let mut info = Vec::with_capacity(2 + line_numbers.len() * 4);
+            info.extend_from_slice(&(line_numbers.len() as u16).to_be_bytes());
+            for (line, start_pc) in line_numbers {
+                info.extend_from_slice(&start_pc.to_be_bytes());
+                info.extend_from_slice(&line.to_be_bytes());
+            }
+
+            let name = self.utf8_constant("LineNumberTable");
+            attributes.push(&attribute_bytes(name, &info));
+        }
+        if let Some(info) = local_variables {
+            let name = self.utf8_constant("LocalVariableTable");
+            attributes.push(&attribute_bytes(name, &info));
+        }
+        if let Some(info) = local_variable_types {
+            let name = self.utf8_constant("LocalVariableTypeTable");
+            attributes.push(&attribute_bytes(name, &info));
+        }
+        if let Some(info) = stack_map_table {
+            let name = self.utf8_constant("StackMapTable");
+            attributes.push(&attribute_bytes(name, &info));
+        }
+
+        attributes.append(code.attributes);
+
+        let Some((_, member_attributes)) = &mut self.member else {
+            return Ok(());
+        };
+
+        let mut info = Vec::new();
+        info.extend_from_slice(&code.header.max_stack.to_be_bytes());
+        info.extend_from_slice(&code.header.max_locals.to_be_bytes());
+        info.extend_from_slice(&(assembled.code.len() as u32).to_be_bytes());
+        info.extend_from_slice(&assembled.code);
+        info.extend_from_slice(&(code.try_catch_blocks.len() as u16).to_be_bytes());
+        info.extend_from_slice(&exception_table);
+        attributes.write(&mut info);
+
+        member_attributes.push(&attribute_bytes(code.header.attribute_name_index, &info));
+
+        Ok(())
+    }
+
+    fn visit_method_end(&mut self) {
+        if let Some(method) = self.end_member() {
+            self.methods.push(&method);
+        }
+    }
+
+    fn visit_attribute(&mut self, attribute: &LazyAttribute<'clazz>) {
+        let table = match (&mut self.code, &mut self.member) {
+            (Some(code), _) => &mut code.attributes,
+            (None, Some((_, attributes))) => attributes,
+            (None, None) => &mut self.attributes,
+        };
+
+        table.push(attribute.as_bytes());
+    }
+}
+
+// a Code attribute which is still being visited
+struct PendingCode {
+    header: CodeHeader,
+    try_catch_blocks: Vec<TryCatchBlock>,
+    items: Vec<CodeItem>,
+    local_variables: Vec<LocalVariable>,
+    local_variable_types: Vec<LocalVariable>,
+    attributes: Table,
+}
+
+// the bytes of the code of a method, and where its labels and frames ended up
+struct Assembled<'a> {
+    code: Vec<u8>,
+    labels: HashMap<Label, usize>,
+    frames: Vec<(u16, &'a Frame)>,
+    // where each item is
+    pcs: Vec<usize>,
+    // where the code goes on after each conditional jump written over a goto_w
+    jumped_over: Vec<u16>,
+}
+
+impl Assembled<'_> {
+    fn pc(&self, label: Label) -> ParseResult<u16> {
+        match self.labels.get(&label) {
+            Some(&pc) => Ok(pc as u16),
+            None => Err(ParseError::UnvisitedLabel { label }),
+        }
+    }
+}
+
+// Lays out the code until every jump whose target is too far away is wide, as that moves what
+// comes after them, and then writes it with the offsets of its labels.
+fn assemble(items: &[CodeItem]) -> ParseResult<Assembled<'_>> {
+    let mut wide = vec![false; items.len()];
+
+    loop {
+        let layout = emit(items, &wide, &HashMap::new());
+
+        let mut widened = false;
+        for (index, item) in items.iter().enumerate() {
+            let CodeItem::Jump(_, target) = *item else {
+                continue;
+            };
+
+            let offset = layout.pc(target)? as i64 - layout.pcs[index] as i64;
+            if !wide[index] && i16::try_from(offset).is_err() {
+                wide[index] = true;
+                widened = true;
+            }
+        }
+
+        if !widened {
+            return Ok(emit(items, &wide, &layout.labels));
+        }
+    }
+}
+
+// writes the code, with the offsets of the labels not in `labels` left as 0
+fn emit<'a>(items: &'a [CodeItem], wide: &[bool], labels: &HashMap<Label, usize>) -> Assembled<'a> {
+    let mut code = CodeWriter::new();
+    let mut assembled = Assembled {
+        code: Vec::new(),
+        labels: HashMap::new(),
+        frames: Vec::new(),
+        pcs: Vec::with_capacity(items.len()),
+        jumped_over: Vec::new(),
+    };
+
+    for (item, &wide) in items.iter().zip(wide) {
+        let pc = code.pc();
+        let offset = |target: &Label| {
+            labels
+                .get(target)
+                .map_or(0, |&target| target as i32 - pc as i32)
+        };
+        assembled.pcs.push(pc);
+
+        match item {
+            CodeItem::Label(label) => {
+                assembled.labels.insert(*label, pc);
+            }
+            CodeItem::Frame(frame) => assembled.frames.push((pc as u16, frame)),
+            CodeItem::LineNumber(..) => (),
+            CodeItem::Instruction(instruction) => code.write(instruction),
+            CodeItem::Jump(jump, target) => match jump.opposite() {
+                // the opposite jump skips itself and the goto_w, which starts 3 bytes further on
+                Some(opposite) if wide => {
+                    code.write(&opposite.instruction(8, false));
+                    code.write(&Jump::Goto.instruction(offset(target) - 3, true));
+                    assembled.jumped_over.push(code.pc() as u16);
+                }
+                _ => code.write(&jump.instruction(offset(target), wide)),
+            },
+            CodeItem::TableSwitch {
+                low,
+                high,
+                default,
+                labels,
+            } => code.write(&Instruction::TableSwitch {
+                default: offset(default),
+                low: *low,
+                high: *high,
+                jump_offsets: labels.iter().map(offset).collect(),
+            }),
+            CodeItem::LookupSwitch { default, pairs } => code.write(&Instruction::LookupSwitch {
+                default: offset(default),
+                npairs: pairs.len() as i32,
+                match_offset_pairs: pairs
+                    .iter()
+                    .map(|(value, target)| (*value, offset(target)))
+                    .collect(),
+            }),
+        }
+    }
+
+    assembled.code = code.into_bytes();
+    assembled
+}
+
+// the contents of a LocalVariableTable or LocalVariableTypeTable, if there are any variables
+fn local_variable_table(
+    variables: &[LocalVariable],
+    assembled: &Assembled,
+) -> ParseResult<Option<Vec<u8>>> {
+    if variables.is_empty() {
+        return Ok(None);
+    }
+
+    let mut info = Vec::with_capacity(2 + variables.len() * 10);
+    info.extend_from_slice(&(variables.len() as u16).to_be_bytes());
+    for variable in variables {
+        let start = assembled.pc(variable.start)?;
+        info.extend_from_slice(&start.to_be_bytes());
+        info.extend_from_slice(&(assembled.pc(variable.end)? - start).to_be_bytes());
+        info.extend_from_slice(&variable.name_index.to_be_bytes());
+        info.extend_from_slice(&variable.descriptor_index.to_be_bytes());
+        info.extend_from_slice(&variable.index.to_be_bytes());
+    }
+
+    Ok(Some(info))
+}
+
+// the contents of the StackMapTable, if there are any frames
+fn stack_map_table(assembled: &Assembled) -> ParseResult<Option<Vec<u8>>> {
+    if assembled.frames.is_empty() {
+        return Ok(None);
+    }
+
+    // the code after a goto_w is only reached by the jump over it, so it needs a frame, which the
+    // writer cannot work out
+    for &pc in &assembled.jumped_over {
+        if !assembled.frames.iter().any(|&(frame_pc, _)| frame_pc == pc) {
+            return Err(ParseError::MissingFrame { pc });
+        }
+    }
+
+    let mut stack_map_table = Vec::with_capacity(assembled.frames.len());
+    let mut previous = None;
+    for &(pc, frame) in &assembled.frames {
+        let offset_delta = match previous {
+            None => pc,
+            Some(previous) if pc > previous => pc - previous - 1,
+            Some(_) => return Err(ParseError::DuplicateFrame { pc }),
+        };
+        previous = Some(pc);
+
+        stack_map_table.push(stack_map_frame(frame, offset_delta, assembled)?);
+    }
+
+    let mut info = Vec::new();
+    encode_stack_map_table(&stack_map_table, &mut info);
+
+    Ok(Some(info))
+}
+
+fn stack_map_frame(
+    frame: &Frame,
+    offset_delta: u16,
+    assembled: &Assembled,
+) -> ParseResult<StackMapFrame> {
+    let values = |values: &[FrameValue]| {
+        values
+            .iter()
+            .map(|value| verification_type(value, assembled))
+            .collect::<ParseResult<Vec<_>>>()
+    };
+
+    Ok(match frame {
+        Frame::Same if offset_delta < 64 => StackMapFrame::SameFrame {
+            frame_type: offset_delta as u8,
+        },
+        Frame::Same => StackMapFrame::SameFrameExtended {
+            frame_type: 251,
+            offset_delta,
+        },
+        Frame::SameLocals1StackItem { stack } if offset_delta < 64 => {
+            StackMapFrame::SameLocals1StackItemFrame {
+                frame_type: 64 + offset_delta as u8,
+                stack: verification_type(stack, assembled)?,
+            }
+        }
+        Frame::SameLocals1StackItem { stack } => StackMapFrame::SameLocals1StackItemFrameExtended {
+            frame_type: 247,
+            offset_delta,
+            stack: verification_type(stack, assembled)?,
+        },
+        Frame::Chop { count } => StackMapFrame::ChopFrame {
+            frame_type: 251 - count,
+            offset_delta,
+        },
+        Frame::Append { locals } => StackMapFrame::AppendFrame {
+            frame_type: 251 + locals.len() as u8,
+            offset_delta,
+            locals: values(locals)?,
+        },
+        Frame::Full { locals, stack } => StackMapFrame::FullFrame {
+            frame_type: 255,
+            offset_delta,
+            number_of_locals: locals.len() as u16,
+            locals: values(locals)?,
+            number_of_stack_items: stack.len() as u16,
+            stack: values(stack)?,
+        },
+    })
+}
+
+fn verification_type(
+    value: &FrameValue,
+    assembled: &Assembled,
+) -> ParseResult<VerificationTypeInfo> {
+    Ok(match *value {
+        FrameValue::Top => VerificationTypeInfo::TopVariableInfo { tag: 0 },
+        FrameValue::Integer => VerificationTypeInfo::IntegerVariableInfo { tag: 1 },
+        FrameValue::Float => VerificationTypeInfo::FloatVariableInfo { tag: 2 },
+        FrameValue::Double => VerificationTypeInfo::DoubleVariableInfo { tag: 3 },
+        FrameValue::Long => VerificationTypeInfo::LongVariableInfo { tag: 4 },
+        FrameValue::Null => VerificationTypeInfo::NullVariableInfo { tag: 5 },
+        FrameValue::UninitializedThis => {
+            VerificationTypeInfo::UninitializedThisVariableInfo { tag: 6 }
+        }
+        FrameValue::Object { cpool_index } => VerificationTypeInfo::ObjectVariableInfo {
+            tag: 7,
+            cpool_index,
+        },
+        FrameValue::Uninitialized { new } => VerificationTypeInfo::UninitializedVariableInfo {
+            tag: 8,
+            offset: assembled.pc(new)?,
+        },
+    })
+}
+
+/// Encodes the contents of a `StackMapTable` attribute.
+pub fn encode_stack_map_table(frames: &[StackMapFrame], output: &mut Vec<u8>) {
+    output.extend_from_slice(&(frames.len() as u16).to_be_bytes());
+
+    for frame in frames {
+        match frame {
+            StackMapFrame::SameFrame { frame_type } => output.push(*frame_type),
+            StackMapFrame::SameLocals1StackItemFrame { frame_type, stack } => {
+                output.push(*frame_type);
+                encode_verification_type(stack, output);
+            }
+            StackMapFrame::SameLocals1StackItemFrameExtended {
+                frame_type,
+                offset_delta,
+                stack,
+            } => {
+                output.push(*frame_type);
+                output.extend_from_slice(&offset_delta.to_be_bytes());
+                encode_verification_type(stack, output);
+            }
+            StackMapFrame::ChopFrame {
+                frame_type,
+                offset_delta,
+            }
+            | StackMapFrame::SameFrameExtended {
+                frame_type,
+                offset_delta,
+            } => {
+                output.push(*frame_type);
+                output.extend_from_slice(&offset_delta.to_be_bytes());
+            }
+            StackMapFrame::AppendFrame {
+                frame_type,
+                offset_delta,
+                locals,
+            } => {
+                output.push(*frame_type);
+                output.extend_from_slice(&offset_delta.to_be_bytes());
+                for local in locals {
+                    encode_verification_type(local, output);
+                }
+            }
+            StackMapFrame::FullFrame {
+                frame_type,
+                offset_delta,
+                locals,
+                stack,
+                ..
+            } => {
+                output.push(*frame_type);
+                output.extend_from_slice(&offset_delta.to_be_bytes());
+                output.extend_from_slice(&(locals.len() as u16).to_be_bytes());
+                for local in locals {
+                    encode_verification_type(local, output);
+                }
+                output.extend_from_slice(&(stack.len() as u16).to_be_bytes());
+                for item in stack {
+                    encode_verification_type(item, output);
+                }
+            }
+        }
+    }
+}
+
+fn encode_verification_type(r#type: &VerificationTypeInfo, output: &mut Vec<u8>) {
+    match *r#type {
+        VerificationTypeInfo::TopVariableInfo { tag }
+        | VerificationTypeInfo::IntegerVariableInfo { tag }
+        | VerificationTypeInfo::FloatVariableInfo { tag }
+        | VerificationTypeInfo::DoubleVariableInfo { tag }
+        | VerificationTypeInfo::LongVariableInfo { tag }
+        | VerificationTypeInfo::NullVariableInfo { tag }
+        | VerificationTypeInfo::UninitializedThisVariableInfo { tag } => output.push(tag),
+        VerificationTypeInfo::ObjectVariableInfo {
+            tag,
+            cpool_index: index,
+        }
+        | VerificationTypeInfo::UninitializedVariableInfo { tag, offset: index } => {
+            output.push(tag);
+            output.extend_from_slice(&index.to_be_bytes());
+        }
+    }
+}
+
+// encoded entries of a table preceded by their count, such as fields or attributes
+#[derive(Default)]
+struct Table {
+    count: u16,
+    bytes: Vec<u8>,
+}
+
+impl Table {
+    fn push(&mut self, entry: &[u8]) {
+        self.count += 1;
+        self.bytes.extend_from_slice(entry);
+    }
+
+    fn append(&mut self, other: Table) {
+        self.count += other.count;
+        self.bytes.extend_from_slice(&other.bytes);
+    }
+
+    fn write(&self, output: &mut Vec<u8>) {
+        output.extend_from_slice(&self.count.to_be_bytes());
+        output.extend_from_slice(&self.bytes);
+    }
+}
+
+fn attribute_bytes(attribute_name_index: u16, info: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(6 + info.len());
+    output.extend_from_slice(&attribute_name_index.to_be_bytes());
+    output.extend_from_slice(&(info.len() as u32).to_be_bytes());
+    output.extend_from_slice(info);
+
+    output
+}
+
+fn constant_tag(info: &CpInfoType) -> u8 {
+    match info {
+        CpInfoType::ConstantUnusable => 0,
+        CpInfoType::ConstantUtf8 { .. } => 1,
+        CpInfoType::ConstantInteger { .. } => 3,
+        CpInfoType::ConstantFloat { .. } => 4,
+        CpInfoType::ConstantLong { .. } => 5,
+        CpInfoType::ConstantDouble { .. } => 6,
+        CpInfoType::ConstantClass { .. } => 7,
+        CpInfoType::ConstantString { .. } => 8,
+        CpInfoType::ConstantFieldRef { .. } => 9,
+        CpInfoType::ConstantMethodRef { .. } => 10,
+        CpInfoType::ConstantInterfaceMethodRef { .. } => 11,
+        CpInfoType::ConstantNameAndType { .. } => 12,
+        CpInfoType::ConstantMethodHandle { .. } => 15,
+        CpInfoType::ConstantMethodType { .. } => 16,
+        CpInfoType::ConstantDynamic { .. } => 17,
+        CpInfoType::ConstantInvokeDynamic { .. } => 18,
+        CpInfoType::ConstantModule { .. } => 19,
+        CpInfoType::ConstantPackage { .. } => 20,
+    }
+}
+
+fn write_constant(output: &mut Vec<u8>, info: &CpInfoType) {
+    // the slot after a long or double takes no space in the class file
+    if let CpInfoType::ConstantUnusable = info {
+        return;
+    }
+
+    output.push(constant_tag(info));
+    match *info {
+        CpInfoType::ConstantUtf8 { string, .. } => {
+            output.extend_from_slice(&(string.len() as u16).to_be_bytes());
+            output.extend_from_slice(string.as_bytes());
+        }
+        CpInfoType::ConstantInteger { bytes } | CpInfoType::ConstantFloat { bytes } => {
+            output.extend_from_slice(&bytes.to_be_bytes());
+        }
+        CpInfoType::ConstantLong {
+            high_bytes,
+            low_bytes,
+        }
+        | CpInfoType::ConstantDouble {
+            high_bytes,
+            low_bytes,
+        } => {
+            output.extend_from_slice(&high_bytes.to_be_bytes());
+            output.extend_from_slice(&low_bytes.to_be_bytes());
+        }
+        CpInfoType::ConstantClass { name_index }
+        | CpInfoType::ConstantModule { name_index }
+        | CpInfoType::ConstantPackage { name_index } => {
+            output.extend_from_slice(&name_index.to_be_bytes());
+        }
+        CpInfoType::ConstantString { string_index } => {
+            output.extend_from_slice(&string_index.to_be_bytes());
+        }
+        CpInfoType::ConstantFieldRef {
+            class_index,
+            name_and_type_index,
+        }
+        | CpInfoType::ConstantMethodRef {
+            class_index,
+            name_and_type_index,
+        }
+        | CpInfoType::ConstantInterfaceMethodRef {
+            class_index,
+            name_and_type_index,
+        } => {
+            output.extend_from_slice(&class_index.to_be_bytes());
+            output.extend_from_slice(&name_and_type_index.to_be_bytes());
+        }
+        CpInfoType::ConstantNameAndType {
+            name_index,
+            descriptor_index,
+        } => {
+            output.extend_from_slice(&name_index.to_be_bytes());
+            output.extend_from_slice(&descriptor_index.to_be_bytes());
+        }
+        CpInfoType::ConstantMethodHandle {
+            reference_kind,
+            reference_index,
+        } => {
+            output.push(reference_kind);
+            output.extend_from_slice(&reference_index.to_be_bytes());
+        }
+        CpInfoType::ConstantMethodType { descriptor_index } => {
+            output.extend_from_slice(&descriptor_index.to_be_bytes());
+        }
+        CpInfoType::ConstantDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        }
+        | CpInfoType::ConstantInvokeDynamic {
+            bootstrap_method_attr_index,
+            name_and_type_index,
+        } => {
+            output.extend_from_slice(&bootstrap_method_attr_index.to_be_bytes());
+            output.extend_from_slice(&name_and_type_index.to_be_bytes());
+        }
+        CpInfoType::ConstantUnusable => unreachable!(),
+    }
+}
@@ -0,0 +1,44 @@
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::be::JavaBeUtil;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+
+#[test]
+fn bootstrap_methods() {
+    let bytes =
+        std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/Bootstraps.class")
+            .unwrap();
+    let classfile = ClassFileParser::new(&bytes).parse().unwrap();
+    let tag = |index: u16| classfile.constant_pool[index as usize - 1].tag;
+
+    let bootstrap_methods = classfile
+        .attributes
+        .iter()
+        .find_map(|attribute| match &attribute.info {
+            AttributeInfoType::BootstrapMethodsAttribute {
+                bootstrap_methods, ..
+            } => Some(bootstrap_methods),
+            _ => None,
+        })
+        .unwrap();
+
+    // LambdaMetafactory.metafactory, then StringConcatFactory.makeConcatWithConstants
+    let arguments = bootstrap_methods
+        .iter()
+        .map(|bootstrap_method| {
+            assert_eq!(tag(bootstrap_method.bootstrap_method_ref), 15);
+            assert_eq!(
+                bootstrap_method.num_bootstrap_arguments as usize,
+                bootstrap_method.bootstrap_arguments.len()
+            );
+
+            bootstrap_method
+                .bootstrap_arguments
+                .iter()
+                .map(|index| tag(index.to_u16()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // two method types around the method handle of the lambda body, and the concat recipe
+    assert_eq!(arguments, [vec![16, 15, 16], vec![8]]);
+}
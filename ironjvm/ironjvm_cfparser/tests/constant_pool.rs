@@ -0,0 +1,49 @@
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+
+// a class named A whose constant pool starts with a long and a double, each taking two indices
+const WIDE_CONSTANTS: &[u8] = &[
+    0xCA, 0xFE, 0xBA, 0xBE, // magic
+    0x00, 0x00, 0x00, 0x34, // minor_version, major_version
+    0x00, 0x09, // constant_pool_count
+    0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, // #1: Long 1
+    0x06, 0x3F, 0xF0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // #3: Double 1.0
+    0x01, 0x00, 0x01, b'A', // #5: Utf8 "A"
+    0x07, 0x00, 0x05, // #6: Class #5
+    0x01, 0x00, 0x10, b'j', b'a', b'v', b'a', b'/', b'l', b'a', b'n', b'g', b'/', b'O', b'b', b'j',
+    b'e', b'c', b't', // #7: Utf8 "java/lang/Object"
+    0x07, 0x00, 0x07, // #8: Class #7
+    0x00, 0x21, // access_flags
+    0x00, 0x06, // this_class
+    0x00, 0x08, // super_class
+    0x00, 0x00, // interfaces_count
+    0x00, 0x00, // fields_count
+    0x00, 0x00, // methods_count
+    0x00, 0x00, // attributes_count
+];
+
+#[test]
+fn wide_constants_take_two_indices() {
+    let classfile = ClassFileParser::new(WIDE_CONSTANTS).parse().unwrap();
+    let pool = &classfile.constant_pool;
+
+    assert_eq!(pool.len(), 8);
+    assert!(matches!(
+        pool[0].info,
+        CpInfoType::ConstantLong { low_bytes: 1, .. }
+    ));
+    assert!(matches!(pool[1].info, CpInfoType::ConstantUnusable));
+    assert!(matches!(pool[2].info, CpInfoType::ConstantDouble { .. }));
+    assert!(matches!(pool[3].info, CpInfoType::ConstantUnusable));
+
+    // constant pool indices start at 1, so index n is at position n - 1
+    let this_class = &pool[classfile.this_class as usize - 1].info;
+    assert!(matches!(
+        this_class,
+        CpInfoType::ConstantClass { name_index: 5 }
+    ));
+    assert!(matches!(
+        pool[4].info,
+        CpInfoType::ConstantUtf8 { string, .. } if string.as_bytes() == b"A"
+    ));
+}
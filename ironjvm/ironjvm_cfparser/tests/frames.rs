@@ -0,0 +1,337 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use ironjvm_cfparser::code::CodeParser;
+use ironjvm_cfparser::error::ParseResult;
+use ironjvm_cfparser::frames;
+use ironjvm_cfparser::frames::ComputedFrames;
+use ironjvm_cfparser::frames::FrameMethod;
+use ironjvm_cfparser::frames::SuperclassResolver;
+use ironjvm_cfparser::lazy::LazyAttribute;
+use ironjvm_cfparser::visitor::ClassHeader;
+use ironjvm_cfparser::visitor::ClassVisitor;
+use ironjvm_cfparser::visitor::CodeHeader;
+use ironjvm_cfparser::visitor::Jump;
+use ironjvm_cfparser::visitor::Label;
+use ironjvm_cfparser::visitor::LocalVariable;
+use ironjvm_cfparser::visitor::MemberHeader;
+use ironjvm_cfparser::visitor::TryCatchBlock;
+use ironjvm_cfparser::writer;
+use ironjvm_cfparser::writer::ClassWriter;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_specimpl::classfile::attrinfo::cattr::CodeAttributeExceptionTableEntry;
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::attrinfo::smtattr::StackMapFrame;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::CpInfo;
+
+const TESTS: &str = "com/github/htgazurex1212/ironjvm/tests";
+
+// the classes Frames.java merges, which all other classes are merged to Object with
+struct FramesResolver;
+
+impl SuperclassResolver for FramesResolver {
+    fn common_superclass(&self, a: &JStr, b: &JStr) -> JString {
+        let numbers = [
+            "java/lang/Double",
+            "java/lang/Integer",
+            "java/lang/Long",
+            "java/lang/Number",
+        ];
+        if numbers.iter().any(|name| a == *name) && numbers.iter().any(|name| b == *name) {
+            return JString::from("java/lang/Number");
+        }
+
+        JString::from("java/lang/Object")
+    }
+}
+
+fn utf8<'clazz>(constant_pool: &[CpInfo<'clazz>], index: u16) -> &'clazz JStr {
+    match constant_pool[index as usize - 1].info {
+        CpInfoType::ConstantUtf8 { string, .. } => string,
+        _ => panic!("{index} is not a Utf8 constant"),
+    }
+}
+
+fn utf8_index(constant_pool: &[CpInfo], name: &str) -> u16 {
+    constant_pool
+        .iter()
+        .position(|cp_info| match cp_info.info {
+            CpInfoType::ConstantUtf8 { string, .. } => string == name,
+            _ => false,
+        })
+        .unwrap() as u16
+        + 1
+}
+
+// the frames of each method with code, and the StackMapTable attribute they make
+fn compute(bytes: &[u8]) -> Vec<Option<(ComputedFrames, Vec<u8>)>> {
+    let classfile = ClassFileParser::new(bytes).parse().unwrap();
+    let constant_pool = &classfile.constant_pool;
+    let this_class = match constant_pool[classfile.this_class as usize - 1].info {
+        CpInfoType::ConstantClass { name_index } => utf8(constant_pool, name_index),
+        _ => unreachable!(),
+    };
+
+    let mut methods = Vec::new();
+    for method in &classfile.methods {
+        let code = method
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfoType::CodeAttribute {
+                    code,
+                    exception_table,
+                    ..
+                } => Some((code, exception_table)),
+                _ => None,
+            });
+        let Some((code, exception_table)) = code else {
+            methods.push(None);
+            continue;
+        };
+
+        let name = utf8(constant_pool, method.name_index);
+        let computed = frames::compute_frames(
+            &FrameMethod {
+                constant_pool,
+                this_class,
+                access_flags: method.access_flags,
+                name,
+                descriptor: utf8(constant_pool, method.descriptor_index),
+            },
+            &CodeParser::new(code).parse().unwrap(),
+            exception_table,
+            &FramesResolver,
+            |class| {
+                let position = constant_pool.iter().position(|cp_info| match cp_info.info {
+                    CpInfoType::ConstantClass { name_index } => {
+                        utf8(constant_pool, name_index) == class
+                    }
+                    _ => false,
+                });
+
+                position.expect("the class is in the constant pool") as u16 + 1
+            },
+        )
+        .unwrap_or_else(|error| panic!("{}: {error:?}", name.display()));
+        assert!(computed.unreachable.is_empty());
+
+        let mut info = Vec::new();
+        writer::encode_stack_map_table(&computed.stack_map_table, &mut info);
+        let mut attribute = Vec::new();
+        attribute.extend_from_slice(&utf8_index(constant_pool, "StackMapTable").to_be_bytes());
+        attribute.extend_from_slice(&(info.len() as u32).to_be_bytes());
+        attribute.extend_from_slice(&info);
+
+        methods.push(Some((computed, attribute)));
+    }
+
+    methods
+}
+
+// writes the class again with the computed limits and frames instead of those of javac
+struct SwapFrames<'clazz> {
+    writer: ClassWriter<'clazz>,
+    methods: std::slice::Iter<'clazz, Option<(ComputedFrames, Vec<u8>)>>,
+    current: Option<&'clazz (ComputedFrames, Vec<u8>)>,
+}
+
+impl<'clazz> ClassVisitor<'clazz> for SwapFrames<'clazz> {
+    fn visit_header(&mut self, header: &ClassHeader<'_, 'clazz>) {
+        self.writer.visit_header(header);
+    }
+
+    fn visit_field(&mut self, field: &MemberHeader) {
+        self.writer.visit_field(field);
+    }
+
+    fn visit_field_end(&mut self) {
+        self.writer.visit_field_end();
+    }
+
+    fn visit_method(&mut self, method: &MemberHeader) {
+        self.current = self.methods.next().unwrap().as_ref();
+
+        self.writer.visit_method(method);
+    }
+
+    fn visit_code(&mut self, code: &CodeHeader) {
+        let (computed, _) = self.current.unwrap();
+
+        self.writer.visit_code(&CodeHeader {
+            attribute_name_index: code.attribute_name_index,
+            max_stack: computed.max_stack,
+            max_locals: computed.max_locals,
+        });
+    }
+
+    fn visit_try_catch_block(&mut self, block: &TryCatchBlock) {
+        self.writer.visit_try_catch_block(block);
+    }
+
+    fn visit_label(&mut self, label: Label) {
+        self.writer.visit_label(label);
+    }
+
+    fn visit_line_number(&mut self, line: u16, start: Label) {
+        self.writer.visit_line_number(line, start);
+    }
+
+    fn visit_instruction(&mut self, instruction: &Instruction) {
+        self.writer.visit_instruction(instruction);
+    }
+
+    fn visit_jump(&mut self, jump: Jump, target: Label) {
+        self.writer.visit_jump(jump, target);
+    }
+
+    fn visit_table_switch(&mut self, low: i32, high: i32, default: Label, labels: &[Label]) {
+        self.writer.visit_table_switch(low, high, default, labels);
+    }
+
+    fn visit_lookup_switch(&mut self, default: Label, pairs: &[(i32, Label)]) {
+        self.writer.visit_lookup_switch(default, pairs);
+    }
+
+    fn visit_local_variable(&mut self, variable: &LocalVariable) {
+        self.writer.visit_local_variable(variable);
+    }
+
+    fn visit_local_variable_type(&mut self, variable: &LocalVariable) {
+        self.writer.visit_local_variable_type(variable);
+    }
+
+    fn visit_code_end(&mut self) -> ParseResult<()> {
+        let (computed, attribute) = self.current.unwrap();
+        if !computed.stack_map_table.is_empty() {
+            self.writer
+                .visit_attribute(&LazyAttribute::from_bytes(attribute).unwrap());
+        }
+
+        self.writer.visit_code_end()
+    }
+
+    fn visit_method_end(&mut self) {
+        self.writer.visit_method_end();
+    }
+
+    fn visit_attribute(&mut self, attribute: &LazyAttribute<'clazz>) {
+        self.writer.visit_attribute(attribute);
+    }
+
+    fn visit_end(&mut self) {
+        self.writer.visit_end();
+    }
+}
+
+fn run_frames(classpath: &Path) -> String {
+    let output = Command::new("java")
+        .arg("-Xverify:all")
+        .arg("-cp")
+        .arg(classpath)
+        .arg("com.github.htgazurex1212.ironjvm.tests.Frames")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn verified() {
+    let classpath = std::env::temp_dir().join("ironjvm-frames-test");
+    fs::create_dir_all(classpath.join(TESTS)).unwrap();
+
+    for name in ["Frames", "Frames$Point"] {
+        let bytes = fs::read(format!("../test_classes/{TESTS}/{name}.class")).unwrap();
+        let computed = compute(&bytes);
+
+        let mut visitor = SwapFrames {
+            writer: ClassWriter::new(),
+            methods: computed.iter(),
+            current: None,
+        };
+        ClassFileParser::new(&bytes).accept(&mut visitor).unwrap();
+
+        fs::write(
+            classpath.join(format!("{TESTS}/{name}.class")),
+            visitor.writer.into_bytes(),
+        )
+        .unwrap();
+    }
+
+    assert_eq!(
+        run_frames(&classpath),
+        run_frames(Path::new("../test_classes"))
+    );
+}
+
+#[test]
+fn unreachable() {
+    // code after a return, inside a try block whose handler rethrows
+    let instructions = [
+        (0, Instruction::IConst0),
+        (1, Instruction::IReturn),
+        (2, Instruction::IConst1),
+        (3, Instruction::IReturn),
+        (4, Instruction::AThrow),
+    ];
+    let exception_table = [CodeAttributeExceptionTableEntry {
+        start_pc: 0,
+        end_pc: 4,
+        handler_pc: 4,
+        catch_type: 0,
+    }];
+
+    let computed = frames::compute_frames(
+        &FrameMethod {
+            constant_pool: &[],
+            this_class: JStr::from_jutf8(b"Test").ok().unwrap(),
+            access_flags: 0x0008,
+            name: JStr::from_jutf8(b"m").ok().unwrap(),
+            descriptor: JStr::from_jutf8(b"()I").ok().unwrap(),
+        },
+        &instructions,
+        &exception_table,
+        &FramesResolver,
+        |name| {
+            assert!(name == "java/lang/Throwable");
+            1
+        },
+    )
+    .unwrap();
+
+    assert_eq!(computed.unreachable, [(2, 4)]);
+    assert_eq!(computed.max_stack, 1);
+    // with no locals to begin with, only the Throwable on the stack is new
+    assert!(matches!(
+        computed.stack_map_table.as_slice(),
+        [
+            StackMapFrame::SameLocals1StackItemFrame { frame_type: 66, .. },
+            StackMapFrame::SameLocals1StackItemFrame { frame_type: 65, .. },
+        ]
+    ));
+
+    let mut code = [0x03, 0xac, 0x04, 0xac, 0xbf];
+    let exception_table =
+        frames::replace_unreachable(&mut code, &exception_table, &computed.unreachable);
+    assert_eq!(code, [0x03, 0xac, 0x00, 0xbf, 0xbf]);
+    assert!(matches!(
+        exception_table.as_slice(),
+        [CodeAttributeExceptionTableEntry {
+            start_pc: 0,
+            end_pc: 2,
+            handler_pc: 4,
+            ..
+        }]
+    ));
+}
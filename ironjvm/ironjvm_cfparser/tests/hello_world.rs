@@ -13,7 +13,7 @@ fn hello_world() {
     }
 
     let classfile = result.unwrap();
-    let expect = expect_test::expect![["
+    let expect = expect_test::expect![[r#"
 ClassFile {
     magic: 3405691582,
     minor_version: 0,
@@ -44,49 +44,21 @@ ClassFile {
             tag: 1,
             info: ConstantUtf8 {
                 length: 16,
-                bytes: [
-                    106,
-                    97,
-                    118,
-                    97,
-                    47,
-                    108,
-                    97,
-                    110,
-                    103,
-                    47,
-                    79,
-                    98,
-                    106,
-                    101,
-                    99,
-                    116,
-                ],
+                string: "java/lang/Object",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 6,
-                bytes: [
-                    60,
-                    105,
-                    110,
-                    105,
-                    116,
-                    62,
-                ],
+                string: "<init>",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 3,
-                bytes: [
-                    40,
-                    41,
-                    86,
-                ],
+                string: "()V",
             },
         },
         CpInfo {
@@ -113,64 +85,21 @@ ClassFile {
             tag: 1,
             info: ConstantUtf8 {
                 length: 16,
-                bytes: [
-                    106,
-                    97,
-                    118,
-                    97,
-                    47,
-                    108,
-                    97,
-                    110,
-                    103,
-                    47,
-                    83,
-                    121,
-                    115,
-                    116,
-                    101,
-                    109,
-                ],
+                string: "java/lang/System",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 3,
-                bytes: [
-                    111,
-                    117,
-                    116,
-                ],
+                string: "out",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 21,
-                bytes: [
-                    76,
-                    106,
-                    97,
-                    118,
-                    97,
-                    47,
-                    105,
-                    111,
-                    47,
-                    80,
-                    114,
-                    105,
-                    110,
-                    116,
-                    83,
-                    116,
-                    114,
-                    101,
-                    97,
-                    109,
-                    59,
-                ],
+                string: "Ljava/io/PrintStream;",
             },
         },
         CpInfo {
@@ -183,32 +112,7 @@ ClassFile {
             tag: 1,
             info: ConstantUtf8 {
                 length: 24,
-                bytes: [
-                    72,
-                    101,
-                    108,
-                    108,
-                    111,
-                    44,
-                    32,
-                    119,
-                    111,
-                    114,
-                    108,
-                    100,
-                    32,
-                    105,
-                    110,
-                    32,
-                    73,
-                    114,
-                    111,
-                    110,
-                    74,
-                    86,
-                    77,
-                    33,
-                ],
+                string: "Hello, world in IronJVM!",
             },
         },
         CpInfo {
@@ -235,71 +139,21 @@ ClassFile {
             tag: 1,
             info: ConstantUtf8 {
                 length: 19,
-                bytes: [
-                    106,
-                    97,
-                    118,
-                    97,
-                    47,
-                    105,
-                    111,
-                    47,
-                    80,
-                    114,
-                    105,
-                    110,
-                    116,
-                    83,
-                    116,
-                    114,
-                    101,
-                    97,
-                    109,
-                ],
+                string: "java/io/PrintStream",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 7,
-                bytes: [
-                    112,
-                    114,
-                    105,
-                    110,
-                    116,
-                    108,
-                    110,
-                ],
+                string: "println",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 21,
-                bytes: [
-                    40,
-                    76,
-                    106,
-                    97,
-                    118,
-                    97,
-                    47,
-                    108,
-                    97,
-                    110,
-                    103,
-                    47,
-                    83,
-                    116,
-                    114,
-                    105,
-                    110,
-                    103,
-                    59,
-                    41,
-                    86,
-                ],
+                string: "(Ljava/lang/String;)V",
             },
         },
         CpInfo {
@@ -312,175 +166,49 @@ ClassFile {
             tag: 1,
             info: ConstantUtf8 {
                 length: 49,
-                bytes: [
-                    99,
-                    111,
-                    109,
-                    47,
-                    103,
-                    105,
-                    116,
-                    104,
-                    117,
-                    98,
-                    47,
-                    104,
-                    116,
-                    103,
-                    97,
-                    122,
-                    117,
-                    114,
-                    101,
-                    120,
-                    49,
-                    50,
-                    49,
-                    50,
-                    47,
-                    105,
-                    114,
-                    111,
-                    110,
-                    106,
-                    118,
-                    109,
-                    47,
-                    116,
-                    101,
-                    115,
-                    116,
-                    115,
-                    47,
-                    72,
-                    101,
-                    108,
-                    108,
-                    111,
-                    87,
-                    111,
-                    114,
-                    108,
-                    100,
-                ],
+                string: "com/github/htgazurex1212/ironjvm/tests/HelloWorld",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 4,
-                bytes: [
-                    67,
-                    111,
-                    100,
-                    101,
-                ],
+                string: "Code",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 15,
-                bytes: [
-                    76,
-                    105,
-                    110,
-                    101,
-                    78,
-                    117,
-                    109,
-                    98,
-                    101,
-                    114,
-                    84,
-                    97,
-                    98,
-                    108,
-                    101,
-                ],
+                string: "LineNumberTable",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 4,
-                bytes: [
-                    109,
-                    97,
-                    105,
-                    110,
-                ],
+                string: "main",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 22,
-                bytes: [
-                    40,
-                    91,
-                    76,
-                    106,
-                    97,
-                    118,
-                    97,
-                    47,
-                    108,
-                    97,
-                    110,
-                    103,
-                    47,
-                    83,
-                    116,
-                    114,
-                    105,
-                    110,
-                    103,
-                    59,
-                    41,
-                    86,
-                ],
+                string: "([Ljava/lang/String;)V",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 10,
-                bytes: [
-                    83,
-                    111,
-                    117,
-                    114,
-                    99,
-                    101,
-                    70,
-                    105,
-                    108,
-                    101,
-                ],
+                string: "SourceFile",
             },
         },
         CpInfo {
             tag: 1,
             info: ConstantUtf8 {
                 length: 15,
-                bytes: [
-                    72,
-                    101,
-                    108,
-                    108,
-                    111,
-                    87,
-                    111,
-                    114,
-                    108,
-                    100,
-                    46,
-                    106,
-                    97,
-                    118,
-                    97,
-                ],
+                string: "HelloWorld.java",
             },
         },
     ],
@@ -597,6 +325,6 @@ ClassFile {
         },
     ],
 }
-"]];
+"#]];
     expect.assert_debug_eq(&classfile);
 }
@@ -0,0 +1,88 @@
+use ironjvm_cfparser::error::ParseError;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+
+fn hello_world() -> Vec<u8> {
+    std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/HelloWorld.class")
+        .unwrap()
+}
+
+#[test]
+fn truncated() {
+    let bytes = hello_world();
+
+    for len in 0..bytes.len() {
+        match ClassFileParser::new(&bytes[..len]).parse_lazy() {
+            Err(ParseError::UnexpectedEndOfClassFile) => (),
+            Err(error) => panic!("cut at {len} bytes: {error:?}"),
+            Ok(_) => panic!("cut at {len} bytes: parsed"),
+        }
+    }
+
+    assert!(ClassFileParser::new(&bytes).parse_lazy().is_ok());
+}
+
+#[test]
+fn unknown_attribute() {
+    // renames the SourceFile attribute, which keeps its two bytes of contents
+    let mut bytes = hello_world();
+    let at = bytes
+        .windows(10)
+        .position(|window| window == b"SourceFile")
+        .unwrap();
+    bytes[at..at + 10].copy_from_slice(b"SourceFilf");
+
+    let lazy = ClassFileParser::new(&bytes).parse_lazy().unwrap();
+    let attribute = lazy.attribute("SourceFilf").unwrap().unwrap();
+    assert!(matches!(
+        attribute.info,
+        AttributeInfoType::UnknownAttribute { info: [0, 28] }
+    ));
+    assert!(lazy.attribute("SourceFile").unwrap().is_none());
+
+    let eager = ClassFileParser::new(&bytes).parse().unwrap();
+    assert_eq!(
+        format!("{:?}", eager.attributes[0]),
+        format!("{attribute:?}")
+    );
+}
+
+#[test]
+fn corrupt_attributes() {
+    let bytes =
+        std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/Annotated.class")
+            .unwrap();
+
+    // finds the class annotations, which start with one annotation whose first value is a string
+    let lazy = ClassFileParser::new(&bytes).parse_lazy().unwrap();
+    let annotations = lazy
+        .attributes
+        .iter()
+        .find(|attribute| {
+            attribute.name(&lazy.constant_pool).unwrap().as_bytes() == b"RuntimeVisibleAnnotations"
+        })
+        .unwrap();
+    let at = annotations.info().as_ptr() as usize - bytes.as_ptr() as usize;
+    assert_eq!((&bytes[at..at + 2], bytes[at + 8]), (&[0, 1][..], b's'));
+
+    // a second annotation would run past the end of the attribute
+    let mut truncated = bytes.clone();
+    truncated[at + 1] = 2;
+    let lazy = ClassFileParser::new(&truncated).parse_lazy().unwrap();
+    assert!(matches!(
+        lazy.attribute("RuntimeVisibleAnnotations"),
+        Err(ParseError::UnexpectedEndOfClassFile)
+    ));
+
+    let mut corrupt = bytes.clone();
+    corrupt[at + 8] = b'x';
+    let lazy = ClassFileParser::new(&corrupt).parse_lazy().unwrap();
+    assert!(matches!(
+        lazy.attribute("RuntimeVisibleAnnotations"),
+        Err(ParseError::InvalidElementValueTag { tag: b'x' })
+    ));
+    assert!(matches!(
+        ClassFileParser::new(&corrupt).parse(),
+        Err(ParseError::InvalidElementValueTag { tag: b'x' })
+    ));
+}
@@ -0,0 +1,54 @@
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+
+#[test]
+fn parameter_annotations() {
+    let bytes =
+        std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/Annotated.class")
+            .unwrap();
+    let classfile = ClassFileParser::new(&bytes).parse().unwrap();
+    let name = |index: u16| match classfile.constant_pool[index as usize - 1].info {
+        CpInfoType::ConstantUtf8 { string, .. } => string.as_bytes(),
+        _ => unreachable!(),
+    };
+
+    let sum = classfile
+        .methods
+        .iter()
+        .find(|method| name(method.name_index) == b"sum")
+        .unwrap();
+
+    // num_parameters is a u1, so reading a u2 would take the count of annotations on the first
+    // parameter as part of it
+    let annotations = sum
+        .attributes
+        .iter()
+        .filter_map(|attribute| match &attribute.info {
+            AttributeInfoType::RuntimeVisibleParameterAnnotationsAttribute {
+                num_parameters,
+                parameter_annotations,
+            }
+            | AttributeInfoType::RuntimeInvisibleParameterAnnotationsAttribute {
+                num_parameters,
+                parameter_annotations,
+            } => {
+                assert_eq!(*num_parameters as usize, parameter_annotations.len());
+                Some(
+                    parameter_annotations
+                        .iter()
+                        .map(|parameter| parameter.annotations.len())
+                        .collect::<Vec<_>>(),
+                )
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    // @Visible on the first parameter and @Invisible on the second
+    assert_eq!(annotations, [vec![1, 0, 0], vec![0, 1, 0]]);
+    assert!(sum
+        .attributes
+        .iter()
+        .any(|attribute| matches!(attribute.info, AttributeInfoType::CodeAttribute { .. })));
+}
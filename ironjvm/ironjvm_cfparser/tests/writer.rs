@@ -0,0 +1,281 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use ironjvm_cfparser::code::CodeParser;
+use ironjvm_cfparser::error::ParseError;
+use ironjvm_cfparser::error::ParseResult;
+use ironjvm_cfparser::lazy::LazyAttribute;
+use ironjvm_cfparser::visitor::ClassHeader;
+use ironjvm_cfparser::visitor::ClassVisitor;
+use ironjvm_cfparser::visitor::CodeHeader;
+use ironjvm_cfparser::visitor::Frame;
+use ironjvm_cfparser::visitor::Jump;
+use ironjvm_cfparser::visitor::Label;
+use ironjvm_cfparser::visitor::LocalVariable;
+use ironjvm_cfparser::visitor::MemberHeader;
+use ironjvm_cfparser::visitor::TryCatchBlock;
+use ironjvm_cfparser::writer::ClassWriter;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+
+const TESTS: &str = "../test_classes/com/github/htgazurex1212/ironjvm/tests";
+
+// puts a nop before every jump and switch, which moves everything after it
+struct InsertNops<'clazz> {
+    writer: ClassWriter<'clazz>,
+    inserted: usize,
+}
+
+impl InsertNops<'_> {
+    fn nop(&mut self) {
+        self.writer.visit_instruction(&Instruction::Nop);
+        self.inserted += 1;
+    }
+}
+
+impl<'clazz> ClassVisitor<'clazz> for InsertNops<'clazz> {
+    fn visit_header(&mut self, header: &ClassHeader<'_, 'clazz>) {
+        self.writer.visit_header(header);
+    }
+
+    fn visit_field(&mut self, field: &MemberHeader) {
+        self.writer.visit_field(field);
+    }
+
+    fn visit_field_end(&mut self) {
+        self.writer.visit_field_end();
+    }
+
+    fn visit_method(&mut self, method: &MemberHeader) {
+        self.writer.visit_method(method);
+    }
+
+    fn visit_code(&mut self, code: &CodeHeader) {
+        self.writer.visit_code(code);
+    }
+
+    fn visit_try_catch_block(&mut self, block: &TryCatchBlock) {
+        self.writer.visit_try_catch_block(block);
+    }
+
+    fn visit_label(&mut self, label: Label) {
+        self.writer.visit_label(label);
+    }
+
+    fn visit_frame(&mut self, frame: &Frame) {
+        self.writer.visit_frame(frame);
+    }
+
+    fn visit_line_number(&mut self, line: u16, start: Label) {
+        self.writer.visit_line_number(line, start);
+    }
+
+    fn visit_instruction(&mut self, instruction: &Instruction) {
+        self.writer.visit_instruction(instruction);
+    }
+
+    fn visit_jump(&mut self, jump: Jump, target: Label) {
+        self.nop();
+        self.writer.visit_jump(jump, target);
+    }
+
+    fn visit_table_switch(&mut self, low: i32, high: i32, default: Label, labels: &[Label]) {
+        self.nop();
+        self.writer.visit_table_switch(low, high, default, labels);
+    }
+
+    fn visit_lookup_switch(&mut self, default: Label, pairs: &[(i32, Label)]) {
+        self.nop();
+        self.writer.visit_lookup_switch(default, pairs);
+    }
+
+    fn visit_local_variable(&mut self, variable: &LocalVariable) {
+        self.writer.visit_local_variable(variable);
+    }
+
+    fn visit_local_variable_type(&mut self, variable: &LocalVariable) {
+        self.writer.visit_local_variable_type(variable);
+    }
+
+    fn visit_code_end(&mut self) -> ParseResult<()> {
+        self.writer.visit_code_end()
+    }
+
+    fn visit_method_end(&mut self) {
+        self.writer.visit_method_end();
+    }
+
+    fn visit_attribute(&mut self, attribute: &LazyAttribute<'clazz>) {
+        self.writer.visit_attribute(attribute);
+    }
+
+    fn visit_end(&mut self) {
+        self.writer.visit_end();
+    }
+}
+
+fn run_branches(classpath: &Path) -> String {
+    let output = Command::new("java")
+        .arg("-Xverify:all")
+        .arg("-cp")
+        .arg(classpath)
+        .arg("com.github.htgazurex1212.ironjvm.tests.Branches")
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn round_trip() {
+    for name in ["HelloWorld", "Benchmark", "Branches"] {
+        let bytes = fs::read(format!("{TESTS}/{name}.class")).unwrap();
+
+        let mut writer = ClassWriter::new();
+        ClassFileParser::new(&bytes).accept(&mut writer).unwrap();
+        assert!(writer.into_bytes() == bytes, "{name} changed");
+    }
+}
+
+#[test]
+fn insert_before_jumps() {
+    let bytes = fs::read(format!("{TESTS}/Branches.class")).unwrap();
+
+    let mut visitor = InsertNops {
+        writer: ClassWriter::new(),
+        inserted: 0,
+    };
+    ClassFileParser::new(&bytes).accept(&mut visitor).unwrap();
+    assert!(visitor.inserted > 0);
+
+    let output = visitor.writer.into_bytes();
+
+    let classpath = std::env::temp_dir().join("ironjvm-writer-test");
+    let package = classpath.join("com/github/htgazurex1212/ironjvm/tests");
+    fs::create_dir_all(&package).unwrap();
+    fs::write(package.join("Branches.class"), output).unwrap();
+
+    assert_eq!(
+        run_branches(&classpath),
+        run_branches(Path::new("../test_classes"))
+    );
+}
+
+// writes a method with the code `visit` visits, along with what visit_code_end returned
+fn write_method(
+    visit: impl FnOnce(&mut ClassWriter<'static>),
+) -> (ClassWriter<'static>, ParseResult<()>) {
+    let mut writer = ClassWriter::new();
+    let attribute_name_index = writer.add_constant(CpInfoType::ConstantUtf8 {
+        length: 4,
+        string: JStr::from_jutf8(b"Code").ok().unwrap(),
+    });
+
+    writer.visit_method(&MemberHeader {
+        access_flags: 0,
+        name_index: 0,
+        descriptor_index: 0,
+    });
+    writer.visit_code(&CodeHeader {
+        attribute_name_index,
+        max_stack: 1,
+        max_locals: 1,
+    });
+    visit(&mut writer);
+    let result = writer.visit_code_end();
+    writer.visit_method_end();
+
+    (writer, result)
+}
+
+fn instructions(writer: ClassWriter) -> Vec<(u16, Instruction)> {
+    let bytes = Box::leak(writer.into_bytes().into_boxed_slice());
+    let classfile = ClassFileParser::new(bytes).parse().unwrap();
+    let AttributeInfoType::CodeAttribute { code, .. } = classfile.methods[0].attributes[0].info
+    else {
+        panic!("no code");
+    };
+
+    CodeParser::new(code).parse().unwrap()
+}
+
+// iload_0, then ifeq over 40000 nops to a return, with a frame at the return if `frames`
+fn far_jump(writer: &mut ClassWriter, frames: bool) {
+    let far = Label::new();
+
+    writer.visit_instruction(&Instruction::ILoad0);
+    writer.visit_jump(Jump::IfEq, far);
+    for _ in 0..40000 {
+        writer.visit_instruction(&Instruction::Nop);
+    }
+    writer.visit_label(far);
+    if frames {
+        writer.visit_frame(&Frame::Same);
+    }
+    writer.visit_instruction(&Instruction::Return);
+}
+
+#[test]
+fn far_conditional_jumps() {
+    // ifne skips the goto_w, which is 3 bytes further on and jumps the rest of the way
+    let (writer, result) = write_method(|writer| far_jump(writer, false));
+    assert!(result.is_ok());
+    let instructions = instructions(writer);
+    assert_eq!(
+        instructions[..4],
+        [
+            (0, Instruction::ILoad0),
+            (1, Instruction::IfNe { offset: 8 }),
+            (4, Instruction::GotoW { offset: 40005 }),
+            (9, Instruction::Nop),
+        ]
+    );
+    assert_eq!(instructions.last(), Some(&(40009, Instruction::Return)));
+
+    // the code after the goto_w is only reached by jumping to it, so it needs a frame
+    let (_, result) = write_method(|writer| far_jump(writer, true));
+    assert!(matches!(result, Err(ParseError::MissingFrame { pc: 9 })));
+
+    let (_, result) = write_method(|writer| {
+        let far = Label::new();
+        writer.visit_instruction(&Instruction::ILoad0);
+        writer.visit_jump(Jump::IfEq, far);
+        writer.visit_frame(&Frame::Same);
+        for _ in 0..40000 {
+            writer.visit_instruction(&Instruction::Nop);
+        }
+        writer.visit_label(far);
+        writer.visit_frame(&Frame::Same);
+        writer.visit_instruction(&Instruction::Return);
+    });
+    assert!(result.is_ok());
+}
+
+#[test]
+fn code_errors() {
+    let missing = Label::new();
+    let (_, result) = write_method(|writer| {
+        writer.visit_jump(Jump::Goto, missing);
+        writer.visit_instruction(&Instruction::Return);
+    });
+    assert!(matches!(
+        result,
+        Err(ParseError::UnvisitedLabel { label }) if label == missing
+    ));
+
+    let (_, result) = write_method(|writer| {
+        writer.visit_frame(&Frame::Same);
+        writer.visit_frame(&Frame::Same);
+        writer.visit_instruction(&Instruction::Return);
+    });
+    assert!(matches!(result, Err(ParseError::DuplicateFrame { pc: 0 })));
+}
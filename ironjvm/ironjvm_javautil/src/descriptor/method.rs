@@ -20,18 +20,42 @@
 
 // Credit: code referenced from https://gitlab.com/frozo/noak/
 
+use std::fmt;
+use std::slice;
+
 use crate::descriptor::error::InvalidDescriptorError;
 use crate::descriptor::BaseType;
 use crate::descriptor::TypeDescriptor;
 use crate::jstr::CharsLossy;
 use crate::jstr::JStr;
+use crate::jstr::JString;
 
+#[derive(Clone, Debug)]
 pub struct MethodDescriptor<'a> {
-    input: &'a JStr,
-    return_index: usize,
+    parameters: Parameters<'a>,
+    return_type: ReturnDescriptor<'a>,
+}
+
+// descriptors read from class files decode their parameters as they are iterated, so that
+// parsing one does not allocate
+#[derive(Clone, Debug)]
+enum Parameters<'a> {
+    // the validated descriptor from its first parameter on
+    Encoded(&'a JStr),
+    Decoded(Vec<ParameterDescriptor<'a>>),
 }
 
 impl<'a> MethodDescriptor<'a> {
+    pub fn new(
+        parameters: Vec<ParameterDescriptor<'a>>,
+        return_type: ReturnDescriptor<'a>,
+    ) -> Self {
+        Self {
+            parameters: Parameters::Decoded(parameters),
+            return_type,
+        }
+    }
+
     pub fn from_jstr(input: &'a JStr) -> Result<Self, InvalidDescriptorError> {
         let mut chars = input.chars_lossy();
         if let Some('(') = chars.next() {
@@ -48,9 +72,20 @@ impl<'a> MethodDescriptor<'a> {
             validate_descriptor(chars.next(), &mut chars, true)?;
 
             if chars.next().is_none() {
+                let return_type = match &input[return_index..] {
+                    void if void.as_bytes() == b"V" => ReturnDescriptor::VoidDescriptor,
+                    r#type => {
+                        let mut chars = r#type.chars_lossy();
+                        ReturnDescriptor::FieldType(read_descriptor(
+                            chars.next().unwrap(),
+                            &mut chars,
+                        ))
+                    }
+                };
+
                 return Ok(Self {
-                    input,
-                    return_index,
+                    parameters: Parameters::Encoded(&input[1..]),
+                    return_type,
                 });
             }
         }
@@ -58,49 +93,158 @@ impl<'a> MethodDescriptor<'a> {
         Err(InvalidDescriptorError)
     }
 
-    pub fn parameters_iter(&self) -> impl Iterator<Item = ParameterDescriptor<'a>> + 'a {
-        ParametersDescriptorsIter {
-            chars: self.input.chars_lossy(),
+    pub fn parameters_iter(&self) -> ParametersIter<'a, '_> {
+        match &self.parameters {
+            Parameters::Encoded(input) => ParametersIter {
+                chars: input.chars_lossy(),
+                decoded: [].iter(),
+            },
+            Parameters::Decoded(parameters) => ParametersIter {
+                chars: <&JStr>::default().chars_lossy(),
+                decoded: parameters.iter(),
+            },
+        }
+    }
+
+    pub fn parameter_count(&self) -> usize {
+        match &self.parameters {
+            Parameters::Encoded(_) => self.parameters_iter().count(),
+            Parameters::Decoded(parameters) => parameters.len(),
         }
     }
 
     pub fn return_type(&self) -> ReturnDescriptor<'a> {
-        let input = &self.input[self.return_index..];
-        if input.as_bytes() == b"V" {
-            ReturnDescriptor::VoidDescriptor
-        } else {
-            let mut chars = input.chars_lossy();
-            ReturnDescriptor::FieldType(read_descriptor(chars.next().unwrap(), &mut chars))
+        self.return_type.clone()
+    }
+
+    /// The number of local variable slots taken by the parameters, not counting the receiver of
+    /// an instance method.
+    pub fn parameter_slots(&self) -> usize {
+        self.parameters_iter()
+            .map(|parameter| parameter.slot_size())
+            .sum()
+    }
+
+    pub fn to_jstring(&self) -> JString {
+        let mut output = JString::new();
+
+        output.push('(');
+        for parameter in self.parameters_iter() {
+            parameter.encode(&mut output);
+        }
+        output.push(')');
+
+        match &self.return_type {
+            ReturnDescriptor::FieldType(r#type) => r#type.encode(&mut output),
+            ReturnDescriptor::VoidDescriptor => output.push('V'),
+        }
+
+        output
+    }
+
+    /// Renders a method with this descriptor as it is declared in Java source, such as
+    /// `void foo(int, long)`.
+    pub fn display_with_name<'b>(&'b self, name: &'b str) -> MethodDisplay<'a, 'b> {
+        MethodDisplay {
+            descriptor: self,
+            name,
         }
     }
 }
 
-#[derive(Eq, PartialEq)]
+impl PartialEq for MethodDescriptor<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.parameters_iter().eq(other.parameters_iter()) && self.return_type == other.return_type
+    }
+}
+
+impl Eq for MethodDescriptor<'_> {}
+
+/// Renders the descriptor like `MethodType.toString`, such as `(int,long)void`.
+impl fmt::Display for MethodDescriptor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        for (index, parameter) in self.parameters_iter().enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+
+            write!(f, "{parameter}")?;
+        }
+
+        write!(f, "){}", self.return_type)
+    }
+}
+
+pub struct MethodDisplay<'a, 'b> {
+    descriptor: &'b MethodDescriptor<'a>,
+    name: &'b str,
+}
+
+impl fmt::Display for MethodDisplay<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}(", self.descriptor.return_type, self.name)?;
+        for (index, parameter) in self.descriptor.parameters_iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+
+            write!(f, "{parameter}")?;
+        }
+
+        f.write_str(")")
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ReturnDescriptor<'a> {
     FieldType(TypeDescriptor<'a>),
     VoidDescriptor,
 }
 
-pub struct ParametersDescriptorsIter<'a> {
+impl ReturnDescriptor<'_> {
+    pub fn slot_size(&self) -> usize {
+        match self {
+            Self::FieldType(r#type) => r#type.slot_size(),
+            Self::VoidDescriptor => 0,
+        }
+    }
+}
+
+impl fmt::Display for ReturnDescriptor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FieldType(r#type) => write!(f, "{}", r#type),
+            Self::VoidDescriptor => f.write_str("void"),
+        }
+    }
+}
+
+pub type ParameterDescriptor<'a> = TypeDescriptor<'a>;
+
+pub struct ParametersIter<'a, 'b> {
     chars: CharsLossy<'a>,
+    decoded: slice::Iter<'b, ParameterDescriptor<'a>>,
 }
 
-impl<'a> Iterator for ParametersDescriptorsIter<'a> {
+impl<'a> Iterator for ParametersIter<'a, '_> {
     type Item = ParameterDescriptor<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let char = self.chars.next();
-        if char == Some(')') || char == None {
-            self.chars = <&JStr>::default().chars_lossy();
-            None
-        } else {
-            Some(read_descriptor(char.unwrap(), &mut self.chars))
+        if let Some(parameter) = self.decoded.next() {
+            return Some(parameter.clone());
+        }
+
+        match self.chars.next() {
+            Some(')') | None => {
+                self.chars = <&JStr>::default().chars_lossy();
+                None
+            }
+            Some(char) => Some(read_descriptor(char, &mut self.chars)),
         }
     }
 }
 
-pub type ParameterDescriptor<'a> = TypeDescriptor<'a>;
-
 fn read_descriptor<'a>(mut char: char, iter: &mut CharsLossy<'a>) -> TypeDescriptor<'a> {
     let mut dimensions = 0;
     while char == '[' {
@@ -144,7 +288,7 @@ fn validate_descriptor(
     }
 
     let mut dimensions = 0u8;
-    while let Some('[') = iter.next() {
+    while let Some('[') = char {
         dimensions = dimensions.checked_add(1).ok_or(InvalidDescriptorError)?;
         char = iter.next();
     }
@@ -20,14 +20,18 @@
 
 // Credit: code referenced from https://gitlab.com/frozo/noak/
 
+use std::fmt;
+use std::fmt::Write;
+
 use crate::descriptor::error::InvalidDescriptorError;
 use crate::jstr::JStr;
+use crate::jstr::JString;
 
 pub mod error;
 pub mod field;
 pub mod method;
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub enum BaseType<'a> {
     Boolean,
     Byte,
@@ -40,13 +44,56 @@ pub enum BaseType<'a> {
     Object(&'a JStr),
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct TypeDescriptor<'a> {
     pub dimensions: u8,
     pub r#type: BaseType<'a>,
 }
 
+impl<'a> BaseType<'a> {
+    fn encode(&self, output: &mut JString) {
+        match self {
+            Self::Boolean => output.push('Z'),
+            Self::Byte => output.push('B'),
+            Self::Char => output.push('C'),
+            Self::Double => output.push('D'),
+            Self::Float => output.push('F'),
+            Self::Int => output.push('I'),
+            Self::Long => output.push('J'),
+            Self::Short => output.push('S'),
+            Self::Object(name) => {
+                output.push('L');
+                output.push_jstr(name);
+                output.push(';');
+            }
+        }
+    }
+}
+
+impl fmt::Display for BaseType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Boolean => f.write_str("boolean"),
+            Self::Byte => f.write_str("byte"),
+            Self::Char => f.write_str("char"),
+            Self::Double => f.write_str("double"),
+            Self::Float => f.write_str("float"),
+            Self::Int => f.write_str("int"),
+            Self::Long => f.write_str("long"),
+            Self::Short => f.write_str("short"),
+            // binary names, with nested classes still separated by $
+            Self::Object(name) => name
+                .chars_lossy()
+                .try_for_each(|char| f.write_char(if char == '/' { '.' } else { char })),
+        }
+    }
+}
+
 impl<'a> TypeDescriptor<'a> {
+    pub fn new(dimensions: u8, r#type: BaseType<'a>) -> Self {
+        Self { dimensions, r#type }
+    }
+
     pub fn from_jstr(input: &'a JStr) -> Result<Self, InvalidDescriptorError> {
         let mut chars = input.chars_lossy().enumerate();
         let mut dimensions = 0u8;
@@ -93,4 +140,39 @@ impl<'a> TypeDescriptor<'a> {
 
         Err(InvalidDescriptorError)
     }
+
+    /// The number of local variable or operand stack slots taken by a value of this type.
+    pub fn slot_size(&self) -> usize {
+        match self.r#type {
+            BaseType::Long | BaseType::Double if self.dimensions == 0 => 2,
+            _ => 1,
+        }
+    }
+
+    pub fn to_jstring(&self) -> JString {
+        let mut output = JString::new();
+        self.encode(&mut output);
+
+        output
+    }
+
+    pub(crate) fn encode(&self, output: &mut JString) {
+        for _ in 0..self.dimensions {
+            output.push('[');
+        }
+
+        self.r#type.encode(output);
+    }
+}
+
+/// Renders the type as it is written in Java source, such as `java.lang.String[]`.
+impl fmt::Display for TypeDescriptor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.r#type)?;
+        for _ in 0..self.dimensions {
+            f.write_str("[]")?;
+        }
+
+        Ok(())
+    }
 }
@@ -36,7 +36,29 @@ use crate::jstr::error::JUtf8Error;
 
 pub mod error;
 
-fn validate_jstr(v: &[u8]) -> bool {
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod simd;
+
+/// Checks that `v` is valid modified UTF-8, using vector instructions where the CPU has them.
+pub fn validate_jstr(v: &[u8]) -> bool {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if v.len() >= simd::AVX2_BLOCK_SIZE && is_x86_feature_detected!("avx2") {
+            // SAFETY: the CPU has just been checked to support AVX2
+            return unsafe { simd::validate_jstr_avx2(v) };
+        }
+
+        if v.len() >= simd::SSE2_BLOCK_SIZE && is_x86_feature_detected!("sse2") {
+            // SAFETY: the CPU has just been checked to support SSE2
+            return unsafe { simd::validate_jstr_sse2(v) };
+        }
+    }
+
+    validate_jstr_scalar(v)
+}
+
+/// The portable validator, also used for inputs too short to fill a vector.
+pub fn validate_jstr_scalar(v: &[u8]) -> bool {
     const CONSTANTS_LARGE_ENOUGH: bool = size_of::<u64>() >= size_of::<usize>();
 
     macro_rules! is_block_non_ascii {
@@ -59,42 +81,9 @@ fn validate_jstr(v: &[u8]) -> bool {
         let b1 = v[i];
 
         if b1 >= 0x80 {
-            let width = if b1 & 0b1111_0000 == 0b1110_0000 {
-                3
-            } else if b1 & 0b1110_0000 == 0b1100_0000 {
-                2
-            } else {
-                return false;
-            };
-            if v.len() < i + width {
-                return false;
-            }
-            match width {
-                2 => {
-                    // two byte case: U+0000 and U+0080 to U+07FF
-                    if v[i + 1] & 0b1100_0000 != 0b1000_0000 {
-                        return false;
-                    }
-                    // overlong encodings which do not encode `0` are not allowed
-                    if b1 & 0b0001_1110 == 0 && (b1 != 0b1100_0000 && v[i + 1] != 0b1000_0000) {
-                        return false;
-                    }
-                    i += 2;
-                }
-                3 => {
-                    // three byte case: U+0800 and above
-                    if v[i + 1] & 0b1100_0000 != 0b1000_0000
-                        || v[i + 2] & 0b1100_0000 != 0b1000_0000
-                    {
-                        return false;
-                    }
-                    // overlong encodings are not allowed
-                    if b1 & 0b0000_1111 == 0 && v[i + 1] & 0b0010_0000 == 0 {
-                        return false;
-                    }
-                    i += 3;
-                }
-                _ => return false,
+            match sequence_width(&v[i..]) {
+                Some(width) => i += width,
+                None => return false,
             }
         } else {
             // ASCII case: U+0001 to 0+007F
@@ -136,6 +125,46 @@ fn validate_jstr(v: &[u8]) -> bool {
     true
 }
 
+// the length of the multi-byte sequence at the start of `v`, or `None` if it is not valid
+fn sequence_width(v: &[u8]) -> Option<usize> {
+    let b1 = v[0];
+    let width = if b1 & 0b1111_0000 == 0b1110_0000 {
+        3
+    } else if b1 & 0b1110_0000 == 0b1100_0000 {
+        2
+    } else {
+        return None;
+    };
+    if v.len() < width {
+        return None;
+    }
+
+    match width {
+        2 => {
+            // two byte case: U+0000 and U+0080 to U+07FF
+            if v[1] & 0b1100_0000 != 0b1000_0000 {
+                return None;
+            }
+            // overlong encodings which do not encode `0` are not allowed
+            if b1 & 0b0001_1110 == 0 && !(b1 == 0b1100_0000 && v[1] == 0b1000_0000) {
+                return None;
+            }
+        }
+        _ => {
+            // three byte case: U+0800 and above
+            if v[1] & 0b1100_0000 != 0b1000_0000 || v[2] & 0b1100_0000 != 0b1000_0000 {
+                return None;
+            }
+            // overlong encodings are not allowed
+            if b1 & 0b0000_1111 == 0 && v[1] & 0b0010_0000 == 0 {
+                return None;
+            }
+        }
+    }
+
+    Some(width)
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct JStr {
@@ -182,6 +211,15 @@ impl JStr {
         str::from_utf8(&self.inner).ok()
     }
 
+    /// Converts to a `str`, replacing unpaired surrogates with U+FFFD.
+    #[must_use]
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        match self.to_str() {
+            Some(string) => Cow::Borrowed(string),
+            None => Cow::Owned(self.chars_lossy().collect()),
+        }
+    }
+
     #[inline]
     #[must_use]
     pub fn is_char_boundary(&self, index: usize) -> bool {
@@ -212,6 +250,41 @@ impl JStr {
     pub fn display(&self) -> Display<'_> {
         Display { inner: &self.inner }
     }
+
+    #[inline]
+    #[must_use]
+    pub fn utf16_units(&self) -> Utf16Units<'_> {
+        Utf16Units { inner: &self.inner }
+    }
+
+    /// The number of UTF-16 code units in the string, which is the length of the equivalent
+    /// `java.lang.String`.
+    #[must_use]
+    pub fn utf16_len(&self) -> usize {
+        self.inner
+            .iter()
+            .filter(|byte| **byte & 0b1100_0000 != 0b1000_0000)
+            .count()
+    }
+
+    /// Decodes the string into UTF-16 code units, keeping unpaired surrogates as they are.
+    #[must_use]
+    pub fn to_utf16(&self) -> Vec<u16> {
+        self.utf16_units().collect()
+    }
+
+    /// Whether every character is in Latin-1, so that a compact string can hold them.
+    #[must_use]
+    pub fn is_latin1(&self) -> bool {
+        self.utf16_units().all(|unit| unit <= 0xFF)
+    }
+
+    #[must_use]
+    pub fn to_latin1(&self) -> Option<Vec<u8>> {
+        self.utf16_units()
+            .map(|unit| u8::try_from(unit).ok())
+            .collect()
+    }
 }
 
 impl Default for &'static JStr {
@@ -401,6 +474,32 @@ impl JString {
         let size = encode_jutf8_char(ch, &mut buf);
         self.buf.extend_from_slice(&buf[..size]);
     }
+
+    /// Encodes UTF-16 code units, such as the contents of a `java.lang.String`. Unpaired
+    /// surrogates are encoded like any other code unit.
+    #[must_use]
+    pub fn from_utf16(units: &[u16]) -> JString {
+        let mut buf = Vec::with_capacity(units.len());
+        for unit in units {
+            encode_jutf8_unit(*unit, &mut buf);
+        }
+
+        JString { buf }
+    }
+
+    #[must_use]
+    pub fn from_latin1(bytes: &[u8]) -> JString {
+        let mut buf = Vec::with_capacity(bytes.len());
+        for byte in bytes {
+            encode_jutf8_unit(u16::from(*byte), &mut buf);
+        }
+
+        JString { buf }
+    }
+
+    pub fn push_jstr(&mut self, string: &JStr) {
+        self.buf.extend_from_slice(string.as_bytes());
+    }
 }
 
 impl Deref for JString {
@@ -558,6 +657,45 @@ impl<'a> fmt::Debug for Chars<'a> {
     }
 }
 
+// every one, two or three byte sequence of modified UTF-8 encodes a single UTF-16 code unit,
+// including each half of a surrogate pair
+pub struct Utf16Units<'a> {
+    inner: &'a [u8],
+}
+
+impl<'a> Iterator for Utf16Units<'a> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let b1 = u16::from(*self.inner.first()?);
+
+        let (size, unit) = if b1 & 0b1000_0000 == 0 {
+            (1, b1)
+        } else if b1 & 0b1110_0000 == 0b1100_0000 {
+            let b2 = u16::from(self.inner[1]);
+            (2, (b1 & 0b0001_1111) << 6 | (b2 & 0b0011_1111))
+        } else {
+            let b2 = u16::from(self.inner[1]);
+            let b3 = u16::from(self.inner[2]);
+            (
+                3,
+                (b1 & 0b0000_1111) << 12 | (b2 & 0b0011_1111) << 6 | (b3 & 0b0011_1111),
+            )
+        };
+
+        self.inner = &self.inner[size..];
+        Some(unit)
+    }
+}
+
+impl<'a> fmt::Debug for Utf16Units<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // SAFETY: This is safe because the underlying buffer is guaranteed to be valid.
+        let s = unsafe { JStr::from_jutf8_unchecked(self.inner) };
+        f.debug_struct("Utf16Units").field("remaining", &s).finish()
+    }
+}
+
 pub struct CharsLossy<'a> {
     inner: &'a [u8],
 }
@@ -774,3 +912,18 @@ fn encode_jutf8_char(ch: char, buf: &mut [u8]) -> usize {
         }
     }
 }
+
+fn encode_jutf8_unit(unit: u16, buf: &mut Vec<u8>) {
+    match unit {
+        0x01..=0x7F => buf.push(unit as u8),
+        0 | 0x80..=0x7FF => buf.extend_from_slice(&[
+            (0b1100_0000 | (unit >> 6)) as u8,
+            (0b1000_0000 | (unit & 0b0011_1111)) as u8,
+        ]),
+        _ => buf.extend_from_slice(&[
+            (0b1110_0000 | (unit >> 12)) as u8,
+            (0b1000_0000 | ((unit >> 6) & 0b0011_1111)) as u8,
+            (0b1000_0000 | (unit & 0b0011_1111)) as u8,
+        ]),
+    }
+}
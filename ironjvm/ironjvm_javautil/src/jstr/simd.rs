@@ -0,0 +1,379 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Vectorised modified UTF-8 validation. The AVX2 validator checks multi-byte sequences with the
+// lookup tables described in "Validating UTF-8 In Less Than One Instruction Per Byte" (Keiser and
+// Lemire), adjusted for modified UTF-8: four byte sequences and NUL bytes are rejected, while
+// surrogates and the two byte encoding of U+0000 are allowed.
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+
+use crate::jstr::sequence_width;
+use crate::jstr::validate_jstr_scalar;
+
+pub const SSE2_BLOCK_SIZE: usize = 16;
+pub const AVX2_BLOCK_SIZE: usize = 32;
+
+// a lead byte followed by something other than a continuation byte
+const TOO_SHORT: u8 = 1 << 0;
+// a continuation byte following an ASCII byte
+const TOO_LONG: u8 = 1 << 1;
+// 0xE0 followed by 0x80..=0x9F
+const OVERLONG_3: u8 = 1 << 2;
+// 0xC0 or 0xC1 followed by a continuation byte, other than the 0xC0 0x80 encoding of U+0000
+const OVERLONG_2: u8 = 1 << 5;
+// two continuation bytes in a row, only valid as the last two bytes of a three byte sequence
+const TWO_CONTS: u8 = 1 << 7;
+const CARRY: u8 = TOO_SHORT | TOO_LONG | TWO_CONTS;
+
+// indexed by the high nibble of the first byte of a pair
+#[rustfmt::skip]
+const BYTE_1_HIGH: [u8; 16] = [
+    // 0xxx: ASCII
+    TOO_LONG, TOO_LONG, TOO_LONG, TOO_LONG, TOO_LONG, TOO_LONG, TOO_LONG, TOO_LONG,
+    // 10xx: continuation
+    TWO_CONTS, TWO_CONTS, TWO_CONTS, TWO_CONTS,
+    // 1100: two byte lead, possibly overlong
+    TOO_SHORT | OVERLONG_2,
+    // 1101: two byte lead
+    TOO_SHORT,
+    // 1110: three byte lead
+    TOO_SHORT | OVERLONG_3,
+    // 1111: rejected separately
+    TOO_SHORT,
+];
+
+// indexed by the low nibble of the first byte of a pair
+#[rustfmt::skip]
+const BYTE_1_LOW: [u8; 16] = [
+    CARRY | OVERLONG_3 | OVERLONG_2,
+    CARRY | OVERLONG_2,
+    CARRY, CARRY, CARRY, CARRY, CARRY, CARRY, CARRY, CARRY, CARRY, CARRY, CARRY, CARRY, CARRY,
+    CARRY,
+];
+
+// indexed by the high nibble of the second byte of a pair
+#[rustfmt::skip]
+const BYTE_2_HIGH: [u8; 16] = [
+    // 0xxx: ASCII
+    TOO_SHORT, TOO_SHORT, TOO_SHORT, TOO_SHORT, TOO_SHORT, TOO_SHORT, TOO_SHORT, TOO_SHORT,
+    // 100x: continuation, 0x80..=0x9F
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | OVERLONG_3,
+    TOO_LONG | OVERLONG_2 | TWO_CONTS | OVERLONG_3,
+    // 101x: continuation, 0xA0..=0xBF
+    TOO_LONG | OVERLONG_2 | TWO_CONTS,
+    TOO_LONG | OVERLONG_2 | TWO_CONTS,
+    // 11xx: lead
+    TOO_SHORT, TOO_SHORT, TOO_SHORT, TOO_SHORT,
+];
+
+/// Skips blocks of ASCII, leaving each multi-byte sequence to the scalar validator.
+///
+/// # Safety
+///
+/// The CPU must support SSE2.
+#[target_feature(enable = "sse2")]
+pub unsafe fn validate_jstr_sse2(v: &[u8]) -> bool {
+    let zero = _mm_setzero_si128();
+
+    let mut i = 0;
+    while i + SSE2_BLOCK_SIZE <= v.len() {
+        let block = _mm_loadu_si128(v.as_ptr().add(i).cast());
+        if _mm_movemask_epi8(_mm_cmpeq_epi8(block, zero)) != 0 {
+            return false;
+        }
+
+        let non_ascii = _mm_movemask_epi8(block);
+        if non_ascii == 0 {
+            i += SSE2_BLOCK_SIZE;
+            continue;
+        }
+
+        // the loads are unaligned anyway, so the next block starts right after these sequences
+        i += non_ascii.trailing_zeros() as usize;
+        while i < v.len() && v[i] >= 0x80 {
+            match sequence_width(&v[i..]) {
+                Some(width) => i += width,
+                None => return false,
+            }
+        }
+    }
+
+    validate_jstr_scalar(&v[i..])
+}
+
+/// Validates every byte with vector instructions, apart from a tail shorter than a block.
+///
+/// # Safety
+///
+/// The CPU must support AVX2.
+#[target_feature(enable = "avx2")]
+pub unsafe fn validate_jstr_avx2(v: &[u8]) -> bool {
+    let zero = _mm256_setzero_si256();
+    let mut error = zero;
+    let mut previous = zero;
+
+    let mut i = 0;
+    while i + AVX2_BLOCK_SIZE <= v.len() {
+        let block = _mm256_loadu_si256(v.as_ptr().add(i).cast());
+        let nul = _mm256_cmpeq_epi8(block, zero);
+
+        let block_error = if _mm256_movemask_epi8(block) == 0 {
+            // only a sequence left unfinished by the previous block can make ASCII invalid
+            is_incomplete(previous)
+        } else {
+            check_block(block, previous)
+        };
+
+        error = _mm256_or_si256(error, _mm256_or_si256(nul, block_error));
+        previous = block;
+        i += AVX2_BLOCK_SIZE;
+    }
+
+    if _mm256_testz_si256(error, error) == 0 {
+        return false;
+    }
+
+    // the tail starts at whichever sequence the last block left unfinished
+    let tail = if i == 0 {
+        0
+    } else if v[i - 1] >= 0xC0 {
+        i - 1
+    } else if v[i - 2] >= 0xE0 {
+        i - 2
+    } else {
+        i
+    };
+
+    validate_jstr_scalar(&v[tail..])
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn check_block(block: __m256i, previous: __m256i) -> __m256i {
+    let nibble = _mm256_set1_epi8(0x0F);
+
+    // the block shifted along by one and two bytes, carrying in the end of the previous block
+    let carried = _mm256_permute2x128_si256(previous, block, 0x21);
+    let prev1 = _mm256_alignr_epi8(block, carried, 15);
+    let prev2 = _mm256_alignr_epi8(block, carried, 14);
+
+    let byte_1_high = _mm256_shuffle_epi8(
+        lookup_table(&BYTE_1_HIGH),
+        _mm256_and_si256(_mm256_srli_epi16(prev1, 4), nibble),
+    );
+    let byte_1_low =
+        _mm256_shuffle_epi8(lookup_table(&BYTE_1_LOW), _mm256_and_si256(prev1, nibble));
+    let byte_2_high = _mm256_shuffle_epi8(
+        lookup_table(&BYTE_2_HIGH),
+        _mm256_and_si256(_mm256_srli_epi16(block, 4), nibble),
+    );
+    let special_cases = _mm256_and_si256(_mm256_and_si256(byte_1_high, byte_1_low), byte_2_high);
+
+    let encoded_nul = _mm256_and_si256(
+        _mm256_cmpeq_epi8(prev1, _mm256_set1_epi8(0xC0u8 as i8)),
+        _mm256_cmpeq_epi8(block, _mm256_set1_epi8(0x80u8 as i8)),
+    );
+    let special_cases = _mm256_andnot_si256(encoded_nul, special_cases);
+
+    // the third byte of a three byte sequence must be a continuation following a continuation
+    let must_be_third = _mm256_and_si256(
+        _mm256_subs_epu8(prev2, _mm256_set1_epi8(0x60)),
+        _mm256_set1_epi8(0x80u8 as i8),
+    );
+
+    let four_byte_lead = _mm256_subs_epu8(block, _mm256_set1_epi8(0xEFu8 as i8));
+
+    _mm256_or_si256(
+        _mm256_xor_si256(must_be_third, special_cases),
+        four_byte_lead,
+    )
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn is_incomplete(block: __m256i) -> __m256i {
+    // nonzero where the last byte is any lead, or the second last is a three byte lead
+    #[rustfmt::skip]
+    let max = _mm256_setr_epi8(
+        -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1,
+        -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, 0xDFu8 as i8, 0xBFu8 as i8,
+    );
+
+    _mm256_subs_epu8(block, max)
+}
+
+#[target_feature(enable = "avx2")]
+unsafe fn lookup_table(table: &[u8; 16]) -> __m256i {
+    _mm256_broadcastsi128_si256(_mm_loadu_si128(table.as_ptr().cast()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // pieces that are valid or invalid on their own, put together so that they straddle the
+    // vector block boundaries
+    const FRAGMENTS: [&[u8]; 24] = [
+        b"a",
+        b"\x7f",
+        b"\x00",
+        b"\xc0\x80",
+        b"\xc0\x81",
+        b"\xc0\xbf",
+        b"\xc1\x80",
+        b"\xc1\xbf",
+        b"\xc2\x80",
+        b"\xdf\xbf",
+        b"\xe0\xa0\x80",
+        b"\xe0\x80\x80",
+        b"\xe0\x9f\xbf",
+        b"\xef\xbf\xbf",
+        b"\xed\xa0\x80\xed\xb0\x80",
+        b"\xed\xa0\x80",
+        b"\xed\xbf\xbf",
+        b"\xf0\x9f\x98\x80",
+        b"\xf4\x8f\xbf\xbf",
+        b"\x80",
+        b"\xbf\xbf",
+        b"\xe0\xa0",
+        b"\xc2",
+        b"\xff",
+    ];
+
+    // xorshift, so that the inputs are the same on every run
+    struct Random(u64);
+
+    impl Random {
+        fn next(&mut self) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0 as usize
+        }
+    }
+
+    fn check(input: &[u8]) {
+        let expected = validate_jstr_scalar(input);
+
+        // SAFETY: SSE2 is checked for, and AVX2 too before its validator is run
+        unsafe {
+            if is_x86_feature_detected!("sse2") {
+                assert_eq!(validate_jstr_sse2(input), expected, "sse2: {input:02x?}");
+            }
+
+            if is_x86_feature_detected!("avx2") {
+                assert_eq!(validate_jstr_avx2(input), expected, "avx2: {input:02x?}");
+            }
+        }
+
+        assert_eq!(crate::jstr::validate_jstr(input), expected, "{input:02x?}");
+    }
+
+    #[test]
+    fn scalar_agrees_with_the_specification() {
+        assert!(validate_jstr_scalar(b"\xc0\x80"));
+        assert!(validate_jstr_scalar(b"\xed\xa0\x80"));
+        assert!(validate_jstr_scalar(b"\xed\xa0\x80\xed\xb0\x80"));
+        assert!(!validate_jstr_scalar(b"\x00"));
+        assert!(!validate_jstr_scalar(b"\xc0\x81"));
+        assert!(!validate_jstr_scalar(b"\xc1\xbf"));
+        assert!(!validate_jstr_scalar(b"\xe0\x80\x80"));
+        assert!(!validate_jstr_scalar(b"\xf0\x9f\x98\x80"));
+        assert!(!validate_jstr_scalar(b"\xe0\xa0"));
+    }
+
+    #[test]
+    fn fragments_at_every_offset() {
+        for fragment in FRAGMENTS {
+            for prefix in 0..=2 * AVX2_BLOCK_SIZE {
+                for suffix in [0, 1, 2, 15, 16, 31, 32, 33] {
+                    let mut input = vec![b'a'; prefix];
+                    input.extend_from_slice(fragment);
+                    input.extend(std::iter::repeat_n(b'b', suffix));
+
+                    check(&input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pairs_of_fragments_across_a_boundary() {
+        for first in FRAGMENTS {
+            for second in FRAGMENTS {
+                for boundary in [SSE2_BLOCK_SIZE, AVX2_BLOCK_SIZE] {
+                    for offset in 0..=first.len() {
+                        let mut input = vec![b'a'; boundary - offset];
+                        input.extend_from_slice(first);
+                        input.extend_from_slice(second);
+                        input.extend_from_slice(b"cd");
+
+                        check(&input);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn random_fragments() {
+        let mut random = Random(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..20_000 {
+            let mut input = Vec::new();
+            let count = random.next() % 48;
+            for _ in 0..count {
+                // mostly valid pieces, so that an error is not always found in the first block
+                let fragment = match random.next() % 4 {
+                    0 => FRAGMENTS[random.next() % FRAGMENTS.len()],
+                    1 => b"\xe4\xb8\xad",
+                    2 => b"\xc3\xa9",
+                    _ => b"ascii",
+                };
+                input.extend_from_slice(fragment);
+            }
+
+            check(&input);
+        }
+    }
+
+    #[test]
+    fn random_bytes() {
+        let mut random = Random(0x9e37_79b9_7f4a_7c15);
+
+        for _ in 0..20_000 {
+            let length = random.next() % 100;
+            let input = (0..length)
+                .map(|_| match random.next() % 8 {
+                    // skewed towards bytes that form valid sequences
+                    0..=3 => b'a' + (random.next() % 26) as u8,
+                    4 => 0x80 | (random.next() % 0x40) as u8,
+                    5 => 0xc0 | (random.next() % 0x20) as u8,
+                    6 => 0xe0 | (random.next() % 0x10) as u8,
+                    _ => random.next() as u8,
+                })
+                .collect::<Vec<_>>();
+
+            check(&input);
+        }
+    }
+}
@@ -23,3 +23,5 @@
 pub mod be;
 pub mod descriptor;
 pub mod jstr;
+pub mod names;
+pub mod signature;
@@ -0,0 +1,198 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// The forms of names in class files (JVMS 4.2), and conversions to the binary names used by Java
+// code.
+
+use crate::descriptor::BaseType;
+use crate::descriptor::TypeDescriptor;
+use crate::jstr::JStr;
+use crate::jstr::JString;
+use crate::names::error::InvalidNameError;
+
+pub mod error;
+
+/// A class or interface name in internal form, such as `java/lang/String`, or the descriptor of
+/// an array class when created with [`InternalName::from_class_constant`].
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct InternalName<'a>(&'a JStr);
+
+impl<'a> InternalName<'a> {
+    pub fn from_jstr(input: &'a JStr) -> Result<Self, InvalidNameError> {
+        if !input
+            .as_bytes()
+            .split(|byte| *byte == b'/')
+            .all(is_unqualified)
+        {
+            return Err(InvalidNameError);
+        }
+
+        Ok(Self(input))
+    }
+
+    /// Accepts any name a `ConstantClass` entry may hold, which includes array classes.
+    pub fn from_class_constant(input: &'a JStr) -> Result<Self, InvalidNameError> {
+        if input.as_bytes().first() != Some(&b'[') {
+            return Self::from_jstr(input);
+        }
+
+        let descriptor = TypeDescriptor::from_jstr(input).map_err(|_| InvalidNameError)?;
+        if let BaseType::Object(name) = descriptor.r#type {
+            Self::from_jstr(name)?;
+        }
+
+        Ok(Self(input))
+    }
+
+    pub fn as_jstr(&self) -> &'a JStr {
+        self.0
+    }
+
+    pub fn is_array(&self) -> bool {
+        self.0.as_bytes().first() == Some(&b'[')
+    }
+
+    /// The package in internal form, or `None` for the unnamed package and array classes.
+    pub fn package(&self) -> Option<&'a JStr> {
+        if self.is_array() {
+            return None;
+        }
+
+        let index = self.0.as_bytes().iter().rposition(|byte| *byte == b'/')?;
+        Some(&self.0[..index])
+    }
+
+    /// The name without its package, which still includes the names of enclosing classes.
+    pub fn simple_name(&self) -> &'a JStr {
+        match self.0.as_bytes().iter().rposition(|byte| *byte == b'/') {
+            Some(index) if !self.is_array() => &self.0[index + 1..],
+            _ => self.0,
+        }
+    }
+
+    pub fn to_binary_name(&self) -> String {
+        self.0
+            .chars_lossy()
+            .map(|char| if char == '/' { '.' } else { char })
+            .collect()
+    }
+}
+
+/// A class or interface name as used by Java code, such as `java.lang.String`.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct BinaryName<'a>(&'a str);
+
+impl<'a> BinaryName<'a> {
+    pub fn new(input: &'a str) -> Result<Self, InvalidNameError> {
+        if !input
+            .as_bytes()
+            .split(|byte| *byte == b'.')
+            .all(is_unqualified)
+        {
+            return Err(InvalidNameError);
+        }
+
+        Ok(Self(input))
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+
+    /// The package, or `None` for the unnamed package.
+    pub fn package(&self) -> Option<&'a str> {
+        self.0.rsplit_once('.').map(|(package, _)| package)
+    }
+
+    pub fn to_internal_name(&self) -> JString {
+        let mut output = JString::with_capacity(self.0.len());
+        for char in self.0.chars() {
+            output.push(if char == '.' { '/' } else { char });
+        }
+
+        output
+    }
+}
+
+/// The name of a field, method, local variable or formal parameter.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct UnqualifiedName<'a>(&'a JStr);
+
+impl<'a> UnqualifiedName<'a> {
+    pub fn from_jstr(input: &'a JStr) -> Result<Self, InvalidNameError> {
+        if !is_unqualified(input.as_bytes()) {
+            return Err(InvalidNameError);
+        }
+
+        Ok(Self(input))
+    }
+
+    /// Method names additionally exclude `<` and `>`, apart from the special names `<init>` and
+    /// `<clinit>`.
+    pub fn from_method_name(input: &'a JStr) -> Result<Self, InvalidNameError> {
+        let bytes = input.as_bytes();
+        if bytes != b"<init>"
+            && bytes != b"<clinit>"
+            && (!is_unqualified(bytes) || bytes.iter().any(|byte| b"<>".contains(byte)))
+        {
+            return Err(InvalidNameError);
+        }
+
+        Ok(Self(input))
+    }
+
+    pub fn as_jstr(&self) -> &'a JStr {
+        self.0
+    }
+}
+
+/// The name of a module, which may escape `\`, `:` and `@` with a backslash.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ModuleName<'a>(&'a JStr);
+
+impl<'a> ModuleName<'a> {
+    pub fn from_jstr(input: &'a JStr) -> Result<Self, InvalidNameError> {
+        if input.is_empty() {
+            return Err(InvalidNameError);
+        }
+
+        // the checks are on code points, since NUL is encoded as two bytes in modified UTF-8
+        let mut chars = input.chars_lossy();
+        while let Some(char) = chars.next() {
+            match char {
+                '\0'..='\u{1f}' | ':' | '@' => return Err(InvalidNameError),
+                '\\' if !matches!(chars.next(), Some('\\' | ':' | '@')) => {
+                    return Err(InvalidNameError)
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self(input))
+    }
+
+    pub fn as_jstr(&self) -> &'a JStr {
+        self.0
+    }
+}
+
+fn is_unqualified(bytes: &[u8]) -> bool {
+    !bytes.is_empty() && !bytes.iter().any(|byte| b".;[/".contains(byte))
+}
@@ -0,0 +1,514 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Generic signatures, as found in Signature attributes (JVMS 4.7.9.1).
+
+use std::fmt;
+use std::fmt::Write;
+
+use crate::descriptor::BaseType;
+use crate::jstr::JStr;
+use crate::signature::error::InvalidSignatureError;
+
+pub mod error;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClassSignature<'a> {
+    pub type_parameters: Vec<TypeParameter<'a>>,
+    pub superclass: ClassTypeSignature<'a>,
+    pub superinterfaces: Vec<ClassTypeSignature<'a>>,
+}
+
+impl<'a> ClassSignature<'a> {
+    pub fn from_jstr(input: &'a JStr) -> Result<Self, InvalidSignatureError> {
+        let mut parser = SignatureParser::new(input);
+
+        let type_parameters = parser.type_parameters()?;
+        let superclass = parser.class_type()?;
+        let mut superinterfaces = Vec::new();
+        while parser.peek().is_some() {
+            superinterfaces.push(parser.class_type()?);
+        }
+
+        Ok(Self {
+            type_parameters,
+            superclass,
+            superinterfaces,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MethodSignature<'a> {
+    pub type_parameters: Vec<TypeParameter<'a>>,
+    pub parameters: Vec<JavaTypeSignature<'a>>,
+    /// `None` if the method returns `void`.
+    pub result: Option<JavaTypeSignature<'a>>,
+    pub throws: Vec<ThrowsSignature<'a>>,
+}
+
+impl<'a> MethodSignature<'a> {
+    pub fn from_jstr(input: &'a JStr) -> Result<Self, InvalidSignatureError> {
+        let mut parser = SignatureParser::new(input);
+
+        let type_parameters = parser.type_parameters()?;
+
+        parser.expect(b'(')?;
+        let mut parameters = Vec::new();
+        while parser.peek() != Some(b')') {
+            parameters.push(parser.java_type()?);
+        }
+        parser.expect(b')')?;
+
+        let result = if parser.peek() == Some(b'V') {
+            parser.position += 1;
+            None
+        } else {
+            Some(parser.java_type()?)
+        };
+
+        let mut throws = Vec::new();
+        while parser.peek().is_some() {
+            parser.expect(b'^')?;
+            let start = parser.position;
+            throws.push(match parser.reference_type()? {
+                ReferenceTypeSignature::Class(class) => ThrowsSignature::Class(class),
+                ReferenceTypeSignature::TypeVariable(name) => ThrowsSignature::TypeVariable(name),
+                ReferenceTypeSignature::Array(_) => {
+                    return Err(InvalidSignatureError { position: start })
+                }
+            });
+        }
+
+        Ok(Self {
+            type_parameters,
+            parameters,
+            result,
+            throws,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ThrowsSignature<'a> {
+    Class(ClassTypeSignature<'a>),
+    TypeVariable(&'a JStr),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TypeParameter<'a> {
+    pub name: &'a JStr,
+    // may be absent when the parameter is only bounded by interfaces
+    pub class_bound: Option<ReferenceTypeSignature<'a>>,
+    pub interface_bounds: Vec<ReferenceTypeSignature<'a>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum JavaTypeSignature<'a> {
+    /// A primitive type, never [`BaseType::Object`].
+    Base(BaseType<'a>),
+    Reference(ReferenceTypeSignature<'a>),
+}
+
+/// The signature of a field, or any other use of a reference type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReferenceTypeSignature<'a> {
+    Class(ClassTypeSignature<'a>),
+    TypeVariable(&'a JStr),
+    Array(Box<JavaTypeSignature<'a>>),
+}
+
+impl<'a> ReferenceTypeSignature<'a> {
+    pub fn from_jstr(input: &'a JStr) -> Result<Self, InvalidSignatureError> {
+        let mut parser = SignatureParser::new(input);
+
+        let signature = parser.reference_type()?;
+        if parser.peek().is_some() {
+            return Err(parser.error());
+        }
+
+        Ok(signature)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ClassTypeSignature<'a> {
+    /// The package in internal form, such as `java/util`.
+    pub package: Option<&'a JStr>,
+    /// The class followed by the inner classes nested in it, such as `Map` and `Entry` for
+    /// `Ljava/util/Map<TK;TV;>.Entry;`.
+    pub classes: Vec<SimpleClassTypeSignature<'a>>,
+}
+
+impl<'a> ClassTypeSignature<'a> {
+    /// The internal name of the erasure of this type, such as `java/util/Map$Entry`.
+    pub fn erasure(&self) -> String {
+        let mut name = String::new();
+        if let Some(package) = self.package {
+            name.extend(package.chars_lossy());
+            name.push('/');
+        }
+
+        for (index, class) in self.classes.iter().enumerate() {
+            if index > 0 {
+                name.push('$');
+            }
+
+            name.extend(class.name.chars_lossy());
+        }
+
+        name
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SimpleClassTypeSignature<'a> {
+    pub name: &'a JStr,
+    pub type_arguments: Vec<TypeArgument<'a>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TypeArgument<'a> {
+    Exact(ReferenceTypeSignature<'a>),
+    Extends(ReferenceTypeSignature<'a>),
+    Super(ReferenceTypeSignature<'a>),
+    Wildcard,
+}
+
+impl fmt::Display for ClassSignature<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_type_parameters(f, &self.type_parameters)?;
+        write!(f, "{}", self.superclass)?;
+        for superinterface in &self.superinterfaces {
+            write!(f, "{superinterface}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for MethodSignature<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_type_parameters(f, &self.type_parameters)?;
+        f.write_str("(")?;
+        for parameter in &self.parameters {
+            write!(f, "{parameter}")?;
+        }
+        f.write_str(")")?;
+
+        match &self.result {
+            Some(result) => write!(f, "{result}")?,
+            None => f.write_str("V")?,
+        }
+
+        for throws in &self.throws {
+            match throws {
+                ThrowsSignature::Class(class) => write!(f, "^{class}")?,
+                ThrowsSignature::TypeVariable(name) => {
+                    f.write_str("^")?;
+                    write_type_variable(f, name)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for JavaTypeSignature<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Base(BaseType::Boolean) => f.write_str("Z"),
+            Self::Base(BaseType::Byte) => f.write_str("B"),
+            Self::Base(BaseType::Char) => f.write_str("C"),
+            Self::Base(BaseType::Double) => f.write_str("D"),
+            Self::Base(BaseType::Float) => f.write_str("F"),
+            Self::Base(BaseType::Int) => f.write_str("I"),
+            Self::Base(BaseType::Long) => f.write_str("J"),
+            Self::Base(BaseType::Short) => f.write_str("S"),
+            Self::Base(BaseType::Object(name)) => {
+                f.write_str("L")?;
+                write_identifier(f, name)?;
+                f.write_str(";")
+            }
+            Self::Reference(reference) => write!(f, "{reference}"),
+        }
+    }
+}
+
+impl fmt::Display for ReferenceTypeSignature<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Class(class) => write!(f, "{class}"),
+            Self::TypeVariable(name) => write_type_variable(f, name),
+            Self::Array(component) => write!(f, "[{component}"),
+        }
+    }
+}
+
+impl fmt::Display for ClassTypeSignature<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("L")?;
+        if let Some(package) = self.package {
+            write_identifier(f, package)?;
+            f.write_str("/")?;
+        }
+
+        for (index, class) in self.classes.iter().enumerate() {
+            if index > 0 {
+                f.write_str(".")?;
+            }
+
+            write_identifier(f, class.name)?;
+            if !class.type_arguments.is_empty() {
+                f.write_str("<")?;
+                for type_argument in &class.type_arguments {
+                    match type_argument {
+                        TypeArgument::Exact(r#type) => write!(f, "{type}")?,
+                        TypeArgument::Extends(bound) => write!(f, "+{bound}")?,
+                        TypeArgument::Super(bound) => write!(f, "-{bound}")?,
+                        TypeArgument::Wildcard => f.write_str("*")?,
+                    }
+                }
+                f.write_str(">")?;
+            }
+        }
+
+        f.write_str(";")
+    }
+}
+
+// JStr::display passes an encoded NUL through, so identifiers are written a character at a time
+fn write_identifier(f: &mut fmt::Formatter<'_>, identifier: &JStr) -> fmt::Result {
+    identifier
+        .chars_lossy()
+        .try_for_each(|char| f.write_char(char))
+}
+
+fn write_type_variable(f: &mut fmt::Formatter<'_>, name: &JStr) -> fmt::Result {
+    f.write_str("T")?;
+    write_identifier(f, name)?;
+    f.write_str(";")
+}
+
+fn write_type_parameters(
+    f: &mut fmt::Formatter<'_>,
+    type_parameters: &[TypeParameter],
+) -> fmt::Result {
+    if type_parameters.is_empty() {
+        return Ok(());
+    }
+
+    f.write_str("<")?;
+    for type_parameter in type_parameters {
+        write_identifier(f, type_parameter.name)?;
+        f.write_str(":")?;
+        if let Some(class_bound) = &type_parameter.class_bound {
+            write!(f, "{class_bound}")?;
+        }
+
+        for interface_bound in &type_parameter.interface_bounds {
+            write!(f, ":{interface_bound}")?;
+        }
+    }
+
+    f.write_str(">")
+}
+
+// the characters that end an identifier are all ASCII, so they never appear inside a multi-byte
+// character and the input can be scanned byte by byte
+struct SignatureParser<'a> {
+    input: &'a JStr,
+    position: usize,
+}
+
+impl<'a> SignatureParser<'a> {
+    fn new(input: &'a JStr) -> Self {
+        Self { input, position: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.position).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), InvalidSignatureError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error());
+        }
+
+        self.position += 1;
+        Ok(())
+    }
+
+    fn error(&self) -> InvalidSignatureError {
+        InvalidSignatureError {
+            position: self.position,
+        }
+    }
+
+    fn identifier(&mut self) -> Result<&'a JStr, InvalidSignatureError> {
+        let start = self.position;
+        while let Some(byte) = self.peek() {
+            if b".;[/<>:".contains(&byte) {
+                break;
+            }
+
+            self.position += 1;
+        }
+
+        if self.position == start {
+            return Err(self.error());
+        }
+
+        Ok(&self.input[start..self.position])
+    }
+
+    fn type_parameters(&mut self) -> Result<Vec<TypeParameter<'a>>, InvalidSignatureError> {
+        let mut type_parameters = Vec::new();
+        if self.peek() != Some(b'<') {
+            return Ok(type_parameters);
+        }
+
+        self.position += 1;
+        loop {
+            let name = self.identifier()?;
+            self.expect(b':')?;
+
+            let class_bound = match self.peek() {
+                Some(b'L' | b'T' | b'[') => Some(self.reference_type()?),
+                _ => None,
+            };
+
+            let mut interface_bounds = Vec::new();
+            while self.peek() == Some(b':') {
+                self.position += 1;
+                interface_bounds.push(self.reference_type()?);
+            }
+
+            type_parameters.push(TypeParameter {
+                name,
+                class_bound,
+                interface_bounds,
+            });
+
+            if self.peek() == Some(b'>') {
+                self.position += 1;
+                return Ok(type_parameters);
+            }
+        }
+    }
+
+    fn java_type(&mut self) -> Result<JavaTypeSignature<'a>, InvalidSignatureError> {
+        let r#type = match self.peek() {
+            Some(b'Z') => BaseType::Boolean,
+            Some(b'B') => BaseType::Byte,
+            Some(b'C') => BaseType::Char,
+            Some(b'D') => BaseType::Double,
+            Some(b'F') => BaseType::Float,
+            Some(b'I') => BaseType::Int,
+            Some(b'J') => BaseType::Long,
+            Some(b'S') => BaseType::Short,
+            _ => return self.reference_type().map(JavaTypeSignature::Reference),
+        };
+
+        self.position += 1;
+        Ok(JavaTypeSignature::Base(r#type))
+    }
+
+    fn reference_type(&mut self) -> Result<ReferenceTypeSignature<'a>, InvalidSignatureError> {
+        match self.peek() {
+            Some(b'L') => self.class_type().map(ReferenceTypeSignature::Class),
+            Some(b'T') => {
+                self.position += 1;
+                let name = self.identifier()?;
+                self.expect(b';')?;
+
+                Ok(ReferenceTypeSignature::TypeVariable(name))
+            }
+            Some(b'[') => {
+                self.position += 1;
+                Ok(ReferenceTypeSignature::Array(Box::new(self.java_type()?)))
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    fn class_type(&mut self) -> Result<ClassTypeSignature<'a>, InvalidSignatureError> {
+        self.expect(b'L')?;
+
+        let start = self.position;
+        let mut name_start = start;
+        self.identifier()?;
+        while self.peek() == Some(b'/') {
+            self.position += 1;
+            name_start = self.position;
+            self.identifier()?;
+        }
+
+        let package = (name_start > start).then(|| &self.input[start..name_start - 1]);
+        let mut classes = vec![SimpleClassTypeSignature {
+            name: &self.input[name_start..self.position],
+            type_arguments: self.type_arguments()?,
+        }];
+
+        while self.peek() == Some(b'.') {
+            self.position += 1;
+            classes.push(SimpleClassTypeSignature {
+                name: self.identifier()?,
+                type_arguments: self.type_arguments()?,
+            });
+        }
+
+        self.expect(b';')?;
+
+        Ok(ClassTypeSignature { package, classes })
+    }
+
+    fn type_arguments(&mut self) -> Result<Vec<TypeArgument<'a>>, InvalidSignatureError> {
+        let mut type_arguments = Vec::new();
+        if self.peek() != Some(b'<') {
+            return Ok(type_arguments);
+        }
+
+        self.position += 1;
+        loop {
+            type_arguments.push(match self.peek() {
+                Some(b'*') => {
+                    self.position += 1;
+                    TypeArgument::Wildcard
+                }
+                Some(b'+') => {
+                    self.position += 1;
+                    TypeArgument::Extends(self.reference_type()?)
+                }
+                Some(b'-') => {
+                    self.position += 1;
+                    TypeArgument::Super(self.reference_type()?)
+                }
+                _ => TypeArgument::Exact(self.reference_type()?),
+            });
+
+            if self.peek() == Some(b'>') {
+                self.position += 1;
+                return Ok(type_arguments);
+            }
+        }
+    }
+}
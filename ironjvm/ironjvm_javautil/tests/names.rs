@@ -0,0 +1,168 @@
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::names::BinaryName;
+use ironjvm_javautil::names::InternalName;
+use ironjvm_javautil::names::ModuleName;
+use ironjvm_javautil::names::UnqualifiedName;
+
+// NUL is encoded as C0 80 in modified UTF-8, so a raw 0 byte is never part of a JStr
+const NUL: &[u8] = b"a\xC0\x80b";
+
+fn jstr(bytes: &[u8]) -> &JStr {
+    JStr::from_jutf8(bytes).ok().unwrap()
+}
+
+#[test]
+fn internal_names() {
+    for name in [
+        &b"java/lang/String"[..],
+        b"Outer$Inner",
+        b"A",
+        "p\u{e4}ckage/Klasse".as_bytes(),
+        NUL,
+    ] {
+        assert!(InternalName::from_jstr(jstr(name)).is_ok(), "{name:?}");
+    }
+
+    for name in [
+        &b""[..],
+        b"/A",
+        b"java/",
+        b"java//lang",
+        b"java.lang.String",
+        b"Ljava/lang/String;",
+        b"[I",
+    ] {
+        assert!(InternalName::from_jstr(jstr(name)).is_err(), "{name:?}");
+    }
+
+    let string = InternalName::from_jstr(jstr(b"java/lang/String")).unwrap();
+    assert!(!string.is_array());
+    assert_eq!(string.package(), Some(jstr(b"java/lang")));
+    assert_eq!(string.simple_name(), jstr(b"String"));
+    assert_eq!(string.to_binary_name(), "java.lang.String");
+
+    let entry = InternalName::from_jstr(jstr(b"Map$Entry")).unwrap();
+    assert_eq!(entry.package(), None);
+    assert_eq!(entry.simple_name(), jstr(b"Map$Entry"));
+}
+
+#[test]
+fn class_constant_names() {
+    for name in [&b"java/lang/String"[..], b"[I", b"[[Ljava/lang/String;"] {
+        assert!(
+            InternalName::from_class_constant(jstr(name)).is_ok(),
+            "{name:?}"
+        );
+    }
+
+    // the element class of an array is checked like any other class name
+    for name in [
+        &b"["[..],
+        b"[V",
+        b"[L;",
+        b"[Ljava.lang.String;",
+        b"[Ljava//String;",
+        b"[Ljava/lang/String",
+    ] {
+        assert!(
+            InternalName::from_class_constant(jstr(name)).is_err(),
+            "{name:?}"
+        );
+    }
+
+    let strings = InternalName::from_class_constant(jstr(b"[Ljava/lang/String;")).unwrap();
+    assert!(strings.is_array());
+    assert_eq!(strings.package(), None);
+    assert_eq!(strings.simple_name(), jstr(b"[Ljava/lang/String;"));
+}
+
+#[test]
+fn binary_names() {
+    for name in [
+        "java.lang.String",
+        "Outer$Inner",
+        "A",
+        "p\u{e4}ckage.Klasse",
+        "a\0b",
+    ] {
+        assert!(BinaryName::new(name).is_ok(), "{name:?}");
+    }
+
+    for name in [
+        "",
+        ".A",
+        "java.",
+        "java..lang",
+        "java/lang/String",
+        "A;",
+        "[I",
+    ] {
+        assert!(BinaryName::new(name).is_err(), "{name:?}");
+    }
+
+    let string = BinaryName::new("java.lang.String").unwrap();
+    assert_eq!(string.package(), Some("java.lang"));
+    assert_eq!(&*string.to_internal_name(), jstr(b"java/lang/String"));
+    assert_eq!(BinaryName::new("A").unwrap().package(), None);
+
+    // NUL is written back in its two byte form
+    assert_eq!(
+        &*BinaryName::new("a\0b").unwrap().to_internal_name(),
+        jstr(NUL)
+    );
+}
+
+#[test]
+fn unqualified_names() {
+    // field names only exclude . ; [ and /
+    for name in [&b"value"[..], b"<init>", b"a<b>", b"$", NUL] {
+        assert!(UnqualifiedName::from_jstr(jstr(name)).is_ok(), "{name:?}");
+    }
+
+    for name in [&b""[..], b"a.b", b"a;", b"[a", b"a/b"] {
+        assert!(UnqualifiedName::from_jstr(jstr(name)).is_err(), "{name:?}");
+    }
+
+    for name in [&b"run"[..], b"<init>", b"<clinit>", b"lambda$main$0", NUL] {
+        assert!(
+            UnqualifiedName::from_method_name(jstr(name)).is_ok(),
+            "{name:?}"
+        );
+    }
+
+    for name in [&b""[..], b"<run>", b"<init", b"a<b", b"a>", b"a.b", b"a/b"] {
+        assert!(
+            UnqualifiedName::from_method_name(jstr(name)).is_err(),
+            "{name:?}"
+        );
+    }
+}
+
+#[test]
+fn module_names() {
+    for name in [
+        &b"java.base"[..],
+        b"m\xC3\xB6dule",
+        b"a\\:b",
+        b"a\\@b",
+        b"a\\\\b",
+        b"with space",
+    ] {
+        assert!(ModuleName::from_jstr(jstr(name)).is_ok(), "{name:?}");
+    }
+
+    // NUL is rejected like the other control characters, even though its encoding is not 0
+    for name in [
+        &b""[..],
+        NUL,
+        b"a\x01b",
+        b"a\tb",
+        b"a\x1fb",
+        b"a:b",
+        b"a@1.0",
+        b"a\\b",
+        b"a\\",
+    ] {
+        assert!(ModuleName::from_jstr(jstr(name)).is_err(), "{name:?}");
+    }
+}
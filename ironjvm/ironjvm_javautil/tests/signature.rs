@@ -0,0 +1,319 @@
+use ironjvm_javautil::descriptor::BaseType;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::signature::ClassSignature;
+use ironjvm_javautil::signature::ClassTypeSignature;
+use ironjvm_javautil::signature::JavaTypeSignature;
+use ironjvm_javautil::signature::MethodSignature;
+use ironjvm_javautil::signature::ReferenceTypeSignature;
+use ironjvm_javautil::signature::SimpleClassTypeSignature;
+use ironjvm_javautil::signature::ThrowsSignature;
+use ironjvm_javautil::signature::TypeArgument;
+use ironjvm_javautil::signature::TypeParameter;
+
+fn jstr(string: &str) -> &JStr {
+    JStr::from_jutf8(string.as_bytes()).ok().unwrap()
+}
+
+fn class<'a>(
+    package: Option<&'a str>,
+    classes: Vec<(&'a str, Vec<TypeArgument<'a>>)>,
+) -> ClassTypeSignature<'a> {
+    ClassTypeSignature {
+        package: package.map(jstr),
+        classes: classes
+            .into_iter()
+            .map(|(name, type_arguments)| SimpleClassTypeSignature {
+                name: jstr(name),
+                type_arguments,
+            })
+            .collect(),
+    }
+}
+
+fn object() -> ClassTypeSignature<'static> {
+    class(Some("java/lang"), vec![("Object", vec![])])
+}
+
+fn variable(name: &str) -> ReferenceTypeSignature<'_> {
+    ReferenceTypeSignature::TypeVariable(jstr(name))
+}
+
+#[test]
+fn class_signatures() {
+    // class Enum<E extends Enum<E>> implements Comparable<E>, Serializable
+    let signature = "<E:Ljava/lang/Enum<TE;>;>Ljava/lang/Object;Ljava/lang/Comparable<TE;>;\
+        Ljava/io/Serializable;";
+    assert_eq!(
+        ClassSignature::from_jstr(jstr(signature)).unwrap(),
+        ClassSignature {
+            type_parameters: vec![TypeParameter {
+                name: jstr("E"),
+                class_bound: Some(ReferenceTypeSignature::Class(class(
+                    Some("java/lang"),
+                    vec![("Enum", vec![TypeArgument::Exact(variable("E"))])]
+                ))),
+                interface_bounds: vec![],
+            }],
+            superclass: object(),
+            superinterfaces: vec![
+                class(
+                    Some("java/lang"),
+                    vec![("Comparable", vec![TypeArgument::Exact(variable("E"))])]
+                ),
+                class(Some("java/io"), vec![("Serializable", vec![])]),
+            ],
+        }
+    );
+
+    // <T extends Comparable<T> & Serializable, U> with only interface bounds on T
+    assert_eq!(
+        ClassSignature::from_jstr(jstr(
+            "<T::Ljava/lang/Comparable<TT;>;:Ljava/io/Serializable;U:Ljava/lang/Object;>\
+            Ljava/lang/Object;"
+        ))
+        .unwrap()
+        .type_parameters,
+        [
+            TypeParameter {
+                name: jstr("T"),
+                class_bound: None,
+                interface_bounds: vec![
+                    ReferenceTypeSignature::Class(class(
+                        Some("java/lang"),
+                        vec![("Comparable", vec![TypeArgument::Exact(variable("T"))])]
+                    )),
+                    ReferenceTypeSignature::Class(class(
+                        Some("java/io"),
+                        vec![("Serializable", vec![])]
+                    )),
+                ],
+            },
+            TypeParameter {
+                name: jstr("U"),
+                class_bound: Some(ReferenceTypeSignature::Class(object())),
+                interface_bounds: vec![],
+            },
+        ]
+    );
+}
+
+#[test]
+fn method_signatures() {
+    // <T extends Throwable> void sneaky(int[], List<? extends T>) throws T, IOException
+    let signature =
+        "<T:Ljava/lang/Throwable;>([ILjava/util/List<+TT;>;)V^TT;^Ljava/io/IOException;";
+    assert_eq!(
+        MethodSignature::from_jstr(jstr(signature)).unwrap(),
+        MethodSignature {
+            type_parameters: vec![TypeParameter {
+                name: jstr("T"),
+                class_bound: Some(ReferenceTypeSignature::Class(class(
+                    Some("java/lang"),
+                    vec![("Throwable", vec![])]
+                ))),
+                interface_bounds: vec![],
+            }],
+            parameters: vec![
+                JavaTypeSignature::Reference(ReferenceTypeSignature::Array(Box::new(
+                    JavaTypeSignature::Base(BaseType::Int)
+                ))),
+                JavaTypeSignature::Reference(ReferenceTypeSignature::Class(class(
+                    Some("java/util"),
+                    vec![("List", vec![TypeArgument::Extends(variable("T"))])]
+                ))),
+            ],
+            result: None,
+            throws: vec![
+                ThrowsSignature::TypeVariable(jstr("T")),
+                ThrowsSignature::Class(class(Some("java/io"), vec![("IOException", vec![])])),
+            ],
+        }
+    );
+
+    let signature =
+        MethodSignature::from_jstr(jstr("(Ljava/util/Comparator<-TT;>;)[[TT;")).unwrap();
+    assert!(signature.type_parameters.is_empty());
+    assert_eq!(
+        signature.parameters,
+        [JavaTypeSignature::Reference(ReferenceTypeSignature::Class(
+            class(
+                Some("java/util"),
+                vec![("Comparator", vec![TypeArgument::Super(variable("T"))])]
+            )
+        ))]
+    );
+    assert_eq!(
+        signature.result,
+        Some(JavaTypeSignature::Reference(ReferenceTypeSignature::Array(
+            Box::new(JavaTypeSignature::Reference(ReferenceTypeSignature::Array(
+                Box::new(JavaTypeSignature::Reference(variable("T")))
+            )))
+        )))
+    );
+    assert!(signature.throws.is_empty());
+}
+
+#[test]
+fn field_signatures() {
+    // Map<K, V>.Entry<?, String> in the default package, then a nested class with a package
+    assert_eq!(
+        ReferenceTypeSignature::from_jstr(jstr("LMap<TK;TV;>.Entry<*Ljava/lang/String;>;"))
+            .unwrap(),
+        ReferenceTypeSignature::Class(class(
+            None,
+            vec![
+                (
+                    "Map",
+                    vec![
+                        TypeArgument::Exact(variable("K")),
+                        TypeArgument::Exact(variable("V"))
+                    ]
+                ),
+                (
+                    "Entry",
+                    vec![
+                        TypeArgument::Wildcard,
+                        TypeArgument::Exact(ReferenceTypeSignature::Class(class(
+                            Some("java/lang"),
+                            vec![("String", vec![])]
+                        )))
+                    ]
+                ),
+            ]
+        ))
+    );
+
+    let ReferenceTypeSignature::Class(entry) =
+        ReferenceTypeSignature::from_jstr(jstr("Ljava/util/Map<TK;TV;>.Entry;")).unwrap()
+    else {
+        panic!("not a class type");
+    };
+    assert_eq!(entry.package, Some(jstr("java/util")));
+    assert_eq!(entry.erasure(), "java/util/Map$Entry");
+
+    assert_eq!(
+        ReferenceTypeSignature::from_jstr(jstr("TT;")).unwrap(),
+        variable("T")
+    );
+    assert_eq!(
+        ReferenceTypeSignature::from_jstr(jstr("[Z")).unwrap(),
+        ReferenceTypeSignature::Array(Box::new(JavaTypeSignature::Base(BaseType::Boolean)))
+    );
+}
+
+#[test]
+fn round_trips() {
+    for signature in [
+        "<E:Ljava/lang/Enum<TE;>;>Ljava/lang/Object;Ljava/lang/Comparable<TE;>;",
+        "<T::Ljava/lang/Comparable<-TT;>;:Ljava/io/Serializable;>Ljava/lang/Object;",
+        "LOuter;LOuter$Nested<*>;",
+        // identifiers are any characters other than . ; [ / < > :
+        "<Ä:Ljava/lang/Object;>Lpäckage/Klasse<TÄ;>.Inner;",
+    ] {
+        assert_eq!(
+            ClassSignature::from_jstr(jstr(signature))
+                .unwrap()
+                .to_string(),
+            signature
+        );
+    }
+
+    for signature in [
+        "()V",
+        "<T:Ljava/lang/Throwable;>([ILjava/util/List<+TT;>;)V^TT;^Ljava/io/IOException;",
+        "(ZBCDFIJS)[[TT;",
+        "<K:Ljava/lang/Object;V:Ljava/lang/Object;>(Ljava/util/Map<TK;TV;>.Entry<TK;TV;>;)TV;",
+    ] {
+        assert_eq!(
+            MethodSignature::from_jstr(jstr(signature))
+                .unwrap()
+                .to_string(),
+            signature
+        );
+    }
+
+    for signature in [
+        "TT;",
+        "[[Ljava/lang/String;",
+        "Ljava/util/Map<TK;+[TV;>.Entry<*-Ljava/lang/Number;>;",
+    ] {
+        assert_eq!(
+            ReferenceTypeSignature::from_jstr(jstr(signature))
+                .unwrap()
+                .to_string(),
+            signature
+        );
+    }
+
+    // a NUL in an identifier is written as a character rather than its modified UTF-8 bytes
+    let nul = JStr::from_jutf8(b"TA\xC0\x80;").ok().unwrap();
+    assert_eq!(
+        ReferenceTypeSignature::from_jstr(nul).unwrap().to_string(),
+        "TA\0;"
+    );
+}
+
+#[test]
+fn malformed_signatures() {
+    // each signature with the offset at which it is rejected
+    for (signature, position) in [
+        ("", 0),
+        ("<>Ljava/lang/Object;", 1),
+        ("<T>Ljava/lang/Object;", 2),
+        ("<T:Ljava/lang/Object;", 21),
+        ("TT;", 0),
+        ("Ljava/lang/Object;I", 18),
+        ("Ljava/lang/Object", 17),
+    ] {
+        assert_eq!(
+            ClassSignature::from_jstr(jstr(signature))
+                .unwrap_err()
+                .position,
+            position,
+            "{signature}"
+        );
+    }
+
+    for (signature, position) in [
+        ("V", 0),
+        ("(", 1),
+        ("(V)V", 1),
+        ("()", 2),
+        ("()VV", 3),
+        ("()V^", 4),
+        ("()VLjava/lang/Exception;", 3),
+        // arrays and primitive types cannot be thrown
+        ("()V^[Ljava/lang/Exception;", 4),
+        ("()V^I", 4),
+        ("()V^TT", 6),
+    ] {
+        assert_eq!(
+            MethodSignature::from_jstr(jstr(signature))
+                .unwrap_err()
+                .position,
+            position,
+            "{signature}"
+        );
+    }
+
+    for (signature, position) in [
+        ("I", 0),
+        ("[", 1),
+        ("T;", 1),
+        ("L;", 1),
+        ("Ljava/;", 6),
+        ("Ljava/util/List<>;", 16),
+        ("Ljava/util/List<*;", 17),
+        ("Ljava/util/List<I>;", 16),
+        ("Ljava/util/Map.;", 15),
+        ("Ljava/util/List;;", 16),
+    ] {
+        assert_eq!(
+            ReferenceTypeSignature::from_jstr(jstr(signature))
+                .unwrap_err()
+                .position,
+            position,
+            "{signature}"
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+
+fn jstr(bytes: &[u8]) -> &JStr {
+    JStr::from_jutf8(bytes).ok().unwrap()
+}
+
+// encodes the code units, then checks that they decode back to themselves
+fn round_trip(units: &[u16], encoded: &[u8]) {
+    let jstring = JString::from_utf16(units);
+    assert_eq!(jstring.as_bytes(), encoded, "{units:04X?}");
+
+    let jstr = jstr(encoded);
+    assert_eq!(jstr.to_utf16(), units, "{encoded:02X?}");
+    assert_eq!(jstr.utf16_len(), units.len(), "{encoded:02X?}");
+}
+
+#[test]
+fn code_unit_boundaries() {
+    round_trip(&[], b"");
+    round_trip(&[0x41, 0x7F], b"A\x7F");
+    round_trip(&[0x80, 0x7FF], b"\xC2\x80\xDF\xBF");
+    round_trip(&[0x800, 0xFFFF], b"\xE0\xA0\x80\xEF\xBF\xBF");
+}
+
+#[test]
+fn supplementary_characters() {
+    // U+1F600 is the surrogate pair D83D DE00, each half taking three bytes
+    let encoded = b"\xED\xA0\xBD\xED\xB8\x80";
+    round_trip(&[0xD83D, 0xDE00], encoded);
+    round_trip(&[0x61, 0xD83D, 0xDE00, 0x62], b"a\xED\xA0\xBD\xED\xB8\x80b");
+
+    // the six byte form is what JString produces from a char, and decodes back to it
+    assert_eq!(JString::from("\u{1F600}").as_bytes(), encoded);
+    assert_eq!(jstr(encoded).chars_lossy().collect::<String>(), "\u{1F600}");
+
+    // U+10000 and U+10FFFF are the first and last supplementary characters
+    round_trip(&[0xD800, 0xDC00], b"\xED\xA0\x80\xED\xB0\x80");
+    round_trip(&[0xDBFF, 0xDFFF], b"\xED\xAF\xBF\xED\xBF\xBF");
+    assert_eq!(
+        JString::from("\u{10000}\u{10FFFF}").as_bytes(),
+        b"\xED\xA0\x80\xED\xB0\x80\xED\xAF\xBF\xED\xBF\xBF"
+    );
+
+    // the four byte form of standard UTF-8 is not modified UTF-8
+    assert!(JStr::from_jutf8(b"\xF0\x9F\x98\x80").is_err());
+}
+
+#[test]
+fn nul() {
+    // NUL takes two bytes, so that the encoding never contains a zero byte
+    round_trip(&[0], b"\xC0\x80");
+    round_trip(&[0x61, 0, 0x62], b"a\xC0\x80b");
+    assert_eq!(
+        JString::from_latin1(&[0, 0xFF]).as_bytes(),
+        b"\xC0\x80\xC3\xBF"
+    );
+    assert_eq!(jstr(b"a\xC0\x80b").to_latin1(), Some(vec![0x61, 0, 0x62]));
+}
+
+#[test]
+fn lone_surrogates() {
+    // a surrogate without its other half is encoded like any other code unit
+    round_trip(&[0xD800], b"\xED\xA0\x80");
+    round_trip(&[0xDC00], b"\xED\xB0\x80");
+    round_trip(&[0xD83D, 0x61], b"\xED\xA0\xBDa");
+    round_trip(&[0x61, 0xDE00], b"a\xED\xB8\x80");
+
+    // a low surrogate followed by a high one is two lone surrogates rather than a pair
+    round_trip(&[0xDE00, 0xD83D], b"\xED\xB8\x80\xED\xA0\xBD");
+    round_trip(
+        &[0xD83D, 0xD83D, 0xDE00],
+        b"\xED\xA0\xBD\xED\xA0\xBD\xED\xB8\x80",
+    );
+
+    let lone = jstr(b"\xED\xA0\x80");
+    assert!(!lone.is_latin1());
+    assert_eq!(lone.to_latin1(), None);
+}
+
+#[test]
+fn latin1() {
+    let latin1 = jstr("caf\u{e9} \u{ff}".as_bytes());
+    assert!(latin1.is_latin1());
+    assert_eq!(latin1.to_latin1(), Some(b"caf\xE9 \xFF".to_vec()));
+    assert_eq!(
+        JString::from_latin1(b"caf\xE9 \xFF").as_bytes(),
+        latin1.as_bytes()
+    );
+
+    for string in ["\u{100}", "\u{20AC}", "\u{1F600}"] {
+        let jstring = JString::from(string);
+        assert!(!jstring.is_latin1(), "{string}");
+        assert_eq!(jstring.to_latin1(), None, "{string}");
+    }
+}
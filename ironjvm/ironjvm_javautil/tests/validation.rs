@@ -0,0 +1,47 @@
+use ironjvm_javautil::jstr::JStr;
+
+fn is_valid(bytes: &[u8]) -> bool {
+    JStr::from_jutf8(bytes).is_ok()
+}
+
+#[test]
+fn overlong_sequences() {
+    // C0 80 is the only overlong form allowed, and encodes NUL
+    assert!(is_valid(b"\xC0\x80"));
+    assert!(is_valid(b"a\xC0\x80b"));
+
+    // the other two byte forms below U+0080, whichever byte makes them overlong
+    for overlong in [
+        &b"\xC0\x81"[..],
+        b"\xC0\xBF",
+        b"\xC1\x80",
+        b"\xC1\xBF",
+        b"a\xC1\x81b",
+    ] {
+        assert!(!is_valid(overlong), "{overlong:02X?}");
+    }
+
+    // the shortest forms on either side of the two and three byte boundaries
+    assert!(is_valid(b"\xC2\x80"));
+    assert!(is_valid(b"\xDF\xBF"));
+    assert!(is_valid(b"\xE0\xA0\x80"));
+    assert!(!is_valid(b"\xE0\x80\x80"));
+    assert!(!is_valid(b"\xE0\x9F\xBF"));
+}
+
+#[test]
+fn malformed_sequences() {
+    for malformed in [
+        &b"\0"[..],
+        b"\x80",
+        b"\xBF",
+        b"\xC2",
+        b"\xC2a",
+        b"\xE0\xA0",
+        b"\xE0\xA0a",
+        b"\xF0\x9F\x98\x80",
+        b"\xFF",
+    ] {
+        assert!(!is_valid(malformed), "{malformed:02X?}");
+    }
+}
@@ -0,0 +1,295 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Class and interface initialization, following the procedure of JVMS 5.5.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+use std::thread;
+use std::thread::ThreadId;
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::flags::ClassAccessFlags;
+use ironjvm_specimpl::classfile::flags::FieldAccessFlags;
+use ironjvm_specimpl::classfile::flags::FlagsExt;
+use ironjvm_specimpl::classfile::flags::MethodAccessFlags;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::FieldInfo;
+
+use crate::indy;
+use crate::indy::LoadableConstant;
+
+/// The runtime services needed to initialize a class, provided by the class loader and the
+/// interpreter.
+pub trait ClassInitializer {
+    type Class: Clone + Eq + Hash;
+    type Throwable;
+
+    fn is_interface(&self, class: &Self::Class) -> bool;
+
+    fn superclass(&self, class: &Self::Class) -> Option<Self::Class>;
+
+    /// The direct superinterfaces of `class`, in the order of its `interfaces` array.
+    fn superinterfaces(&self, class: &Self::Class) -> Vec<Self::Class>;
+
+    /// Whether `class` declares at least one non-abstract, non-static method; see
+    /// [`declares_default_methods`].
+    fn declares_default_methods(&self, class: &Self::Class) -> bool;
+
+    /// Assigns the static fields of `class` their `ConstantValue` attributes; see
+    /// [`constant_value`].
+    fn preload_constant_values(&self, class: &Self::Class);
+
+    /// Executes the `<clinit>` method of `class`, if any.
+    fn run_clinit(&self, class: &Self::Class) -> Result<(), Self::Throwable>;
+
+    fn no_class_def_found_error(&self, class: &Self::Class) -> Self::Throwable;
+
+    /// Wraps `exception` in an `ExceptionInInitializerError`, unless it is an `Error` already.
+    fn exception_in_initializer_error(&self, exception: Self::Throwable) -> Self::Throwable;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum InitializationState {
+    BeingInitialized(ThreadId),
+    Initialized,
+    Erroneous,
+}
+
+/// The initialization state of every class, which also serves as the initialization lock `LC` of
+/// each of them.
+pub struct InitializationTable<C> {
+    states: Mutex<HashMap<C, InitializationState>>,
+    state_changed: Condvar,
+}
+
+impl<C: Clone + Eq + Hash> InitializationTable<C> {
+    pub fn new() -> Self {
+        Self {
+            states: Mutex::new(HashMap::new()),
+            state_changed: Condvar::new(),
+        }
+    }
+
+    /// Returns `None` if initialization of `class` has not been started.
+    pub fn state(&self, class: &C) -> Option<InitializationState> {
+        self.states.lock().unwrap().get(class).copied()
+    }
+
+    /// Initializes `class` and, first, the supertypes that need it, running `<clinit>` at most
+    /// once.
+    ///
+    /// If the initializer panics, `class` is marked erroneous before the panic propagates, so
+    /// other threads fail with `NoClassDefFoundError` rather than waiting forever.
+    pub fn initialize<I: ClassInitializer<Class = C>>(
+        &self,
+        initializer: &I,
+        class: &C,
+    ) -> Result<(), I::Throwable> {
+        let current = thread::current().id();
+
+        let mut states = self.states.lock().unwrap();
+        loop {
+            match states.get(class) {
+                Some(InitializationState::BeingInitialized(thread)) if *thread != current => {
+                    states = self.state_changed.wait(states).unwrap();
+                }
+                // a recursive request from the initializing thread completes immediately
+                Some(
+                    InitializationState::BeingInitialized(_) | InitializationState::Initialized,
+                ) => return Ok(()),
+                Some(InitializationState::Erroneous) => {
+                    return Err(initializer.no_class_def_found_error(class))
+                }
+                None => break,
+            }
+        }
+
+        states.insert(
+            class.clone(),
+            InitializationState::BeingInitialized(current),
+        );
+        drop(states);
+
+        // stays erroneous unless initialization completes, so that a panic does not leave the
+        // class being initialized forever
+        let mut guard = InitializationGuard {
+            table: self,
+            class,
+            state: InitializationState::Erroneous,
+        };
+
+        initializer.preload_constant_values(class);
+
+        // exceptions from supertypes are rethrown as-is, only those of our own <clinit> are
+        // wrapped
+        let result = self
+            .initialize_supertypes(initializer, class)
+            .and_then(|_| {
+                initializer
+                    .run_clinit(class)
+                    .map_err(|exception| initializer.exception_in_initializer_error(exception))
+            });
+
+        if result.is_ok() {
+            guard.state = InitializationState::Initialized;
+        }
+
+        result
+    }
+
+    fn initialize_supertypes<I: ClassInitializer<Class = C>>(
+        &self,
+        initializer: &I,
+        class: &C,
+    ) -> Result<(), I::Throwable> {
+        if initializer.is_interface(class) {
+            return Ok(());
+        }
+
+        if let Some(superclass) = initializer.superclass(class) {
+            self.initialize(initializer, &superclass)?;
+        }
+
+        let mut superinterfaces = Vec::new();
+        for interface in initializer.superinterfaces(class) {
+            enumerate_superinterfaces(initializer, interface, &mut superinterfaces);
+        }
+
+        for interface in superinterfaces {
+            if initializer.declares_default_methods(&interface) {
+                self.initialize(initializer, &interface)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// records the outcome of an initialization and wakes up the threads waiting for it when dropped
+struct InitializationGuard<'a, C: Clone + Eq + Hash> {
+    table: &'a InitializationTable<C>,
+    class: &'a C,
+    state: InitializationState,
+}
+
+impl<C: Clone + Eq + Hash> Drop for InitializationGuard<'_, C> {
+    fn drop(&mut self) {
+        // the lock is never held while initializers run, but a waiting thread may have panicked
+        let mut states = self
+            .table
+            .states
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        states.insert(self.class.clone(), self.state);
+        drop(states);
+
+        self.table.state_changed.notify_all();
+    }
+}
+
+impl<C: Clone + Eq + Hash> Default for InitializationTable<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// superinterfaces of an interface come before the interface itself
+fn enumerate_superinterfaces<I: ClassInitializer>(
+    initializer: &I,
+    interface: I::Class,
+    output: &mut Vec<I::Class>,
+) {
+    for superinterface in initializer.superinterfaces(&interface) {
+        enumerate_superinterfaces(initializer, superinterface, output);
+    }
+
+    output.push(interface);
+}
+
+/// Whether executing `instruction` requires the class it references to be initialized.
+pub fn is_initialization_trigger(instruction: &Instruction) -> bool {
+    matches!(
+        instruction,
+        Instruction::New { .. }
+            | Instruction::GetStatic { .. }
+            | Instruction::PutStatic { .. }
+            | Instruction::InvokeStatic { .. }
+    )
+}
+
+pub fn declares_default_methods(classfile: &ClassFile) -> bool {
+    classfile
+        .access_flags
+        .flag_set(ClassAccessFlags::ACC_INTERFACE)
+        && classfile.methods.iter().any(|method| {
+            !method
+                .access_flags
+                .flag_set(MethodAccessFlags::ACC_ABSTRACT)
+                && !method.access_flags.flag_set(MethodAccessFlags::ACC_STATIC)
+        })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConstantValue<'clazz> {
+    Int(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    String(&'clazz JStr),
+}
+
+/// Returns the value a static field is assigned during initialization, as given by its
+/// `ConstantValue` attribute.
+pub fn constant_value<'clazz>(
+    classfile: &ClassFile<'clazz>,
+    field: &FieldInfo<'clazz>,
+) -> Option<ConstantValue<'clazz>> {
+    // the attribute is silently ignored on instance fields
+    if !field.access_flags.flag_set(FieldAccessFlags::ACC_STATIC) {
+        return None;
+    }
+
+    let index = field
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute.info {
+            AttributeInfoType::ConstantValueAttribute {
+                constantvalue_index,
+            } => Some(constantvalue_index),
+            _ => None,
+        })?;
+
+    Some(
+        match indy::loadable_constant(&classfile.constant_pool, index).ok()? {
+            LoadableConstant::Int(value) => ConstantValue::Int(value),
+            LoadableConstant::Float(value) => ConstantValue::Float(value),
+            LoadableConstant::Long(value) => ConstantValue::Long(value),
+            LoadableConstant::Double(value) => ConstantValue::Double(value),
+            LoadableConstant::String(value) => ConstantValue::String(value),
+            _ => return None,
+        },
+    )
+}
@@ -0,0 +1,135 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::CpInfo;
+
+pub fn constant_utf8<'clazz>(constant_pool: &[CpInfo<'clazz>], index: u16) -> Option<&'clazz JStr> {
+    let CpInfoType::ConstantUtf8 { string, .. } =
+        constant_pool.get((index as usize).checked_sub(1)?)?.info
+    else {
+        return None;
+    };
+
+    Some(string)
+}
+
+pub fn constant_class_name<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    index: u16,
+) -> Option<&'clazz JStr> {
+    let CpInfoType::ConstantClass { name_index } =
+        constant_pool.get((index as usize).checked_sub(1)?)?.info
+    else {
+        return None;
+    };
+
+    constant_utf8(constant_pool, name_index)
+}
+
+pub fn constant_name_and_type<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    index: u16,
+) -> Option<(&'clazz JStr, &'clazz JStr)> {
+    let CpInfoType::ConstantNameAndType {
+        name_index,
+        descriptor_index,
+    } = constant_pool.get((index as usize).checked_sub(1)?)?.info
+    else {
+        return None;
+    };
+
+    Some((
+        constant_utf8(constant_pool, name_index)?,
+        constant_utf8(constant_pool, descriptor_index)?,
+    ))
+}
+
+// resolves a ConstantFieldRef, ConstantMethodRef or ConstantInterfaceMethodRef into its class
+// name, member name and descriptor
+pub fn constant_member_ref<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    index: u16,
+) -> Option<(&'clazz JStr, &'clazz JStr, &'clazz JStr)> {
+    let (CpInfoType::ConstantFieldRef {
+        class_index,
+        name_and_type_index,
+    }
+    | CpInfoType::ConstantMethodRef {
+        class_index,
+        name_and_type_index,
+    }
+    | CpInfoType::ConstantInterfaceMethodRef {
+        class_index,
+        name_and_type_index,
+    }) = constant_pool.get((index as usize).checked_sub(1)?)?.info
+    else {
+        return None;
+    };
+    let (name, descriptor) = constant_name_and_type(constant_pool, name_and_type_index)?;
+
+    Some((
+        constant_class_name(constant_pool, class_index)?,
+        name,
+        descriptor,
+    ))
+}
+
+pub fn constant_invoke_dynamic<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    index: u16,
+) -> Option<(&'clazz JStr, &'clazz JStr)> {
+    let CpInfoType::ConstantInvokeDynamic {
+        name_and_type_index,
+        ..
+    } = constant_pool.get((index as usize).checked_sub(1)?)?.info
+    else {
+        return None;
+    };
+
+    constant_name_and_type(constant_pool, name_and_type_index)
+}
+
+pub fn constant_module_name<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    index: u16,
+) -> Option<&'clazz JStr> {
+    let CpInfoType::ConstantModule { name_index } =
+        constant_pool.get((index as usize).checked_sub(1)?)?.info
+    else {
+        return None;
+    };
+
+    constant_utf8(constant_pool, name_index)
+}
+
+pub fn constant_package_name<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    index: u16,
+) -> Option<&'clazz JStr> {
+    let CpInfoType::ConstantPackage { name_index } =
+        constant_pool.get((index as usize).checked_sub(1)?)?.info
+    else {
+        return None;
+    };
+
+    constant_utf8(constant_pool, name_index)
+}
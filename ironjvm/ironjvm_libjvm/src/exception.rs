@@ -0,0 +1,176 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_javautil::names::InternalName;
+use ironjvm_specimpl::classfile::attrinfo::cattr::CodeAttributeExceptionTableEntry;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::AttributeInfo;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::MethodInfo;
+
+use crate::cpool;
+
+pub struct StackTraceElement<'clazz> {
+    // the binary name, such as java.lang.String
+    pub declaring_class: JString,
+    pub method_name: &'clazz JStr,
+    pub file_name: Option<&'clazz JStr>,
+    pub line_number: i32,
+}
+
+impl<'clazz> StackTraceElement<'clazz> {
+    // same conventions as java.lang.StackTraceElement
+    pub const UNKNOWN_LINE_NUMBER: i32 = -1;
+    pub const NATIVE_METHOD_LINE_NUMBER: i32 = -2;
+
+    pub fn new(
+        classfile: &ClassFile<'clazz>,
+        method: &MethodInfo<'clazz>,
+        pc: u16,
+    ) -> Option<Self> {
+        let constant_pool = classfile.constant_pool.as_slice();
+        let this_class = cpool::constant_class_name(constant_pool, classfile.this_class)?;
+
+        let line_number = if code_attribute(method).is_none() {
+            Self::NATIVE_METHOD_LINE_NUMBER
+        } else {
+            line_number(method, pc)
+                .map(i32::from)
+                .unwrap_or(Self::UNKNOWN_LINE_NUMBER)
+        };
+
+        Some(Self {
+            declaring_class: InternalName::from_jstr(this_class)
+                .ok()?
+                .to_binary_name()
+                .as_str()
+                .into(),
+            method_name: cpool::constant_utf8(constant_pool, method.name_index)?,
+            file_name: source_file(classfile),
+            line_number,
+        })
+    }
+}
+
+// an activation of a method on a thread's stack, at the instruction it is executing or, in
+// callers, at the invoke instruction it is waiting on
+pub struct Frame<'a, 'clazz> {
+    pub classfile: &'a ClassFile<'clazz>,
+    pub method: &'a MethodInfo<'clazz>,
+    pub pc: u16,
+}
+
+/// Unwinds `frames`, innermost last, for an exception thrown at the pc of the innermost one, as
+/// `athrow` and implicitly thrown exceptions do. Frames without a handler for the exception at
+/// their pc are popped, and the pc of the first frame with one moves to its handler, where the
+/// exception is pushed onto an otherwise empty operand stack.
+///
+/// Returns whether a handler was found. If not, every frame has been popped and the exception
+/// goes to the thread's uncaught exception handler.
+///
+/// `is_caught_by` receives the frame and the `catch_type` of each handler covering its pc, and
+/// returns whether the exception is an instance of that class in the frame's constant pool.
+pub fn unwind<F>(frames: &mut Vec<Frame>, mut is_caught_by: F) -> bool
+where
+    F: FnMut(&Frame, u16) -> bool,
+{
+    while let Some(frame) = frames.last_mut() {
+        let handler_pc = find_exception_handler(frame.method, frame.pc, |catch_type| {
+            is_caught_by(frame, catch_type)
+        });
+
+        if let Some(handler_pc) = handler_pc {
+            frame.pc = handler_pc;
+            return true;
+        }
+
+        frames.pop();
+    }
+
+    false
+}
+
+/// `is_caught_by` receives the `catch_type` of each handler covering `pc` and returns
+/// whether the thrown exception is an instance of that class. Handlers are tried in the order
+/// of the exception table, and a `catch_type` of 0 catches everything.
+pub fn find_exception_handler<F>(method: &MethodInfo, pc: u16, mut is_caught_by: F) -> Option<u16>
+where
+    F: FnMut(u16) -> bool,
+{
+    let (exception_table, _) = code_attribute(method)?;
+
+    exception_table
+        .iter()
+        .find(|entry| {
+            (entry.start_pc..entry.end_pc).contains(&pc)
+                && (entry.catch_type == 0 || is_caught_by(entry.catch_type))
+        })
+        .map(|entry| entry.handler_pc)
+}
+
+pub fn line_number(method: &MethodInfo, pc: u16) -> Option<u16> {
+    let (_, attributes) = code_attribute(method)?;
+
+    attributes
+        .iter()
+        .filter_map(|attribute| match &attribute.info {
+            AttributeInfoType::LineNumberTableAttribute {
+                line_number_table, ..
+            } => Some(line_number_table),
+            _ => None,
+        })
+        .flatten()
+        .filter(|line_number| line_number.start_pc <= pc)
+        .max_by_key(|line_number| line_number.start_pc)
+        .map(|line_number| line_number.line_number)
+}
+
+pub fn source_file<'clazz>(classfile: &ClassFile<'clazz>) -> Option<&'clazz JStr> {
+    classfile
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute.info {
+            AttributeInfoType::SourceFileAttribute { sourcefile_index } => {
+                cpool::constant_utf8(&classfile.constant_pool, sourcefile_index)
+            }
+            _ => None,
+        })
+}
+
+fn code_attribute<'a, 'clazz>(
+    method: &'a MethodInfo<'clazz>,
+) -> Option<(
+    &'a [CodeAttributeExceptionTableEntry],
+    &'a [AttributeInfo<'clazz>],
+)> {
+    method
+        .attributes
+        .iter()
+        .find_map(|attribute| match &attribute.info {
+            AttributeInfoType::CodeAttribute {
+                exception_table,
+                attributes,
+                ..
+            } => Some((exception_table.as_slice(), attributes.as_slice())),
+            _ => None,
+        })
+}
@@ -0,0 +1,186 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Recognition of the bootstrap methods javac emits for string concatenation and lambdas, so
+// their call sites can be linked to built-in implementations instead of running java.lang.invoke.
+
+use ironjvm_javautil::descriptor::method::MethodDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_specimpl::classfile::cpinfo::ReferenceKind;
+
+use crate::indy::BootstrapSpecifier;
+use crate::indy::LoadableConstant;
+use crate::indy::MethodHandleConstant;
+
+// the tags of StringConcatFactory recipes
+const TAG_ARGUMENT: u8 = 0x01;
+const TAG_CONSTANT: u8 = 0x02;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum IndyIntrinsic<'clazz> {
+    /// `StringConcatFactory.makeConcatWithConstants`
+    StringConcat(StringConcatRecipe),
+    /// `LambdaMetafactory.metafactory`
+    Lambda {
+        interface_method_name: &'clazz JStr,
+        factory_type: &'clazz JStr,
+        interface_method_type: &'clazz JStr,
+        implementation: MethodHandleConstant<'clazz>,
+        dynamic_method_type: &'clazz JStr,
+    },
+}
+
+impl<'clazz> IndyIntrinsic<'clazz> {
+    /// Returns `None` if the call site must be linked by its bootstrap method, including when
+    /// the bootstrap method would reject its arguments.
+    pub fn from_specifier(specifier: &BootstrapSpecifier<'clazz>) -> Option<Self> {
+        let method = &specifier.method;
+        if method.reference_kind != ReferenceKind::InvokeStatic {
+            return None;
+        }
+
+        match (method.class.to_str()?, method.name.to_str()?) {
+            ("java/lang/invoke/StringConcatFactory", "makeConcatWithConstants") => {
+                StringConcatRecipe::from_specifier(specifier).map(Self::StringConcat)
+            }
+            ("java/lang/invoke/LambdaMetafactory", "metafactory") => {
+                let [interface_method_type, implementation, dynamic_method_type] =
+                    specifier.arguments[..]
+                else {
+                    return None;
+                };
+
+                let (
+                    LoadableConstant::MethodType(interface_method_type),
+                    LoadableConstant::MethodHandle(implementation),
+                    LoadableConstant::MethodType(dynamic_method_type),
+                ) = (interface_method_type, implementation, dynamic_method_type)
+                else {
+                    return None;
+                };
+
+                Some(Self::Lambda {
+                    interface_method_name: specifier.name,
+                    factory_type: specifier.descriptor,
+                    interface_method_type,
+                    implementation,
+                    dynamic_method_type,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RecipeElement {
+    Argument,
+    Literal(JString),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StringConcatRecipe {
+    pub elements: Vec<RecipeElement>,
+    pub argument_count: usize,
+}
+
+impl StringConcatRecipe {
+    fn from_specifier(specifier: &BootstrapSpecifier) -> Option<Self> {
+        let [LoadableConstant::String(recipe), ref constants @ ..] = specifier.arguments[..] else {
+            return None;
+        };
+
+        let mut constants = constants.iter();
+        let mut elements = Vec::new();
+        let mut literal = JString::new();
+        let mut argument_count = 0;
+        let mut start = 0;
+
+        // the tags are ASCII, so they never occur inside the encoding of another character, and
+        // copying the modified UTF-8 between them keeps unpaired surrogates as they are
+        for (index, byte) in recipe.as_bytes().iter().enumerate() {
+            if !matches!(*byte, TAG_ARGUMENT | TAG_CONSTANT) {
+                continue;
+            }
+
+            literal.push_jstr(&recipe[start..index]);
+            start = index + 1;
+
+            if *byte == TAG_ARGUMENT {
+                if !literal.is_empty() {
+                    elements.push(RecipeElement::Literal(std::mem::take(&mut literal)));
+                }
+
+                elements.push(RecipeElement::Argument);
+                argument_count += 1;
+                continue;
+            }
+
+            // constants are folded into the surrounding literal; those whose string conversion
+            // differs from Rust's are left to the bootstrap method
+            match constants.next()? {
+                LoadableConstant::Int(value) => literal.extend(value.to_string().chars()),
+                LoadableConstant::Long(value) => literal.extend(value.to_string().chars()),
+                LoadableConstant::String(value) => literal.push_jstr(value),
+                _ => return None,
+            }
+        }
+
+        literal.push_jstr(&recipe[start..]);
+        if !literal.is_empty() {
+            elements.push(RecipeElement::Literal(literal));
+        }
+
+        let parameter_count = MethodDescriptor::from_jstr(specifier.descriptor)
+            .ok()?
+            .parameters_iter()
+            .count();
+
+        if constants.next().is_some() || argument_count != parameter_count {
+            return None;
+        }
+
+        Some(Self {
+            elements,
+            argument_count,
+        })
+    }
+
+    /// Concatenates the string conversions of the dynamic arguments of the call site. The
+    /// strings are joined as their UTF-16 contents would be, so that surrogates split between
+    /// them form a pair.
+    pub fn concat(&self, arguments: &[&JStr]) -> Option<JString> {
+        if arguments.len() != self.argument_count {
+            return None;
+        }
+
+        let mut arguments = arguments.iter();
+        let mut output = JString::new();
+        for element in &self.elements {
+            match element {
+                RecipeElement::Argument => output.push_jstr(arguments.next()?),
+                RecipeElement::Literal(literal) => output.push_jstr(literal),
+            }
+        }
+
+        Some(output)
+    }
+}
@@ -0,0 +1,281 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Bootstrap linkage of invokedynamic call sites and dynamically-computed constants
+// (JVMS 5.4.3.6).
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use ironjvm_javautil::be::JavaBeUtil;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::cpinfo::ReferenceKind;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::cpool;
+use crate::indy::error::IndyError;
+use crate::indy::error::IndyResult;
+use crate::indy::intrinsic::IndyIntrinsic;
+
+pub mod error;
+pub mod intrinsic;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MethodHandleConstant<'clazz> {
+    pub reference_kind: ReferenceKind,
+    pub class: &'clazz JStr,
+    pub name: &'clazz JStr,
+    pub descriptor: &'clazz JStr,
+    // whether the handle refers to a ConstantInterfaceMethodRef
+    pub is_interface: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoadableConstant<'clazz> {
+    Int(i32),
+    Float(f32),
+    Long(i64),
+    Double(f64),
+    Class(&'clazz JStr),
+    String(&'clazz JStr),
+    MethodHandle(MethodHandleConstant<'clazz>),
+    MethodType(&'clazz JStr),
+    // computed by its own bootstrap method, see bootstrap_specifier
+    Dynamic { index: u16 },
+}
+
+/// The symbolic information passed to a bootstrap method.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BootstrapSpecifier<'clazz> {
+    pub method: MethodHandleConstant<'clazz>,
+    pub name: &'clazz JStr,
+    pub descriptor: &'clazz JStr,
+    pub arguments: Vec<LoadableConstant<'clazz>>,
+}
+
+/// Invokes bootstrap methods on behalf of the linker.
+pub trait BootstrapInvoker {
+    /// A `CallSite` for `invokedynamic`, or the value of a dynamically-computed constant.
+    type Value: Clone;
+    type Throwable: Clone;
+
+    /// Invokes the bootstrap method of `specifier`, checking the type of what it returns.
+    fn invoke_bootstrap(
+        &self,
+        specifier: &BootstrapSpecifier,
+    ) -> Result<Self::Value, Self::Throwable>;
+
+    /// Links a call site recognised as an intrinsic without running its bootstrap method, or
+    /// returns `None` to fall back to it.
+    fn link_intrinsic(&self, _intrinsic: &IndyIntrinsic) -> Option<Self::Value> {
+        None
+    }
+
+    fn is_error(&self, throwable: &Self::Throwable) -> bool;
+
+    fn bootstrap_method_error(&self, cause: Self::Throwable) -> Self::Throwable;
+}
+
+/// The outcome of bootstrapping each call site or dynamically-computed constant. Call sites are
+/// keyed by instruction, as every `invokedynamic` instruction is linked separately, while
+/// constants are keyed by constant pool index.
+pub struct LinkageCache<K, V, T> {
+    linked: Mutex<HashMap<K, Result<V, T>>>,
+    use_intrinsics: bool,
+}
+
+impl<K: Eq + Hash, V: Clone, T: Clone> LinkageCache<K, V, T> {
+    pub fn new(use_intrinsics: bool) -> Self {
+        Self {
+            linked: Mutex::new(HashMap::new()),
+            use_intrinsics,
+        }
+    }
+
+    pub fn link<I: BootstrapInvoker<Value = V, Throwable = T>>(
+        &self,
+        invoker: &I,
+        key: K,
+        specifier: &BootstrapSpecifier,
+    ) -> Result<V, T> {
+        if let Some(result) = self.linked.lock().unwrap().get(&key) {
+            return result.clone();
+        }
+
+        // bootstrap methods may run concurrently for the same key, in which case the first
+        // result to complete is used by every thread
+        let intrinsic = self
+            .use_intrinsics
+            .then(|| IndyIntrinsic::from_specifier(specifier))
+            .flatten()
+            .and_then(|intrinsic| invoker.link_intrinsic(&intrinsic));
+
+        let result = match intrinsic {
+            Some(value) => Ok(value),
+            None => invoker.invoke_bootstrap(specifier).map_err(|exception| {
+                if invoker.is_error(&exception) {
+                    exception
+                } else {
+                    invoker.bootstrap_method_error(exception)
+                }
+            }),
+        };
+
+        self.linked
+            .lock()
+            .unwrap()
+            .entry(key)
+            .or_insert(result)
+            .clone()
+    }
+}
+
+impl<K: Eq + Hash, V: Clone, T: Clone> Default for LinkageCache<K, V, T> {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+/// Collects the bootstrap specifier of a `ConstantInvokeDynamic` or `ConstantDynamic` entry.
+pub fn bootstrap_specifier<'clazz>(
+    classfile: &ClassFile<'clazz>,
+    index: u16,
+) -> IndyResult<BootstrapSpecifier<'clazz>> {
+    let constant_pool = &classfile.constant_pool;
+    let invalid = |index| IndyError::InvalidConstantPoolEntry { index };
+
+    let (CpInfoType::ConstantInvokeDynamic {
+        bootstrap_method_attr_index,
+        name_and_type_index,
+    }
+    | CpInfoType::ConstantDynamic {
+        bootstrap_method_attr_index,
+        name_and_type_index,
+    }) = constant_info(constant_pool, index).ok_or(invalid(index))?
+    else {
+        return Err(invalid(index));
+    };
+
+    let (name, descriptor) = cpool::constant_name_and_type(constant_pool, *name_and_type_index)
+        .ok_or(invalid(*name_and_type_index))?;
+
+    let bootstrap_methods = classfile
+        .attributes
+        .iter()
+        .find_map(|attribute| match &attribute.info {
+            AttributeInfoType::BootstrapMethodsAttribute {
+                bootstrap_methods, ..
+            } => Some(bootstrap_methods),
+            _ => None,
+        })
+        .ok_or(IndyError::MissingBootstrapMethodsAttribute)?;
+
+    let bootstrap_method = bootstrap_methods
+        .get(*bootstrap_method_attr_index as usize)
+        .ok_or(IndyError::InvalidBootstrapMethodIndex {
+            index: *bootstrap_method_attr_index,
+        })?;
+
+    let LoadableConstant::MethodHandle(method) =
+        loadable_constant(constant_pool, bootstrap_method.bootstrap_method_ref)?
+    else {
+        return Err(invalid(bootstrap_method.bootstrap_method_ref));
+    };
+
+    let arguments = bootstrap_method
+        .bootstrap_arguments
+        .iter()
+        .map(|index| loadable_constant(constant_pool, index.to_u16()))
+        .collect::<IndyResult<_>>()?;
+
+    Ok(BootstrapSpecifier {
+        method,
+        name,
+        descriptor,
+        arguments,
+    })
+}
+
+pub fn loadable_constant<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    index: u16,
+) -> IndyResult<LoadableConstant<'clazz>> {
+    let invalid = || IndyError::InvalidConstantPoolEntry { index };
+
+    Ok(
+        match *constant_info(constant_pool, index).ok_or_else(invalid)? {
+            CpInfoType::ConstantInteger { bytes } => LoadableConstant::Int(bytes as i32),
+            CpInfoType::ConstantFloat { bytes } => LoadableConstant::Float(f32::from_bits(bytes)),
+            CpInfoType::ConstantLong {
+                high_bytes,
+                low_bytes,
+            } => LoadableConstant::Long(((high_bytes as u64) << 32 | low_bytes as u64) as i64),
+            CpInfoType::ConstantDouble {
+                high_bytes,
+                low_bytes,
+            } => LoadableConstant::Double(f64::from_bits(
+                (high_bytes as u64) << 32 | low_bytes as u64,
+            )),
+            CpInfoType::ConstantClass { .. } => LoadableConstant::Class(
+                cpool::constant_class_name(constant_pool, index).ok_or_else(invalid)?,
+            ),
+            CpInfoType::ConstantString { string_index } => LoadableConstant::String(
+                cpool::constant_utf8(constant_pool, string_index).ok_or_else(invalid)?,
+            ),
+            CpInfoType::ConstantMethodHandle {
+                reference_kind,
+                reference_index,
+            } => {
+                let reference_kind = ReferenceKind::from_u8(reference_kind).ok_or_else(invalid)?;
+                let (class, name, descriptor) =
+                    cpool::constant_member_ref(constant_pool, reference_index)
+                        .ok_or_else(invalid)?;
+                let is_interface = matches!(
+                    constant_info(constant_pool, reference_index),
+                    Some(CpInfoType::ConstantInterfaceMethodRef { .. })
+                );
+
+                LoadableConstant::MethodHandle(MethodHandleConstant {
+                    reference_kind,
+                    class,
+                    name,
+                    descriptor,
+                    is_interface,
+                })
+            }
+            CpInfoType::ConstantMethodType { descriptor_index } => LoadableConstant::MethodType(
+                cpool::constant_utf8(constant_pool, descriptor_index).ok_or_else(invalid)?,
+            ),
+            CpInfoType::ConstantDynamic { .. } => LoadableConstant::Dynamic { index },
+            _ => return Err(invalid()),
+        },
+    )
+}
+
+fn constant_info<'a, 'clazz>(
+    constant_pool: &'a [CpInfo<'clazz>],
+    index: u16,
+) -> Option<&'a CpInfoType<'clazz>> {
+    Some(&constant_pool.get((index as usize).checked_sub(1)?)?.info)
+}
@@ -23,16 +23,31 @@ use std::ffi::c_void;
 use jni_sys::jint;
 use jni_sys::JavaVM;
 use jni_sys::JavaVMInitArgs;
+use jni_sys::JNI_EINVAL;
+use jni_sys::JNI_ERR;
 use jni_sys::JNI_EVERSION;
 use jni_sys::JNI_OK;
 use jni_sys::JNI_VERSION_10;
 
+use crate::vmopts::error::VmOptionsError;
+use crate::vmopts::VmOptions;
+
 #[no_mangle]
 pub extern "C" fn JNI_CreateJavaVM(
     _: *mut *mut JavaVM,
     _: *mut *mut c_void,
-    _: *mut c_void,
+    vm_args: *mut c_void,
 ) -> jint {
+    // FIXME: hand the parsed options over to the VM once one is actually created here
+    // the invocation API fails unrecognized options with JNI_ERR, and malformed ones with
+    // JNI_EINVAL
+    if let Err(error) = unsafe { VmOptions::from_init_args(vm_args as *const JavaVMInitArgs) } {
+        return match error {
+            VmOptionsError::UnrecognizedOption { .. } => JNI_ERR,
+            _ => JNI_EINVAL,
+        };
+    }
+
     JNI_OK
 }
 
@@ -0,0 +1,131 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// The string conversions behind the string functions of the JNI, between the modified UTF-8 of
+// native code and the contents of java.lang.String.
+
+use std::ffi::CStr;
+use std::os::raw::c_char;
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+
+/// The contents of a `java.lang.String`, held one byte per character when they are all Latin-1
+/// and compact strings are enabled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum StringValue {
+    Latin1(Vec<u8>),
+    Utf16(Vec<u16>),
+}
+
+impl StringValue {
+    pub fn from_jstr(jstr: &JStr, compact_strings: bool) -> Self {
+        match jstr.to_latin1() {
+            Some(bytes) if compact_strings => Self::Latin1(bytes),
+            _ => Self::Utf16(jstr.to_utf16()),
+        }
+    }
+
+    pub fn from_utf16(units: &[u16], compact_strings: bool) -> Self {
+        if compact_strings && units.iter().all(|unit| *unit <= 0xFF) {
+            Self::Latin1(units.iter().map(|unit| *unit as u8).collect())
+        } else {
+            Self::Utf16(units.to_vec())
+        }
+    }
+
+    /// The number of UTF-16 code units, as returned by `GetStringLength`.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Latin1(bytes) => bytes.len(),
+            Self::Utf16(units) => units.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A copy of the characters, as returned by `GetStringChars`.
+    pub fn to_utf16(&self) -> Vec<u16> {
+        match self {
+            Self::Latin1(bytes) => bytes.iter().map(|byte| u16::from(*byte)).collect(),
+            Self::Utf16(units) => units.clone(),
+        }
+    }
+
+    pub fn to_jstring(&self) -> JString {
+        match self {
+            Self::Latin1(bytes) => JString::from_latin1(bytes),
+            Self::Utf16(units) => JString::from_utf16(units),
+        }
+    }
+
+    /// The NUL-terminated modified UTF-8 returned by `GetStringUTFChars`.
+    pub fn utf_chars(&self) -> Vec<u8> {
+        let mut bytes = self.to_jstring().as_bytes().to_vec();
+        bytes.push(0);
+
+        bytes
+    }
+
+    /// The length of the modified UTF-8 encoding without its terminator, as returned by
+    /// `GetStringUTFLength`.
+    pub fn utf_length(&self) -> usize {
+        self.to_jstring().len()
+    }
+
+    /// The characters copied by `GetStringRegion`, or `None` if the region is out of bounds and
+    /// a `StringIndexOutOfBoundsException` is to be thrown.
+    pub fn region(&self, start: usize, len: usize) -> Option<Vec<u16>> {
+        let end = start.checked_add(len).filter(|end| *end <= self.len())?;
+
+        Some(match self {
+            Self::Latin1(bytes) => bytes[start..end]
+                .iter()
+                .map(|byte| u16::from(*byte))
+                .collect(),
+            Self::Utf16(units) => units[start..end].to_vec(),
+        })
+    }
+
+    /// The NUL-terminated modified UTF-8 copied by `GetStringUTFRegion`, where the region is
+    /// given in UTF-16 code units.
+    pub fn utf_region(&self, start: usize, len: usize) -> Option<Vec<u8>> {
+        let mut bytes = JString::from_utf16(&self.region(start, len)?)
+            .as_bytes()
+            .to_vec();
+        bytes.push(0);
+
+        Some(bytes)
+    }
+}
+
+/// Creates the contents of the string returned by `NewStringUTF`, or `None` if `utf` is not
+/// valid modified UTF-8.
+///
+/// # Safety
+///
+/// `utf` must point to a NUL-terminated string.
+pub unsafe fn new_string_utf(utf: *const c_char, compact_strings: bool) -> Option<StringValue> {
+    let jstr = JStr::from_jutf8(CStr::from_ptr(utf).to_bytes()).ok()?;
+
+    Some(StringValue::from_jstr(jstr, compact_strings))
+}
@@ -20,4 +20,19 @@
 
 //! Implementation of the LibJVM dynamic library.
 
+#![feature(let_else)]
+
+mod cpool;
+
+pub mod classinit;
+pub mod exception;
+pub mod indy;
 pub mod jniinv;
+pub mod jnistring;
+pub mod methodhandle;
+pub mod module;
+pub mod npe;
+pub mod reflect;
+pub mod resolve;
+pub mod stringtable;
+pub mod vmopts;
@@ -0,0 +1,224 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// The VM side of java.lang.invoke: resolution of method handle constants (JVMS 5.4.3.5) and
+// recognition of signature polymorphic methods (JVMS 2.9.3).
+
+use ironjvm_javautil::descriptor::method::MethodDescriptor;
+use ironjvm_javautil::descriptor::method::ParameterDescriptor;
+use ironjvm_javautil::descriptor::method::ReturnDescriptor;
+use ironjvm_javautil::descriptor::BaseType;
+use ironjvm_javautil::descriptor::TypeDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::cpinfo::ReferenceKind;
+use ironjvm_specimpl::classfile::flags::FlagsExt;
+use ironjvm_specimpl::classfile::flags::MethodAccessFlags;
+use ironjvm_specimpl::classfile::CpInfo;
+use ironjvm_specimpl::classfile::MethodInfo;
+
+use crate::cpool;
+use crate::indy::MethodHandleConstant;
+use crate::resolve;
+use crate::resolve::error::ResolutionError;
+use crate::resolve::error::ResolutionResult;
+use crate::resolve::ClassLoader;
+use crate::resolve::DirectReference;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MethodType<'a> {
+    pub parameters: Vec<ParameterDescriptor<'a>>,
+    pub return_type: ReturnDescriptor<'a>,
+}
+
+impl<'a> MethodType<'a> {
+    pub fn from_jstr(descriptor: &'a JStr) -> Option<Self> {
+        let descriptor = MethodDescriptor::from_jstr(descriptor).ok()?;
+
+        Some(Self {
+            parameters: descriptor.parameters_iter().collect(),
+            return_type: descriptor.return_type(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DirectMethodHandle<C> {
+    pub kind: ReferenceKind,
+    pub reference: DirectReference<C>,
+}
+
+/// The intrinsics of `MethodHandle` that lambda forms are compiled to. `invokeBasic` takes the
+/// target method handle as its receiver, while the `linkTo*` methods take the `MemberName` to
+/// call as their trailing argument.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MethodHandleIntrinsic {
+    InvokeBasic,
+    LinkToVirtual,
+    LinkToStatic,
+    LinkToSpecial,
+    LinkToInterface,
+    LinkToNative,
+}
+
+impl MethodHandleIntrinsic {
+    pub fn from_method(class_name: &JStr, name: &JStr) -> Option<Self> {
+        if class_name != "java/lang/invoke/MethodHandle" {
+            return None;
+        }
+
+        Some(match name.to_str()? {
+            "invokeBasic" => Self::InvokeBasic,
+            "linkToVirtual" => Self::LinkToVirtual,
+            "linkToStatic" => Self::LinkToStatic,
+            "linkToSpecial" => Self::LinkToSpecial,
+            "linkToInterface" => Self::LinkToInterface,
+            "linkToNative" => Self::LinkToNative,
+            _ => return None,
+        })
+    }
+}
+
+/// Whether a method declared in `class_name` is signature polymorphic, such as
+/// `MethodHandle.invokeExact` or `VarHandle.get`.
+pub fn is_signature_polymorphic(
+    constant_pool: &[CpInfo],
+    class_name: &JStr,
+    method: &MethodInfo,
+) -> bool {
+    if class_name != "java/lang/invoke/MethodHandle" && class_name != "java/lang/invoke/VarHandle" {
+        return false;
+    }
+
+    if !method.access_flags.flag_set(MethodAccessFlags::ACC_VARARGS)
+        || !method.access_flags.flag_set(MethodAccessFlags::ACC_NATIVE)
+    {
+        return false;
+    }
+
+    let Some(descriptor) = cpool::constant_utf8(constant_pool, method.descriptor_index) else {
+        return false;
+    };
+    let Ok(descriptor) = MethodDescriptor::from_jstr(descriptor) else {
+        return false;
+    };
+
+    let mut parameters = descriptor.parameters_iter();
+    let is_object_array = matches!(
+        parameters.next(),
+        Some(TypeDescriptor {
+            dimensions: 1,
+            r#type: BaseType::Object(name),
+        }) if name == "java/lang/Object"
+    );
+
+    is_object_array && parameters.next().is_none()
+}
+
+pub fn resolve_method_handle<L: ClassLoader>(
+    loader: &L,
+    accessing: L::Class,
+    constant: &MethodHandleConstant,
+) -> ResolutionResult<DirectMethodHandle<L::Class>> {
+    if constant.reference_kind == ReferenceKind::NewInvokeSpecial && constant.name != "<init>" {
+        return Err(ResolutionError::IncompatibleClassChangeError {
+            message: "REF_newInvokeSpecial must refer to a constructor".to_string(),
+        });
+    }
+
+    let field = |expect_static| {
+        let (class, index) = resolve::resolve_field(
+            loader,
+            accessing,
+            constant.class,
+            constant.name,
+            constant.descriptor,
+        )?;
+        let reference = DirectReference::Field { class, index };
+        resolve::check_static(loader, reference, expect_static)?;
+
+        Ok(reference)
+    };
+
+    let method = |expect_static| {
+        let (class, index) = if constant.is_interface {
+            resolve::resolve_interface_method(
+                loader,
+                accessing,
+                constant.class,
+                constant.name,
+                constant.descriptor,
+            )?
+        } else {
+            resolve::resolve_method(
+                loader,
+                accessing,
+                constant.class,
+                constant.name,
+                constant.descriptor,
+            )?
+        };
+        let reference = DirectReference::Method { class, index };
+        resolve::check_static(loader, reference, expect_static)?;
+
+        Ok(reference)
+    };
+
+    let reference = match constant.reference_kind {
+        ReferenceKind::GetField | ReferenceKind::PutField => field(false)?,
+        ReferenceKind::GetStatic | ReferenceKind::PutStatic => field(true)?,
+        ReferenceKind::InvokeStatic => method(true)?,
+        ReferenceKind::InvokeVirtual
+        | ReferenceKind::InvokeSpecial
+        | ReferenceKind::NewInvokeSpecial
+        | ReferenceKind::InvokeInterface => method(false)?,
+    };
+
+    // the classes named by the type of the handle are resolved as well
+    let descriptor = match constant.reference_kind {
+        ReferenceKind::GetField
+        | ReferenceKind::GetStatic
+        | ReferenceKind::PutField
+        | ReferenceKind::PutStatic => TypeDescriptor::from_jstr(constant.descriptor)
+            .ok()
+            .into_iter()
+            .collect(),
+        _ => MethodType::from_jstr(constant.descriptor)
+            .map(|method_type| {
+                let mut types = method_type.parameters;
+                if let ReturnDescriptor::FieldType(return_type) = method_type.return_type {
+                    types.push(return_type);
+                }
+
+                types
+            })
+            .unwrap_or_default(),
+    };
+
+    for r#type in descriptor {
+        if let BaseType::Object(name) = r#type.r#type {
+            resolve::resolve_class(loader, accessing, name)?;
+        }
+    }
+
+    Ok(DirectMethodHandle {
+        kind: constant.reference_kind,
+        reference,
+    })
+}
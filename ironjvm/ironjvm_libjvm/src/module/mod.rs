@@ -0,0 +1,547 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// The module system: module declarations read from module-info classes, resolution of the module
+// graph, and the readability and accessibility relations it defines.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::be::JavaBeUtil;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::flags::ClassAccessFlags;
+use ironjvm_specimpl::classfile::flags::FlagsExt;
+use ironjvm_specimpl::classfile::flags::ModuleFlags;
+use ironjvm_specimpl::classfile::flags::ModuleRequireFlags;
+use ironjvm_specimpl::classfile::ClassFile;
+
+use crate::cpool;
+use crate::module::error::ModuleError;
+use crate::module::error::ModuleResult;
+
+pub mod error;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleRequires {
+    pub module: String,
+    pub is_transitive: bool,
+    pub is_static: bool,
+}
+
+// an exported or opened package, granted to every module if there are no targets
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackageGrant {
+    pub package: String,
+    pub targets: Vec<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleProvides {
+    pub service: String,
+    pub providers: Vec<String>,
+}
+
+/// A module declaration, with packages and classes named in internal form.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleDescriptor {
+    pub name: String,
+    pub is_open: bool,
+    pub requires: Vec<ModuleRequires>,
+    pub exports: Vec<PackageGrant>,
+    pub opens: Vec<PackageGrant>,
+    pub uses: Vec<String>,
+    pub provides: Vec<ModuleProvides>,
+    pub packages: BTreeSet<String>,
+    pub main_class: Option<String>,
+}
+
+impl ModuleDescriptor {
+    pub fn from_classfile(classfile: &ClassFile) -> ModuleResult<Self> {
+        if !classfile
+            .access_flags
+            .flag_set(ClassAccessFlags::ACC_MODULE)
+        {
+            return Err(ModuleError::InvalidModuleDescriptor);
+        }
+
+        let constant_pool = &classfile.constant_pool;
+        let string = |name: Option<&JStr>| {
+            name.map(|string| string.to_string_lossy().into_owned())
+                .ok_or(ModuleError::InvalidModuleDescriptor)
+        };
+        let class_name = |index: u16| string(cpool::constant_class_name(constant_pool, index));
+        let module_name = |index: u16| string(cpool::constant_module_name(constant_pool, index));
+        let package_name = |index: u16| string(cpool::constant_package_name(constant_pool, index));
+        let grant = |package_index: u16, targets: &[[u8; 2]]| {
+            Ok(PackageGrant {
+                package: package_name(package_index)?,
+                targets: targets
+                    .iter()
+                    .map(|index| module_name(index.to_u16()))
+                    .collect::<ModuleResult<_>>()?,
+            })
+        };
+
+        let mut descriptor = None;
+        let mut packages = BTreeSet::new();
+        let mut main_class = None;
+
+        for attribute in &classfile.attributes {
+            match &attribute.info {
+                AttributeInfoType::ModuleAttribute {
+                    module_name_index,
+                    module_flags,
+                    requires,
+                    exports,
+                    opens,
+                    uses_index,
+                    provides,
+                    ..
+                } => {
+                    descriptor = Some(Self {
+                        name: module_name(*module_name_index)?,
+                        is_open: module_flags.flag_set(ModuleFlags::ACC_OPEN),
+                        requires: requires
+                            .iter()
+                            .map(|requires| {
+                                Ok(ModuleRequires {
+                                    module: module_name(requires.requires_index)?,
+                                    is_transitive: requires
+                                        .requires_flags
+                                        .flag_set(ModuleRequireFlags::ACC_TRANSITIVE),
+                                    is_static: requires
+                                        .requires_flags
+                                        .flag_set(ModuleRequireFlags::ACC_STATIC_PHASE),
+                                })
+                            })
+                            .collect::<ModuleResult<_>>()?,
+                        exports: exports
+                            .iter()
+                            .map(|exports| grant(exports.exports_index, exports.exports_to_index))
+                            .collect::<ModuleResult<_>>()?,
+                        opens: opens
+                            .iter()
+                            .map(|opens| grant(opens.opens_index, opens.opens_to_index))
+                            .collect::<ModuleResult<_>>()?,
+                        uses: uses_index
+                            .iter()
+                            .map(|index| class_name(index.to_u16()))
+                            .collect::<ModuleResult<_>>()?,
+                        provides: provides
+                            .iter()
+                            .map(|provides| {
+                                Ok(ModuleProvides {
+                                    service: class_name(provides.provides_index)?,
+                                    providers: provides
+                                        .provides_with_index
+                                        .iter()
+                                        .map(|index| class_name(index.to_u16()))
+                                        .collect::<ModuleResult<_>>()?,
+                                })
+                            })
+                            .collect::<ModuleResult<_>>()?,
+                        packages: BTreeSet::new(),
+                        main_class: None,
+                    });
+                }
+                AttributeInfoType::ModulePackagesAttribute { package_index, .. } => {
+                    for index in package_index.iter() {
+                        packages.insert(package_name(index.to_u16())?);
+                    }
+                }
+                AttributeInfoType::ModuleMainClassAttribute { main_class_index } => {
+                    main_class = Some(class_name(*main_class_index)?);
+                }
+                _ => {}
+            }
+        }
+
+        let mut descriptor = descriptor.ok_or(ModuleError::InvalidModuleDescriptor)?;
+
+        // without a ModulePackages attribute, only the packages named by the declaration itself
+        // are known
+        packages.extend(
+            descriptor
+                .exports
+                .iter()
+                .chain(&descriptor.opens)
+                .map(|grant| grant.package.clone()),
+        );
+        packages.extend(
+            descriptor
+                .provides
+                .iter()
+                .flat_map(|provides| &provides.providers)
+                .chain(&main_class)
+                .filter_map(|class| Some(class.rsplit_once('/')?.0.to_string())),
+        );
+
+        descriptor.packages = packages;
+        descriptor.main_class = main_class;
+
+        Ok(descriptor)
+    }
+}
+
+/// Finds the modules on a module path made of exploded module directories and directories of
+/// them. When several modules share a name, the first one found is used.
+// FIXME: modular JAR files, and the system modules in the runtime image
+pub fn find_modules(module_path: &[PathBuf]) -> ModuleResult<Vec<ModuleDescriptor>> {
+    let mut modules: Vec<ModuleDescriptor> = Vec::new();
+
+    for entry in module_path {
+        let mut directories = Vec::new();
+        if entry.join("module-info.class").is_file() {
+            directories.push(entry.clone());
+        } else if entry.is_dir() {
+            let mut children = fs::read_dir(entry)?
+                .map(|child| child.map(|child| child.path()))
+                .collect::<Result<Vec<_>, _>>()?;
+            children.sort();
+
+            directories.extend(
+                children
+                    .into_iter()
+                    .filter(|child| child.join("module-info.class").is_file()),
+            );
+        }
+
+        for directory in directories {
+            let bytes = fs::read(directory.join("module-info.class"))?;
+            let classfile = ClassFileParser::new(&bytes)
+                .parse()
+                .map_err(|_| ModuleError::InvalidModuleDescriptor)?;
+
+            let mut descriptor = ModuleDescriptor::from_classfile(&classfile)?;
+            exploded_packages(&directory, "", &mut descriptor.packages)?;
+
+            if !modules.iter().any(|module| module.name == descriptor.name) {
+                modules.push(descriptor);
+            }
+        }
+    }
+
+    Ok(modules)
+}
+
+// every directory holding a class file is a package of an exploded module
+fn exploded_packages(
+    directory: &Path,
+    package: &str,
+    packages: &mut BTreeSet<String>,
+) -> ModuleResult<()> {
+    for child in fs::read_dir(directory)? {
+        let path = child?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+
+        if path.is_dir() {
+            let subpackage = if package.is_empty() {
+                name.to_string()
+            } else {
+                format!("{package}/{name}")
+            };
+
+            exploded_packages(&path, &subpackage, packages)?;
+        } else if !package.is_empty() && name.ends_with(".class") {
+            packages.insert(package.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+struct ResolvedModule {
+    descriptor: ModuleDescriptor,
+    reads: BTreeSet<String>,
+}
+
+/// A resolved set of modules. Queries take `None` for the unnamed module, which reads every
+/// module and exports all of its packages, but is read by no named module.
+pub struct ModuleGraph {
+    modules: BTreeMap<String, ResolvedModule>,
+    packages: HashMap<String, String>,
+}
+
+impl ModuleGraph {
+    /// Resolves `roots` and the modules they require from `available`, then adds the modules
+    /// providing services used by the resolved modules, as `Configuration.resolveAndBind` does.
+    pub fn resolve(available: Vec<ModuleDescriptor>, roots: &[&str]) -> ModuleResult<Self> {
+        let mut by_name = HashMap::new();
+        for descriptor in available {
+            by_name.entry(descriptor.name.clone()).or_insert(descriptor);
+        }
+
+        let mut resolved: BTreeMap<String, ModuleDescriptor> = BTreeMap::new();
+        let mut pending = roots
+            .iter()
+            .map(|root| (root.to_string(), None))
+            .collect::<Vec<(String, Option<String>)>>();
+
+        loop {
+            while let Some((name, required_by)) = pending.pop() {
+                if resolved.contains_key(&name) {
+                    continue;
+                }
+
+                let Some(descriptor) = by_name.get(&name) else {
+                    return Err(ModuleError::ModuleNotFound {
+                        module: name,
+                        required_by,
+                    });
+                };
+
+                // static dependences are only read if something else resolves them
+                for requires in descriptor
+                    .requires
+                    .iter()
+                    .filter(|requires| !requires.is_static)
+                {
+                    pending.push((requires.module.clone(), Some(name.clone())));
+                }
+
+                resolved.insert(name, descriptor.clone());
+            }
+
+            let used = resolved
+                .values()
+                .flat_map(|descriptor| descriptor.uses.iter().cloned())
+                .collect::<BTreeSet<_>>();
+
+            for descriptor in by_name.values() {
+                if !resolved.contains_key(&descriptor.name)
+                    && descriptor
+                        .provides
+                        .iter()
+                        .any(|provides| used.contains(&provides.service))
+                {
+                    pending.push((descriptor.name.clone(), None));
+                }
+            }
+
+            if pending.is_empty() {
+                break;
+            }
+        }
+
+        let mut visited = BTreeSet::new();
+        for name in resolved.keys() {
+            check_cycles(&resolved, name, &mut BTreeSet::new(), &mut visited)?;
+        }
+
+        let mut packages = HashMap::new();
+        for descriptor in resolved.values() {
+            for package in &descriptor.packages {
+                if let Some(first) = packages.insert(package.clone(), descriptor.name.clone()) {
+                    return Err(ModuleError::SplitPackage {
+                        package: package.clone(),
+                        first,
+                        second: descriptor.name.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut modules = BTreeMap::new();
+        for (name, descriptor) in &resolved {
+            let mut reads = BTreeSet::new();
+            for requires in &descriptor.requires {
+                add_readability(&resolved, &requires.module, &mut reads);
+            }
+
+            modules.insert(
+                name.clone(),
+                ResolvedModule {
+                    descriptor: descriptor.clone(),
+                    reads,
+                },
+            );
+        }
+
+        Ok(Self { modules, packages })
+    }
+
+    pub fn module(&self, name: &str) -> Option<&ModuleDescriptor> {
+        self.modules.get(name).map(|module| &module.descriptor)
+    }
+
+    /// Returns `None` if `package` belongs to the unnamed module.
+    pub fn module_of_package(&self, package: &str) -> Option<&str> {
+        self.packages.get(package).map(String::as_str)
+    }
+
+    pub fn reads(&self, from: Option<&str>, to: Option<&str>) -> bool {
+        match (from, to) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(from), Some(to)) => {
+                from == to
+                    || matches!(self.modules.get(from), Some(module) if module.reads.contains(to))
+            }
+        }
+    }
+
+    // opened packages are exported as well at run time
+    pub fn is_exported(&self, module: Option<&str>, package: &str, to: Option<&str>) -> bool {
+        let Some(name) = module else {
+            return true;
+        };
+        let Some(module) = self.modules.get(name) else {
+            return false;
+        };
+
+        Some(name) == to
+            || is_granted(&module.descriptor.exports, package, to)
+            || self.is_open(Some(name), package, to)
+    }
+
+    pub fn is_open(&self, module: Option<&str>, package: &str, to: Option<&str>) -> bool {
+        let Some(name) = module else {
+            return true;
+        };
+        let Some(module) = self.modules.get(name) else {
+            return false;
+        };
+
+        Some(name) == to
+            || (module.descriptor.is_open && module.descriptor.packages.contains(package))
+            || is_granted(&module.descriptor.opens, package, to)
+    }
+
+    /// Whether code in module `from` can access the public types of `package` in module `to`.
+    /// Reflective access to members that are not public additionally needs the package to be
+    /// open.
+    pub fn can_access(
+        &self,
+        from: Option<&str>,
+        to: Option<&str>,
+        package: &str,
+        reflective: bool,
+    ) -> bool {
+        self.reads(from, to)
+            && if reflective {
+                self.is_open(to, package, from)
+            } else {
+                self.is_exported(to, package, from)
+            }
+    }
+
+    /// The modules and classes providing `service`, as `ServiceLoader` finds them. Returns
+    /// `None` if `consumer` is a named module not declaring that it uses the service.
+    pub fn providers(&self, consumer: Option<&str>, service: &str) -> Option<Vec<(&str, &str)>> {
+        if let Some(consumer) = consumer {
+            if !self
+                .modules
+                .get(consumer)?
+                .descriptor
+                .uses
+                .iter()
+                .any(|uses| uses == service)
+            {
+                return None;
+            }
+        }
+
+        Some(
+            self.modules
+                .iter()
+                .flat_map(|(name, module)| {
+                    module
+                        .descriptor
+                        .provides
+                        .iter()
+                        .filter(|provides| provides.service == service)
+                        .flat_map(move |provides| {
+                            provides
+                                .providers
+                                .iter()
+                                .map(move |provider| (name.as_str(), provider.as_str()))
+                        })
+                })
+                .collect(),
+        )
+    }
+}
+
+fn check_cycles<'a>(
+    resolved: &'a BTreeMap<String, ModuleDescriptor>,
+    name: &'a str,
+    visiting: &mut BTreeSet<&'a str>,
+    visited: &mut BTreeSet<&'a str>,
+) -> ModuleResult<()> {
+    if visited.contains(name) {
+        return Ok(());
+    }
+
+    if !visiting.insert(name) {
+        return Err(ModuleError::CycleDetected {
+            module: name.to_string(),
+        });
+    }
+
+    for requires in &resolved[name].requires {
+        if resolved.contains_key(&requires.module) {
+            check_cycles(resolved, &requires.module, visiting, visited)?;
+        }
+    }
+
+    visiting.remove(name);
+    visited.insert(name);
+
+    Ok(())
+}
+
+// a module reads what it requires, and whatever that requires transitively
+fn add_readability(
+    resolved: &BTreeMap<String, ModuleDescriptor>,
+    name: &str,
+    reads: &mut BTreeSet<String>,
+) {
+    let Some(descriptor) = resolved.get(name) else {
+        return;
+    };
+
+    if !reads.insert(name.to_string()) {
+        return;
+    }
+
+    for requires in descriptor
+        .requires
+        .iter()
+        .filter(|requires| requires.is_transitive)
+    {
+        add_readability(resolved, &requires.module, reads);
+    }
+}
+
+fn is_granted(grants: &[PackageGrant], package: &str, to: Option<&str>) -> bool {
+    grants.iter().any(|grant| {
+        grant.package == package
+            && (grant.targets.is_empty()
+                || matches!(to, Some(to) if grant.targets.iter().any(|target| target == to)))
+    })
+}
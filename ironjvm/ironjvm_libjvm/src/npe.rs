@@ -0,0 +1,758 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Helpful NullPointerException messages, as specified by JEP 358 and implemented in HotSpot's
+// bytecodeUtils.cpp: the operand stack of the faulting method is simulated to find which
+// instruction pushed the null reference, and that instruction is described in Java-like syntax.
+
+use std::collections::BTreeSet;
+
+use ironjvm_cfparser::code::CodeParser;
+use ironjvm_javautil::descriptor::method::MethodDescriptor;
+use ironjvm_javautil::descriptor::TypeDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::cattr::CodeAttributeExceptionTableEntry;
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::attrinfo::lvtattr::LocalVariable;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::flags::FlagsExt;
+use ironjvm_specimpl::classfile::flags::MethodAccessFlags;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::CpInfo;
+use ironjvm_specimpl::classfile::MethodInfo;
+
+use crate::cpool;
+
+// how deeply nested expressions are described before giving up
+const MAX_CAUSE_DETAIL: u32 = 5;
+
+/// Computes the message of a `NullPointerException` raised by the instruction at `pc`, such as
+/// `Cannot invoke "String.length()" because "<local1>" is null`.
+///
+/// Returns `None` if the instruction at `pc` cannot raise a `NullPointerException`.
+pub fn npe_message(classfile: &ClassFile, method: &MethodInfo, pc: u16) -> Option<String> {
+    let analysis = NpeAnalysis::new(classfile, method)?;
+    let index = analysis.instruction_index(pc)?;
+
+    let (mut message, slot) = analysis.failed_action(index)?;
+    if let Some(cause) = analysis.cause(index, slot) {
+        message.push_str(&cause);
+    }
+
+    Some(message)
+}
+
+#[derive(Clone, Default)]
+struct SimulatedState {
+    // pc of the instruction that pushed each stack slot, or None if it is unknown or differs
+    // between the paths reaching the instruction
+    stack: Vec<Option<u16>>,
+    written_locals: BTreeSet<u16>,
+}
+
+struct NpeAnalysis<'a, 'clazz> {
+    constant_pool: &'a [CpInfo<'clazz>],
+    method: &'a MethodInfo<'clazz>,
+    instructions: Vec<(u16, Instruction)>,
+    local_variable_table: Vec<&'a LocalVariable>,
+    states: Vec<Option<SimulatedState>>,
+}
+
+impl<'a, 'clazz> NpeAnalysis<'a, 'clazz> {
+    fn new(classfile: &'a ClassFile<'clazz>, method: &'a MethodInfo<'clazz>) -> Option<Self> {
+        let (code, exception_table, attributes) =
+            method
+                .attributes
+                .iter()
+                .find_map(|attribute| match &attribute.info {
+                    AttributeInfoType::CodeAttribute {
+                        code,
+                        exception_table,
+                        attributes,
+                        ..
+                    } => Some((*code, exception_table, attributes)),
+                    _ => None,
+                })?;
+
+        let instructions = CodeParser::new(code).parse().ok()?;
+        let local_variable_table = attributes
+            .iter()
+            .filter_map(|attribute| match &attribute.info {
+                AttributeInfoType::LocalVariableTableAttribute {
+                    local_variable_table,
+                    ..
+                } => Some(local_variable_table),
+                _ => None,
+            })
+            .flatten()
+            .collect();
+
+        let mut analysis = Self {
+            constant_pool: &classfile.constant_pool,
+            method,
+            states: vec![None; instructions.len()],
+            instructions,
+            local_variable_table,
+        };
+
+        // without a consistent simulation we can still describe the failed action, just not
+        // its cause
+        if analysis.simulate_method(exception_table).is_none() {
+            analysis.states.fill(None);
+        }
+
+        Some(analysis)
+    }
+
+    fn instruction_index(&self, pc: u16) -> Option<usize> {
+        self.instructions
+            .binary_search_by_key(&pc, |(pc, _)| *pc)
+            .ok()
+    }
+
+    fn simulate_method(
+        &mut self,
+        exception_table: &[CodeAttributeExceptionTableEntry],
+    ) -> Option<()> {
+        self.states[0] = Some(SimulatedState::default());
+        let mut worklist = vec![0];
+
+        while let Some(index) = worklist.pop() {
+            let pc = self.instructions[index].0;
+            let mut state = self.states[index].clone()?;
+            self.simulate_instruction(&mut state, index)?;
+
+            for entry in exception_table
+                .iter()
+                .filter(|entry| (entry.start_pc..entry.end_pc).contains(&pc))
+            {
+                let handler_state = SimulatedState {
+                    stack: vec![None],
+                    written_locals: state.written_locals.clone(),
+                };
+                self.merge_state(entry.handler_pc, handler_state, &mut worklist)?;
+            }
+
+            for successor in self.successors(index)? {
+                self.merge_state(successor, state.clone(), &mut worklist)?;
+            }
+        }
+
+        Some(())
+    }
+
+    fn merge_state(
+        &mut self,
+        pc: u16,
+        state: SimulatedState,
+        worklist: &mut Vec<usize>,
+    ) -> Option<()> {
+        let index = self.instruction_index(pc)?;
+
+        let Some(existing) = &mut self.states[index] else {
+            self.states[index] = Some(state);
+            worklist.push(index);
+
+            return Some(());
+        };
+
+        if existing.stack.len() != state.stack.len() {
+            return None;
+        }
+
+        let mut changed = false;
+        for (slot, other) in existing.stack.iter_mut().zip(state.stack) {
+            if slot.is_some() && *slot != other {
+                *slot = None;
+                changed = true;
+            }
+        }
+        for local in state.written_locals {
+            changed |= existing.written_locals.insert(local);
+        }
+
+        if changed {
+            worklist.push(index);
+        }
+
+        Some(())
+    }
+
+    fn successors(&self, index: usize) -> Option<Vec<u16>> {
+        let (pc, instruction) = &self.instructions[index];
+        let next = self.instructions.get(index + 1).map(|(pc, _)| *pc);
+        let target = |offset: i32| u16::try_from(*pc as i32 + offset).ok();
+
+        Some(match instruction {
+            Instruction::IfEq { offset }
+            | Instruction::IfNe { offset }
+            | Instruction::IfLt { offset }
+            | Instruction::IfGe { offset }
+            | Instruction::IfGt { offset }
+            | Instruction::IfLe { offset }
+            | Instruction::IfICmpEq { offset }
+            | Instruction::IfICmpNe { offset }
+            | Instruction::IfICmpLt { offset }
+            | Instruction::IfICmpGe { offset }
+            | Instruction::IfICmpGt { offset }
+            | Instruction::IfICmpLe { offset }
+            | Instruction::IfACmpEq { offset }
+            | Instruction::IfACmpNe { offset }
+            | Instruction::IfNull { offset }
+            | Instruction::IfNonNull { offset } => vec![target(*offset as i32)?, next?],
+            Instruction::Goto { offset } | Instruction::Jsr { offset } => {
+                vec![target(*offset as i32)?]
+            }
+            Instruction::GotoW { offset } | Instruction::JsrW { offset } => vec![target(*offset)?],
+            Instruction::TableSwitch {
+                default,
+                jump_offsets,
+                ..
+            } => std::iter::once(default)
+                .chain(jump_offsets)
+                .map(|offset| target(*offset))
+                .collect::<Option<_>>()?,
+            Instruction::LookupSwitch {
+                default,
+                match_offset_pairs,
+                ..
+            } => std::iter::once(default)
+                .chain(match_offset_pairs.iter().map(|(_, offset)| offset))
+                .map(|offset| target(*offset))
+                .collect::<Option<_>>()?,
+            Instruction::IReturn
+            | Instruction::LReturn
+            | Instruction::FReturn
+            | Instruction::DReturn
+            | Instruction::AReturn
+            | Instruction::Return
+            | Instruction::AThrow
+            | Instruction::Ret { .. }
+            | Instruction::RetW { .. } => vec![],
+            _ => vec![next?],
+        })
+    }
+
+    fn simulate_instruction(&self, state: &mut SimulatedState, index: usize) -> Option<()> {
+        let (pc, instruction) = &self.instructions[index];
+
+        if let Some((_, size)) = load_index(instruction) {
+            state.stack.resize(state.stack.len() + size, Some(*pc));
+            return Some(());
+        }
+
+        if let Some((local, size)) = store_index(instruction) {
+            state.stack.truncate(state.stack.len().checked_sub(size)?);
+            state.written_locals.extend(local..local + size as u16);
+            return Some(());
+        }
+
+        match instruction {
+            Instruction::IInc { index, .. } => {
+                state.written_locals.insert(*index as u16);
+            }
+            Instruction::IIncW { index, .. } => {
+                state.written_locals.insert(*index);
+            }
+            // the null reference flows through a checkcast unchanged
+            Instruction::CheckCast { .. } => {}
+            Instruction::Dup => duplicate(&mut state.stack, 1, 0)?,
+            Instruction::DupX1 => duplicate(&mut state.stack, 1, 1)?,
+            Instruction::DupX2 => duplicate(&mut state.stack, 1, 2)?,
+            Instruction::Dup2 => duplicate(&mut state.stack, 2, 0)?,
+            Instruction::Dup2X1 => duplicate(&mut state.stack, 2, 1)?,
+            Instruction::Dup2X2 => duplicate(&mut state.stack, 2, 2)?,
+            Instruction::Swap => {
+                let len = state.stack.len();
+                state.stack.swap(len.checked_sub(1)?, len.checked_sub(2)?);
+            }
+            _ => {
+                let (pops, pushes) = self.stack_effect(instruction)?;
+                state.stack.truncate(state.stack.len().checked_sub(pops)?);
+                state.stack.resize(state.stack.len() + pushes, Some(*pc));
+            }
+        }
+
+        Some(())
+    }
+
+    // number of stack slots popped and pushed by instructions other than loads, stores and
+    // stack manipulation
+    fn stack_effect(&self, instruction: &Instruction) -> Option<(usize, usize)> {
+        Some(match instruction {
+            Instruction::Nop
+            | Instruction::IInc { .. }
+            | Instruction::IIncW { .. }
+            | Instruction::Goto { .. }
+            | Instruction::GotoW { .. }
+            | Instruction::Ret { .. }
+            | Instruction::RetW { .. }
+            | Instruction::Return => (0, 0),
+            Instruction::AConstNull
+            | Instruction::IConstM1
+            | Instruction::IConst0
+            | Instruction::IConst1
+            | Instruction::IConst2
+            | Instruction::IConst3
+            | Instruction::IConst4
+            | Instruction::IConst5
+            | Instruction::FConst0
+            | Instruction::FConst1
+            | Instruction::FConst2
+            | Instruction::BIPush { .. }
+            | Instruction::SIPush { .. }
+            | Instruction::Ldc { .. }
+            | Instruction::LdcW { .. }
+            | Instruction::Jsr { .. }
+            | Instruction::JsrW { .. }
+            | Instruction::New { .. } => (0, 1),
+            Instruction::LConst0
+            | Instruction::LConst1
+            | Instruction::DConst0
+            | Instruction::DConst1
+            | Instruction::Ldc2W { .. } => (0, 2),
+            Instruction::IALoad
+            | Instruction::FALoad
+            | Instruction::AALoad
+            | Instruction::BALoad
+            | Instruction::CALoad
+            | Instruction::SALoad => (2, 1),
+            Instruction::LALoad | Instruction::DALoad => (2, 2),
+            Instruction::IAStore
+            | Instruction::FAStore
+            | Instruction::AAStore
+            | Instruction::BAStore
+            | Instruction::CAStore
+            | Instruction::SAStore => (3, 0),
+            Instruction::LAStore | Instruction::DAStore => (4, 0),
+            Instruction::Pop
+            | Instruction::IfEq { .. }
+            | Instruction::IfNe { .. }
+            | Instruction::IfLt { .. }
+            | Instruction::IfGe { .. }
+            | Instruction::IfGt { .. }
+            | Instruction::IfLe { .. }
+            | Instruction::IfNull { .. }
+            | Instruction::IfNonNull { .. }
+            | Instruction::TableSwitch { .. }
+            | Instruction::LookupSwitch { .. }
+            | Instruction::IReturn
+            | Instruction::FReturn
+            | Instruction::AReturn
+            | Instruction::AThrow
+            | Instruction::MonitorEnter
+            | Instruction::MonitorExit => (1, 0),
+            Instruction::Pop2
+            | Instruction::IfICmpEq { .. }
+            | Instruction::IfICmpNe { .. }
+            | Instruction::IfICmpLt { .. }
+            | Instruction::IfICmpGe { .. }
+            | Instruction::IfICmpGt { .. }
+            | Instruction::IfICmpLe { .. }
+            | Instruction::IfACmpEq { .. }
+            | Instruction::IfACmpNe { .. }
+            | Instruction::LReturn
+            | Instruction::DReturn => (2, 0),
+            Instruction::IAdd
+            | Instruction::ISub
+            | Instruction::IMul
+            | Instruction::IDiv
+            | Instruction::IRem
+            | Instruction::IShl
+            | Instruction::IShr
+            | Instruction::IUShr
+            | Instruction::IAnd
+            | Instruction::IOr
+            | Instruction::IXor
+            | Instruction::FAdd
+            | Instruction::FSub
+            | Instruction::FMul
+            | Instruction::FDiv
+            | Instruction::FRem
+            | Instruction::FCmpL
+            | Instruction::FCmpG
+            | Instruction::L2I
+            | Instruction::L2F
+            | Instruction::D2I
+            | Instruction::D2F => (2, 1),
+            Instruction::INeg
+            | Instruction::FNeg
+            | Instruction::I2F
+            | Instruction::F2I
+            | Instruction::I2B
+            | Instruction::I2C
+            | Instruction::I2S
+            | Instruction::NewArray { .. }
+            | Instruction::ANewArray { .. }
+            | Instruction::ArrayLength
+            | Instruction::InstanceOf { .. } => (1, 1),
+            Instruction::I2L | Instruction::I2D | Instruction::F2L | Instruction::F2D => (1, 2),
+            Instruction::LNeg | Instruction::DNeg | Instruction::L2D | Instruction::D2L => (2, 2),
+            Instruction::LShl | Instruction::LShr | Instruction::LUShr => (3, 2),
+            Instruction::LAdd
+            | Instruction::LSub
+            | Instruction::LMul
+            | Instruction::LDiv
+            | Instruction::LRem
+            | Instruction::LAnd
+            | Instruction::LOr
+            | Instruction::LXor
+            | Instruction::DAdd
+            | Instruction::DSub
+            | Instruction::DMul
+            | Instruction::DDiv
+            | Instruction::DRem => (4, 2),
+            Instruction::LCmp | Instruction::DCmpL | Instruction::DCmpG => (4, 1),
+            Instruction::GetStatic { index } => (0, self.field_size(*index)?),
+            Instruction::PutStatic { index } => (self.field_size(*index)?, 0),
+            Instruction::GetField { index } => (1, self.field_size(*index)?),
+            Instruction::PutField { index } => (1 + self.field_size(*index)?, 0),
+            Instruction::InvokeVirtual { index }
+            | Instruction::InvokeSpecial { index }
+            | Instruction::InvokeInterface { index, .. } => {
+                let (_, _, descriptor) = cpool::constant_member_ref(self.constant_pool, *index)?;
+                let (arguments, result) = method_sizes(descriptor)?;
+
+                (1 + arguments, result)
+            }
+            Instruction::InvokeStatic { index } => {
+                let (_, _, descriptor) = cpool::constant_member_ref(self.constant_pool, *index)?;
+
+                method_sizes(descriptor)?
+            }
+            Instruction::InvokeDynamic { index } => {
+                let (_, descriptor) = cpool::constant_invoke_dynamic(self.constant_pool, *index)?;
+
+                method_sizes(descriptor)?
+            }
+            Instruction::MultiANewArray { dimensions, .. } => (*dimensions as usize, 1),
+            _ => return None,
+        })
+    }
+
+    fn field_size(&self, index: u16) -> Option<usize> {
+        let (_, _, descriptor) = cpool::constant_member_ref(self.constant_pool, index)?;
+
+        Some(TypeDescriptor::from_jstr(descriptor).ok()?.slot_size())
+    }
+
+    // describes the instruction that could not be executed, along with the stack slot (counted
+    // from the top) holding the null reference
+    fn failed_action(&self, index: usize) -> Option<(String, usize)> {
+        let (_, instruction) = &self.instructions[index];
+
+        Some(match instruction {
+            Instruction::IALoad => ("Cannot load from int array".to_string(), 1),
+            Instruction::LALoad => ("Cannot load from long array".to_string(), 1),
+            Instruction::FALoad => ("Cannot load from float array".to_string(), 1),
+            Instruction::DALoad => ("Cannot load from double array".to_string(), 1),
+            Instruction::AALoad => ("Cannot load from object array".to_string(), 1),
+            Instruction::BALoad => ("Cannot load from byte/boolean array".to_string(), 1),
+            Instruction::CALoad => ("Cannot load from char array".to_string(), 1),
+            Instruction::SALoad => ("Cannot load from short array".to_string(), 1),
+            Instruction::IAStore => ("Cannot store to int array".to_string(), 2),
+            Instruction::LAStore => ("Cannot store to long array".to_string(), 3),
+            Instruction::FAStore => ("Cannot store to float array".to_string(), 2),
+            Instruction::DAStore => ("Cannot store to double array".to_string(), 3),
+            Instruction::AAStore => ("Cannot store to object array".to_string(), 2),
+            Instruction::BAStore => ("Cannot store to byte/boolean array".to_string(), 2),
+            Instruction::CAStore => ("Cannot store to char array".to_string(), 2),
+            Instruction::SAStore => ("Cannot store to short array".to_string(), 2),
+            Instruction::ArrayLength => ("Cannot read the array length".to_string(), 0),
+            Instruction::AThrow => ("Cannot throw exception".to_string(), 0),
+            Instruction::MonitorEnter => ("Cannot enter synchronized block".to_string(), 0),
+            Instruction::MonitorExit => ("Cannot exit synchronized block".to_string(), 0),
+            Instruction::GetField { index } => {
+                let (_, name, _) = cpool::constant_member_ref(self.constant_pool, *index)?;
+
+                (
+                    format!("Cannot read field \"{}\"", name.to_string_lossy()),
+                    0,
+                )
+            }
+            Instruction::PutField { index } => {
+                let (_, name, _) = cpool::constant_member_ref(self.constant_pool, *index)?;
+
+                (
+                    format!("Cannot assign field \"{}\"", name.to_string_lossy()),
+                    self.field_size(*index)?,
+                )
+            }
+            Instruction::InvokeVirtual { index }
+            | Instruction::InvokeSpecial { index }
+            | Instruction::InvokeInterface { index, .. } => {
+                let (_, _, descriptor) = cpool::constant_member_ref(self.constant_pool, *index)?;
+                let (arguments, _) = method_sizes(descriptor)?;
+
+                (
+                    format!("Cannot invoke \"{}\"", self.method_name(*index)?),
+                    arguments,
+                )
+            }
+            _ => return None,
+        })
+    }
+
+    fn cause(&self, index: usize, slot: usize) -> Option<String> {
+        let source = self.source(index, slot)?;
+
+        match &self.instructions[source].1 {
+            Instruction::InvokeVirtual { index }
+            | Instruction::InvokeSpecial { index }
+            | Instruction::InvokeStatic { index }
+            | Instruction::InvokeInterface { index, .. } => Some(format!(
+                " because the return value of \"{}\" is null",
+                self.method_name(*index)?
+            )),
+            _ => Some(format!(
+                " because \"{}\" is null",
+                self.describe(index, slot, MAX_CAUSE_DETAIL)?
+            )),
+        }
+    }
+
+    // index of the instruction that pushed the given stack slot of the instruction at `index`
+    fn source(&self, index: usize, slot: usize) -> Option<usize> {
+        let stack = &self.states[index].as_ref()?.stack;
+        let pc = (*stack.get(stack.len().checked_sub(slot + 1)?)?)?;
+
+        self.instruction_index(pc)
+    }
+
+    fn describe(&self, index: usize, slot: usize, max_detail: u32) -> Option<String> {
+        if max_detail == 0 {
+            return None;
+        }
+
+        let source = self.source(index, slot)?;
+        let (source_pc, instruction) = &self.instructions[source];
+
+        Some(match instruction {
+            Instruction::ILoad { .. }
+            | Instruction::ILoad0
+            | Instruction::ILoad1
+            | Instruction::ILoad2
+            | Instruction::ILoad3
+            | Instruction::ILoadW { .. }
+            | Instruction::ALoad { .. }
+            | Instruction::ALoad0
+            | Instruction::ALoad1
+            | Instruction::ALoad2
+            | Instruction::ALoad3
+            | Instruction::ALoadW { .. } => {
+                let (local, _) = load_index(instruction)?;
+
+                self.describe_local(index, *source_pc, local)?
+            }
+            Instruction::AConstNull => "null".to_string(),
+            Instruction::IConstM1 => "-1".to_string(),
+            Instruction::IConst0 => "0".to_string(),
+            Instruction::IConst1 => "1".to_string(),
+            Instruction::IConst2 => "2".to_string(),
+            Instruction::IConst3 => "3".to_string(),
+            Instruction::IConst4 => "4".to_string(),
+            Instruction::IConst5 => "5".to_string(),
+            Instruction::BIPush { value } => value.to_string(),
+            Instruction::SIPush { value } => value.to_string(),
+            Instruction::IALoad
+            | Instruction::LALoad
+            | Instruction::FALoad
+            | Instruction::DALoad
+            | Instruction::AALoad
+            | Instruction::BALoad
+            | Instruction::CALoad
+            | Instruction::SALoad => {
+                let array = self
+                    .describe(source, 1, max_detail - 1)
+                    .unwrap_or_else(|| "<array>".to_string());
+                let index = self
+                    .describe(source, 0, max_detail - 1)
+                    .unwrap_or_else(|| "...".to_string());
+
+                format!("{array}[{index}]")
+            }
+            Instruction::GetStatic { index } => {
+                let (class, name, _) = cpool::constant_member_ref(self.constant_pool, *index)?;
+
+                format!("{}.{}", class_name(class), name.to_string_lossy())
+            }
+            Instruction::GetField { index } => {
+                let (_, name, _) = cpool::constant_member_ref(self.constant_pool, *index)?;
+
+                match self.describe(source, 0, max_detail - 1) {
+                    Some(object) => format!("{object}.{}", name.to_string_lossy()),
+                    None => name.to_string_lossy().into_owned(),
+                }
+            }
+            Instruction::InvokeVirtual { index }
+            | Instruction::InvokeSpecial { index }
+            | Instruction::InvokeStatic { index }
+            | Instruction::InvokeInterface { index, .. } => self.method_name(*index)?,
+            _ => return None,
+        })
+    }
+
+    fn describe_local(&self, index: usize, source_pc: u16, local: u16) -> Option<String> {
+        if let Some(variable) = self.local_variable_table.iter().find(|variable| {
+            variable.index == local
+                && (variable.start_pc as u32..variable.start_pc as u32 + variable.length as u32)
+                    .contains(&(source_pc as u32))
+        }) {
+            return cpool::constant_utf8(self.constant_pool, variable.name_index)
+                .map(|string| string.to_string_lossy().into_owned());
+        }
+
+        let is_parameter = !self.states[index].as_ref()?.written_locals.contains(&local);
+        let is_static = self
+            .method
+            .access_flags
+            .flag_set(MethodAccessFlags::ACC_STATIC);
+
+        if !is_static && local == 0 && is_parameter {
+            return Some("this".to_string());
+        }
+
+        if is_parameter {
+            let descriptor =
+                cpool::constant_utf8(self.constant_pool, self.method.descriptor_index)?;
+            let mut slot = if is_static { 0 } else { 1 };
+
+            for (i, parameter) in MethodDescriptor::from_jstr(descriptor)
+                .ok()?
+                .parameters_iter()
+                .enumerate()
+            {
+                let size = parameter.slot_size() as u16;
+                if (slot..slot + size).contains(&local) {
+                    return Some(format!("<parameter{}>", i + 1));
+                }
+
+                slot += size;
+            }
+        }
+
+        Some(format!("<local{local}>"))
+    }
+
+    fn method_name(&self, index: u16) -> Option<String> {
+        let (class, name, descriptor) = cpool::constant_member_ref(self.constant_pool, index)?;
+        let parameters = MethodDescriptor::from_jstr(descriptor)
+            .ok()?
+            .parameters_iter()
+            .map(|parameter| parameter_name(&parameter))
+            .collect::<Vec<_>>();
+
+        Some(format!(
+            "{}.{}({})",
+            class_name(class),
+            name.to_string_lossy(),
+            parameters.join(", ")
+        ))
+    }
+}
+
+fn load_index(instruction: &Instruction) -> Option<(u16, usize)> {
+    Some(match instruction {
+        Instruction::ILoad0 | Instruction::FLoad0 | Instruction::ALoad0 => (0, 1),
+        Instruction::ILoad1 | Instruction::FLoad1 | Instruction::ALoad1 => (1, 1),
+        Instruction::ILoad2 | Instruction::FLoad2 | Instruction::ALoad2 => (2, 1),
+        Instruction::ILoad3 | Instruction::FLoad3 | Instruction::ALoad3 => (3, 1),
+        Instruction::LLoad0 | Instruction::DLoad0 => (0, 2),
+        Instruction::LLoad1 | Instruction::DLoad1 => (1, 2),
+        Instruction::LLoad2 | Instruction::DLoad2 => (2, 2),
+        Instruction::LLoad3 | Instruction::DLoad3 => (3, 2),
+        Instruction::ILoad { index }
+        | Instruction::FLoad { index }
+        | Instruction::ALoad { index } => (*index as u16, 1),
+        Instruction::LLoad { index } | Instruction::DLoad { index } => (*index as u16, 2),
+        Instruction::ILoadW { index }
+        | Instruction::FLoadW { index }
+        | Instruction::ALoadW { index } => (*index, 1),
+        Instruction::LLoadW { index } | Instruction::DLoadW { index } => (*index, 2),
+        _ => return None,
+    })
+}
+
+fn store_index(instruction: &Instruction) -> Option<(u16, usize)> {
+    Some(match instruction {
+        Instruction::IStore0 | Instruction::FStore0 | Instruction::AStore0 => (0, 1),
+        Instruction::IStore1 | Instruction::FStore1 | Instruction::AStore1 => (1, 1),
+        Instruction::IStore2 | Instruction::FStore2 | Instruction::AStore2 => (2, 1),
+        Instruction::IStore3 | Instruction::FStore3 | Instruction::AStore3 => (3, 1),
+        Instruction::LStore0 | Instruction::DStore0 => (0, 2),
+        Instruction::LStore1 | Instruction::DStore1 => (1, 2),
+        Instruction::LStore2 | Instruction::DStore2 => (2, 2),
+        Instruction::LStore3 | Instruction::DStore3 => (3, 2),
+        Instruction::IStore { index }
+        | Instruction::FStore { index }
+        | Instruction::AStore { index } => (*index as u16, 1),
+        Instruction::LStore { index } | Instruction::DStore { index } => (*index as u16, 2),
+        Instruction::IStoreW { index }
+        | Instruction::FStoreW { index }
+        | Instruction::AStoreW { index } => (*index, 1),
+        Instruction::LStoreW { index } | Instruction::DStoreW { index } => (*index, 2),
+        _ => return None,
+    })
+}
+
+// copies the top `count` slots of the stack below the `depth` slots beneath them
+fn duplicate(stack: &mut Vec<Option<u16>>, count: usize, depth: usize) -> Option<()> {
+    let top = stack.len().checked_sub(count)?;
+    let position = top.checked_sub(depth)?;
+    let copied = stack[top..].to_vec();
+    stack.splice(position..position, copied);
+
+    Some(())
+}
+
+// number of stack slots taken by the arguments and the result of a method
+fn method_sizes(descriptor: &JStr) -> Option<(usize, usize)> {
+    let descriptor = MethodDescriptor::from_jstr(descriptor).ok()?;
+
+    Some((
+        descriptor.parameter_slots(),
+        descriptor.return_type().slot_size(),
+    ))
+}
+
+// HotSpot abbreviates java.lang.Object and java.lang.String differently depending on where the
+// name appears: a class is only shortened if it is exactly one of them (print_klass_name), while
+// parameter types are shortened by prefix (trim_well_known_class_names_from_signature), so that
+// "java.lang.StringBuilder.append(String)" takes a "StringBuilder" as parameter but keeps its
+// own name
+fn class_name(name: &JStr) -> String {
+    let name = name.to_string_lossy().replace('/', ".");
+
+    match name.as_str() {
+        "java.lang.Object" => "Object".to_string(),
+        "java.lang.String" => "String".to_string(),
+        _ => name,
+    }
+}
+
+fn parameter_name(descriptor: &TypeDescriptor) -> String {
+    let name = descriptor.to_string();
+
+    if name.starts_with("java.lang.Object") || name.starts_with("java.lang.String") {
+        name["java.lang.".len()..].to_string()
+    } else {
+        name
+    }
+}
@@ -0,0 +1,326 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Annotations as seen by reflection: decoded for the VM itself, or re-encoded into the raw bytes
+// java.lang.reflect hands to AnnotationParser along with the constant pool.
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::rvanriaattr::Annotation;
+use ironjvm_specimpl::classfile::attrinfo::rvanriaattr::ElementValue;
+use ironjvm_specimpl::classfile::attrinfo::rvanriaattr::ElementValueValue;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::AttributeInfo;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::cpool;
+use crate::indy;
+use crate::indy::LoadableConstant;
+use crate::reflect::error::ReflectError;
+use crate::reflect::error::ReflectResult;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnnotationInfo<'clazz> {
+    /// The field descriptor of the annotation interface, such as `Ljava/lang/Deprecated;`.
+    pub type_descriptor: &'clazz JStr,
+    pub elements: Vec<(&'clazz JStr, AnnotationValue<'clazz>)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnnotationValue<'clazz> {
+    Boolean(bool),
+    Byte(i8),
+    Char(u16),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(&'clazz JStr),
+    Enum {
+        type_descriptor: &'clazz JStr,
+        name: &'clazz JStr,
+    },
+    // a return descriptor, which is V for void.class
+    Class(&'clazz JStr),
+    Annotation(AnnotationInfo<'clazz>),
+    Array(Vec<AnnotationValue<'clazz>>),
+}
+
+impl<'clazz> AnnotationInfo<'clazz> {
+    pub fn element(&self, name: &str) -> Option<&AnnotationValue<'clazz>> {
+        self.elements
+            .iter()
+            .find(|(element, _)| element.as_bytes() == name.as_bytes())
+            .map(|(_, value)| value)
+    }
+}
+
+/// The runtime-visible annotations declared on a class, field, method or record component with
+/// these attributes.
+pub fn annotations<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    attributes: &[AttributeInfo<'clazz>],
+) -> ReflectResult<Vec<AnnotationInfo<'clazz>>> {
+    visible_annotations(attributes)
+        .map_or(&[][..], Vec::as_slice)
+        .iter()
+        .map(|annotation| decode_annotation(constant_pool, annotation))
+        .collect()
+}
+
+/// Finds the annotation of the interface with the internal name `type_name`, such as
+/// `java/lang/Deprecated`.
+pub fn find_annotation<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    attributes: &[AttributeInfo<'clazz>],
+    type_name: &str,
+) -> ReflectResult<Option<AnnotationInfo<'clazz>>> {
+    let descriptor = format!("L{type_name};");
+
+    Ok(annotations(constant_pool, attributes)?
+        .into_iter()
+        .find(|annotation| annotation.type_descriptor.as_bytes() == descriptor.as_bytes()))
+}
+
+/// The runtime-visible annotations of each parameter of a method, which may cover fewer
+/// parameters than the descriptor declares.
+pub fn parameter_annotations<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    attributes: &[AttributeInfo<'clazz>],
+) -> ReflectResult<Vec<Vec<AnnotationInfo<'clazz>>>> {
+    let Some(parameter_annotations) =
+        attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfoType::RuntimeVisibleParameterAnnotationsAttribute {
+                    parameter_annotations,
+                    ..
+                } => Some(parameter_annotations),
+                _ => None,
+            })
+    else {
+        return Ok(Vec::new());
+    };
+
+    parameter_annotations
+        .iter()
+        .map(|parameter| {
+            parameter
+                .annotations
+                .iter()
+                .map(|annotation| decode_annotation(constant_pool, annotation))
+                .collect()
+        })
+        .collect()
+}
+
+pub fn annotation_default<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    attributes: &[AttributeInfo<'clazz>],
+) -> ReflectResult<Option<AnnotationValue<'clazz>>> {
+    default_value(attributes)
+        .map(|value| decode_element_value(constant_pool, value))
+        .transpose()
+}
+
+/// The contents of the `RuntimeVisibleAnnotations` attribute, as returned by
+/// `Class.getRawAnnotations`.
+pub fn raw_annotations(attributes: &[AttributeInfo]) -> Option<Vec<u8>> {
+    let annotations = visible_annotations(attributes)?;
+
+    let mut output = Vec::new();
+    encode_annotations(annotations, &mut output);
+
+    Some(output)
+}
+
+/// The contents of the `RuntimeVisibleParameterAnnotations` attribute.
+pub fn raw_parameter_annotations(attributes: &[AttributeInfo]) -> Option<Vec<u8>> {
+    attributes
+        .iter()
+        .find_map(|attribute| match &attribute.info {
+            AttributeInfoType::RuntimeVisibleParameterAnnotationsAttribute {
+                num_parameters,
+                parameter_annotations,
+            } => {
+                let mut output = vec![*num_parameters];
+                for parameter in parameter_annotations {
+                    encode_annotations(&parameter.annotations, &mut output);
+                }
+
+                Some(output)
+            }
+            _ => None,
+        })
+}
+
+/// The contents of the `AnnotationDefault` attribute.
+pub fn raw_annotation_default(attributes: &[AttributeInfo]) -> Option<Vec<u8>> {
+    let value = default_value(attributes)?;
+
+    let mut output = Vec::new();
+    encode_element_value(value, &mut output);
+
+    Some(output)
+}
+
+fn visible_annotations<'a>(attributes: &'a [AttributeInfo]) -> Option<&'a Vec<Annotation>> {
+    attributes
+        .iter()
+        .find_map(|attribute| match &attribute.info {
+            AttributeInfoType::RuntimeVisibleAnnotationsAttribute { annotations, .. } => {
+                Some(annotations)
+            }
+            _ => None,
+        })
+}
+
+fn default_value<'a>(attributes: &'a [AttributeInfo]) -> Option<&'a ElementValue> {
+    attributes
+        .iter()
+        .find_map(|attribute| match &attribute.info {
+            AttributeInfoType::AnnotationDefaultAttribute { default_value } => Some(default_value),
+            _ => None,
+        })
+}
+
+fn decode_annotation<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    annotation: &Annotation,
+) -> ReflectResult<AnnotationInfo<'clazz>> {
+    Ok(AnnotationInfo {
+        type_descriptor: utf8(constant_pool, annotation.type_index)?,
+        elements: annotation
+            .element_value_pairs
+            .iter()
+            .map(|pair| {
+                Ok((
+                    utf8(constant_pool, pair.element_name_index)?,
+                    decode_element_value(constant_pool, &pair.value)?,
+                ))
+            })
+            .collect::<ReflectResult<_>>()?,
+    })
+}
+
+fn decode_element_value<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    value: &ElementValue,
+) -> ReflectResult<AnnotationValue<'clazz>> {
+    let invalid_tag = || ReflectError::InvalidElementValueTag { tag: value.tag };
+
+    Ok(match &value.value {
+        ElementValueValue::ConstValueIndex { const_value_index } => {
+            let index = *const_value_index;
+            if value.tag == b's' {
+                return Ok(AnnotationValue::String(utf8(constant_pool, index)?));
+            }
+
+            let constant = indy::loadable_constant(constant_pool, index)
+                .map_err(|_| ReflectError::InvalidConstantPoolEntry { index })?;
+
+            // the narrower types are stored as ConstantInteger
+            match (value.tag, constant) {
+                (b'Z', LoadableConstant::Int(value)) => AnnotationValue::Boolean(value != 0),
+                (b'B', LoadableConstant::Int(value)) => AnnotationValue::Byte(value as i8),
+                (b'C', LoadableConstant::Int(value)) => AnnotationValue::Char(value as u16),
+                (b'S', LoadableConstant::Int(value)) => AnnotationValue::Short(value as i16),
+                (b'I', LoadableConstant::Int(value)) => AnnotationValue::Int(value),
+                (b'J', LoadableConstant::Long(value)) => AnnotationValue::Long(value),
+                (b'F', LoadableConstant::Float(value)) => AnnotationValue::Float(value),
+                (b'D', LoadableConstant::Double(value)) => AnnotationValue::Double(value),
+                (b'Z' | b'B' | b'C' | b'S' | b'I' | b'J' | b'F' | b'D', _) => {
+                    return Err(ReflectError::InvalidConstantPoolEntry { index })
+                }
+                _ => return Err(invalid_tag()),
+            }
+        }
+        ElementValueValue::EnumConstValue {
+            type_name_index,
+            const_name_index,
+        } => AnnotationValue::Enum {
+            type_descriptor: utf8(constant_pool, *type_name_index)?,
+            name: utf8(constant_pool, *const_name_index)?,
+        },
+        ElementValueValue::ClassInfoIndex { class_info_index } => {
+            AnnotationValue::Class(utf8(constant_pool, *class_info_index)?)
+        }
+        ElementValueValue::AnnotationValue { annotation_value } => {
+            AnnotationValue::Annotation(decode_annotation(constant_pool, annotation_value)?)
+        }
+        ElementValueValue::ArrayValue { values, .. } => AnnotationValue::Array(
+            values
+                .iter()
+                .map(|value| decode_element_value(constant_pool, value))
+                .collect::<ReflectResult<_>>()?,
+        ),
+    })
+}
+
+fn encode_annotations(annotations: &[Annotation], output: &mut Vec<u8>) {
+    output.extend_from_slice(&(annotations.len() as u16).to_be_bytes());
+    for annotation in annotations {
+        encode_annotation(annotation, output);
+    }
+}
+
+fn encode_annotation(annotation: &Annotation, output: &mut Vec<u8>) {
+    output.extend_from_slice(&annotation.type_index.to_be_bytes());
+    output.extend_from_slice(&(annotation.element_value_pairs.len() as u16).to_be_bytes());
+
+    for pair in &annotation.element_value_pairs {
+        output.extend_from_slice(&pair.element_name_index.to_be_bytes());
+        encode_element_value(&pair.value, output);
+    }
+}
+
+fn encode_element_value(value: &ElementValue, output: &mut Vec<u8>) {
+    output.push(value.tag);
+
+    match &value.value {
+        ElementValueValue::ConstValueIndex { const_value_index } => {
+            output.extend_from_slice(&const_value_index.to_be_bytes());
+        }
+        ElementValueValue::EnumConstValue {
+            type_name_index,
+            const_name_index,
+        } => {
+            output.extend_from_slice(&type_name_index.to_be_bytes());
+            output.extend_from_slice(&const_name_index.to_be_bytes());
+        }
+        ElementValueValue::ClassInfoIndex { class_info_index } => {
+            output.extend_from_slice(&class_info_index.to_be_bytes());
+        }
+        ElementValueValue::AnnotationValue { annotation_value } => {
+            encode_annotation(annotation_value, output);
+        }
+        ElementValueValue::ArrayValue { values, .. } => {
+            output.extend_from_slice(&(values.len() as u16).to_be_bytes());
+            for value in values {
+                encode_element_value(value, output);
+            }
+        }
+    }
+}
+
+fn utf8<'clazz>(constant_pool: &[CpInfo<'clazz>], index: u16) -> ReflectResult<&'clazz JStr> {
+    cpool::constant_utf8(constant_pool, index)
+        .ok_or(ReflectError::InvalidConstantPoolEntry { index })
+}
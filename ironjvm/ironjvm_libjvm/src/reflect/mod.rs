@@ -0,0 +1,352 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// VM support for java.lang.reflect: the declared members of a class, and the conversions applied
+// to the values passed to Method.invoke, Constructor.newInstance and Field.set.
+
+use ironjvm_javautil::be::JavaBeUtil;
+use ironjvm_javautil::descriptor::method::MethodDescriptor;
+use ironjvm_javautil::descriptor::BaseType;
+use ironjvm_javautil::descriptor::TypeDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::flags::FieldAccessFlags;
+use ironjvm_specimpl::classfile::flags::FlagsExt;
+use ironjvm_specimpl::classfile::flags::MethodAccessFlags;
+use ironjvm_specimpl::classfile::AttributeInfo;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::cpool;
+use crate::reflect::annotation::raw_annotation_default;
+use crate::reflect::annotation::raw_annotations;
+use crate::reflect::annotation::raw_parameter_annotations;
+use crate::reflect::error::ReflectError;
+use crate::reflect::error::ReflectResult;
+
+pub mod annotation;
+pub mod error;
+
+// the modifiers java.lang.reflect reports, which leave out ACC_SUPER and the like
+const RECOGNIZED_FIELD_MODIFIERS: u16 = FieldAccessFlags::ACC_PUBLIC
+    | FieldAccessFlags::ACC_PRIVATE
+    | FieldAccessFlags::ACC_PROTECTED
+    | FieldAccessFlags::ACC_STATIC
+    | FieldAccessFlags::ACC_FINAL
+    | FieldAccessFlags::ACC_VOLATILE
+    | FieldAccessFlags::ACC_TRANSIENT
+    | FieldAccessFlags::ACC_SYNTHETIC
+    | FieldAccessFlags::ACC_ENUM;
+const RECOGNIZED_METHOD_MODIFIERS: u16 = MethodAccessFlags::ACC_PUBLIC
+    | MethodAccessFlags::ACC_PRIVATE
+    | MethodAccessFlags::ACC_PROTECTED
+    | MethodAccessFlags::ACC_STATIC
+    | MethodAccessFlags::ACC_FINAL
+    | MethodAccessFlags::ACC_SYNCHRONIZED
+    | MethodAccessFlags::ACC_BRIDGE
+    | MethodAccessFlags::ACC_VARARGS
+    | MethodAccessFlags::ACC_NATIVE
+    | MethodAccessFlags::ACC_ABSTRACT
+    | MethodAccessFlags::ACC_STRICT
+    | MethodAccessFlags::ACC_SYNTHETIC;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MemberKind {
+    Field,
+    Method,
+    Constructor,
+}
+
+/// What the VM fills in when creating a `Field`, `Method` or `Constructor` object.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReflectedMember<'clazz> {
+    pub kind: MemberKind,
+    /// The index of the member in the `fields` or `methods` of its class.
+    pub slot: usize,
+    pub name: &'clazz JStr,
+    pub descriptor: &'clazz JStr,
+    pub modifiers: u16,
+    pub signature: Option<&'clazz JStr>,
+    /// The checked exceptions a method or constructor declares.
+    pub exceptions: Vec<&'clazz JStr>,
+    pub annotations: Option<Vec<u8>>,
+    pub parameter_annotations: Option<Vec<u8>>,
+    pub annotation_default: Option<Vec<u8>>,
+}
+
+/// The fields of a class, as returned by `Class.getDeclaredFields0`.
+pub fn declared_fields<'clazz>(
+    classfile: &ClassFile<'clazz>,
+    public_only: bool,
+) -> ReflectResult<Vec<ReflectedMember<'clazz>>> {
+    let constant_pool = &classfile.constant_pool;
+
+    classfile
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, field)| {
+            !public_only || field.access_flags.flag_set(FieldAccessFlags::ACC_PUBLIC)
+        })
+        .map(|(slot, field)| {
+            Ok(ReflectedMember {
+                kind: MemberKind::Field,
+                slot,
+                name: utf8(constant_pool, field.name_index)?,
+                descriptor: utf8(constant_pool, field.descriptor_index)?,
+                modifiers: field.access_flags & RECOGNIZED_FIELD_MODIFIERS,
+                signature: signature(constant_pool, &field.attributes)?,
+                exceptions: Vec::new(),
+                annotations: raw_annotations(&field.attributes),
+                parameter_annotations: None,
+                annotation_default: None,
+            })
+        })
+        .collect()
+}
+
+/// The methods of a class other than constructors and initializers, as returned by
+/// `Class.getDeclaredMethods0`.
+pub fn declared_methods<'clazz>(
+    classfile: &ClassFile<'clazz>,
+    public_only: bool,
+) -> ReflectResult<Vec<ReflectedMember<'clazz>>> {
+    Ok(declared_methods_and_constructors(classfile, public_only)?
+        .into_iter()
+        .filter(|member| member.kind == MemberKind::Method)
+        .collect())
+}
+
+/// The constructors of a class, as returned by `Class.getDeclaredConstructors0`.
+pub fn declared_constructors<'clazz>(
+    classfile: &ClassFile<'clazz>,
+    public_only: bool,
+) -> ReflectResult<Vec<ReflectedMember<'clazz>>> {
+    Ok(declared_methods_and_constructors(classfile, public_only)?
+        .into_iter()
+        .filter(|member| member.kind == MemberKind::Constructor)
+        .collect())
+}
+
+fn declared_methods_and_constructors<'clazz>(
+    classfile: &ClassFile<'clazz>,
+    public_only: bool,
+) -> ReflectResult<Vec<ReflectedMember<'clazz>>> {
+    let constant_pool = &classfile.constant_pool;
+    let mut members = Vec::new();
+
+    for (slot, method) in classfile.methods.iter().enumerate() {
+        if public_only && !method.access_flags.flag_set(MethodAccessFlags::ACC_PUBLIC) {
+            continue;
+        }
+
+        let name = utf8(constant_pool, method.name_index)?;
+        let kind = match name.as_bytes() {
+            b"<init>" => MemberKind::Constructor,
+            b"<clinit>" => continue,
+            _ => MemberKind::Method,
+        };
+
+        let exceptions = method
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute.info {
+                AttributeInfoType::ExceptionsAttribute {
+                    exception_index_table,
+                    ..
+                } => Some(exception_index_table),
+                _ => None,
+            })
+            .unwrap_or_default()
+            .iter()
+            .map(|index| {
+                let index = index.to_u16();
+                cpool::constant_class_name(constant_pool, index)
+                    .ok_or(ReflectError::InvalidConstantPoolEntry { index })
+            })
+            .collect::<ReflectResult<_>>()?;
+
+        members.push(ReflectedMember {
+            kind,
+            slot,
+            name,
+            descriptor: utf8(constant_pool, method.descriptor_index)?,
+            modifiers: method.access_flags & RECOGNIZED_METHOD_MODIFIERS,
+            signature: signature(constant_pool, &method.attributes)?,
+            exceptions,
+            annotations: raw_annotations(&method.attributes),
+            parameter_annotations: raw_parameter_annotations(&method.attributes),
+            annotation_default: raw_annotation_default(&method.attributes),
+        });
+    }
+
+    Ok(members)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PrimitiveValue {
+    Boolean(bool),
+    Byte(i8),
+    Char(u16),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+}
+
+impl PrimitiveValue {
+    /// Applies an identity or widening primitive conversion (JLS 5.1.2) to `target`.
+    pub fn widen(self, target: &BaseType) -> Option<Self> {
+        Some(match target {
+            BaseType::Boolean => match self {
+                Self::Boolean(value) => Self::Boolean(value),
+                _ => return None,
+            },
+            BaseType::Byte => match self {
+                Self::Byte(value) => Self::Byte(value),
+                _ => return None,
+            },
+            BaseType::Char => match self {
+                Self::Char(value) => Self::Char(value),
+                _ => return None,
+            },
+            BaseType::Short => match self {
+                Self::Byte(value) => Self::Short(value.into()),
+                Self::Short(value) => Self::Short(value),
+                _ => return None,
+            },
+            BaseType::Int => Self::Int(self.as_int()?),
+            BaseType::Long => Self::Long(match self {
+                Self::Long(value) => value,
+                _ => self.as_int()?.into(),
+            }),
+            BaseType::Float => Self::Float(match self {
+                Self::Float(value) => value,
+                Self::Long(value) => value as f32,
+                _ => self.as_int()? as f32,
+            }),
+            BaseType::Double => Self::Double(match self {
+                Self::Double(value) => value,
+                Self::Float(value) => value.into(),
+                Self::Long(value) => value as f64,
+                _ => self.as_int()?.into(),
+            }),
+            BaseType::Object(_) => return None,
+        })
+    }
+
+    fn as_int(self) -> Option<i32> {
+        match self {
+            Self::Byte(value) => Some(value.into()),
+            Self::Char(value) => Some(value.into()),
+            Self::Short(value) => Some(value.into()),
+            Self::Int(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+/// The object model the conversions need, provided by the heap.
+pub trait ReflectionSupport {
+    type Object: Clone;
+    type Throwable;
+
+    /// The value of a primitive wrapper such as `java.lang.Integer`, or `None` for any other
+    /// object.
+    fn unbox(&self, object: &Self::Object) -> Option<PrimitiveValue>;
+
+    fn is_instance(&self, object: &Self::Object, r#type: &TypeDescriptor) -> bool;
+
+    fn illegal_argument_exception(&self, message: String) -> Self::Throwable;
+}
+
+/// A value converted to the type of a parameter or field, where `None` is the null reference.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConvertedValue<O> {
+    Primitive(PrimitiveValue),
+    Reference(Option<O>),
+}
+
+/// Converts the arguments of `Method.invoke` or `Constructor.newInstance` to the parameter types
+/// of `descriptor`. The caller is left to check the receiver, invoke the method, and wrap what it
+/// throws in an `InvocationTargetException`.
+pub fn convert_arguments<S: ReflectionSupport>(
+    support: &S,
+    descriptor: &MethodDescriptor,
+    arguments: &[Option<S::Object>],
+) -> Result<Vec<ConvertedValue<S::Object>>, S::Throwable> {
+    let parameters = descriptor.parameters_iter().collect::<Vec<_>>();
+    if parameters.len() != arguments.len() {
+        return Err(support.illegal_argument_exception(format!(
+            "wrong number of arguments: {} expected: {}",
+            arguments.len(),
+            parameters.len()
+        )));
+    }
+
+    parameters
+        .iter()
+        .zip(arguments)
+        .map(|(parameter, argument)| convert_value(support, parameter, argument.as_ref()))
+        .collect()
+}
+
+/// Converts a value to `type`, unboxing and widening it if the type is primitive, as done for the
+/// arguments of `Method.invoke` and the value of `Field.set`.
+pub fn convert_value<S: ReflectionSupport>(
+    support: &S,
+    r#type: &TypeDescriptor,
+    value: Option<&S::Object>,
+) -> Result<ConvertedValue<S::Object>, S::Throwable> {
+    let mismatch = || support.illegal_argument_exception("argument type mismatch".to_string());
+
+    if r#type.dimensions > 0 || matches!(r#type.r#type, BaseType::Object(_)) {
+        return match value {
+            Some(object) if !support.is_instance(object, r#type) => Err(mismatch()),
+            _ => Ok(ConvertedValue::Reference(value.cloned())),
+        };
+    }
+
+    value
+        .and_then(|object| support.unbox(object))
+        .and_then(|primitive| primitive.widen(&r#type.r#type))
+        .map(ConvertedValue::Primitive)
+        .ok_or_else(mismatch)
+}
+
+fn signature<'clazz>(
+    constant_pool: &[CpInfo<'clazz>],
+    attributes: &[AttributeInfo<'clazz>],
+) -> ReflectResult<Option<&'clazz JStr>> {
+    attributes
+        .iter()
+        .find_map(|attribute| match attribute.info {
+            AttributeInfoType::SignatureAttribute { signature_index } => Some(signature_index),
+            _ => None,
+        })
+        .map(|index| utf8(constant_pool, index))
+        .transpose()
+}
+
+fn utf8<'clazz>(constant_pool: &[CpInfo<'clazz>], index: u16) -> ReflectResult<&'clazz JStr> {
+    cpool::constant_utf8(constant_pool, index)
+        .ok_or(ReflectError::InvalidConstantPoolEntry { index })
+}
@@ -0,0 +1,717 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// Resolution of symbolic references (JVMS 5.4.3) and access control (JVMS 5.4.4).
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+
+use ironjvm_javautil::be::JavaBeUtil;
+use ironjvm_javautil::descriptor::BaseType;
+use ironjvm_javautil::descriptor::TypeDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::flags::ClassAccessFlags;
+use ironjvm_specimpl::classfile::flags::FlagsExt;
+use ironjvm_specimpl::classfile::flags::MethodAccessFlags;
+use ironjvm_specimpl::classfile::ClassFile;
+
+use crate::cpool;
+use crate::methodhandle;
+use crate::resolve::error::ResolutionError;
+use crate::resolve::error::ResolutionResult;
+
+pub mod error;
+
+// fields and methods share the values of these flags
+const ACC_PUBLIC: u16 = MethodAccessFlags::ACC_PUBLIC;
+const ACC_PRIVATE: u16 = MethodAccessFlags::ACC_PRIVATE;
+const ACC_PROTECTED: u16 = MethodAccessFlags::ACC_PROTECTED;
+const ACC_STATIC: u16 = MethodAccessFlags::ACC_STATIC;
+
+/// Loads classes on behalf of the resolver.
+pub trait ClassLoader {
+    type Class: Copy + Eq + Hash;
+
+    /// Loads the class or interface named `name` (which may be an array class) using the
+    /// defining loader of `accessing`.
+    fn load_class(&self, name: &JStr, accessing: Self::Class) -> ResolutionResult<Self::Class>;
+
+    fn classfile(&self, class: Self::Class) -> &ClassFile<'_>;
+
+    fn same_runtime_package(&self, a: Self::Class, b: Self::Class) -> bool;
+
+    /// Whether the module of `accessing` reads the module of `class`, which exports the package
+    /// of `class` to it; see [`ModuleGraph::can_access`](crate::module::ModuleGraph::can_access).
+    fn is_exported_to(&self, _class: Self::Class, _accessing: Self::Class) -> bool {
+        true
+    }
+
+    /// Whether `a` and `b` are in the same module. Classes in unnamed modules are only
+    /// considered to be if they are in the same runtime package.
+    fn same_module(&self, a: Self::Class, b: Self::Class) -> bool {
+        self.same_runtime_package(a, b)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DirectReference<C> {
+    Class(C),
+    // the index of the field or method in the declaring class
+    Field { class: C, index: usize },
+    Method { class: C, index: usize },
+}
+
+/// The run-time constant pool of a class, caching the outcome of every resolution. As required
+/// by JVMS 5.4.3, a failed resolution fails with the same error on subsequent attempts.
+pub struct RuntimeConstantPool<C> {
+    class: C,
+    resolved: Mutex<HashMap<u16, ResolutionResult<DirectReference<C>>>>,
+}
+
+impl<C: Copy + Eq + Hash> RuntimeConstantPool<C> {
+    pub fn new(class: C) -> Self {
+        Self {
+            class,
+            resolved: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn resolve<L: ClassLoader<Class = C>>(
+        &self,
+        loader: &L,
+        index: u16,
+    ) -> ResolutionResult<DirectReference<C>> {
+        if let Some(result) = self.resolved.lock().unwrap().get(&index) {
+            return result.clone();
+        }
+
+        // the lock is not held while resolving, as loading may resolve other entries; if two
+        // threads race, the first result is kept
+        let result = resolve_entry(loader, self.class, index);
+
+        self.resolved
+            .lock()
+            .unwrap()
+            .entry(index)
+            .or_insert(result)
+            .clone()
+    }
+}
+
+fn resolve_entry<L: ClassLoader>(
+    loader: &L,
+    accessing: L::Class,
+    index: u16,
+) -> ResolutionResult<DirectReference<L::Class>> {
+    let constant_pool = &loader.classfile(accessing).constant_pool;
+    let invalid = || ResolutionError::InvalidConstantPoolEntry { index };
+
+    let cp_info = constant_pool
+        .get((index as usize).checked_sub(1).ok_or_else(invalid)?)
+        .ok_or_else(invalid)?;
+
+    match cp_info.info {
+        CpInfoType::ConstantClass { .. } => {
+            let name = cpool::constant_class_name(constant_pool, index).ok_or_else(invalid)?;
+
+            resolve_class(loader, accessing, name).map(DirectReference::Class)
+        }
+        CpInfoType::ConstantFieldRef { .. } => {
+            let (class, name, descriptor) =
+                cpool::constant_member_ref(constant_pool, index).ok_or_else(invalid)?;
+            let (class, index) = resolve_field(loader, accessing, class, name, descriptor)?;
+
+            Ok(DirectReference::Field { class, index })
+        }
+        CpInfoType::ConstantMethodRef { .. } => {
+            let (class, name, descriptor) =
+                cpool::constant_member_ref(constant_pool, index).ok_or_else(invalid)?;
+            let (class, index) = resolve_method(loader, accessing, class, name, descriptor)?;
+
+            Ok(DirectReference::Method { class, index })
+        }
+        CpInfoType::ConstantInterfaceMethodRef { .. } => {
+            let (class, name, descriptor) =
+                cpool::constant_member_ref(constant_pool, index).ok_or_else(invalid)?;
+            let (class, index) =
+                resolve_interface_method(loader, accessing, class, name, descriptor)?;
+
+            Ok(DirectReference::Method { class, index })
+        }
+        _ => Err(invalid()),
+    }
+}
+
+pub fn resolve_class<L: ClassLoader>(
+    loader: &L,
+    accessing: L::Class,
+    name: &JStr,
+) -> ResolutionResult<L::Class> {
+    // an array class is accessible if its element type is
+    if name.as_bytes().first() == Some(&b'[') {
+        let descriptor =
+            TypeDescriptor::from_jstr(name).map_err(|_| ResolutionError::NoClassDefFoundError {
+                class: name.to_string_lossy().into_owned(),
+            })?;
+
+        if let BaseType::Object(element) = descriptor.r#type {
+            resolve_class(loader, accessing, element)?;
+        }
+
+        return loader.load_class(name, accessing);
+    }
+
+    let class = loader.load_class(name, accessing)?;
+
+    let is_public = loader
+        .classfile(class)
+        .access_flags
+        .flag_set(ClassAccessFlags::ACC_PUBLIC);
+
+    if !(is_public && loader.is_exported_to(class, accessing))
+        && !loader.same_runtime_package(class, accessing)
+    {
+        return Err(ResolutionError::IllegalAccessError {
+            message: format!(
+                "class {} cannot access its superclass or referenced class {}",
+                class_name(loader, accessing),
+                name.to_string_lossy()
+            ),
+        });
+    }
+
+    Ok(class)
+}
+
+pub fn resolve_field<L: ClassLoader>(
+    loader: &L,
+    accessing: L::Class,
+    class_name: &JStr,
+    name: &JStr,
+    descriptor: &JStr,
+) -> ResolutionResult<(L::Class, usize)> {
+    let referenced = resolve_class(loader, accessing, class_name)?;
+
+    let Some((declaring, index)) = lookup_field(loader, referenced, name, descriptor)? else {
+        return Err(ResolutionError::NoSuchFieldError {
+            field: name.to_string_lossy().into_owned(),
+        });
+    };
+
+    let access_flags = loader.classfile(declaring).fields[index].access_flags;
+    check_member_access(loader, accessing, referenced, declaring, access_flags, name)?;
+
+    Ok((declaring, index))
+}
+
+pub fn resolve_method<L: ClassLoader>(
+    loader: &L,
+    accessing: L::Class,
+    class_name: &JStr,
+    name: &JStr,
+    descriptor: &JStr,
+) -> ResolutionResult<(L::Class, usize)> {
+    let referenced = resolve_class(loader, accessing, class_name)?;
+
+    if is_interface(loader, referenced) {
+        return Err(ResolutionError::IncompatibleClassChangeError {
+            message: format!(
+                "Found interface {}, but class was expected",
+                class_name.to_string_lossy()
+            ),
+        });
+    }
+
+    let mut found = signature_polymorphic_method(loader, referenced, class_name, name);
+    let mut class = if found.is_none() {
+        Some(referenced)
+    } else {
+        None
+    };
+    while let Some(current) = class {
+        if let Some(index) = find_method(loader, current, name, descriptor) {
+            found = Some((current, index));
+            break;
+        }
+
+        class = superclass(loader, current)?;
+    }
+
+    let (declaring, index) = match found {
+        Some(found) => found,
+        None => lookup_superinterface_method(loader, referenced, name, descriptor)?.ok_or_else(
+            || ResolutionError::NoSuchMethodError {
+                method: method_name(class_name, name, descriptor),
+            },
+        )?,
+    };
+
+    let access_flags = loader.classfile(declaring).methods[index].access_flags;
+    check_member_access(loader, accessing, referenced, declaring, access_flags, name)?;
+
+    Ok((declaring, index))
+}
+
+pub fn resolve_interface_method<L: ClassLoader>(
+    loader: &L,
+    accessing: L::Class,
+    class_name: &JStr,
+    name: &JStr,
+    descriptor: &JStr,
+) -> ResolutionResult<(L::Class, usize)> {
+    let referenced = resolve_class(loader, accessing, class_name)?;
+
+    if !is_interface(loader, referenced) {
+        return Err(ResolutionError::IncompatibleClassChangeError {
+            message: format!(
+                "Found class {}, but interface was expected",
+                class_name.to_string_lossy()
+            ),
+        });
+    }
+
+    let mut found =
+        find_method(loader, referenced, name, descriptor).map(|index| (referenced, index));
+
+    // the superclass of an interface is always Object, whose public instance methods are
+    // members of every interface
+    if found.is_none() {
+        if let Some(object) = superclass(loader, referenced)? {
+            found = find_method(loader, object, name, descriptor)
+                .filter(|index| {
+                    let access_flags = loader.classfile(object).methods[*index].access_flags;
+
+                    access_flags.flag_set(ACC_PUBLIC) && !access_flags.flag_set(ACC_STATIC)
+                })
+                .map(|index| (object, index));
+        }
+    }
+
+    let (declaring, index) = match found {
+        Some(found) => found,
+        None => lookup_superinterface_method(loader, referenced, name, descriptor)?.ok_or_else(
+            || ResolutionError::NoSuchMethodError {
+                method: method_name(class_name, name, descriptor),
+            },
+        )?,
+    };
+
+    let access_flags = loader.classfile(declaring).methods[index].access_flags;
+    check_member_access(loader, accessing, referenced, declaring, access_flags, name)?;
+
+    Ok((declaring, index))
+}
+
+/// Checks that a resolved field or method is static if and only if the instruction referring to
+/// it expects so, e.g. `getstatic` or `invokestatic`.
+pub fn check_static<L: ClassLoader>(
+    loader: &L,
+    reference: DirectReference<L::Class>,
+    expect_static: bool,
+) -> ResolutionResult<()> {
+    let (access_flags, kind) = match reference {
+        DirectReference::Class(_) => return Ok(()),
+        DirectReference::Field { class, index } => {
+            (loader.classfile(class).fields[index].access_flags, "field")
+        }
+        DirectReference::Method { class, index } => (
+            loader.classfile(class).methods[index].access_flags,
+            "method",
+        ),
+    };
+
+    if access_flags.flag_set(ACC_STATIC) == expect_static {
+        return Ok(());
+    }
+
+    Err(ResolutionError::IncompatibleClassChangeError {
+        message: format!(
+            "Expected {}static {kind}",
+            if expect_static { "" } else { "non-" }
+        ),
+    })
+}
+
+/// Determines the nest host of a class, which is the class itself unless it has a valid
+/// `NestHost` attribute.
+pub fn nest_host<L: ClassLoader>(loader: &L, class: L::Class) -> L::Class {
+    let classfile = loader.classfile(class);
+
+    let Some(host_class_index) = classfile
+        .attributes
+        .iter()
+        .find_map(|attribute| match attribute.info {
+            AttributeInfoType::NestHostAttribute { host_class_index } => Some(host_class_index),
+            _ => None,
+        })
+    else {
+        return class;
+    };
+
+    let Some(host_name) = cpool::constant_class_name(&classfile.constant_pool, host_class_index)
+    else {
+        return class;
+    };
+
+    let Ok(host) = loader.load_class(host_name, class) else {
+        return class;
+    };
+
+    if !loader.same_runtime_package(host, class) {
+        return class;
+    }
+
+    let this_name = cpool::constant_class_name(&classfile.constant_pool, classfile.this_class);
+    let host_classfile = loader.classfile(host);
+    let is_member = host_classfile
+        .attributes
+        .iter()
+        .any(|attribute| match attribute.info {
+            AttributeInfoType::NestMembersAttribute { classes, .. } => {
+                classes.iter().any(|index| {
+                    this_name.is_some()
+                        && cpool::constant_class_name(&host_classfile.constant_pool, index.to_u16())
+                            == this_name
+                })
+            }
+            _ => false,
+        });
+
+    if is_member {
+        host
+    } else {
+        class
+    }
+}
+
+/// Checks that `class` is permitted to extend or implement each of its direct supertypes that is
+/// sealed (JVMS 5.3.5).
+pub fn check_permitted_subclass<L: ClassLoader>(
+    loader: &L,
+    class: L::Class,
+) -> ResolutionResult<()> {
+    let classfile = loader.classfile(class);
+    let this_name = cpool::constant_class_name(&classfile.constant_pool, classfile.this_class);
+
+    for supertype in superclass(loader, class)?
+        .into_iter()
+        .chain(superinterfaces(loader, class)?)
+    {
+        let super_classfile = loader.classfile(supertype);
+
+        // the attribute is ignored in class files older than Java 17
+        if super_classfile.major_version < 61 {
+            continue;
+        }
+
+        let Some(classes) = super_classfile
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute.info {
+                AttributeInfoType::PermittedSubclassesAttribute { classes, .. } => Some(classes),
+                _ => None,
+            })
+        else {
+            continue;
+        };
+
+        let is_permitted = loader.same_module(supertype, class)
+            && classes.iter().any(|index| {
+                this_name.is_some()
+                    && cpool::constant_class_name(&super_classfile.constant_pool, index.to_u16())
+                        == this_name
+            });
+
+        if !is_permitted {
+            let (relation, kind) = if is_interface(loader, supertype) {
+                ("implement", "interface")
+            } else {
+                ("inherit from", "class")
+            };
+
+            return Err(ResolutionError::IncompatibleClassChangeError {
+                message: format!(
+                    "class {} cannot {relation} sealed {kind} {}",
+                    class_name(loader, class),
+                    class_name(loader, supertype)
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn check_member_access<L: ClassLoader>(
+    loader: &L,
+    accessing: L::Class,
+    referenced: L::Class,
+    declaring: L::Class,
+    access_flags: u16,
+    name: &JStr,
+) -> ResolutionResult<()> {
+    let accessible = if access_flags.flag_set(ACC_PUBLIC) {
+        true
+    } else if access_flags.flag_set(ACC_PRIVATE) {
+        accessing == declaring || nest_host(loader, accessing) == nest_host(loader, declaring)
+    } else if loader.same_runtime_package(declaring, accessing) {
+        true
+    } else if access_flags.flag_set(ACC_PROTECTED) {
+        // protected instance members are only accessible through a reference to a class related
+        // to the accessing one
+        is_subclass(loader, accessing, declaring)?
+            && (access_flags.flag_set(ACC_STATIC)
+                || is_subclass(loader, referenced, accessing)?
+                || is_subclass(loader, accessing, referenced)?)
+    } else {
+        false
+    };
+
+    if accessible {
+        return Ok(());
+    }
+
+    Err(ResolutionError::IllegalAccessError {
+        message: format!(
+            "class {} tried to access {}.{}",
+            class_name(loader, accessing),
+            class_name(loader, declaring),
+            name.to_string_lossy()
+        ),
+    })
+}
+
+fn lookup_field<L: ClassLoader>(
+    loader: &L,
+    class: L::Class,
+    name: &JStr,
+    descriptor: &JStr,
+) -> ResolutionResult<Option<(L::Class, usize)>> {
+    let classfile = loader.classfile(class);
+
+    if let Some(index) = classfile.fields.iter().position(|field| {
+        cpool::constant_utf8(&classfile.constant_pool, field.name_index) == Some(name)
+            && cpool::constant_utf8(&classfile.constant_pool, field.descriptor_index)
+                == Some(descriptor)
+    }) {
+        return Ok(Some((class, index)));
+    }
+
+    for interface in superinterfaces(loader, class)? {
+        if let Some(found) = lookup_field(loader, interface, name, descriptor)? {
+            return Ok(Some(found));
+        }
+    }
+
+    match superclass(loader, class)? {
+        Some(superclass) => lookup_field(loader, superclass, name, descriptor),
+        None => Ok(None),
+    }
+}
+
+// selects among the maximally-specific superinterface methods, preferring the only non-abstract
+// one and otherwise picking any
+fn lookup_superinterface_method<L: ClassLoader>(
+    loader: &L,
+    class: L::Class,
+    name: &JStr,
+    descriptor: &JStr,
+) -> ResolutionResult<Option<(L::Class, usize)>> {
+    let interfaces = all_superinterfaces(loader, class)?;
+
+    let mut candidates = Vec::new();
+    for interface in &interfaces {
+        let Some(index) = find_method(loader, *interface, name, descriptor) else {
+            continue;
+        };
+
+        let access_flags = loader.classfile(*interface).methods[index].access_flags;
+        if !access_flags.flag_set(ACC_PRIVATE) && !access_flags.flag_set(ACC_STATIC) {
+            candidates.push((*interface, index));
+        }
+    }
+
+    let mut maximally_specific = Vec::new();
+    for (interface, index) in &candidates {
+        let mut overridden = false;
+        for (other, _) in &candidates {
+            if other != interface && all_superinterfaces(loader, *other)?.contains(interface) {
+                overridden = true;
+                break;
+            }
+        }
+
+        if !overridden {
+            maximally_specific.push((*interface, *index));
+        }
+    }
+
+    let non_abstract = maximally_specific
+        .iter()
+        .filter(|(interface, index)| {
+            !loader.classfile(*interface).methods[*index]
+                .access_flags
+                .flag_set(MethodAccessFlags::ACC_ABSTRACT)
+        })
+        .collect::<Vec<_>>();
+
+    if let [only] = non_abstract[..] {
+        return Ok(Some(*only));
+    }
+
+    Ok(maximally_specific.first().copied())
+}
+
+// a signature polymorphic method is resolved by name alone, provided no other method shares it
+fn signature_polymorphic_method<L: ClassLoader>(
+    loader: &L,
+    class: L::Class,
+    class_name: &JStr,
+    name: &JStr,
+) -> Option<(L::Class, usize)> {
+    let classfile = loader.classfile(class);
+
+    let mut candidates = classfile.methods.iter().enumerate().filter(|(_, method)| {
+        cpool::constant_utf8(&classfile.constant_pool, method.name_index) == Some(name)
+    });
+
+    let (index, method) = candidates.next()?;
+    if candidates.next().is_some()
+        || !methodhandle::is_signature_polymorphic(&classfile.constant_pool, class_name, method)
+    {
+        return None;
+    }
+
+    Some((class, index))
+}
+
+fn find_method<L: ClassLoader>(
+    loader: &L,
+    class: L::Class,
+    name: &JStr,
+    descriptor: &JStr,
+) -> Option<usize> {
+    let classfile = loader.classfile(class);
+
+    classfile.methods.iter().position(|method| {
+        cpool::constant_utf8(&classfile.constant_pool, method.name_index) == Some(name)
+            && cpool::constant_utf8(&classfile.constant_pool, method.descriptor_index)
+                == Some(descriptor)
+    })
+}
+
+fn superclass<L: ClassLoader>(loader: &L, class: L::Class) -> ResolutionResult<Option<L::Class>> {
+    let classfile = loader.classfile(class);
+    if classfile.super_class == 0 {
+        return Ok(None);
+    }
+
+    let name = cpool::constant_class_name(&classfile.constant_pool, classfile.super_class).ok_or(
+        ResolutionError::InvalidConstantPoolEntry {
+            index: classfile.super_class,
+        },
+    )?;
+
+    loader.load_class(name, class).map(Some)
+}
+
+fn superinterfaces<L: ClassLoader>(loader: &L, class: L::Class) -> ResolutionResult<Vec<L::Class>> {
+    let classfile = loader.classfile(class);
+
+    classfile
+        .interfaces
+        .iter()
+        .map(|index| {
+            let index = index.to_u16();
+            let name = cpool::constant_class_name(&classfile.constant_pool, index)
+                .ok_or(ResolutionError::InvalidConstantPoolEntry { index })?;
+
+            loader.load_class(name, class)
+        })
+        .collect()
+}
+
+// the direct and indirect superinterfaces of a class and its superclasses
+fn all_superinterfaces<L: ClassLoader>(
+    loader: &L,
+    class: L::Class,
+) -> ResolutionResult<Vec<L::Class>> {
+    let mut output = Vec::new();
+    let mut pending = vec![class];
+
+    while let Some(current) = pending.pop() {
+        for interface in superinterfaces(loader, current)? {
+            if !output.contains(&interface) {
+                output.push(interface);
+                pending.push(interface);
+            }
+        }
+
+        if let Some(superclass) = superclass(loader, current)? {
+            pending.push(superclass);
+        }
+    }
+
+    Ok(output)
+}
+
+fn is_subclass<L: ClassLoader>(
+    loader: &L,
+    class: L::Class,
+    superclass_of: L::Class,
+) -> ResolutionResult<bool> {
+    let mut current = Some(class);
+    while let Some(class) = current {
+        if class == superclass_of {
+            return Ok(true);
+        }
+
+        current = superclass(loader, class)?;
+    }
+
+    Ok(false)
+}
+
+fn is_interface<L: ClassLoader>(loader: &L, class: L::Class) -> bool {
+    loader
+        .classfile(class)
+        .access_flags
+        .flag_set(ClassAccessFlags::ACC_INTERFACE)
+}
+
+fn class_name<L: ClassLoader>(loader: &L, class: L::Class) -> String {
+    let classfile = loader.classfile(class);
+
+    cpool::constant_class_name(&classfile.constant_pool, classfile.this_class)
+        .map(|string| string.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn method_name(class_name: &JStr, name: &JStr, descriptor: &JStr) -> String {
+    format!(
+        "{}.{}{}",
+        class_name.to_string_lossy(),
+        name.to_string_lossy(),
+        descriptor.to_string_lossy()
+    )
+}
@@ -0,0 +1,208 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+// The table of interned strings shared by ldc of string constants and String.intern.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::CpInfo;
+
+use crate::cpool;
+
+// each shard has its own lock, so threads interning different strings rarely wait on each other
+const SHARD_COUNT: usize = 32;
+
+/// A weak reference to an interned string, which the heap clears once the string is no longer
+/// reachable. The table only holds these, so that interning does not keep strings alive.
+///
+/// There is no heap in the tree yet to hand out handles its collector traces and clears, so the
+/// only implementation is `std::sync::Weak`, which is cleared by reference counting instead.
+pub trait WeakHandle {
+    type Strong: Clone;
+
+    fn downgrade(strong: &Self::Strong) -> Self;
+
+    fn upgrade(&self) -> Option<Self::Strong>;
+
+    fn is_cleared(&self) -> bool;
+}
+
+impl<S> WeakHandle for Weak<S> {
+    type Strong = Arc<S>;
+
+    fn downgrade(strong: &Arc<S>) -> Self {
+        Arc::downgrade(strong)
+    }
+
+    fn upgrade(&self) -> Option<Arc<S>> {
+        Weak::upgrade(self)
+    }
+
+    fn is_cleared(&self) -> bool {
+        self.strong_count() == 0
+    }
+}
+
+/// Maps string contents to the `java.lang.String` instance interned for them, through weak
+/// handles so that interned strings which are no longer referenced can be collected.
+pub struct StringTable<W> {
+    shards: Vec<Mutex<HashMap<JString, W>>>,
+}
+
+impl<W: WeakHandle> StringTable<W> {
+    pub fn new() -> Self {
+        Self {
+            shards: (0..SHARD_COUNT)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+        }
+    }
+
+    /// Returns the string interned for `string`, interning the one made by `create` if there is
+    /// none. `String.intern` passes a `create` returning the receiver itself.
+    pub fn intern(&self, string: &JStr, create: impl FnOnce() -> W::Strong) -> W::Strong {
+        if let Some(interned) = self.lookup(string) {
+            return interned;
+        }
+
+        // creating a string may allocate and so collect, which purges the table; no shard is
+        // locked meanwhile, and a string interned by another thread in the meantime wins
+        let created = create();
+
+        let mut shard = self.shard(string).lock().unwrap();
+        if let Some(interned) = shard.get(string).and_then(W::upgrade) {
+            return interned;
+        }
+
+        // a cleared entry for the same contents is simply replaced
+        shard.insert(string.to_owned(), W::downgrade(&created));
+
+        created
+    }
+
+    pub fn lookup(&self, string: &JStr) -> Option<W::Strong> {
+        self.shard(string)
+            .lock()
+            .unwrap()
+            .get(string)
+            .and_then(W::upgrade)
+    }
+
+    /// Removes the entries of strings that have been collected, returning how many there were.
+    pub fn purge(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| {
+                let mut shard = shard.lock().unwrap();
+                let before = shard.len();
+                shard.retain(|_, interned| !interned.is_cleared());
+
+                before - shard.len()
+            })
+            .sum()
+    }
+
+    pub fn statistics(&self) -> StringTableStatistics {
+        let mut statistics = StringTableStatistics {
+            shards: self.shards.len(),
+            ..Default::default()
+        };
+
+        for shard in &self.shards {
+            let shard = shard.lock().unwrap();
+
+            statistics.entries += shard.len();
+            statistics.max_shard_entries = statistics.max_shard_entries.max(shard.len());
+            for (string, interned) in shard.iter() {
+                if interned.is_cleared() {
+                    statistics.dead_entries += 1;
+                } else {
+                    statistics.literal_bytes += string.len();
+                }
+            }
+        }
+
+        statistics
+    }
+
+    fn shard(&self, string: &JStr) -> &Mutex<HashMap<JString, W>> {
+        // the maps themselves are keyed randomly, so a fixed hasher is fine for picking the shard
+        let mut hasher = DefaultHasher::new();
+        string.hash(&mut hasher);
+
+        &self.shards[hasher.finish() as usize % self.shards.len()]
+    }
+}
+
+impl<W: WeakHandle> Default for StringTable<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What `-XX:+PrintStringTableStatistics` prints at exit.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct StringTableStatistics {
+    pub shards: usize,
+    pub entries: usize,
+    // entries whose strings have been collected but not purged yet
+    pub dead_entries: usize,
+    pub literal_bytes: usize,
+    pub max_shard_entries: usize,
+}
+
+impl fmt::Display for StringTableStatistics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "StringTable statistics:")?;
+        writeln!(f, "Number of shards        : {:>9}", self.shards)?;
+        writeln!(f, "Number of entries       : {:>9}", self.entries)?;
+        writeln!(f, "Number of dead entries  : {:>9}", self.dead_entries)?;
+        writeln!(f, "Total literal bytes     : {:>9}", self.literal_bytes)?;
+        write!(f, "Maximum shard size      : {:>9}", self.max_shard_entries)
+    }
+}
+
+/// Resolves the `ConstantString` at `index` for `ldc`, creating a string for its contents with
+/// `create` unless one has been interned already.
+pub fn resolve_string_constant<W: WeakHandle>(
+    table: &StringTable<W>,
+    constant_pool: &[CpInfo],
+    index: u16,
+    create: impl FnOnce(&JStr) -> W::Strong,
+) -> Option<W::Strong> {
+    let CpInfoType::ConstantString { string_index } =
+        constant_pool.get((index as usize).checked_sub(1)?)?.info
+    else {
+        return None;
+    };
+
+    let string = cpool::constant_utf8(constant_pool, string_index)?;
+    Some(table.intern(string, || create(string)))
+}
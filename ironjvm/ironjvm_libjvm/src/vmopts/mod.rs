@@ -0,0 +1,283 @@
+// SPDX-License-Identifier: GPL-2.0
+/*
+ * IronJVM: JVM Implementation in Rust
+ * Copyright (C) 2022 HTGAzureX1212.
+ *
+ * This program is free software; you can redistribute it and/or modify
+ * it under the terms of the GNU General Public License as published by
+ * the Free Software Foundation; either version 2 of the License, or
+ * (at your option) any later version.
+ *
+ * This program is distributed in the hope that it will be useful,
+ * but WITHOUT ANY WARRANTY; without even the implied warranty of
+ * MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+ * GNU General Public License for more details.
+ *
+ * You should have received a copy of the GNU General Public License along
+ * with this program; if not, write to the Free Software Foundation, Inc.,
+ * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
+ */
+
+use std::collections::HashMap;
+use std::env;
+use std::ffi::CStr;
+use std::path::PathBuf;
+
+use jni_sys::JavaVMInitArgs;
+use jni_sys::JNI_TRUE;
+
+use crate::vmopts::error::VmOptionsError;
+use crate::vmopts::error::VmOptionsResult;
+
+pub mod error;
+
+// the object age is stored in 4 bits of the object header
+pub const MAX_OBJECT_AGE: u8 = 15;
+
+// HotSpot bounds ThreadStackSize, which -Xss sets, to 1GB
+pub const MAX_THREAD_STACK_SIZE: usize = 1 << 30;
+
+// the launcher passes its exit, abort and vfprintf hooks as options of these names, with the
+// function pointer in extraInfo
+const HOOK_OPTIONS: [&str; 3] = ["exit", "abort", "vfprintf"];
+
+// FIXME: there is no heap yet, so the nursery size and tenuring thresholds are only parsed and
+// validated until a generational collector uses them
+#[derive(Debug)]
+pub struct VmOptions {
+    pub new_size: Option<usize>,
+    pub initial_tenuring_threshold: u8,
+    pub max_tenuring_threshold: u8,
+    // whether -XX:InitialTenuringThreshold was given, rather than defaulted
+    initial_tenuring_threshold_set: bool,
+    // FIXME: there are no Java threads yet, so -Xss is only validated
+    pub thread_stack_size: Option<usize>,
+    pub system_properties: HashMap<String, String>,
+    // options that are accepted but have no effect yet, such as -Xmx, and unrecognized ones
+    // skipped because of ignoreUnrecognized
+    pub ignored_options: Vec<String>,
+    pub use_indy_intrinsics: bool,
+    pub module_path: Vec<PathBuf>,
+    pub compact_strings: bool,
+    pub print_string_table_statistics: bool,
+}
+
+impl VmOptions {
+    /// Parses the options passed to `JNI_CreateJavaVM`.
+    ///
+    /// # Safety
+    ///
+    /// `args` must be null or point to a valid `JavaVMInitArgs` whose `options` array holds
+    /// `nOptions` entries with NUL-terminated option strings.
+    pub unsafe fn from_init_args(args: *const JavaVMInitArgs) -> VmOptionsResult<Self> {
+        if args.is_null() {
+            return Err(VmOptionsError::NullInitArgs);
+        }
+
+        let mut options = Self::default();
+        let args = &*args;
+        if args.nOptions > 0 && args.options.is_null() {
+            return Err(VmOptionsError::NullInitArgs);
+        }
+
+        let ignore_unrecognized = args.ignoreUnrecognized == JNI_TRUE;
+
+        for i in 0..args.nOptions.max(0) as usize {
+            let option = &*args.options.add(i);
+            if option.optionString.is_null() {
+                return Err(VmOptionsError::InvalidOptionString);
+            }
+
+            let Ok(string) = CStr::from_ptr(option.optionString).to_str() else {
+                return Err(VmOptionsError::InvalidOptionString);
+            };
+
+            // as the invocation API specifies, ignoreUnrecognized only skips unrecognized -X
+            // and _ options, and malformed values of recognized options are always an error
+            match options.parse_option(string) {
+                Err(VmOptionsError::UnrecognizedOption { option })
+                    if ignore_unrecognized
+                        && (option.starts_with("-X") || option.starts_with('_')) =>
+                {
+                    options.ignored_options.push(option);
+                }
+                result => result?,
+            }
+        }
+
+        options.validate()?;
+
+        Ok(options)
+    }
+
+    pub fn parse_option(&mut self, option: &str) -> VmOptionsResult<()> {
+        if let Some(flag) = option.strip_prefix("-XX:") {
+            return self.parse_xx_option(option, flag);
+        }
+
+        if HOOK_OPTIONS.contains(&option) {
+            return Ok(());
+        }
+
+        if let Some(property) = option.strip_prefix("-D") {
+            let (name, value) = property.split_once('=').unwrap_or((property, ""));
+
+            // the launcher passes --module-path as this property
+            if name == "jdk.module.path" {
+                self.module_path = env::split_paths(value).collect();
+            }
+
+            self.system_properties
+                .insert(name.to_string(), value.to_string());
+            return Ok(());
+        }
+
+        if let Some(size) = option.strip_prefix("-Xmn") {
+            self.new_size = Some(parse_size(option, size)?);
+            return Ok(());
+        }
+
+        if let Some(size) = option.strip_prefix("-Xss") {
+            self.thread_stack_size = Some(parse_size(option, size)?);
+            return Ok(());
+        }
+
+        // the heap is not sized yet, but the launcher and scripts pass these routinely
+        if let Some(size) = option
+            .strip_prefix("-Xms")
+            .or_else(|| option.strip_prefix("-Xmx"))
+        {
+            parse_size(option, size)?;
+            self.ignored_options.push(option.to_string());
+            return Ok(());
+        }
+
+        Err(VmOptionsError::UnrecognizedOption {
+            option: option.to_string(),
+        })
+    }
+
+    fn parse_xx_option(&mut self, option: &str, flag: &str) -> VmOptionsResult<()> {
+        if let Some(name) = flag.strip_prefix('+') {
+            return self.parse_xx_bool_option(option, name, true);
+        }
+
+        if let Some(name) = flag.strip_prefix('-') {
+            return self.parse_xx_bool_option(option, name, false);
+        }
+
+        let Some((name, value)) = flag.split_once('=') else {
+            return Err(VmOptionsError::UnrecognizedOption {
+                option: option.to_string(),
+            });
+        };
+
+        match name {
+            "InitialTenuringThreshold" => {
+                self.initial_tenuring_threshold = parse_tenuring_threshold(option, value)?;
+                self.initial_tenuring_threshold_set = true;
+            }
+            "MaxTenuringThreshold" => {
+                self.max_tenuring_threshold = parse_tenuring_threshold(option, value)?;
+            }
+            "NewSize" => self.new_size = Some(parse_size(option, value)?),
+            _ => {
+                return Err(VmOptionsError::UnrecognizedOption {
+                    option: option.to_string(),
+                })
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_xx_bool_option(
+        &mut self,
+        option: &str,
+        name: &str,
+        enabled: bool,
+    ) -> VmOptionsResult<()> {
+        match name {
+            "CompactStrings" => self.compact_strings = enabled,
+            "PrintStringTableStatistics" => self.print_string_table_statistics = enabled,
+            "UseIndyIntrinsics" => self.use_indy_intrinsics = enabled,
+            _ => {
+                return Err(VmOptionsError::UnrecognizedOption {
+                    option: option.to_string(),
+                })
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate(&mut self) -> VmOptionsResult<()> {
+        if let Some(size) = self.thread_stack_size {
+            if size > MAX_THREAD_STACK_SIZE {
+                return Err(VmOptionsError::ThreadStackSizeOutOfRange { size });
+            }
+        }
+
+        // as in HotSpot, a defaulted initial threshold is lowered to the maximum, and only
+        // conflicting explicit values are an error
+        if self.initial_tenuring_threshold > self.max_tenuring_threshold {
+            if !self.initial_tenuring_threshold_set {
+                self.initial_tenuring_threshold = self.max_tenuring_threshold;
+                return Ok(());
+            }
+
+            return Err(VmOptionsError::InitialTenuringThresholdExceedsMax {
+                initial: self.initial_tenuring_threshold,
+                max: self.max_tenuring_threshold,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for VmOptions {
+    fn default() -> Self {
+        Self {
+            new_size: None,
+            initial_tenuring_threshold: 7,
+            max_tenuring_threshold: MAX_OBJECT_AGE,
+            initial_tenuring_threshold_set: false,
+            thread_stack_size: None,
+            system_properties: HashMap::new(),
+            ignored_options: Vec::new(),
+            use_indy_intrinsics: false,
+            module_path: Vec::new(),
+            compact_strings: true,
+            print_string_table_statistics: false,
+        }
+    }
+}
+
+fn parse_size(option: &str, value: &str) -> VmOptionsResult<usize> {
+    let (digits, shift) = match value.as_bytes().last() {
+        Some(b'k' | b'K') => (&value[..value.len() - 1], 10),
+        Some(b'm' | b'M') => (&value[..value.len() - 1], 20),
+        Some(b'g' | b'G') => (&value[..value.len() - 1], 30),
+        Some(b't' | b'T') => (&value[..value.len() - 1], 40),
+        _ => (value, 0),
+    };
+
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|size| size.checked_mul(1usize.checked_shl(shift)?))
+        .ok_or_else(|| VmOptionsError::InvalidValue {
+            option: option.to_string(),
+        })
+}
+
+fn parse_tenuring_threshold(option: &str, value: &str) -> VmOptionsResult<u8> {
+    value
+        .parse::<u8>()
+        .ok()
+        .filter(|threshold| *threshold <= MAX_OBJECT_AGE)
+        .ok_or_else(|| VmOptionsError::InvalidValue {
+            option: option.to_string(),
+        })
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::sync::Barrier;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use ironjvm_libjvm::classinit::ClassInitializer;
+use ironjvm_libjvm::classinit::InitializationState;
+use ironjvm_libjvm::classinit::InitializationTable;
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Clinit {
+    Succeed,
+    Throw,
+    Panic,
+    // requests the initialization of the class again from its own <clinit>
+    Recurse,
+    // blocks until released, to let other threads wait for the initialization
+    Block,
+}
+
+#[derive(Default)]
+struct Classes {
+    table: InitializationTable<&'static str>,
+    superclasses: HashMap<&'static str, &'static str>,
+    superinterfaces: HashMap<&'static str, Vec<&'static str>>,
+    interfaces: Vec<&'static str>,
+    default_methods: Vec<&'static str>,
+    clinits: HashMap<&'static str, Clinit>,
+    // the classes whose <clinit> ran, in order
+    initialized: Mutex<Vec<&'static str>>,
+    recursive_result: Mutex<Option<Result<(), String>>>,
+    // opened by the blocking <clinit> once it starts, and closed by the test to release it
+    started: Option<Barrier>,
+    release: Option<Barrier>,
+}
+
+impl ClassInitializer for Classes {
+    type Class = &'static str;
+    type Throwable = String;
+
+    fn is_interface(&self, class: &&'static str) -> bool {
+        self.interfaces.contains(class)
+    }
+
+    fn superclass(&self, class: &&'static str) -> Option<&'static str> {
+        self.superclasses.get(class).copied()
+    }
+
+    fn superinterfaces(&self, class: &&'static str) -> Vec<&'static str> {
+        self.superinterfaces.get(class).cloned().unwrap_or_default()
+    }
+
+    fn declares_default_methods(&self, class: &&'static str) -> bool {
+        self.default_methods.contains(class)
+    }
+
+    fn preload_constant_values(&self, _: &&'static str) {}
+
+    fn run_clinit(&self, class: &&'static str) -> Result<(), String> {
+        self.initialized.lock().unwrap().push(class);
+
+        match self.clinits.get(class).copied().unwrap_or(Clinit::Succeed) {
+            Clinit::Succeed => Ok(()),
+            Clinit::Throw => Err(format!("RuntimeException in {class}")),
+            Clinit::Panic => panic!("<clinit> of {class} panicked"),
+            Clinit::Recurse => {
+                let result = self.table.initialize(self, class);
+                *self.recursive_result.lock().unwrap() = Some(result);
+                Ok(())
+            }
+            Clinit::Block => {
+                self.started.as_ref().unwrap().wait();
+                self.release.as_ref().unwrap().wait();
+                Ok(())
+            }
+        }
+    }
+
+    fn no_class_def_found_error(&self, class: &&'static str) -> String {
+        format!("NoClassDefFoundError: {class}")
+    }
+
+    fn exception_in_initializer_error(&self, exception: String) -> String {
+        format!("ExceptionInInitializerError: {exception}")
+    }
+}
+
+fn initialize(classes: &Classes, class: &'static str) -> Result<(), String> {
+    classes.table.initialize(classes, &class)
+}
+
+#[test]
+fn supertypes_first() {
+    let classes = Classes {
+        superclasses: HashMap::from([("C", "B"), ("B", "A")]),
+        superinterfaces: HashMap::from([("C", vec!["J"]), ("J", vec!["I", "K"])]),
+        interfaces: vec!["I", "J", "K"],
+        default_methods: vec!["I", "J"],
+        ..Default::default()
+    };
+
+    assert_eq!(initialize(&classes, "C"), Ok(()));
+    // K declares no default methods, and interfaces do not initialize their superinterfaces
+    assert_eq!(
+        *classes.initialized.lock().unwrap(),
+        ["A", "B", "I", "J", "C"]
+    );
+    assert_eq!(initialize(&classes, "J"), Ok(()));
+    assert_eq!(classes.initialized.lock().unwrap().len(), 5);
+    assert_eq!(classes.table.state(&"K"), None);
+}
+
+#[test]
+fn recursive_initialization() {
+    let classes = Classes {
+        clinits: HashMap::from([("A", Clinit::Recurse)]),
+        ..Default::default()
+    };
+
+    assert_eq!(initialize(&classes, "A"), Ok(()));
+    assert_eq!(*classes.recursive_result.lock().unwrap(), Some(Ok(())));
+    assert_eq!(*classes.initialized.lock().unwrap(), ["A"]);
+    assert_eq!(
+        classes.table.state(&"A"),
+        Some(InitializationState::Initialized)
+    );
+}
+
+#[test]
+fn concurrent_initialization() {
+    let classes = Classes {
+        clinits: HashMap::from([("A", Clinit::Block)]),
+        started: Some(Barrier::new(2)),
+        release: Some(Barrier::new(2)),
+        ..Default::default()
+    };
+
+    thread::scope(|scope| {
+        let first = scope.spawn(|| initialize(&classes, "A"));
+        classes.started.as_ref().unwrap().wait();
+
+        let state = classes.table.state(&"A");
+        assert!(matches!(
+            state,
+            Some(InitializationState::BeingInitialized(thread)) if thread == first.thread().id()
+        ));
+
+        let second = scope.spawn(|| initialize(&classes, "A"));
+        // give the second thread time to start waiting for the first one
+        thread::sleep(Duration::from_millis(50));
+        assert!(!second.is_finished());
+
+        classes.release.as_ref().unwrap().wait();
+        assert_eq!(first.join().unwrap(), Ok(()));
+        assert_eq!(second.join().unwrap(), Ok(()));
+    });
+
+    assert_eq!(*classes.initialized.lock().unwrap(), ["A"]);
+}
+
+#[test]
+fn errors() {
+    let classes = Classes {
+        superclasses: HashMap::from([("B", "A")]),
+        clinits: HashMap::from([("A", Clinit::Throw)]),
+        ..Default::default()
+    };
+
+    // the exception of A is wrapped once, and rethrown as-is while initializing B
+    assert_eq!(
+        initialize(&classes, "B"),
+        Err("ExceptionInInitializerError: RuntimeException in A".to_string())
+    );
+    assert_eq!(
+        classes.table.state(&"A"),
+        Some(InitializationState::Erroneous)
+    );
+    assert_eq!(
+        classes.table.state(&"B"),
+        Some(InitializationState::Erroneous)
+    );
+
+    assert_eq!(
+        initialize(&classes, "A"),
+        Err("NoClassDefFoundError: A".to_string())
+    );
+    assert_eq!(
+        initialize(&classes, "B"),
+        Err("NoClassDefFoundError: B".to_string())
+    );
+    assert_eq!(*classes.initialized.lock().unwrap(), ["A"]);
+}
+
+#[test]
+fn panics() {
+    let classes = Classes {
+        superclasses: HashMap::from([("B", "A")]),
+        clinits: HashMap::from([("A", Clinit::Panic)]),
+        ..Default::default()
+    };
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| initialize(&classes, "B")));
+    assert!(result.is_err());
+    assert_eq!(
+        classes.table.state(&"A"),
+        Some(InitializationState::Erroneous)
+    );
+    assert_eq!(
+        classes.table.state(&"B"),
+        Some(InitializationState::Erroneous)
+    );
+
+    // another thread does not wait for the class forever
+    let result = thread::scope(|scope| scope.spawn(|| initialize(&classes, "A")).join());
+    assert_eq!(result.unwrap(), Err("NoClassDefFoundError: A".to_string()));
+}
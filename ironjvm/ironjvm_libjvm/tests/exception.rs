@@ -0,0 +1,307 @@
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_libjvm::exception::find_exception_handler;
+use ironjvm_libjvm::exception::line_number;
+use ironjvm_libjvm::exception::unwind;
+use ironjvm_libjvm::exception::Frame;
+use ironjvm_libjvm::exception::StackTraceElement;
+use ironjvm_specimpl::classfile::attrinfo::cattr::CodeAttributeExceptionTableEntry;
+use ironjvm_specimpl::classfile::attrinfo::lntattr::LineNumber;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::AttributeInfo;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::MethodInfo;
+
+// the catch types of the handlers below, by constant pool index
+const EXCEPTION: u16 = 1;
+const IO_EXCEPTION: u16 = 2;
+const RUNTIME_EXCEPTION: u16 = 3;
+
+// the superclasses of java/io/FileNotFoundException that handlers can name, itself included
+const FILE_NOT_FOUND: [u16; 2] = [IO_EXCEPTION, EXCEPTION];
+
+fn entry(
+    start_pc: u16,
+    end_pc: u16,
+    handler_pc: u16,
+    catch_type: u16,
+) -> CodeAttributeExceptionTableEntry {
+    CodeAttributeExceptionTableEntry {
+        start_pc,
+        end_pc,
+        handler_pc,
+        catch_type,
+    }
+}
+
+fn method(
+    exception_table: Vec<CodeAttributeExceptionTableEntry>,
+    attributes: Vec<AttributeInfo<'static>>,
+) -> MethodInfo<'static> {
+    MethodInfo {
+        access_flags: 0,
+        name_index: 0,
+        descriptor_index: 0,
+        attributes_count: 1,
+        attributes: vec![AttributeInfo {
+            attribute_name_index: 0,
+            attribute_length: 0,
+            info: AttributeInfoType::CodeAttribute {
+                max_stack: 1,
+                max_locals: 0,
+                code_length: 64,
+                code: &[0; 64],
+                exception_table_length: exception_table.len() as u16,
+                exception_table,
+                attributes_count: attributes.len() as u16,
+                attributes,
+            },
+        }],
+    }
+}
+
+fn classfile() -> ClassFile<'static> {
+    ClassFile {
+        magic: 0xCAFEBABE,
+        minor_version: 0,
+        major_version: 61,
+        constant_pool_count: 1,
+        constant_pool: Vec::new(),
+        access_flags: 0,
+        this_class: 0,
+        super_class: 0,
+        interfaces_count: 0,
+        interfaces: &[],
+        fields_count: 0,
+        fields: Vec::new(),
+        methods_count: 0,
+        methods: Vec::new(),
+        attributes_count: 0,
+        attributes: Vec::new(),
+    }
+}
+
+fn file_not_found(catch_type: u16) -> bool {
+    FILE_NOT_FOUND.contains(&catch_type)
+}
+
+#[test]
+fn pc_range_ends() {
+    let method = method(vec![entry(4, 10, 20, 0)], Vec::new());
+
+    // start_pc is inclusive and end_pc exclusive
+    assert_eq!(find_exception_handler(&method, 3, file_not_found), None);
+    assert_eq!(find_exception_handler(&method, 4, file_not_found), Some(20));
+    assert_eq!(find_exception_handler(&method, 9, file_not_found), Some(20));
+    assert_eq!(find_exception_handler(&method, 10, file_not_found), None);
+}
+
+#[test]
+fn handler_order() {
+    // the first matching handler in the table wins, even if a later one is more specific
+    let method = method(
+        vec![
+            entry(0, 10, 20, RUNTIME_EXCEPTION),
+            entry(0, 10, 30, EXCEPTION),
+            entry(0, 10, 40, IO_EXCEPTION),
+        ],
+        Vec::new(),
+    );
+    assert_eq!(find_exception_handler(&method, 5, file_not_found), Some(30));
+
+    // inner try blocks come first, as javac emits them
+    let method = method_with_nested_handlers();
+    assert_eq!(find_exception_handler(&method, 5, file_not_found), Some(20));
+    assert_eq!(
+        find_exception_handler(&method, 12, file_not_found),
+        Some(30)
+    );
+}
+
+fn method_with_nested_handlers() -> MethodInfo<'static> {
+    method(
+        vec![entry(4, 8, 20, IO_EXCEPTION), entry(0, 16, 30, EXCEPTION)],
+        Vec::new(),
+    )
+}
+
+#[test]
+fn catch_types() {
+    // a subclass of the catch type is caught, and other classes are not
+    let method = method(
+        vec![
+            entry(0, 10, 20, RUNTIME_EXCEPTION),
+            entry(0, 10, 30, IO_EXCEPTION),
+        ],
+        Vec::new(),
+    );
+    assert_eq!(find_exception_handler(&method, 0, file_not_found), Some(30));
+    assert_eq!(
+        find_exception_handler(&method, 0, |catch_type| catch_type == RUNTIME_EXCEPTION),
+        Some(20)
+    );
+    assert_eq!(find_exception_handler(&method, 0, |_| false), None);
+
+    // a catch type of 0 is a finally block, which catches everything without asking
+    let method = method_with_finally();
+    let mut asked = Vec::new();
+    let handler = find_exception_handler(&method, 0, |catch_type| {
+        asked.push(catch_type);
+        false
+    });
+    assert_eq!(handler, Some(30));
+    assert_eq!(asked, [RUNTIME_EXCEPTION]);
+}
+
+fn method_with_finally() -> MethodInfo<'static> {
+    method(
+        vec![entry(0, 10, 20, RUNTIME_EXCEPTION), entry(0, 10, 30, 0)],
+        Vec::new(),
+    )
+}
+
+#[test]
+fn no_code() {
+    let method = MethodInfo {
+        access_flags: 0,
+        name_index: 0,
+        descriptor_index: 0,
+        attributes_count: 0,
+        attributes: Vec::new(),
+    };
+
+    assert_eq!(find_exception_handler(&method, 0, |_| true), None);
+    assert_eq!(line_number(&method, 0), None);
+}
+
+#[test]
+fn unwinding() {
+    let classfile = classfile();
+    let caller = method_with_nested_handlers();
+    let callee = method(vec![entry(0, 4, 8, RUNTIME_EXCEPTION)], Vec::new());
+
+    // the callee has no handler for the exception, so it is popped and the caller, waiting at
+    // its invoke instruction, continues at its handler
+    let mut frames = vec![
+        Frame {
+            classfile: &classfile,
+            method: &caller,
+            pc: 5,
+        },
+        Frame {
+            classfile: &classfile,
+            method: &callee,
+            pc: 2,
+        },
+    ];
+    assert!(unwind(&mut frames, |_, catch_type| file_not_found(
+        catch_type
+    )));
+    assert_eq!(frames.len(), 1);
+    assert_eq!(frames[0].pc, 20);
+
+    // a handler in the innermost frame leaves the stack as it is
+    let mut frames = vec![
+        Frame {
+            classfile: &classfile,
+            method: &caller,
+            pc: 5,
+        },
+        Frame {
+            classfile: &classfile,
+            method: &callee,
+            pc: 2,
+        },
+    ];
+    assert!(unwind(&mut frames, |_, catch_type| catch_type == RUNTIME_EXCEPTION));
+    assert_eq!(frames.len(), 2);
+    assert_eq!((frames[0].pc, frames[1].pc), (5, 8));
+
+    // an uncaught exception pops every frame
+    let mut frames = vec![
+        Frame {
+            classfile: &classfile,
+            method: &caller,
+            pc: 16,
+        },
+        Frame {
+            classfile: &classfile,
+            method: &callee,
+            pc: 2,
+        },
+    ];
+    assert!(!unwind(&mut frames, |_, catch_type| file_not_found(
+        catch_type
+    )));
+    assert!(frames.is_empty());
+}
+
+#[test]
+fn line_numbers() {
+    let method = method(
+        Vec::new(),
+        vec![AttributeInfo {
+            attribute_name_index: 0,
+            attribute_length: 0,
+            info: AttributeInfoType::LineNumberTableAttribute {
+                line_number_table_length: 3,
+                line_number_table: vec![
+                    LineNumber {
+                        start_pc: 0,
+                        line_number: 10,
+                    },
+                    LineNumber {
+                        start_pc: 8,
+                        line_number: 12,
+                    },
+                    LineNumber {
+                        start_pc: 4,
+                        line_number: 11,
+                    },
+                ],
+            },
+        }],
+    );
+
+    // the entry with the greatest start_pc not after the pc, in whatever order they come
+    assert_eq!(line_number(&method, 0), Some(10));
+    assert_eq!(line_number(&method, 5), Some(11));
+    assert_eq!(line_number(&method, 8), Some(12));
+    assert_eq!(line_number(&method, 63), Some(12));
+}
+
+#[test]
+fn stack_trace_elements() {
+    let bytes =
+        std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/NullPointers.class")
+            .unwrap();
+    let classfile = ClassFileParser::new(&bytes).parse().unwrap();
+    let element = StackTraceElement::new(&classfile, &classfile.methods[0], 0).unwrap();
+
+    // the internal name of the class is turned into a binary name
+    assert_eq!(
+        element.declaring_class,
+        JString::from("com.github.htgazurex1212.ironjvm.tests.NullPointers")
+    );
+    assert_eq!(element.method_name.as_bytes(), b"<init>");
+    assert_eq!(
+        element.file_name.map(JStr::as_bytes),
+        Some(&b"NullPointers.java"[..])
+    );
+    assert!(element.line_number > 0);
+
+    // a class name that is not valid in internal form has no binary name
+    let mut invalid = ClassFileParser::new(&bytes).parse().unwrap();
+    let this_class = invalid.this_class as usize;
+    let CpInfoType::ConstantClass { name_index } = invalid.constant_pool[this_class - 1].info
+    else {
+        unreachable!();
+    };
+    invalid.constant_pool[name_index as usize - 1].info = CpInfoType::ConstantUtf8 {
+        length: 2,
+        string: JStr::from_jutf8(b"a;").ok().unwrap(),
+    };
+    assert!(StackTraceElement::new(&invalid, &invalid.methods[0], 0).is_none());
+}
@@ -0,0 +1,512 @@
+use std::sync::Mutex;
+
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_libjvm::indy::bootstrap_specifier;
+use ironjvm_libjvm::indy::error::IndyError;
+use ironjvm_libjvm::indy::intrinsic::IndyIntrinsic;
+use ironjvm_libjvm::indy::intrinsic::RecipeElement;
+use ironjvm_libjvm::indy::intrinsic::StringConcatRecipe;
+use ironjvm_libjvm::indy::loadable_constant;
+use ironjvm_libjvm::indy::BootstrapInvoker;
+use ironjvm_libjvm::indy::BootstrapSpecifier;
+use ironjvm_libjvm::indy::LinkageCache;
+use ironjvm_libjvm::indy::LoadableConstant;
+use ironjvm_libjvm::indy::MethodHandleConstant;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::cpinfo::ReferenceKind;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::CpInfo;
+
+fn jstr(string: &str) -> &JStr {
+    JStr::from_jutf8(string.as_bytes()).ok().unwrap()
+}
+
+fn bootstraps() -> ClassFile<'static> {
+    let bytes =
+        std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/Bootstraps.class")
+            .unwrap();
+
+    ClassFileParser::new(Box::leak(bytes.into_boxed_slice()))
+        .parse()
+        .unwrap()
+}
+
+// the indices of the ConstantInvokeDynamic entries, in the order of the call sites
+fn invokedynamic_indices(classfile: &ClassFile) -> Vec<u16> {
+    classfile
+        .constant_pool
+        .iter()
+        .enumerate()
+        .filter(|(_, cp_info)| matches!(cp_info.info, CpInfoType::ConstantInvokeDynamic { .. }))
+        .map(|(index, _)| index as u16 + 1)
+        .collect()
+}
+
+#[test]
+fn lambda_specifier() {
+    let classfile = bootstraps();
+    let specifier = bootstrap_specifier(&classfile, invokedynamic_indices(&classfile)[0]).unwrap();
+
+    assert_eq!(
+        specifier,
+        BootstrapSpecifier {
+            method: MethodHandleConstant {
+                reference_kind: ReferenceKind::InvokeStatic,
+                class: jstr("java/lang/invoke/LambdaMetafactory"),
+                name: jstr("metafactory"),
+                descriptor: jstr(concat!(
+                    "(Ljava/lang/invoke/MethodHandles$Lookup;Ljava/lang/String;",
+                    "Ljava/lang/invoke/MethodType;Ljava/lang/invoke/MethodType;",
+                    "Ljava/lang/invoke/MethodHandle;Ljava/lang/invoke/MethodType;)",
+                    "Ljava/lang/invoke/CallSite;"
+                )),
+                is_interface: false,
+            },
+            name: jstr("get"),
+            descriptor: jstr("()Ljava/util/function/Supplier;"),
+            arguments: vec![
+                LoadableConstant::MethodType(jstr("()Ljava/lang/Object;")),
+                LoadableConstant::MethodHandle(MethodHandleConstant {
+                    reference_kind: ReferenceKind::InvokeStatic,
+                    class: jstr("com/github/htgazurex1212/ironjvm/tests/Bootstraps"),
+                    name: jstr("lambda$lambda$0"),
+                    descriptor: jstr("()Ljava/lang/String;"),
+                    is_interface: false,
+                }),
+                LoadableConstant::MethodType(jstr("()Ljava/lang/String;")),
+            ],
+        }
+    );
+}
+
+#[test]
+fn concat_specifier() {
+    let classfile = bootstraps();
+    let specifier = bootstrap_specifier(&classfile, invokedynamic_indices(&classfile)[1]).unwrap();
+
+    assert_eq!(specifier.method.name, jstr("makeConcatWithConstants"));
+    assert_eq!(specifier.name, jstr("makeConcatWithConstants"));
+    assert_eq!(
+        specifier.descriptor,
+        jstr("(Ljava/lang/String;I)Ljava/lang/String;")
+    );
+    assert_eq!(
+        specifier.arguments,
+        [LoadableConstant::String(jstr(
+            "string \u{1} and number \u{1}"
+        ))]
+    );
+}
+
+#[test]
+fn invalid_specifiers() {
+    let mut classfile = bootstraps();
+    let [lambda, concat] = invokedynamic_indices(&classfile)[..] else {
+        panic!("expected two call sites");
+    };
+
+    assert!(matches!(
+        bootstrap_specifier(&classfile, classfile.this_class),
+        Err(IndyError::InvalidConstantPoolEntry { index }) if index == classfile.this_class
+    ));
+    assert!(matches!(
+        bootstrap_specifier(&classfile, 0),
+        Err(IndyError::InvalidConstantPoolEntry { index: 0 })
+    ));
+
+    for attribute in &mut classfile.attributes {
+        if let AttributeInfoType::BootstrapMethodsAttribute {
+            bootstrap_methods, ..
+        } = &mut attribute.info
+        {
+            bootstrap_methods.truncate(1);
+        }
+    }
+
+    assert!(bootstrap_specifier(&classfile, lambda).is_ok());
+    assert!(matches!(
+        bootstrap_specifier(&classfile, concat),
+        Err(IndyError::InvalidBootstrapMethodIndex { index: 1 })
+    ));
+
+    classfile.attributes.retain(|attribute| {
+        !matches!(
+            attribute.info,
+            AttributeInfoType::BootstrapMethodsAttribute { .. }
+        )
+    });
+
+    assert!(matches!(
+        bootstrap_specifier(&classfile, lambda),
+        Err(IndyError::MissingBootstrapMethodsAttribute)
+    ));
+}
+
+#[test]
+fn loadable_constants() {
+    let cp_info = |tag, info| CpInfo { tag, info };
+    let constant_pool = [
+        cp_info(
+            3,
+            CpInfoType::ConstantInteger {
+                bytes: -2i32 as u32,
+            },
+        ),
+        cp_info(
+            4,
+            CpInfoType::ConstantFloat {
+                bytes: 1.5f32.to_bits(),
+            },
+        ),
+        cp_info(
+            5,
+            CpInfoType::ConstantLong {
+                high_bytes: 0xFFFF_FFFF,
+                low_bytes: 0xFFFF_FFFD,
+            },
+        ),
+        cp_info(0, CpInfoType::ConstantUnusable),
+        cp_info(
+            6,
+            CpInfoType::ConstantDouble {
+                high_bytes: (0.25f64.to_bits() >> 32) as u32,
+                low_bytes: 0.25f64.to_bits() as u32,
+            },
+        ),
+        cp_info(0, CpInfoType::ConstantUnusable),
+        cp_info(
+            1,
+            CpInfoType::ConstantUtf8 {
+                length: 16,
+                string: jstr("java/lang/Object"),
+            },
+        ),
+        cp_info(7, CpInfoType::ConstantClass { name_index: 7 }),
+        cp_info(8, CpInfoType::ConstantString { string_index: 7 }),
+        cp_info(
+            17,
+            CpInfoType::ConstantDynamic {
+                bootstrap_method_attr_index: 0,
+                name_and_type_index: 0,
+            },
+        ),
+    ];
+    let constant = |index| loadable_constant(&constant_pool, index);
+
+    assert_eq!(constant(1).unwrap(), LoadableConstant::Int(-2));
+    assert_eq!(constant(2).unwrap(), LoadableConstant::Float(1.5));
+    assert_eq!(constant(3).unwrap(), LoadableConstant::Long(-3));
+    assert_eq!(constant(5).unwrap(), LoadableConstant::Double(0.25));
+    assert_eq!(
+        constant(8).unwrap(),
+        LoadableConstant::Class(jstr("java/lang/Object"))
+    );
+    assert_eq!(
+        constant(9).unwrap(),
+        LoadableConstant::String(jstr("java/lang/Object"))
+    );
+    assert_eq!(
+        constant(10).unwrap(),
+        LoadableConstant::Dynamic { index: 10 }
+    );
+
+    // neither the second half of a long nor a Utf8 entry is loadable
+    for index in [0, 4, 7, 11] {
+        assert!(matches!(
+            constant(index),
+            Err(IndyError::InvalidConstantPoolEntry { index: found }) if found == index
+        ));
+    }
+}
+
+// links call sites named "get" and fails the others with the throwable named by their name
+#[derive(Default)]
+struct Invoker {
+    invocations: Mutex<u32>,
+}
+
+impl BootstrapInvoker for Invoker {
+    type Value = String;
+    type Throwable = String;
+
+    fn invoke_bootstrap(&self, specifier: &BootstrapSpecifier) -> Result<String, String> {
+        *self.invocations.lock().unwrap() += 1;
+
+        match specifier.name.to_str().unwrap() {
+            "get" => Ok("CallSite".to_string()),
+            throwable => Err(throwable.to_string()),
+        }
+    }
+
+    fn link_intrinsic(&self, intrinsic: &IndyIntrinsic) -> Option<String> {
+        match intrinsic {
+            IndyIntrinsic::StringConcat(_) => Some("StringConcat".to_string()),
+            IndyIntrinsic::Lambda { .. } => None,
+        }
+    }
+
+    fn is_error(&self, throwable: &String) -> bool {
+        throwable.ends_with("Error")
+    }
+
+    fn bootstrap_method_error(&self, cause: String) -> String {
+        format!("BootstrapMethodError: {cause}")
+    }
+}
+
+fn specifier(name: &'static str) -> BootstrapSpecifier<'static> {
+    BootstrapSpecifier {
+        method: MethodHandleConstant {
+            reference_kind: ReferenceKind::InvokeStatic,
+            class: jstr("Bootstrap"),
+            name: jstr("bootstrap"),
+            descriptor: jstr("()Ljava/lang/invoke/CallSite;"),
+            is_interface: false,
+        },
+        name: jstr(name),
+        descriptor: jstr("()V"),
+        arguments: vec![],
+    }
+}
+
+#[test]
+fn linkage_cache() {
+    let invoker = Invoker::default();
+    let cache = LinkageCache::new(false);
+    let exception = specifier("IllegalArgumentException");
+    let error = specifier("OutOfMemoryError");
+
+    assert_eq!(
+        cache.link(&invoker, 1, &specifier("get")),
+        Ok("CallSite".to_string())
+    );
+    assert_eq!(
+        cache.link(&invoker, 1, &specifier("get")),
+        Ok("CallSite".to_string())
+    );
+    assert_eq!(*invoker.invocations.lock().unwrap(), 1);
+
+    // exceptions are wrapped and errors are not, and both are rethrown on later attempts
+    let wrapped = Err("BootstrapMethodError: IllegalArgumentException".to_string());
+    assert_eq!(cache.link(&invoker, 2, &exception), wrapped);
+    assert_eq!(cache.link(&invoker, 2, &exception), wrapped);
+    assert_eq!(
+        cache.link(&invoker, 3, &error),
+        Err("OutOfMemoryError".to_string())
+    );
+    assert_eq!(
+        cache.link(&invoker, 3, &error),
+        Err("OutOfMemoryError".to_string())
+    );
+    assert_eq!(*invoker.invocations.lock().unwrap(), 3);
+}
+
+#[test]
+fn intrinsic_linkage() {
+    let classfile = bootstraps();
+    let [lambda, concat] = invokedynamic_indices(&classfile)[..] else {
+        panic!("expected two call sites");
+    };
+    let lambda = bootstrap_specifier(&classfile, lambda).unwrap();
+    let concat = bootstrap_specifier(&classfile, concat).unwrap();
+
+    // the bootstrap method still runs when the invoker declines the intrinsic
+    let invoker = Invoker::default();
+    let cache = LinkageCache::new(true);
+    assert_eq!(
+        cache.link(&invoker, 1, &concat),
+        Ok("StringConcat".to_string())
+    );
+    assert_eq!(cache.link(&invoker, 2, &lambda), Ok("CallSite".to_string()));
+    assert_eq!(*invoker.invocations.lock().unwrap(), 1);
+
+    let invoker = Invoker::default();
+    let cache = LinkageCache::new(false);
+    assert_eq!(
+        cache.link(&invoker, 1, &concat),
+        Err("BootstrapMethodError: makeConcatWithConstants".to_string())
+    );
+    assert_eq!(*invoker.invocations.lock().unwrap(), 1);
+}
+
+fn concat_recipe(
+    descriptor: &'static str,
+    arguments: Vec<LoadableConstant<'static>>,
+) -> Option<StringConcatRecipe> {
+    let specifier = BootstrapSpecifier {
+        method: MethodHandleConstant {
+            reference_kind: ReferenceKind::InvokeStatic,
+            class: jstr("java/lang/invoke/StringConcatFactory"),
+            name: jstr("makeConcatWithConstants"),
+            descriptor: jstr("()Ljava/lang/invoke/CallSite;"),
+            is_interface: false,
+        },
+        name: jstr("makeConcatWithConstants"),
+        descriptor: jstr(descriptor),
+        arguments,
+    };
+
+    match IndyIntrinsic::from_specifier(&specifier)? {
+        IndyIntrinsic::StringConcat(recipe) => Some(recipe),
+        intrinsic => panic!("expected a string concatenation, found {intrinsic:?}"),
+    }
+}
+
+fn literal(string: &str) -> RecipeElement {
+    RecipeElement::Literal(JString::from(string))
+}
+
+#[test]
+fn intrinsics() {
+    let classfile = bootstraps();
+    let [lambda, concat] = invokedynamic_indices(&classfile)[..] else {
+        panic!("expected two call sites");
+    };
+
+    let lambda = bootstrap_specifier(&classfile, lambda).unwrap();
+    assert!(matches!(
+        IndyIntrinsic::from_specifier(&lambda),
+        Some(IndyIntrinsic::Lambda { interface_method_name, implementation, .. })
+            if interface_method_name == jstr("get")
+                && implementation.name == jstr("lambda$lambda$0")
+    ));
+
+    let concat = bootstrap_specifier(&classfile, concat).unwrap();
+    let Some(IndyIntrinsic::StringConcat(recipe)) = IndyIntrinsic::from_specifier(&concat) else {
+        panic!("expected a string concatenation");
+    };
+    assert_eq!(
+        recipe.elements,
+        [
+            literal("string "),
+            RecipeElement::Argument,
+            literal(" and number "),
+            RecipeElement::Argument,
+        ]
+    );
+    assert_eq!(
+        recipe.concat(&[jstr("one"), jstr("1")]),
+        Some(JString::from("string one and number 1"))
+    );
+    assert_eq!(recipe.concat(&[jstr("one")]), None);
+
+    // other bootstrap methods are left to run
+    assert_eq!(IndyIntrinsic::from_specifier(&specifier("get")), None);
+}
+
+fn string(string: &'static str) -> LoadableConstant<'static> {
+    LoadableConstant::String(jstr(string))
+}
+
+#[test]
+fn recipe_tags() {
+    let recipe = concat_recipe("(II)Ljava/lang/String;", vec![string("\u{1}\u{1}-")]).unwrap();
+    assert_eq!(
+        recipe.elements,
+        [
+            RecipeElement::Argument,
+            RecipeElement::Argument,
+            literal("-")
+        ]
+    );
+    assert_eq!(recipe.argument_count, 2);
+    assert_eq!(
+        recipe.concat(&[jstr("a"), jstr("b")]),
+        Some(JString::from("ab-"))
+    );
+
+    let recipe = concat_recipe("()Ljava/lang/String;", vec![string("")]).unwrap();
+    assert!(recipe.elements.is_empty());
+    assert_eq!(recipe.concat(&[]), Some(JString::new()));
+
+    // the recipe must have a tag for every parameter of the call site
+    assert_eq!(
+        concat_recipe("(II)Ljava/lang/String;", vec![string("\u{1}-")]),
+        None
+    );
+    assert_eq!(concat_recipe("()Ljava/lang/String;", vec![]), None);
+}
+
+#[test]
+fn recipe_constants() {
+    let recipe = concat_recipe(
+        "(I)Ljava/lang/String;",
+        vec![
+            string("\u{2}:\u{1}:\u{2}\u{2}"),
+            LoadableConstant::Int(-1),
+            LoadableConstant::Long(1 << 40),
+            string("constant"),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        recipe.elements,
+        [
+            literal("-1:"),
+            RecipeElement::Argument,
+            literal(":1099511627776constant"),
+        ]
+    );
+    assert_eq!(
+        recipe.concat(&[jstr("x")]),
+        Some(JString::from("-1:x:1099511627776constant"))
+    );
+
+    // the string conversion of floating point constants is left to the bootstrap method, and
+    // every tag needs exactly one constant
+    let constants = |constants: &[LoadableConstant<'static>]| {
+        let mut arguments = vec![string("\u{2}")];
+        arguments.extend_from_slice(constants);
+        concat_recipe("()Ljava/lang/String;", arguments)
+    };
+    assert!(constants(&[string("one")]).is_some());
+    assert_eq!(constants(&[LoadableConstant::Float(1.5)]), None);
+    assert_eq!(constants(&[LoadableConstant::Double(1.5)]), None);
+    assert_eq!(constants(&[]), None);
+    assert_eq!(constants(&[string("one"), string("two")]), None);
+}
+
+#[test]
+fn recipe_surrogates() {
+    // U+1F600 is the pair D83D DE00, each half encoded on its own in modified UTF-8
+    let high = jstr_bytes(&[0xED, 0xA0, 0xBD]);
+    let low = jstr_bytes(&[0xED, 0xB8, 0x80]);
+    let pair = JString::from("\u{1F600}");
+
+    let mut recipe = JString::from("\u{1}");
+    recipe.push_jstr(high);
+    let recipe = concat_recipe(
+        "(Ljava/lang/String;)Ljava/lang/String;",
+        vec![LoadableConstant::String(Box::leak(Box::new(recipe)))],
+    )
+    .unwrap();
+    assert_eq!(
+        recipe.elements,
+        [RecipeElement::Argument, RecipeElement::Literal(high.into())]
+    );
+
+    // the halves of a pair split between the arguments and the recipe join up
+    let mut expected = JString::from("\u{1F600}");
+    expected.push_jstr(high);
+    assert_eq!(recipe.concat(&[&pair]), Some(expected));
+    assert_eq!(
+        recipe
+            .concat(&[low])
+            .map(|string| string.as_bytes().to_vec()),
+        Some(vec![0xED, 0xB8, 0x80, 0xED, 0xA0, 0xBD])
+    );
+
+    let recipe = concat_recipe(
+        "(Ljava/lang/String;Ljava/lang/String;)Ljava/lang/String;",
+        vec![string("\u{1}\u{1}")],
+    )
+    .unwrap();
+    assert_eq!(recipe.concat(&[high, low]), Some(pair));
+}
+
+fn jstr_bytes(bytes: &'static [u8]) -> &'static JStr {
+    JStr::from_jutf8(bytes).ok().unwrap()
+}
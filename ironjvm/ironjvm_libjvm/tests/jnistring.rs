@@ -0,0 +1,89 @@
+use std::ffi::CString;
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_libjvm::jnistring::new_string_utf;
+use ironjvm_libjvm::jnistring::StringValue;
+
+fn jstr(bytes: &[u8]) -> &JStr {
+    JStr::from_jutf8(bytes).ok().unwrap()
+}
+
+#[test]
+fn compact_strings() {
+    let cafe = jstr("caf\u{e9}".as_bytes());
+    assert_eq!(
+        StringValue::from_jstr(cafe, true),
+        StringValue::Latin1(b"caf\xE9".to_vec())
+    );
+    assert_eq!(
+        StringValue::from_jstr(cafe, false),
+        StringValue::Utf16(vec![0x63, 0x61, 0x66, 0xE9])
+    );
+
+    // a single character outside Latin-1 needs the UTF-16 form
+    let euro = jstr("1\u{20AC}".as_bytes());
+    assert_eq!(
+        StringValue::from_jstr(euro, true),
+        StringValue::Utf16(vec![0x31, 0x20AC])
+    );
+    assert_eq!(
+        StringValue::from_utf16(&[0x31, 0xFF], true),
+        StringValue::Latin1(vec![0x31, 0xFF])
+    );
+    assert_eq!(
+        StringValue::from_utf16(&[0x31, 0x100], true),
+        StringValue::Utf16(vec![0x31, 0x100])
+    );
+
+    // both forms hold the same characters
+    for compact_strings in [true, false] {
+        let value = StringValue::from_jstr(cafe, compact_strings);
+        assert_eq!(value.len(), 4);
+        assert_eq!(value.to_utf16(), [0x63, 0x61, 0x66, 0xE9]);
+        assert_eq!(value.to_jstring().as_bytes(), cafe.as_bytes());
+        assert_eq!(value.utf_length(), 5);
+        assert_eq!(value.utf_chars(), b"caf\xC3\xA9\0");
+    }
+}
+
+#[test]
+fn regions() {
+    // U+1F600 between two Latin-1 characters
+    let value = StringValue::from_utf16(&[0x61, 0xD83D, 0xDE00, 0x62], true);
+    assert_eq!(value.len(), 4);
+
+    assert_eq!(value.region(0, 4), Some(vec![0x61, 0xD83D, 0xDE00, 0x62]));
+    assert_eq!(value.region(4, 0), Some(vec![]));
+    assert_eq!(value.region(3, 2), None);
+    assert_eq!(value.region(5, 0), None);
+    assert_eq!(value.region(1, usize::MAX), None);
+
+    // a region holding the whole pair, then one that splits it and keeps a lone surrogate
+    assert_eq!(
+        value.utf_region(1, 2),
+        Some(b"\xED\xA0\xBD\xED\xB8\x80\0".to_vec())
+    );
+    assert_eq!(value.utf_region(2, 2), Some(b"\xED\xB8\x80b\0".to_vec()));
+    assert_eq!(value.utf_region(2, 3), None);
+
+    let latin1 = StringValue::from_utf16(&[0x61, 0, 0xFF], true);
+    assert_eq!(latin1.region(1, 2), Some(vec![0, 0xFF]));
+    assert_eq!(latin1.utf_region(1, 1), Some(b"\xC0\x80\0".to_vec()));
+}
+
+#[test]
+fn new_strings() {
+    let new_string = |utf: &[u8]| {
+        let utf = CString::new(utf).unwrap();
+        unsafe { new_string_utf(utf.as_ptr(), true) }
+    };
+
+    assert_eq!(
+        new_string(b"a\xC0\x80\xED\xA0\x80"),
+        Some(StringValue::Utf16(vec![0x61, 0, 0xD800]))
+    );
+    assert_eq!(new_string(b""), Some(StringValue::Latin1(vec![])));
+
+    // standard UTF-8 for a supplementary character is not accepted
+    assert_eq!(new_string(b"\xF0\x9F\x98\x80"), None);
+}
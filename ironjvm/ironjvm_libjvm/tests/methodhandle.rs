@@ -0,0 +1,139 @@
+use ironjvm_javautil::descriptor::method::ReturnDescriptor;
+use ironjvm_javautil::descriptor::BaseType;
+use ironjvm_javautil::descriptor::TypeDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_libjvm::methodhandle::is_signature_polymorphic;
+use ironjvm_libjvm::methodhandle::MethodHandleIntrinsic;
+use ironjvm_libjvm::methodhandle::MethodType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::cpinfo::ReferenceKind;
+use ironjvm_specimpl::classfile::flags::MethodAccessFlags;
+use ironjvm_specimpl::classfile::CpInfo;
+use ironjvm_specimpl::classfile::MethodInfo;
+
+const NATIVE_VARARGS: u16 = MethodAccessFlags::ACC_NATIVE | MethodAccessFlags::ACC_VARARGS;
+
+fn jstr(string: &str) -> &JStr {
+    JStr::from_jutf8(string.as_bytes()).ok().unwrap()
+}
+
+#[test]
+fn method_types() {
+    let method_type = MethodType::from_jstr(jstr("(I[[Ljava/lang/String;)J")).unwrap();
+
+    assert_eq!(
+        method_type.parameters,
+        [
+            TypeDescriptor {
+                dimensions: 0,
+                r#type: BaseType::Int,
+            },
+            TypeDescriptor {
+                dimensions: 2,
+                r#type: BaseType::Object(jstr("java/lang/String")),
+            },
+        ]
+    );
+    assert_eq!(
+        method_type.return_type,
+        ReturnDescriptor::FieldType(TypeDescriptor {
+            dimensions: 0,
+            r#type: BaseType::Long,
+        })
+    );
+
+    let method_type = MethodType::from_jstr(jstr("()V")).unwrap();
+    assert!(method_type.parameters.is_empty());
+    assert_eq!(method_type.return_type, ReturnDescriptor::VoidDescriptor);
+}
+
+#[test]
+fn reference_kinds() {
+    assert_eq!(ReferenceKind::from_u8(1), Some(ReferenceKind::GetField));
+    assert_eq!(
+        ReferenceKind::from_u8(9),
+        Some(ReferenceKind::InvokeInterface)
+    );
+    assert_eq!(ReferenceKind::from_u8(0), None);
+    assert_eq!(ReferenceKind::from_u8(10), None);
+}
+
+#[test]
+fn intrinsics() {
+    let intrinsic = |class, name| MethodHandleIntrinsic::from_method(jstr(class), jstr(name));
+
+    assert_eq!(
+        intrinsic("java/lang/invoke/MethodHandle", "invokeBasic"),
+        Some(MethodHandleIntrinsic::InvokeBasic)
+    );
+    assert_eq!(
+        intrinsic("java/lang/invoke/MethodHandle", "linkToInterface"),
+        Some(MethodHandleIntrinsic::LinkToInterface)
+    );
+    assert_eq!(
+        intrinsic("java/lang/invoke/MethodHandle", "invokeExact"),
+        None
+    );
+    assert_eq!(intrinsic("java/lang/invoke/VarHandle", "invokeBasic"), None);
+}
+
+#[test]
+fn signature_polymorphic_methods() {
+    let constant_pool = [
+        CpInfo {
+            tag: 1,
+            info: CpInfoType::ConstantUtf8 {
+                length: 39,
+                string: jstr("([Ljava/lang/Object;)Ljava/lang/Object;"),
+            },
+        },
+        CpInfo {
+            tag: 1,
+            info: CpInfoType::ConstantUtf8 {
+                length: 22,
+                string: jstr("([Ljava/lang/Object;)V"),
+            },
+        },
+        CpInfo {
+            tag: 1,
+            info: CpInfoType::ConstantUtf8 {
+                length: 23,
+                string: jstr("(I[Ljava/lang/Object;)V"),
+            },
+        },
+    ];
+    let method = |access_flags, descriptor_index| MethodInfo {
+        access_flags,
+        name_index: 0,
+        descriptor_index,
+        attributes_count: 0,
+        attributes: vec![],
+    };
+    let is_polymorphic =
+        |class, method: &MethodInfo| is_signature_polymorphic(&constant_pool, jstr(class), method);
+
+    let invoke_exact = method(MethodAccessFlags::ACC_PUBLIC | NATIVE_VARARGS, 1);
+    assert!(is_polymorphic(
+        "java/lang/invoke/MethodHandle",
+        &invoke_exact
+    ));
+    assert!(is_polymorphic(
+        "java/lang/invoke/VarHandle",
+        &method(NATIVE_VARARGS, 2)
+    ));
+    assert!(!is_polymorphic("java/lang/Object", &invoke_exact));
+
+    // the only parameter must be an Object[], and the method must be native and varargs
+    assert!(!is_polymorphic(
+        "java/lang/invoke/MethodHandle",
+        &method(NATIVE_VARARGS, 3)
+    ));
+    assert!(!is_polymorphic(
+        "java/lang/invoke/MethodHandle",
+        &method(MethodAccessFlags::ACC_NATIVE, 1)
+    ));
+    assert!(!is_polymorphic(
+        "java/lang/invoke/MethodHandle",
+        &method(MethodAccessFlags::ACC_VARARGS, 1)
+    ));
+}
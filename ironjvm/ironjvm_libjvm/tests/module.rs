@@ -0,0 +1,522 @@
+use std::collections::BTreeSet;
+use std::fs;
+
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_libjvm::module::error::ModuleError;
+use ironjvm_libjvm::module::find_modules;
+use ironjvm_libjvm::module::ModuleDescriptor;
+use ironjvm_libjvm::module::ModuleGraph;
+use ironjvm_libjvm::module::ModuleProvides;
+use ironjvm_libjvm::module::ModuleRequires;
+use ironjvm_libjvm::module::PackageGrant;
+use ironjvm_specimpl::classfile::attrinfo::mattr::ModuleExport;
+use ironjvm_specimpl::classfile::attrinfo::mattr::ModuleOpen;
+use ironjvm_specimpl::classfile::attrinfo::mattr::ModuleProvide;
+use ironjvm_specimpl::classfile::attrinfo::mattr::ModuleRequire;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::flags::ClassAccessFlags;
+use ironjvm_specimpl::classfile::flags::ModuleRequireFlags;
+use ironjvm_specimpl::classfile::AttributeInfo;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::CpInfo;
+
+// module-info of a module named "exploded" with an empty declaration
+const EXPLODED_MODULE_INFO: &[u8] = &[
+    0xCA, 0xFE, 0xBA, 0xBE, // magic
+    0x00, 0x00, 0x00, 0x35, // minor_version, major_version
+    0x00, 0x06, // constant_pool_count
+    0x01, 0x00, 0x0B, b'm', b'o', b'd', b'u', b'l', b'e', b'-', b'i', b'n', b'f',
+    b'o', // #1: Utf8 "module-info"
+    0x07, 0x00, 0x01, // #2: Class #1
+    0x01, 0x00, 0x06, b'M', b'o', b'd', b'u', b'l', b'e', // #3: Utf8 "Module"
+    0x01, 0x00, 0x08, b'e', b'x', b'p', b'l', b'o', b'd', b'e', b'd', // #4: Utf8 "exploded"
+    0x13, 0x00, 0x04, // #5: Module #4
+    0x80, 0x00, // access_flags
+    0x00, 0x02, // this_class
+    0x00, 0x00, // super_class
+    0x00, 0x00, // interfaces_count
+    0x00, 0x00, // fields_count
+    0x00, 0x00, // methods_count
+    0x00, 0x01, // attributes_count
+    0x00, 0x03, 0x00, 0x00, 0x00, 0x10, // Module, 16 bytes long
+    0x00, 0x05, 0x00, 0x00, 0x00, 0x00, // module_name_index, module_flags, version
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // no directives
+];
+
+// builds the constant pool of a hand-written module-info class
+#[derive(Default)]
+struct ConstantPool {
+    entries: Vec<CpInfo<'static>>,
+}
+
+impl ConstantPool {
+    fn push(&mut self, tag: u8, info: CpInfoType<'static>) -> u16 {
+        self.entries.push(CpInfo { tag, info });
+        self.entries.len() as u16
+    }
+
+    fn utf8(&mut self, string: &'static str) -> u16 {
+        self.push(
+            1,
+            CpInfoType::ConstantUtf8 {
+                length: string.len() as u16,
+                string: JStr::from_jutf8(string.as_bytes()).ok().unwrap(),
+            },
+        )
+    }
+
+    fn class(&mut self, name: &'static str) -> u16 {
+        let name_index = self.utf8(name);
+        self.push(7, CpInfoType::ConstantClass { name_index })
+    }
+
+    fn module(&mut self, name: &'static str) -> u16 {
+        let name_index = self.utf8(name);
+        self.push(19, CpInfoType::ConstantModule { name_index })
+    }
+
+    fn package(&mut self, name: &'static str) -> u16 {
+        let name_index = self.utf8(name);
+        self.push(20, CpInfoType::ConstantPackage { name_index })
+    }
+}
+
+fn indices(indices: &[u16]) -> &'static [[u8; 2]] {
+    Box::leak(
+        indices
+            .iter()
+            .map(|index| index.to_be_bytes())
+            .collect::<Vec<_>>()
+            .into_boxed_slice(),
+    )
+}
+
+fn module_info(
+    constant_pool: ConstantPool,
+    this_class: u16,
+    attributes: Vec<AttributeInfoType<'static>>,
+) -> ClassFile<'static> {
+    ClassFile {
+        magic: 0xCAFEBABE,
+        minor_version: 0,
+        major_version: 53,
+        constant_pool_count: constant_pool.entries.len() as u16 + 1,
+        constant_pool: constant_pool.entries,
+        access_flags: ClassAccessFlags::ACC_MODULE,
+        this_class,
+        super_class: 0,
+        interfaces_count: 0,
+        interfaces: &[],
+        fields_count: 0,
+        fields: vec![],
+        methods_count: 0,
+        methods: vec![],
+        attributes_count: attributes.len() as u16,
+        attributes: attributes
+            .into_iter()
+            .map(|info| AttributeInfo {
+                attribute_name_index: 0,
+                attribute_length: 0,
+                info,
+            })
+            .collect(),
+    }
+}
+
+// module greeter {
+//     requires transitive base;
+//     requires static optional;
+//     exports greeter/api;
+//     exports greeter/internal to friend;
+//     opens greeter/impl;
+//     uses greeter/api/Greeting;
+//     provides greeter/api/Greeting with greeter/impl/Hello;
+// }
+// with the main class greeter/Main
+fn greeter(packages: &[&'static str]) -> ClassFile<'static> {
+    let mut constant_pool = ConstantPool::default();
+    let this_class = constant_pool.class("module-info");
+    let greeter = constant_pool.module("greeter");
+    let base = constant_pool.module("base");
+    let optional = constant_pool.module("optional");
+    let friend = constant_pool.module("friend");
+    let api = constant_pool.package("greeter/api");
+    let internal = constant_pool.package("greeter/internal");
+    let implementation = constant_pool.package("greeter/impl");
+    let greeting = constant_pool.class("greeter/api/Greeting");
+    let hello = constant_pool.class("greeter/impl/Hello");
+    let main = constant_pool.class("greeter/Main");
+    let packages = packages
+        .iter()
+        .map(|package| constant_pool.package(package))
+        .collect::<Vec<_>>();
+
+    let requires = |requires_index, requires_flags| ModuleRequire {
+        requires_index,
+        requires_flags,
+        requires_version_index: 0,
+    };
+    let exports = |exports_index, targets: &[u16]| ModuleExport {
+        exports_index,
+        exports_flags: 0,
+        exports_to_count: targets.len() as u16,
+        exports_to_index: indices(targets),
+    };
+
+    let mut attributes = vec![
+        AttributeInfoType::ModuleAttribute {
+            module_name_index: greeter,
+            module_flags: 0,
+            module_version_index: 0,
+            requires_count: 2,
+            requires: vec![
+                requires(base, ModuleRequireFlags::ACC_TRANSITIVE),
+                requires(optional, ModuleRequireFlags::ACC_STATIC_PHASE),
+            ],
+            exports_count: 2,
+            exports: vec![exports(api, &[]), exports(internal, &[friend])],
+            opens_count: 1,
+            opens: vec![ModuleOpen {
+                opens_index: implementation,
+                opens_flags: 0,
+                opens_to_count: 0,
+                opens_to_index: &[],
+            }],
+            uses_count: 1,
+            uses_index: indices(&[greeting]),
+            provides_count: 1,
+            provides: vec![ModuleProvide {
+                provides_index: greeting,
+                provides_with_count: 1,
+                provides_with_index: indices(&[hello]),
+            }],
+        },
+        AttributeInfoType::ModuleMainClassAttribute {
+            main_class_index: main,
+        },
+    ];
+    if !packages.is_empty() {
+        attributes.push(AttributeInfoType::ModulePackagesAttribute {
+            package_count: packages.len() as u16,
+            package_index: indices(&packages),
+        });
+    }
+
+    module_info(constant_pool, this_class, attributes)
+}
+
+fn packages(packages: &[&str]) -> BTreeSet<String> {
+    packages.iter().map(|package| package.to_string()).collect()
+}
+
+#[test]
+fn descriptors() {
+    let descriptor = ModuleDescriptor::from_classfile(&greeter(&[])).unwrap();
+
+    assert_eq!(
+        descriptor,
+        ModuleDescriptor {
+            name: "greeter".to_string(),
+            is_open: false,
+            requires: vec![
+                ModuleRequires {
+                    module: "base".to_string(),
+                    is_transitive: true,
+                    is_static: false,
+                },
+                ModuleRequires {
+                    module: "optional".to_string(),
+                    is_transitive: false,
+                    is_static: true,
+                },
+            ],
+            exports: vec![
+                PackageGrant {
+                    package: "greeter/api".to_string(),
+                    targets: vec![],
+                },
+                PackageGrant {
+                    package: "greeter/internal".to_string(),
+                    targets: vec!["friend".to_string()],
+                },
+            ],
+            opens: vec![PackageGrant {
+                package: "greeter/impl".to_string(),
+                targets: vec![],
+            }],
+            uses: vec!["greeter/api/Greeting".to_string()],
+            provides: vec![ModuleProvides {
+                service: "greeter/api/Greeting".to_string(),
+                providers: vec!["greeter/impl/Hello".to_string()],
+            }],
+            // without a ModulePackages attribute, the packages are those the declaration names
+            packages: packages(&["greeter", "greeter/api", "greeter/impl", "greeter/internal"]),
+            main_class: Some("greeter/Main".to_string()),
+        }
+    );
+
+    let descriptor = ModuleDescriptor::from_classfile(&greeter(&["greeter/util"])).unwrap();
+    assert!(descriptor.packages.contains("greeter/util"));
+    assert!(descriptor.packages.contains("greeter/api"));
+}
+
+#[test]
+fn invalid_descriptors() {
+    let mut classfile = greeter(&[]);
+    classfile.access_flags = ClassAccessFlags::ACC_PUBLIC;
+    assert!(matches!(
+        ModuleDescriptor::from_classfile(&classfile),
+        Err(ModuleError::InvalidModuleDescriptor)
+    ));
+
+    let mut classfile = greeter(&[]);
+    classfile
+        .attributes
+        .retain(|attribute| !matches!(attribute.info, AttributeInfoType::ModuleAttribute { .. }));
+    assert!(matches!(
+        ModuleDescriptor::from_classfile(&classfile),
+        Err(ModuleError::InvalidModuleDescriptor)
+    ));
+
+    // the main class must be named by a Class entry
+    let mut classfile = greeter(&[]);
+    for attribute in &mut classfile.attributes {
+        if let AttributeInfoType::ModuleMainClassAttribute { main_class_index } =
+            &mut attribute.info
+        {
+            *main_class_index = 4;
+        }
+    }
+    assert!(matches!(
+        ModuleDescriptor::from_classfile(&classfile),
+        Err(ModuleError::InvalidModuleDescriptor)
+    ));
+}
+
+#[test]
+fn exploded_modules() {
+    let module_path = std::env::temp_dir().join(format!("ironjvm-modules-{}", std::process::id()));
+    let exploded = module_path.join("exploded");
+    fs::create_dir_all(exploded.join("pkg/sub")).unwrap();
+    fs::create_dir_all(exploded.join("resources")).unwrap();
+    fs::create_dir_all(module_path.join("not-a-module")).unwrap();
+    fs::write(exploded.join("module-info.class"), EXPLODED_MODULE_INFO).unwrap();
+    fs::write(exploded.join("pkg/A.class"), b"").unwrap();
+    fs::write(exploded.join("pkg/sub/B.class"), b"").unwrap();
+    fs::write(exploded.join("resources/data.txt"), b"").unwrap();
+
+    assert!(ClassFileParser::new(EXPLODED_MODULE_INFO).parse().is_ok());
+
+    // the directory of modules and the module itself both find it, and the first one is kept
+    let modules = find_modules(&[module_path.clone(), exploded]).unwrap();
+    fs::remove_dir_all(&module_path).unwrap();
+
+    assert_eq!(modules.len(), 1);
+    assert_eq!(modules[0].name, "exploded");
+    assert_eq!(modules[0].packages, packages(&["pkg", "pkg/sub"]));
+}
+
+// a module exporting its packages to every module
+fn module(name: &str, requires: &[&str], packages: &[&str]) -> ModuleDescriptor {
+    ModuleDescriptor {
+        name: name.to_string(),
+        is_open: false,
+        requires: requires
+            .iter()
+            .map(|requires| {
+                let (module, is_transitive) = match requires.strip_prefix("transitive ") {
+                    Some(module) => (module, true),
+                    None => (*requires, false),
+                };
+                let (module, is_static) = match module.strip_prefix("static ") {
+                    Some(module) => (module, true),
+                    None => (module, false),
+                };
+
+                ModuleRequires {
+                    module: module.to_string(),
+                    is_transitive,
+                    is_static,
+                }
+            })
+            .collect(),
+        exports: packages
+            .iter()
+            .map(|package| PackageGrant {
+                package: package.to_string(),
+                targets: vec![],
+            })
+            .collect(),
+        opens: vec![],
+        uses: vec![],
+        provides: vec![],
+        packages: packages.iter().map(|package| package.to_string()).collect(),
+        main_class: None,
+    }
+}
+
+fn resolved(graph: &ModuleGraph, modules: &[&str]) -> Vec<bool> {
+    modules
+        .iter()
+        .map(|module| graph.module(module).is_some())
+        .collect()
+}
+
+#[test]
+fn resolution() {
+    let available = vec![
+        module("app", &["lib", "static optional"], &["app"]),
+        module("lib", &["transitive base"], &["lib"]),
+        module("base", &[], &["base"]),
+        module("optional", &[], &["optional"]),
+        module("unrelated", &[], &["unrelated"]),
+    ];
+
+    let graph = ModuleGraph::resolve(available.clone(), &["app"]).unwrap();
+    assert_eq!(
+        resolved(&graph, &["app", "lib", "base", "optional", "unrelated"]),
+        [true, true, true, false, false]
+    );
+    assert_eq!(graph.module_of_package("base"), Some("base"));
+    assert_eq!(graph.module_of_package("unrelated"), None);
+
+    // a static dependence is read once something else resolves it
+    let graph = ModuleGraph::resolve(available.clone(), &["app", "optional"]).unwrap();
+    assert!(graph.reads(Some("app"), Some("optional")));
+
+    let missing = available
+        .into_iter()
+        .filter(|module| module.name != "base")
+        .collect();
+    assert!(matches!(
+        ModuleGraph::resolve(missing, &["app"]),
+        Err(ModuleError::ModuleNotFound { module, required_by: Some(required_by) })
+            if module == "base" && required_by == "lib"
+    ));
+    assert!(matches!(
+        ModuleGraph::resolve(vec![], &["app"]),
+        Err(ModuleError::ModuleNotFound { module, required_by: None }) if module == "app"
+    ));
+}
+
+#[test]
+fn invalid_graphs() {
+    let cycle = vec![
+        module("app", &["a"], &[]),
+        module("a", &["b"], &[]),
+        module("b", &["a"], &[]),
+    ];
+    assert!(matches!(
+        ModuleGraph::resolve(cycle, &["app"]),
+        Err(ModuleError::CycleDetected { .. })
+    ));
+
+    let split = vec![
+        module("app", &["a", "b"], &[]),
+        module("a", &[], &["shared"]),
+        module("b", &[], &["shared"]),
+    ];
+    assert!(matches!(
+        ModuleGraph::resolve(split, &["app"]),
+        Err(ModuleError::SplitPackage { package, first, second })
+            if package == "shared" && first == "a" && second == "b"
+    ));
+}
+
+#[test]
+fn readability() {
+    let graph = ModuleGraph::resolve(
+        vec![
+            module("app", &["lib"], &[]),
+            module("lib", &["transitive base", "internal"], &[]),
+            module("base", &[], &[]),
+            module("internal", &[], &[]),
+        ],
+        &["app"],
+    )
+    .unwrap();
+    let reads = |from, to| graph.reads(Some(from), Some(to));
+
+    assert!(reads("app", "app"));
+    assert!(reads("app", "lib"));
+    assert!(reads("app", "base"));
+    assert!(!reads("app", "internal"));
+    assert!(reads("lib", "internal"));
+    assert!(!reads("base", "app"));
+
+    // the unnamed module reads every module, and no named module reads it
+    assert!(graph.reads(None, Some("internal")));
+    assert!(graph.reads(None, None));
+    assert!(!graph.reads(Some("app"), None));
+}
+
+#[test]
+fn accessibility() {
+    let mut greeter = ModuleDescriptor::from_classfile(&greeter(&["greeter/util"])).unwrap();
+    greeter.requires.clear();
+    let mut open = module("open", &[], &[]);
+    open.is_open = true;
+    open.packages = packages(&["open/hidden"]);
+
+    let graph = ModuleGraph::resolve(
+        vec![
+            greeter,
+            open,
+            module("friend", &["greeter", "open"], &[]),
+            module("stranger", &["greeter"], &[]),
+        ],
+        &["friend", "stranger"],
+    )
+    .unwrap();
+    let exported = |package, to| graph.is_exported(Some("greeter"), package, to);
+    let open = |package, to| graph.is_open(Some("greeter"), package, to);
+
+    assert!(exported("greeter/api", Some("stranger")));
+    assert!(exported("greeter/api", None));
+    assert!(exported("greeter/internal", Some("friend")));
+    assert!(!exported("greeter/internal", Some("stranger")));
+    assert!(!exported("greeter/internal", None));
+    assert!(!exported("greeter/util", Some("stranger")));
+    assert!(exported("greeter/util", Some("greeter")));
+    // opened packages are exported at run time
+    assert!(exported("greeter/impl", Some("stranger")));
+
+    assert!(open("greeter/impl", Some("stranger")));
+    assert!(!open("greeter/api", Some("stranger")));
+    assert!(graph.is_open(Some("open"), "open/hidden", Some("friend")));
+    assert!(graph.is_open(None, "anything", Some("friend")));
+
+    // access needs readability as well as an export, or an opening for deep reflection
+    assert!(graph.can_access(Some("friend"), Some("greeter"), "greeter/api", false));
+    assert!(!graph.can_access(Some("friend"), Some("greeter"), "greeter/api", true));
+    assert!(graph.can_access(Some("friend"), Some("open"), "open/hidden", true));
+    assert!(!graph.can_access(Some("stranger"), Some("open"), "open/hidden", false));
+    assert!(graph.can_access(None, Some("greeter"), "greeter/impl", true));
+    assert!(!graph.can_access(Some("greeter"), None, "unnamed", false));
+}
+
+#[test]
+fn services() {
+    let mut greeter = ModuleDescriptor::from_classfile(&greeter(&[])).unwrap();
+    greeter.requires.clear();
+    let mut consumer = module("consumer", &[], &[]);
+    consumer.uses = vec!["greeter/api/Greeting".to_string()];
+
+    // providers of a used service are bound even if nothing requires them
+    let graph = ModuleGraph::resolve(vec![consumer, greeter], &["consumer"]).unwrap();
+    assert!(graph.module("greeter").is_some());
+
+    let providers = vec![("greeter", "greeter/impl/Hello")];
+    assert_eq!(
+        graph.providers(Some("consumer"), "greeter/api/Greeting"),
+        Some(providers.clone())
+    );
+    assert_eq!(
+        graph.providers(None, "greeter/api/Greeting"),
+        Some(providers)
+    );
+    assert_eq!(graph.providers(None, "other/Service"), Some(vec![]));
+    assert_eq!(graph.providers(Some("consumer"), "other/Service"), None);
+}
@@ -0,0 +1,189 @@
+use ironjvm_cfparser::code::CodeParser;
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_libjvm::npe::npe_message;
+use ironjvm_specimpl::classfile::attrinfo::cattr::Instruction;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::MethodInfo;
+
+// the expected messages are the ones HotSpot prints when running NullPointers.main
+fn with_method<R>(name: &str, f: impl FnOnce(&ClassFile, &MethodInfo) -> R) -> R {
+    let bytes =
+        std::fs::read("../test_classes/com/github/htgazurex1212/ironjvm/tests/NullPointers.class")
+            .unwrap();
+    let classfile = ClassFileParser::new(&bytes).parse().unwrap();
+    let method = classfile
+        .methods
+        .iter()
+        .find(
+            |info| match &classfile.constant_pool[info.name_index as usize - 1].info {
+                CpInfoType::ConstantUtf8 { string, .. } => string.as_bytes() == name.as_bytes(),
+                _ => false,
+            },
+        )
+        .unwrap();
+
+    f(&classfile, method)
+}
+
+// the message of the `occurrence`th instruction of `name` for which `faulting` holds
+fn message(name: &str, occurrence: usize, faulting: fn(&Instruction) -> bool) -> String {
+    with_method(name, |classfile, method| {
+        let code = method
+            .attributes
+            .iter()
+            .find_map(|attribute| match &attribute.info {
+                AttributeInfoType::CodeAttribute { code, .. } => Some(*code),
+                _ => None,
+            })
+            .unwrap();
+        let (pc, _) = CodeParser::new(code)
+            .parse()
+            .unwrap()
+            .into_iter()
+            .filter(|(_, instruction)| faulting(instruction))
+            .nth(occurrence)
+            .unwrap();
+
+        npe_message(classfile, method, pc).unwrap()
+    })
+}
+
+#[test]
+fn getfield_chain() {
+    assert_eq!(
+        message("getfieldChain", 1, |i| matches!(
+            i,
+            Instruction::GetField { .. }
+        )),
+        r#"Cannot read field "next" because "<parameter1>.next" is null"#
+    );
+}
+
+#[test]
+fn array_loads_and_stores() {
+    assert_eq!(
+        message("aaload", 0, |i| matches!(i, Instruction::AALoad)),
+        r#"Cannot load from object array because "<parameter1>.objects" is null"#
+    );
+    assert_eq!(
+        message("iastore", 1, |i| matches!(i, Instruction::GetField { .. })),
+        r#"Cannot read field "ints" because "<parameter1>.next" is null"#
+    );
+    assert_eq!(
+        message("iastore", 0, |i| matches!(i, Instruction::IAStore)),
+        r#"Cannot store to int array because "<parameter1>.next.ints" is null"#
+    );
+}
+
+#[test]
+fn arraylength() {
+    assert_eq!(
+        message("arraylength", 0, |i| matches!(i, Instruction::ArrayLength)),
+        r#"Cannot read the array length because "<parameter1>.ints" is null"#
+    );
+}
+
+#[test]
+fn monitorenter() {
+    assert_eq!(
+        message("monitorenter", 0, |i| matches!(
+            i,
+            Instruction::MonitorEnter
+        )),
+        r#"Cannot enter synchronized block because "<parameter1>.string" is null"#
+    );
+}
+
+#[test]
+fn invokevirtual() {
+    assert_eq!(
+        message("invokevirtual", 0, |i| matches!(
+            i,
+            Instruction::InvokeVirtual { .. }
+        )),
+        r#"Cannot invoke "java.lang.StringBuilder.append(String)" because "<parameter1>.builder" is null"#
+    );
+    assert_eq!(
+        message("invokeString", 0, |i| matches!(
+            i,
+            Instruction::InvokeVirtual { .. }
+        )),
+        r#"Cannot invoke "String.concat(String)" because "<parameter1>" is null"#
+    );
+    assert_eq!(
+        message("unboxing", 0, |i| matches!(
+            i,
+            Instruction::InvokeVirtual { .. }
+        )),
+        r#"Cannot invoke "java.lang.Integer.intValue()" because "<parameter1>" is null"#
+    );
+}
+
+#[test]
+fn invokeinterface() {
+    assert_eq!(
+        message("invokeinterface", 0, |i| matches!(
+            i,
+            Instruction::InvokeInterface { .. }
+        )),
+        concat!(
+            r#"Cannot invoke "com.github.htgazurex1212.ironjvm.tests.NullPointers$Callback"#,
+            r#".run(StringBuilder, String, Object, java.util.List)" because "<parameter1>" is null"#
+        )
+    );
+    assert_eq!(
+        message("listSize", 0, |i| matches!(
+            i,
+            Instruction::InvokeInterface { .. }
+        )),
+        r#"Cannot invoke "java.util.List.size()" because "<parameter1>" is null"#
+    );
+}
+
+#[test]
+fn athrow() {
+    assert_eq!(
+        message("athrow", 0, |i| matches!(i, Instruction::AThrow)),
+        r#"Cannot throw exception because "null" is null"#
+    );
+}
+
+#[test]
+fn getstatic() {
+    assert_eq!(
+        message("getstatic", 0, |i| matches!(
+            i,
+            Instruction::GetField { .. }
+        )),
+        concat!(
+            r#"Cannot read field "string" because "#,
+            r#""com.github.htgazurex1212.ironjvm.tests.NullPointers.instance" is null"#
+        )
+    );
+}
+
+#[test]
+fn putfield_on_local() {
+    assert_eq!(
+        message("putfieldLocal", 0, |i| matches!(
+            i,
+            Instruction::PutField { .. }
+        )),
+        r#"Cannot assign field "next" because "<local0>" is null"#
+    );
+}
+
+#[test]
+fn no_message() {
+    // aconst_null cannot raise a NullPointerException, and 1 is the athrow after it
+    assert!(with_method("athrow", |classfile, method| npe_message(
+        classfile, method, 0
+    ))
+    .is_none());
+    assert!(with_method("athrow", |classfile, method| npe_message(
+        classfile, method, 1
+    ))
+    .is_some());
+}
@@ -0,0 +1,371 @@
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::descriptor::method::MethodDescriptor;
+use ironjvm_javautil::descriptor::BaseType;
+use ironjvm_javautil::descriptor::TypeDescriptor;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_libjvm::reflect::annotation;
+use ironjvm_libjvm::reflect::annotation::AnnotationValue;
+use ironjvm_libjvm::reflect::convert_arguments;
+use ironjvm_libjvm::reflect::convert_value;
+use ironjvm_libjvm::reflect::declared_constructors;
+use ironjvm_libjvm::reflect::declared_fields;
+use ironjvm_libjvm::reflect::declared_methods;
+use ironjvm_libjvm::reflect::ConvertedValue;
+use ironjvm_libjvm::reflect::PrimitiveValue;
+use ironjvm_libjvm::reflect::ReflectedMember;
+use ironjvm_libjvm::reflect::ReflectionSupport;
+use ironjvm_specimpl::classfile::ClassFile;
+
+const VISIBLE: &str = "com/github/htgazurex1212/ironjvm/tests/Annotated$Visible";
+
+fn read(class: &str) -> &'static [u8] {
+    let bytes = std::fs::read(format!(
+        "../test_classes/com/github/htgazurex1212/ironjvm/tests/{class}.class"
+    ))
+    .unwrap();
+
+    Box::leak(bytes.into_boxed_slice())
+}
+
+fn classfile(class: &str) -> ClassFile<'static> {
+    ClassFileParser::new(read(class)).parse().unwrap()
+}
+
+fn jstr(string: &str) -> &JStr {
+    JStr::from_jutf8(string.as_bytes()).ok().unwrap()
+}
+
+fn encoded_in(bytes: &[u8], raw: &[u8]) -> bool {
+    bytes.windows(raw.len()).any(|window| window == raw)
+}
+
+fn names(members: &[ReflectedMember]) -> Vec<String> {
+    members
+        .iter()
+        .map(|member| String::from_utf8(member.name.as_bytes().to_vec()).unwrap())
+        .collect()
+}
+
+#[test]
+fn declared_members() {
+    let annotated = classfile("Annotated");
+
+    let fields = declared_fields(&annotated, false).unwrap();
+    assert_eq!(names(&fields), ["ANSWER", "elements"]);
+    assert_eq!((fields[0].slot, fields[0].modifiers), (0, 0x0019));
+    assert_eq!((fields[1].slot, fields[1].modifiers), (1, 0x0004));
+    assert_eq!(fields[0].signature, None);
+    assert_eq!(fields[1].signature, Some(jstr("Ljava/util/List<TT;>;")));
+    assert_eq!(
+        names(&declared_fields(&annotated, true).unwrap()),
+        ["ANSWER"]
+    );
+
+    // the bridge method of Callable.call comes after the declared methods
+    let methods = declared_methods(&annotated, false).unwrap();
+    assert_eq!(names(&methods), ["call", "sum", "call"]);
+    assert_eq!(
+        methods.iter().map(|method| method.slot).collect::<Vec<_>>(),
+        [2, 3, 4]
+    );
+    assert_eq!(methods[1].modifiers, 0x0008);
+    assert_eq!(methods[2].modifiers, 0x1041);
+    assert_eq!(methods[0].signature, Some(jstr("()Ljava/util/List<TT;>;")));
+    assert_eq!(methods[2].signature, None);
+    assert_eq!(methods[0].exceptions, [jstr("java/lang/Exception")]);
+    assert!(methods[1].exceptions.is_empty());
+    assert_eq!(
+        names(&declared_methods(&annotated, true).unwrap()),
+        ["call", "call"]
+    );
+
+    let constructors = declared_constructors(&annotated, false).unwrap();
+    assert_eq!(names(&constructors), ["<init>", "<init>"]);
+    assert_eq!(constructors[0].modifiers, 0x0002);
+    assert_eq!(
+        constructors[1].exceptions,
+        [jstr("java/lang/IllegalArgumentException")]
+    );
+    assert_eq!(
+        constructors[1].signature,
+        Some(jstr("(Ljava/util/List<TT;>;)V"))
+    );
+    assert_eq!(
+        declared_constructors(&annotated, true).unwrap(),
+        [constructors[1].clone()]
+    );
+}
+
+#[test]
+fn raw_annotations() {
+    let bytes = read("Annotated");
+    let annotated = ClassFileParser::new(bytes).parse().unwrap();
+    let methods = declared_methods(&annotated, false).unwrap();
+    let fields = declared_fields(&annotated, false).unwrap();
+
+    // the re-encoded bytes are the contents of the attribute in the class file
+    let encoded = |raw: &Option<Vec<u8>>| encoded_in(bytes, raw.as_ref().unwrap());
+    assert!(encoded(&fields[0].annotations));
+    assert!(encoded(&fields[1].annotations));
+    assert!(encoded(&methods[0].annotations));
+    assert!(encoded(&methods[1].parameter_annotations));
+    assert!(encoded(&annotation::raw_annotations(&annotated.attributes)));
+
+    // num_parameters is a single byte, followed by a count of annotations for each parameter
+    let parameters = methods[1].parameter_annotations.as_ref().unwrap();
+    assert_eq!(parameters[..3], [3, 0, 1]);
+    assert_eq!(parameters[parameters.len() - 4..], [0, 0, 0, 0]);
+
+    assert_eq!(methods[1].annotations, None);
+    assert_eq!(methods[0].parameter_annotations, None);
+    assert_eq!(methods[0].annotation_default, None);
+
+    let visible = classfile("Annotated$Visible");
+    let elements = declared_methods(&visible, false).unwrap();
+    assert_eq!(names(&elements), ["name", "values", "kind", "type"]);
+    assert!(elements.iter().all(|element| encoded_in(
+        read("Annotated$Visible"),
+        element.annotation_default.as_ref().unwrap()
+    )));
+}
+
+#[test]
+fn decoded_annotations() {
+    let annotated = classfile("Annotated");
+    let constant_pool = &annotated.constant_pool;
+
+    let class = annotation::find_annotation(constant_pool, &annotated.attributes, VISIBLE)
+        .unwrap()
+        .unwrap();
+    assert_eq!(class.type_descriptor, jstr(&format!("L{VISIBLE};")));
+    assert_eq!(
+        class.element("name"),
+        Some(&AnnotationValue::String(jstr("class")))
+    );
+    assert_eq!(
+        class.element("values"),
+        Some(&AnnotationValue::Array(vec![
+            AnnotationValue::Int(1),
+            AnnotationValue::Int(2)
+        ]))
+    );
+    assert_eq!(
+        class.element("kind"),
+        Some(&AnnotationValue::Enum {
+            type_descriptor: jstr("Ljava/lang/annotation/ElementType;"),
+            name: jstr("TYPE"),
+        })
+    );
+    // elements left to their default are not in the attribute
+    assert_eq!(class.element("type"), None);
+    assert_eq!(
+        annotation::find_annotation(constant_pool, &annotated.attributes, "java/lang/Deprecated")
+            .unwrap(),
+        None
+    );
+
+    let elements = &annotated.fields[1];
+    let deprecated = annotation::annotations(constant_pool, &elements.attributes).unwrap();
+    assert_eq!(deprecated.len(), 1);
+    assert_eq!(
+        deprecated[0].type_descriptor,
+        jstr("Ljava/lang/Deprecated;")
+    );
+    assert!(deprecated[0].elements.is_empty());
+
+    let call = &annotated.methods[2].attributes;
+    let call = annotation::find_annotation(constant_pool, call, VISIBLE)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        call.elements,
+        [(
+            jstr("type"),
+            AnnotationValue::Class(jstr("Ljava/lang/String;"))
+        )]
+    );
+
+    // the invisible annotation of the second parameter is left out
+    let sum = &annotated.methods[3];
+    let parameters = annotation::parameter_annotations(constant_pool, &sum.attributes).unwrap();
+    assert_eq!(
+        parameters
+            .iter()
+            .map(|annotations| annotations.len())
+            .collect::<Vec<_>>(),
+        [1, 0, 0]
+    );
+    assert_eq!(parameters[0][0].type_descriptor, class.type_descriptor);
+    assert!(
+        annotation::parameter_annotations(constant_pool, &annotated.methods[2].attributes)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn annotation_defaults() {
+    let visible = classfile("Annotated$Visible");
+    let default = |index: usize| {
+        annotation::annotation_default(&visible.constant_pool, &visible.methods[index].attributes)
+            .unwrap()
+            .unwrap()
+    };
+
+    assert_eq!(default(0), AnnotationValue::String(jstr("visible")));
+    assert_eq!(default(1), AnnotationValue::Array(vec![]));
+    assert_eq!(
+        default(2),
+        AnnotationValue::Enum {
+            type_descriptor: jstr("Ljava/lang/annotation/ElementType;"),
+            name: jstr("FIELD"),
+        }
+    );
+    // void.class is the return descriptor V
+    assert_eq!(default(3), AnnotationValue::Class(jstr("V")));
+
+    let annotated = classfile("Annotated");
+    assert_eq!(
+        annotation::annotation_default(&annotated.constant_pool, &annotated.methods[2].attributes)
+            .unwrap(),
+        None
+    );
+}
+
+#[test]
+fn widening() {
+    use PrimitiveValue::*;
+
+    let widened = [
+        (Byte(-1), BaseType::Short, Some(Short(-1))),
+        (Byte(-1), BaseType::Int, Some(Int(-1))),
+        (Char(0xFFFF), BaseType::Int, Some(Int(0xFFFF))),
+        (Short(-2), BaseType::Long, Some(Long(-2))),
+        (Int(3), BaseType::Float, Some(Float(3.0))),
+        (
+            Long(1 << 40),
+            BaseType::Float,
+            Some(Float((1u64 << 40) as f32)),
+        ),
+        (Long(-4), BaseType::Double, Some(Double(-4.0))),
+        (Float(0.5), BaseType::Double, Some(Double(0.5))),
+        (Boolean(true), BaseType::Boolean, Some(Boolean(true))),
+        (Double(1.0), BaseType::Double, Some(Double(1.0))),
+        // narrowing and the conversions between boolean, char and the other types are rejected
+        (Short(1), BaseType::Byte, None),
+        (Char(1), BaseType::Short, None),
+        (Byte(1), BaseType::Char, None),
+        (Long(1), BaseType::Int, None),
+        (Double(1.0), BaseType::Float, None),
+        (Boolean(false), BaseType::Int, None),
+        (Int(0), BaseType::Boolean, None),
+        (Int(0), BaseType::Object(jstr("java/lang/Integer")), None),
+    ];
+
+    for (value, target, expected) in widened {
+        assert_eq!(value.widen(&target), expected, "{value:?} to {target:?}");
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Object {
+    Boxed(PrimitiveValue),
+    String,
+}
+
+struct Support;
+
+impl ReflectionSupport for Support {
+    type Object = Object;
+    type Throwable = String;
+
+    fn unbox(&self, object: &Object) -> Option<PrimitiveValue> {
+        match object {
+            Object::Boxed(value) => Some(*value),
+            Object::String => None,
+        }
+    }
+
+    // boxed values are only instances of Object, and strings also of String
+    fn is_instance(&self, object: &Object, r#type: &TypeDescriptor) -> bool {
+        match r#type.r#type {
+            BaseType::Object(name) if r#type.dimensions == 0 => {
+                name.as_bytes() == b"java/lang/Object"
+                    || (*object == Object::String && name.as_bytes() == b"java/lang/String")
+            }
+            _ => false,
+        }
+    }
+
+    fn illegal_argument_exception(&self, message: String) -> String {
+        message
+    }
+}
+
+#[test]
+fn argument_conversions() {
+    let descriptor =
+        MethodDescriptor::from_jstr(jstr("(JLjava/lang/String;Ljava/lang/Object;)V")).unwrap();
+
+    assert_eq!(
+        convert_arguments(
+            &Support,
+            &descriptor,
+            &[
+                Some(Object::Boxed(PrimitiveValue::Int(7))),
+                None,
+                Some(Object::Boxed(PrimitiveValue::Char(8))),
+            ]
+        ),
+        Ok(vec![
+            ConvertedValue::Primitive(PrimitiveValue::Long(7)),
+            ConvertedValue::Reference(None),
+            ConvertedValue::Reference(Some(Object::Boxed(PrimitiveValue::Char(8)))),
+        ])
+    );
+    assert_eq!(
+        convert_arguments(&Support, &descriptor, &[None]),
+        Err("wrong number of arguments: 1 expected: 3".to_string())
+    );
+
+    let mismatch = Err("argument type mismatch".to_string());
+    let long = TypeDescriptor::from_jstr(jstr("J")).unwrap();
+    let string = TypeDescriptor::from_jstr(jstr("Ljava/lang/String;")).unwrap();
+    let strings = TypeDescriptor::from_jstr(jstr("[Ljava/lang/String;")).unwrap();
+
+    // null and objects that are not boxed primitives cannot be unboxed, and floats do not narrow
+    assert_eq!(convert_value(&Support, &long, None), mismatch);
+    assert_eq!(
+        convert_value(&Support, &long, Some(&Object::String)),
+        mismatch
+    );
+    assert_eq!(
+        convert_value(
+            &Support,
+            &long,
+            Some(&Object::Boxed(PrimitiveValue::Float(1.0)))
+        ),
+        mismatch
+    );
+    assert_eq!(
+        convert_value(
+            &Support,
+            &string,
+            Some(&Object::Boxed(PrimitiveValue::Int(1)))
+        ),
+        mismatch
+    );
+    assert_eq!(
+        convert_value(&Support, &string, Some(&Object::String)),
+        Ok(ConvertedValue::Reference(Some(Object::String)))
+    );
+    // arrays are references even when the component type is not
+    assert_eq!(
+        convert_value(&Support, &strings, Some(&Object::String)),
+        mismatch
+    );
+    assert_eq!(
+        convert_value(&Support, &strings, None),
+        Ok(ConvertedValue::Reference(None))
+    );
+}
@@ -0,0 +1,873 @@
+use std::sync::Mutex;
+
+use ironjvm_cfparser::ClassFileParser;
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_libjvm::indy::MethodHandleConstant;
+use ironjvm_libjvm::methodhandle::resolve_method_handle;
+use ironjvm_libjvm::methodhandle::DirectMethodHandle;
+use ironjvm_libjvm::resolve::check_permitted_subclass;
+use ironjvm_libjvm::resolve::check_static;
+use ironjvm_libjvm::resolve::error::ResolutionError;
+use ironjvm_libjvm::resolve::error::ResolutionResult;
+use ironjvm_libjvm::resolve::nest_host;
+use ironjvm_libjvm::resolve::resolve_class;
+use ironjvm_libjvm::resolve::resolve_field;
+use ironjvm_libjvm::resolve::resolve_interface_method;
+use ironjvm_libjvm::resolve::resolve_method;
+use ironjvm_libjvm::resolve::ClassLoader;
+use ironjvm_libjvm::resolve::DirectReference;
+use ironjvm_libjvm::resolve::RuntimeConstantPool;
+use ironjvm_specimpl::classfile::attrinfo::AttributeInfoType;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::cpinfo::ReferenceKind;
+use ironjvm_specimpl::classfile::AttributeInfo;
+use ironjvm_specimpl::classfile::ClassFile;
+use ironjvm_specimpl::classfile::CpInfo;
+use ironjvm_specimpl::classfile::MethodInfo;
+
+const PACKAGE: &str = "com/github/htgazurex1212/ironjvm/tests/";
+
+// the classes compiled from Resolution.java, ResolutionSubclass.java and Sealed.java, relative
+// to PACKAGE
+const CLASSES: [&str; 14] = [
+    "Resolution",
+    "Named",
+    "Outsider",
+    "Resolution$Greeter",
+    "Resolution$Polite",
+    "Resolution$Greeting",
+    "Resolution$Nested",
+    "other/ResolutionSubclass",
+    "other/Hidden",
+    "Sealed",
+    "Sealed$Shape",
+    "Sealed$Point",
+    "Sealed$Vehicle",
+    "Sealed$Car",
+];
+
+const OBJECT: usize = 0;
+const METHOD_HANDLE: usize = 1;
+
+// loads the test classes, after stand-ins for the JDK classes they refer to
+struct Classes {
+    names: Vec<String>,
+    classfiles: Vec<ClassFile<'static>>,
+    // the names passed to load_class, in order
+    loads: Mutex<Vec<String>>,
+    // packages whose module exports them to no other package
+    unexported: Vec<String>,
+}
+
+impl Classes {
+    fn new(classes: &[&str]) -> Self {
+        let mut names = vec![
+            "java/lang/Object".to_string(),
+            "java/lang/invoke/MethodHandle".to_string(),
+            "java/lang/String".to_string(),
+            "java/lang/Record".to_string(),
+        ];
+        let mut classfiles = vec![
+            jdk_class(
+                "java/lang/Object",
+                &[(0x0001, "toString", "()Ljava/lang/String;")],
+            ),
+            jdk_class(
+                "java/lang/invoke/MethodHandle",
+                &[
+                    // public final native varargs, so signature polymorphic
+                    (
+                        0x0191,
+                        "invokeExact",
+                        "([Ljava/lang/Object;)Ljava/lang/Object;",
+                    ),
+                    (
+                        0x0110,
+                        "invokeBasic",
+                        "([Ljava/lang/Object;)Ljava/lang/Object;",
+                    ),
+                ],
+            ),
+            jdk_class("java/lang/String", &[]),
+            jdk_class("java/lang/Record", &[]),
+        ];
+
+        for class in classes {
+            let Ok(bytes) = std::fs::read(format!("../test_classes/{PACKAGE}{class}.class")) else {
+                continue;
+            };
+
+            names.push(format!("{PACKAGE}{class}"));
+            classfiles.push(
+                ClassFileParser::new(Box::leak(bytes.into_boxed_slice()))
+                    .parse()
+                    .unwrap(),
+            );
+        }
+
+        Self {
+            names,
+            classfiles,
+            loads: Mutex::new(Vec::new()),
+            unexported: Vec::new(),
+        }
+    }
+
+    fn class(&self, name: &str) -> usize {
+        self.names
+            .iter()
+            .position(|other| *other == format!("{PACKAGE}{name}"))
+            .unwrap()
+    }
+
+    fn index_of(&self, class: usize, kind: &str, name: &str) -> usize {
+        let classfile = &self.classfiles[class];
+        let members = match kind {
+            "field" => classfile
+                .fields
+                .iter()
+                .map(|field| field.name_index)
+                .collect::<Vec<_>>(),
+            _ => classfile
+                .methods
+                .iter()
+                .map(|method| method.name_index)
+                .collect(),
+        };
+
+        members
+            .into_iter()
+            .position(
+                |index| match classfile.constant_pool[index as usize - 1].info {
+                    CpInfoType::ConstantUtf8 { string, .. } => string.as_bytes() == name.as_bytes(),
+                    _ => false,
+                },
+            )
+            .unwrap()
+    }
+}
+
+impl ClassLoader for Classes {
+    type Class = usize;
+
+    fn load_class(&self, name: &JStr, _: usize) -> ResolutionResult<usize> {
+        let name = name.to_str().unwrap();
+        self.loads.lock().unwrap().push(name.to_string());
+
+        // array classes have no class file, so Object stands in for them
+        if name.starts_with('[') {
+            return Ok(OBJECT);
+        }
+
+        self.names
+            .iter()
+            .position(|other| other == name)
+            .ok_or_else(|| ResolutionError::NoClassDefFoundError {
+                class: name.to_string(),
+            })
+    }
+
+    fn classfile(&self, class: usize) -> &ClassFile<'_> {
+        &self.classfiles[class]
+    }
+
+    fn same_runtime_package(&self, a: usize, b: usize) -> bool {
+        self.names[a].rsplit_once('/').map(|(package, _)| package)
+            == self.names[b].rsplit_once('/').map(|(package, _)| package)
+    }
+
+    fn is_exported_to(&self, class: usize, _: usize) -> bool {
+        let package = self.names[class]
+            .rsplit_once('/')
+            .map(|(package, _)| package);
+        !self
+            .unexported
+            .iter()
+            .any(|unexported| Some(unexported.as_str()) == package)
+    }
+}
+
+// a class standing in for one of the JDK, declaring the given methods
+fn jdk_class(
+    name: &'static str,
+    methods: &[(u16, &'static str, &'static str)],
+) -> ClassFile<'static> {
+    let utf8 = |bytes: &'static str| CpInfo {
+        tag: 1,
+        info: CpInfoType::ConstantUtf8 {
+            length: bytes.len() as u16,
+            string: jstr(bytes),
+        },
+    };
+    let class = |name_index| CpInfo {
+        tag: 7,
+        info: CpInfoType::ConstantClass { name_index },
+    };
+
+    let mut constant_pool = vec![utf8(name), class(1), utf8("java/lang/Object"), class(3)];
+    let methods = methods
+        .iter()
+        .map(|(access_flags, name, descriptor)| {
+            constant_pool.push(utf8(name));
+            constant_pool.push(utf8(descriptor));
+
+            MethodInfo {
+                access_flags: *access_flags,
+                name_index: constant_pool.len() as u16 - 1,
+                descriptor_index: constant_pool.len() as u16,
+                attributes_count: 0,
+                attributes: vec![],
+            }
+        })
+        .collect::<Vec<_>>();
+
+    ClassFile {
+        magic: 0xCAFEBABE,
+        minor_version: 0,
+        major_version: 61,
+        constant_pool_count: constant_pool.len() as u16 + 1,
+        constant_pool,
+        access_flags: 0x0021,
+        this_class: 2,
+        super_class: if name == "java/lang/Object" { 0 } else { 4 },
+        interfaces_count: 0,
+        interfaces: &[],
+        fields_count: 0,
+        fields: vec![],
+        methods_count: methods.len() as u16,
+        methods,
+        attributes_count: 0,
+        attributes: vec![],
+    }
+}
+
+// the name of a test class relative to PACKAGE, or of a JDK class
+fn qualified(class: &str) -> String {
+    if class.starts_with("java/") {
+        class.to_string()
+    } else {
+        format!("{PACKAGE}{class}")
+    }
+}
+
+fn jstr(string: &str) -> &JStr {
+    JStr::from_jutf8(string.as_bytes()).ok().unwrap()
+}
+
+fn field(
+    classes: &Classes,
+    accessing: &str,
+    class: &str,
+    name: &str,
+    descriptor: &str,
+) -> ResolutionResult<(usize, usize)> {
+    resolve_field(
+        classes,
+        classes.class(accessing),
+        jstr(&format!("{PACKAGE}{class}")),
+        jstr(name),
+        jstr(descriptor),
+    )
+}
+
+fn method(classes: &Classes, class: &str, name: &str, descriptor: &str) -> ResolutionResult<usize> {
+    resolve_method(
+        classes,
+        classes.class("Resolution"),
+        jstr(&qualified(class)),
+        jstr(name),
+        jstr(descriptor),
+    )
+    .map(|(class, _)| class)
+}
+
+fn illegal_access(accessing: &str, declaring: &str, member: &str) -> ResolutionError {
+    ResolutionError::IllegalAccessError {
+        message: format!(
+            "class {PACKAGE}{accessing} tried to access {PACKAGE}{declaring}.{member}"
+        ),
+    }
+}
+
+#[test]
+fn field_lookup() {
+    let classes = Classes::new(&CLASSES);
+    let resolution = classes.class("Resolution");
+
+    // fields are looked up in the superinterfaces and then in the superclass
+    assert_eq!(
+        field(
+            &classes,
+            "Resolution",
+            "Resolution",
+            "NAME",
+            "Ljava/lang/String;"
+        ),
+        Ok((classes.class("Named"), 0))
+    );
+    assert_eq!(
+        field(
+            &classes,
+            "other/ResolutionSubclass",
+            "other/ResolutionSubclass",
+            "publicField",
+            "I"
+        ),
+        Ok((
+            resolution,
+            classes.index_of(resolution, "field", "publicField")
+        ))
+    );
+    assert_eq!(
+        field(&classes, "Resolution", "Resolution", "publicField", "J"),
+        Err(ResolutionError::NoSuchFieldError {
+            field: "publicField".to_string()
+        })
+    );
+}
+
+#[test]
+fn member_access() {
+    let classes = Classes::new(&CLASSES);
+
+    assert!(field(&classes, "Outsider", "Resolution", "packageField", "I").is_ok());
+    assert_eq!(
+        field(&classes, "Outsider", "Resolution", "privateField", "I"),
+        Err(illegal_access("Outsider", "Resolution", "privateField"))
+    );
+    assert_eq!(
+        field(
+            &classes,
+            "other/ResolutionSubclass",
+            "Resolution",
+            "packageField",
+            "I"
+        ),
+        Err(illegal_access(
+            "other/ResolutionSubclass",
+            "Resolution",
+            "packageField"
+        ))
+    );
+
+    // protected instance members need a reference related to the accessing class, static ones
+    // do not
+    assert!(field(
+        &classes,
+        "other/ResolutionSubclass",
+        "other/ResolutionSubclass",
+        "protectedField",
+        "I"
+    )
+    .is_ok());
+    assert!(field(
+        &classes,
+        "other/ResolutionSubclass",
+        "Resolution",
+        "protectedStaticField",
+        "I"
+    )
+    .is_ok());
+    assert!(field(&classes, "Outsider", "Resolution", "protectedField", "I").is_ok());
+}
+
+#[test]
+fn nestmates() {
+    let classes = Classes::new(&CLASSES);
+    let resolution = classes.class("Resolution");
+
+    assert_eq!(
+        nest_host(&classes, classes.class("Resolution$Nested")),
+        resolution
+    );
+    assert_eq!(nest_host(&classes, resolution), resolution);
+    assert_eq!(
+        nest_host(&classes, classes.class("Outsider")),
+        classes.class("Outsider")
+    );
+
+    assert!(field(&classes, "Resolution", "Resolution$Nested", "secret", "I").is_ok());
+    assert_eq!(
+        field(&classes, "Outsider", "Resolution$Nested", "secret", "I"),
+        Err(illegal_access("Outsider", "Resolution$Nested", "secret"))
+    );
+}
+
+#[test]
+fn class_access() {
+    let classes = Classes::new(&CLASSES);
+    let hidden = format!("{PACKAGE}other/Hidden");
+    let inaccessible = Err(ResolutionError::IllegalAccessError {
+        message: format!(
+            "class {PACKAGE}Resolution cannot access its superclass or referenced class {hidden}"
+        ),
+    });
+
+    assert_eq!(
+        resolve_class(&classes, classes.class("Resolution"), jstr(&hidden)),
+        inaccessible
+    );
+    assert_eq!(
+        resolve_class(
+            &classes,
+            classes.class("other/ResolutionSubclass"),
+            jstr(&hidden)
+        ),
+        Ok(classes.class("other/Hidden"))
+    );
+
+    // an array class is as accessible as its element type
+    assert_eq!(
+        resolve_class(
+            &classes,
+            classes.class("Resolution"),
+            jstr(&format!("[[L{hidden};"))
+        ),
+        inaccessible
+    );
+    assert_eq!(
+        resolve_class(&classes, classes.class("Resolution"), jstr("[[I")),
+        Ok(OBJECT)
+    );
+
+    assert_eq!(
+        resolve_class(
+            &classes,
+            classes.class("Resolution"),
+            jstr(&format!("{PACKAGE}Missing"))
+        ),
+        Err(ResolutionError::NoClassDefFoundError {
+            class: format!("{PACKAGE}Missing")
+        })
+    );
+}
+
+#[test]
+fn module_access() {
+    let mut classes = Classes::new(&CLASSES);
+    let subclass = format!("{PACKAGE}other/ResolutionSubclass");
+    assert!(resolve_class(&classes, classes.class("Resolution"), jstr(&subclass)).is_ok());
+
+    // a public class is inaccessible outside its package unless its module exports it
+    classes.unexported.push(format!("{PACKAGE}other"));
+    assert_eq!(
+        resolve_class(&classes, classes.class("Resolution"), jstr(&subclass)),
+        Err(ResolutionError::IllegalAccessError {
+            message: format!(
+                "class {PACKAGE}Resolution cannot access its superclass or referenced class \
+                 {subclass}"
+            ),
+        })
+    );
+    assert_eq!(
+        resolve_class(
+            &classes,
+            classes.class("other/ResolutionSubclass"),
+            jstr(&format!("{PACKAGE}other/Hidden"))
+        ),
+        Ok(classes.class("other/Hidden"))
+    );
+}
+
+fn permitted_subclasses<'a>(classfile: &'a mut ClassFile<'static>) -> &'a mut &'static [[u8; 2]] {
+    classfile
+        .attributes
+        .iter_mut()
+        .find_map(|attribute| match &mut attribute.info {
+            AttributeInfoType::PermittedSubclassesAttribute { classes, .. } => Some(classes),
+            _ => None,
+        })
+        .unwrap()
+}
+
+#[test]
+fn sealed_classes() {
+    let mut classes = Classes::new(&CLASSES);
+    for class in ["Sealed$Point", "Sealed$Vehicle", "Sealed$Car", "Resolution"] {
+        assert_eq!(
+            check_permitted_subclass(&classes, classes.class(class)),
+            Ok(())
+        );
+    }
+
+    let vehicle = classes.class("Sealed$Vehicle");
+    *permitted_subclasses(&mut classes.classfiles[vehicle]) = &[];
+    assert_eq!(
+        check_permitted_subclass(&classes, classes.class("Sealed$Car")),
+        Err(ResolutionError::IncompatibleClassChangeError {
+            message: format!(
+                "class {PACKAGE}Sealed$Car cannot inherit from sealed class \
+                 {PACKAGE}Sealed$Vehicle"
+            ),
+        })
+    );
+
+    // the attribute is ignored before Java 17
+    classes.classfiles[vehicle].major_version = 60;
+    assert_eq!(
+        check_permitted_subclass(&classes, classes.class("Sealed$Car")),
+        Ok(())
+    );
+
+    let shape = classes.class("Sealed$Shape");
+    *permitted_subclasses(&mut classes.classfiles[shape]) = &[];
+    assert_eq!(
+        check_permitted_subclass(&classes, classes.class("Sealed$Point")),
+        Err(ResolutionError::IncompatibleClassChangeError {
+            message: format!(
+                "class {PACKAGE}Sealed$Point cannot implement sealed interface \
+                 {PACKAGE}Sealed$Shape"
+            ),
+        })
+    );
+}
+
+#[test]
+fn sealed_classes_in_other_packages() {
+    let mut classes = Classes::new(&CLASSES);
+    let subclass = Box::leak(format!("{PACKAGE}other/ResolutionSubclass").into_boxed_str());
+
+    // Resolution permits ResolutionSubclass, but only classes of the same module may extend a
+    // sealed class, and unnamed modules are only shared within a package
+    let resolution = classes.class("Resolution");
+    let resolution = &mut classes.classfiles[resolution];
+    resolution.constant_pool.push(CpInfo {
+        tag: 1,
+        info: CpInfoType::ConstantUtf8 {
+            length: subclass.len() as u16,
+            string: jstr(subclass),
+        },
+    });
+    resolution.constant_pool.push(CpInfo {
+        tag: 7,
+        info: CpInfoType::ConstantClass {
+            name_index: resolution.constant_pool.len() as u16,
+        },
+    });
+    let class_index = resolution.constant_pool.len() as u16;
+    resolution.attributes.push(AttributeInfo {
+        attribute_name_index: 0,
+        attribute_length: 0,
+        info: AttributeInfoType::PermittedSubclassesAttribute {
+            number_of_classes: 1,
+            classes: Box::leak(Box::new([class_index.to_be_bytes()])),
+        },
+    });
+
+    assert_eq!(
+        check_permitted_subclass(&classes, classes.class("other/ResolutionSubclass")),
+        Err(ResolutionError::IncompatibleClassChangeError {
+            message: format!(
+                "class {subclass} cannot inherit from sealed class {PACKAGE}Resolution"
+            ),
+        })
+    );
+}
+
+#[test]
+fn method_lookup() {
+    let classes = Classes::new(&CLASSES);
+    let descriptor = "()Ljava/lang/String;";
+
+    // Polite overrides the default method of Greeter, so it is the maximally-specific one
+    assert_eq!(
+        method(&classes, "Resolution$Greeting", "greet", descriptor),
+        Ok(classes.class("Resolution$Polite"))
+    );
+    assert_eq!(
+        method(&classes, "Resolution", "toString", descriptor),
+        Ok(OBJECT)
+    );
+    assert_eq!(
+        method(&classes, "Resolution", "greet", descriptor),
+        Err(ResolutionError::NoSuchMethodError {
+            method: format!("{PACKAGE}Resolution.greet{descriptor}")
+        })
+    );
+    assert_eq!(
+        method(&classes, "Resolution$Greeter", "greet", descriptor),
+        Err(ResolutionError::IncompatibleClassChangeError {
+            message: format!("Found interface {PACKAGE}Resolution$Greeter, but class was expected")
+        })
+    );
+}
+
+#[test]
+fn interface_method_lookup() {
+    let classes = Classes::new(&CLASSES);
+    let resolution = classes.class("Resolution");
+    let interface_method = |class: &str, name: &str| {
+        resolve_interface_method(
+            &classes,
+            resolution,
+            jstr(&format!("{PACKAGE}{class}")),
+            jstr(name),
+            jstr("()Ljava/lang/String;"),
+        )
+        .map(|(class, _)| class)
+    };
+
+    assert_eq!(
+        interface_method("Resolution$Polite", "greet"),
+        Ok(classes.class("Resolution$Polite"))
+    );
+    // the public instance methods of Object are members of every interface
+    assert_eq!(
+        interface_method("Resolution$Greeter", "toString"),
+        Ok(OBJECT)
+    );
+    assert_eq!(
+        interface_method("Resolution", "toString"),
+        Err(ResolutionError::IncompatibleClassChangeError {
+            message: format!("Found class {PACKAGE}Resolution, but interface was expected")
+        })
+    );
+}
+
+#[test]
+fn static_checks() {
+    let classes = Classes::new(&CLASSES);
+    let resolution = classes.class("Resolution");
+    let static_method = DirectReference::Method {
+        class: resolution,
+        index: classes.index_of(resolution, "method", "staticMethod"),
+    };
+    let instance_field = DirectReference::Field {
+        class: resolution,
+        index: classes.index_of(resolution, "field", "publicField"),
+    };
+
+    assert_eq!(check_static(&classes, static_method, true), Ok(()));
+    assert_eq!(
+        check_static(&classes, static_method, false),
+        Err(ResolutionError::IncompatibleClassChangeError {
+            message: "Expected non-static method".to_string()
+        })
+    );
+    assert_eq!(check_static(&classes, instance_field, false), Ok(()));
+    assert_eq!(
+        check_static(&classes, instance_field, true),
+        Err(ResolutionError::IncompatibleClassChangeError {
+            message: "Expected static field".to_string()
+        })
+    );
+    assert_eq!(
+        check_static(&classes, DirectReference::Class(resolution), true),
+        Ok(())
+    );
+}
+
+#[test]
+fn failed_resolutions_are_cached() {
+    // without Resolution, the superclass constructor called by ResolutionSubclass cannot resolve
+    let classes = Classes::new(&["other/ResolutionSubclass"]);
+    let subclass = classes.class("other/ResolutionSubclass");
+    let pool = RuntimeConstantPool::new(subclass);
+
+    let index = classes.classfiles[subclass]
+        .constant_pool
+        .iter()
+        .position(|cp_info| matches!(cp_info.info, CpInfoType::ConstantMethodRef { .. }))
+        .unwrap() as u16
+        + 1;
+    let error = Err(ResolutionError::NoClassDefFoundError {
+        class: format!("{PACKAGE}Resolution"),
+    });
+
+    assert_eq!(pool.resolve(&classes, index), error);
+    assert_eq!(pool.resolve(&classes, index), error);
+    assert_eq!(classes.loads.lock().unwrap().len(), 1);
+
+    assert_eq!(
+        pool.resolve(&classes, 0),
+        Err(ResolutionError::InvalidConstantPoolEntry { index: 0 })
+    );
+
+    // only classes, fields and methods are resolved through the constant pool
+    let utf8 = classes.classfiles[subclass]
+        .constant_pool
+        .iter()
+        .position(|cp_info| matches!(cp_info.info, CpInfoType::ConstantUtf8 { .. }))
+        .unwrap() as u16
+        + 1;
+    assert_eq!(
+        pool.resolve(&classes, utf8),
+        Err(ResolutionError::InvalidConstantPoolEntry { index: utf8 })
+    );
+}
+
+#[test]
+fn signature_polymorphic_methods() {
+    let classes = Classes::new(&CLASSES);
+
+    // resolved by name alone, whatever the descriptor at the call site
+    assert_eq!(
+        method(
+            &classes,
+            "java/lang/invoke/MethodHandle",
+            "invokeExact",
+            "(I)V"
+        ),
+        Ok(METHOD_HANDLE)
+    );
+    assert_eq!(
+        method(
+            &classes,
+            "java/lang/invoke/MethodHandle",
+            "invokeBasic",
+            "(I)V"
+        ),
+        Err(ResolutionError::NoSuchMethodError {
+            method: "java/lang/invoke/MethodHandle.invokeBasic(I)V".to_string()
+        })
+    );
+}
+
+fn method_handle(
+    classes: &Classes,
+    reference_kind: ReferenceKind,
+    class: &str,
+    name: &'static str,
+    descriptor: &'static str,
+) -> ResolutionResult<DirectMethodHandle<usize>> {
+    resolve_method_handle(
+        classes,
+        classes.class("Resolution"),
+        &MethodHandleConstant {
+            reference_kind,
+            class: jstr(Box::leak(qualified(class).into_boxed_str())),
+            name: jstr(name),
+            descriptor: jstr(descriptor),
+            is_interface: class.starts_with("Resolution$"),
+        },
+    )
+}
+
+#[test]
+fn method_handles() {
+    let classes = Classes::new(&CLASSES);
+    let resolution = classes.class("Resolution");
+
+    assert_eq!(
+        method_handle(
+            &classes,
+            ReferenceKind::InvokeStatic,
+            "Resolution",
+            "staticMethod",
+            "()V"
+        ),
+        Ok(DirectMethodHandle {
+            kind: ReferenceKind::InvokeStatic,
+            reference: DirectReference::Method {
+                class: resolution,
+                index: classes.index_of(resolution, "method", "staticMethod"),
+            },
+        })
+    );
+    assert_eq!(
+        method_handle(
+            &classes,
+            ReferenceKind::PutField,
+            "Resolution",
+            "publicField",
+            "I"
+        ),
+        Ok(DirectMethodHandle {
+            kind: ReferenceKind::PutField,
+            reference: DirectReference::Field {
+                class: resolution,
+                index: classes.index_of(resolution, "field", "publicField"),
+            },
+        })
+    );
+
+    let polite = classes.class("Resolution$Polite");
+    assert_eq!(
+        method_handle(
+            &classes,
+            ReferenceKind::InvokeInterface,
+            "Resolution$Polite",
+            "greet",
+            "()Ljava/lang/String;"
+        )
+        .map(|handle| handle.reference),
+        Ok(DirectReference::Method {
+            class: polite,
+            index: classes.index_of(polite, "method", "greet"),
+        })
+    );
+}
+
+#[test]
+fn invalid_method_handles() {
+    let classes = Classes::new(&CLASSES);
+    let incompatible = |message: &str| {
+        Err(ResolutionError::IncompatibleClassChangeError {
+            message: message.to_string(),
+        })
+    };
+
+    assert_eq!(
+        method_handle(
+            &classes,
+            ReferenceKind::InvokeVirtual,
+            "Resolution",
+            "staticMethod",
+            "()V"
+        ),
+        incompatible("Expected non-static method")
+    );
+    assert_eq!(
+        method_handle(
+            &classes,
+            ReferenceKind::GetStatic,
+            "Resolution",
+            "publicField",
+            "I"
+        ),
+        incompatible("Expected static field")
+    );
+    assert_eq!(
+        method_handle(
+            &classes,
+            ReferenceKind::NewInvokeSpecial,
+            "Resolution",
+            "publicMethod",
+            "()V"
+        ),
+        incompatible("REF_newInvokeSpecial must refer to a constructor")
+    );
+}
+
+#[test]
+fn method_handle_types() {
+    let descriptor = "(Lcom/github/htgazurex1212/ironjvm/tests/Outsider;)V";
+    let classes = Classes::new(&CLASSES);
+    assert!(method_handle(
+        &classes,
+        ReferenceKind::InvokeStatic,
+        "Resolution",
+        "accept",
+        descriptor
+    )
+    .is_ok());
+
+    // the classes named by the type of the handle are resolved along with the method
+    let without_outsider = CLASSES
+        .into_iter()
+        .filter(|class| *class != "Outsider")
+        .collect::<Vec<_>>();
+    let classes = Classes::new(&without_outsider);
+    assert_eq!(
+        method_handle(
+            &classes,
+            ReferenceKind::InvokeStatic,
+            "Resolution",
+            "accept",
+            descriptor
+        ),
+        Err(ResolutionError::NoClassDefFoundError {
+            class: format!("{PACKAGE}Outsider")
+        })
+    );
+}
@@ -0,0 +1,202 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Barrier;
+use std::sync::Weak;
+use std::thread;
+
+use ironjvm_javautil::jstr::JStr;
+use ironjvm_javautil::jstr::JString;
+use ironjvm_libjvm::stringtable::resolve_string_constant;
+use ironjvm_libjvm::stringtable::StringTable;
+use ironjvm_specimpl::classfile::cpinfo::CpInfoType;
+use ironjvm_specimpl::classfile::CpInfo;
+
+// a stand-in for java.lang.String, freed once the last Arc is dropped
+type Table = StringTable<Weak<JString>>;
+
+fn jstr(string: &str) -> &JStr {
+    JStr::from_jutf8(string.as_bytes()).ok().unwrap()
+}
+
+fn string(contents: &str) -> Arc<JString> {
+    Arc::new(JString::from(contents))
+}
+
+#[test]
+fn identity() {
+    let table = Table::new();
+    let hello = table.intern(jstr("hello"), || string("hello"));
+
+    // interning the same contents again returns the same string without creating one
+    let again = table.intern(jstr("hello"), || unreachable!());
+    assert!(Arc::ptr_eq(&hello, &again));
+    assert!(Arc::ptr_eq(&table.lookup(jstr("hello")).unwrap(), &hello));
+
+    // String.intern of an equal but distinct string returns the interned one instead
+    let receiver = string("hello");
+    let interned = table.intern(jstr("hello"), || receiver.clone());
+    assert!(Arc::ptr_eq(&interned, &hello));
+    assert!(!Arc::ptr_eq(&interned, &receiver));
+
+    // contents are compared as modified UTF-8, so NUL and a lone surrogate are ordinary keys
+    let odd = JStr::from_jutf8(b"\xC0\x80\xED\xA0\x80").ok().unwrap();
+    let interned = table.intern(odd, || Arc::new(odd.to_owned()));
+    assert!(Arc::ptr_eq(
+        &table.intern(odd, || unreachable!()),
+        &interned
+    ));
+    assert!(table.lookup(jstr("hello!")).is_none());
+}
+
+#[test]
+fn dead_entries() {
+    let table = Table::new();
+    let kept = table.intern(jstr("kept"), || string("kept"));
+    drop(table.intern(jstr("dropped"), || string("dropped")));
+
+    // the entry of the collected string stays until the table is purged
+    assert!(table.lookup(jstr("dropped")).is_none());
+    let statistics = table.statistics();
+    assert_eq!((statistics.entries, statistics.dead_entries), (2, 1));
+    assert_eq!(statistics.literal_bytes, "kept".len());
+
+    assert_eq!(table.purge(), 1);
+    assert_eq!(table.purge(), 0);
+    let statistics = table.statistics();
+    assert_eq!((statistics.entries, statistics.dead_entries), (1, 0));
+    assert!(Arc::ptr_eq(&table.lookup(jstr("kept")).unwrap(), &kept));
+
+    // a dead entry is replaced by the next string interned for the same contents
+    drop(table.intern(jstr("again"), || string("again")));
+    let again = table.intern(jstr("again"), || string("again"));
+    assert!(Arc::ptr_eq(&table.lookup(jstr("again")).unwrap(), &again));
+    assert_eq!(table.statistics().entries, 2);
+}
+
+#[test]
+fn collection_while_creating() {
+    let table = Table::new();
+    drop(table.intern(jstr("garbage"), || string("garbage")));
+
+    // creating a string may trigger a collection, which purges the table
+    let created = table.intern(jstr("garbage"), || {
+        assert_eq!(table.purge(), 1);
+        string("garbage")
+    });
+    assert!(Arc::ptr_eq(
+        &table.lookup(jstr("garbage")).unwrap(),
+        &created
+    ));
+}
+
+#[test]
+fn concurrent_interning() {
+    const THREADS: usize = 8;
+    const STRINGS: usize = 200;
+
+    let table = Table::new();
+    let created = AtomicUsize::new(0);
+    let barrier = Barrier::new(THREADS);
+
+    let interned = thread::scope(|scope| {
+        let threads = (0..THREADS)
+            .map(|_| {
+                scope.spawn(|| {
+                    barrier.wait();
+                    (0..STRINGS)
+                        .map(|index| {
+                            let contents = index.to_string();
+                            table.intern(jstr(&contents), || {
+                                created.fetch_add(1, Ordering::Relaxed);
+                                string(&contents)
+                            })
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        threads
+            .into_iter()
+            .map(|thread| thread.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    // threads may race to create a string, but all of them get the one that was interned
+    for index in 0..STRINGS {
+        let first = &interned[0][index];
+        assert_eq!(first.as_bytes(), index.to_string().as_bytes());
+        assert!(interned
+            .iter()
+            .all(|strings| Arc::ptr_eq(&strings[index], first)));
+    }
+
+    let created = created.into_inner();
+    assert!((STRINGS..=STRINGS * THREADS).contains(&created));
+    assert_eq!(table.statistics().entries, STRINGS);
+    assert_eq!(table.purge(), 0);
+}
+
+#[test]
+fn string_constants() {
+    let constant_pool = [
+        CpInfo {
+            tag: 1,
+            info: CpInfoType::ConstantUtf8 {
+                length: 5,
+                string: jstr("hello"),
+            },
+        },
+        CpInfo {
+            tag: 8,
+            info: CpInfoType::ConstantString { string_index: 1 },
+        },
+    ];
+    let table = Table::new();
+
+    let hello = resolve_string_constant(&table, &constant_pool, 2, |contents| {
+        Arc::new(contents.to_owned())
+    })
+    .unwrap();
+    assert_eq!(hello.as_bytes(), b"hello");
+
+    // ldc of the same constant resolves to the same string, which String.intern also finds
+    let again = resolve_string_constant(&table, &constant_pool, 2, |_| unreachable!());
+    assert!(Arc::ptr_eq(&again.unwrap(), &hello));
+    assert!(Arc::ptr_eq(&table.lookup(jstr("hello")).unwrap(), &hello));
+
+    // only ConstantString entries resolve
+    for index in [0, 1, 3] {
+        assert!(
+            resolve_string_constant(&table, &constant_pool, index, |_| unreachable!()).is_none()
+        );
+    }
+}
+
+#[test]
+fn statistics() {
+    let table = Table::new();
+    let strings =
+        ["a", "bc", "def"].map(|contents| table.intern(jstr(contents), || string(contents)));
+    drop(table.intern(jstr("dead"), || string("dead")));
+
+    let statistics = table.statistics();
+    assert_eq!(statistics.entries, 4);
+    assert_eq!(statistics.dead_entries, 1);
+    assert_eq!(statistics.literal_bytes, 6);
+    assert!((1..=4).contains(&statistics.max_shard_entries));
+    assert_eq!(
+        statistics.to_string(),
+        format!(
+            "StringTable statistics:\n\
+            Number of shards        : {:>9}\n\
+            Number of entries       :         4\n\
+            Number of dead entries  :         1\n\
+            Total literal bytes     :         6\n\
+            Maximum shard size      : {:>9}",
+            statistics.shards, statistics.max_shard_entries
+        )
+    );
+    drop(strings);
+}
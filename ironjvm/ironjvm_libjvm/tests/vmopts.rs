@@ -0,0 +1,294 @@
+use std::env;
+use std::ffi::CString;
+use std::path::PathBuf;
+use std::ptr;
+
+use ironjvm_libjvm::jniinv::JNI_CreateJavaVM;
+use ironjvm_libjvm::vmopts::error::VmOptionsError;
+use ironjvm_libjvm::vmopts::error::VmOptionsResult;
+use ironjvm_libjvm::vmopts::VmOptions;
+use ironjvm_libjvm::vmopts::MAX_OBJECT_AGE;
+use ironjvm_libjvm::vmopts::MAX_THREAD_STACK_SIZE;
+use jni_sys::jboolean;
+use jni_sys::jint;
+use jni_sys::JavaVMInitArgs;
+use jni_sys::JavaVMOption;
+use jni_sys::JNI_EINVAL;
+use jni_sys::JNI_ERR;
+use jni_sys::JNI_FALSE;
+use jni_sys::JNI_OK;
+use jni_sys::JNI_TRUE;
+use jni_sys::JNI_VERSION_10;
+
+// calls `f` with init args holding the options, as the launcher passes them
+fn with_init_args<T>(
+    options: &[&str],
+    ignore_unrecognized: jboolean,
+    f: impl FnOnce(*mut JavaVMInitArgs) -> T,
+) -> T {
+    let strings = options
+        .iter()
+        .map(|option| CString::new(*option).unwrap())
+        .collect::<Vec<_>>();
+    let mut options = strings
+        .iter()
+        .map(|string| JavaVMOption {
+            optionString: string.as_ptr() as *mut _,
+            extraInfo: ptr::null_mut(),
+        })
+        .collect::<Vec<_>>();
+    let mut args = JavaVMInitArgs {
+        version: JNI_VERSION_10,
+        nOptions: options.len() as jint,
+        options: options.as_mut_ptr(),
+        ignoreUnrecognized: ignore_unrecognized,
+    };
+
+    f(&mut args)
+}
+
+fn parse(options: &[&str], ignore_unrecognized: jboolean) -> VmOptionsResult<VmOptions> {
+    with_init_args(options, ignore_unrecognized, |args| unsafe {
+        VmOptions::from_init_args(args)
+    })
+}
+
+fn create_java_vm(options: &[&str], ignore_unrecognized: jboolean) -> jint {
+    with_init_args(options, ignore_unrecognized, |args| {
+        JNI_CreateJavaVM(ptr::null_mut(), ptr::null_mut(), args.cast())
+    })
+}
+
+fn new_size(value: &str) -> VmOptionsResult<Option<usize>> {
+    let mut options = VmOptions::default();
+    options.parse_option(&format!("-Xmn{value}"))?;
+    Ok(options.new_size)
+}
+
+#[test]
+fn size_units() {
+    assert_eq!(new_size("4096").unwrap(), Some(4096));
+    assert_eq!(new_size("64k").unwrap(), Some(64 << 10));
+    assert_eq!(new_size("64K").unwrap(), Some(64 << 10));
+    assert_eq!(new_size("16m").unwrap(), Some(16 << 20));
+    assert_eq!(new_size("2G").unwrap(), Some(2 << 30));
+    assert_eq!(new_size("1t").unwrap(), Some(1 << 40));
+    assert_eq!(new_size("0").unwrap(), Some(0));
+
+    let mut options = VmOptions::default();
+    options.parse_option("-XX:NewSize=8m").unwrap();
+    assert_eq!(options.new_size, Some(8 << 20));
+}
+
+#[test]
+fn malformed_sizes() {
+    for value in [
+        "",
+        "m",
+        "-1",
+        "1.5m",
+        "12q",
+        "0x10",
+        " 1m",
+        "18446744073709551616",
+    ] {
+        assert!(
+            matches!(new_size(value), Err(VmOptionsError::InvalidValue { .. })),
+            "{value:?}"
+        );
+    }
+
+    // sizes that overflow once the unit is applied
+    assert!(new_size("18446744073709551615k").is_err());
+    assert!(new_size("17179869184g").is_err());
+}
+
+#[test]
+fn tenuring_thresholds() {
+    let options = parse(&[], JNI_FALSE).unwrap();
+    assert_eq!(options.initial_tenuring_threshold, 7);
+    assert_eq!(options.max_tenuring_threshold, MAX_OBJECT_AGE);
+
+    let options = parse(&["-XX:InitialTenuringThreshold=0"], JNI_FALSE).unwrap();
+    assert_eq!(options.initial_tenuring_threshold, 0);
+
+    let options = parse(&["-XX:MaxTenuringThreshold=15"], JNI_FALSE).unwrap();
+    assert_eq!(options.max_tenuring_threshold, 15);
+
+    // the object age has 4 bits
+    assert!(matches!(
+        parse(&["-XX:MaxTenuringThreshold=16"], JNI_FALSE),
+        Err(VmOptionsError::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        parse(&["-XX:InitialTenuringThreshold=-1"], JNI_FALSE),
+        Err(VmOptionsError::InvalidValue { .. })
+    ));
+}
+
+#[test]
+fn defaulted_initial_tenuring_threshold() {
+    let options = parse(&["-XX:MaxTenuringThreshold=3"], JNI_FALSE).unwrap();
+    assert_eq!(options.initial_tenuring_threshold, 3);
+    assert_eq!(options.max_tenuring_threshold, 3);
+
+    assert!(matches!(
+        parse(
+            &[
+                "-XX:InitialTenuringThreshold=7",
+                "-XX:MaxTenuringThreshold=3"
+            ],
+            JNI_FALSE
+        ),
+        Err(VmOptionsError::InitialTenuringThresholdExceedsMax { initial: 7, max: 3 })
+    ));
+}
+
+#[test]
+fn launcher_options() {
+    let options = parse(
+        &[
+            "-Djava.class.path=.",
+            "-Dsun.java.launcher=SUN_STANDARD",
+            "-Dempty",
+            "exit",
+            "abort",
+            "vfprintf",
+            "-Xms16m",
+            "-Xmx1g",
+        ],
+        JNI_FALSE,
+    )
+    .unwrap();
+
+    assert_eq!(options.system_properties["java.class.path"], ".");
+    assert_eq!(
+        options.system_properties["sun.java.launcher"],
+        "SUN_STANDARD"
+    );
+    assert_eq!(options.system_properties["empty"], "");
+    assert_eq!(options.ignored_options, ["-Xms16m", "-Xmx1g"]);
+
+    assert!(matches!(
+        parse(&["-Xmxlots"], JNI_FALSE),
+        Err(VmOptionsError::InvalidValue { .. })
+    ));
+}
+
+#[test]
+fn module_path() {
+    let module_path = env::join_paths(["mods", "lib/mods"]).unwrap();
+    let option = format!("-Djdk.module.path={}", module_path.to_str().unwrap());
+    let options = parse(&[&option], JNI_FALSE).unwrap();
+
+    assert_eq!(
+        options.module_path,
+        [PathBuf::from("mods"), PathBuf::from("lib/mods")]
+    );
+    assert_eq!(
+        options.system_properties["jdk.module.path"],
+        module_path.to_str().unwrap()
+    );
+    assert!(parse(&[], JNI_FALSE).unwrap().module_path.is_empty());
+}
+
+#[test]
+fn unrecognized_options() {
+    assert!(matches!(
+        parse(&["-Xfoo"], JNI_FALSE),
+        Err(VmOptionsError::UnrecognizedOption { option }) if option == "-Xfoo"
+    ));
+
+    // only -X and _ options may be ignored
+    let options = parse(&["-Xfoo", "-XX:+Bar", "_baz"], JNI_TRUE).unwrap();
+    assert_eq!(options.ignored_options, ["-Xfoo", "-XX:+Bar", "_baz"]);
+    assert!(matches!(
+        parse(&["--foo"], JNI_TRUE),
+        Err(VmOptionsError::UnrecognizedOption { .. })
+    ));
+
+    // ignoreUnrecognized does not cover malformed values of recognized options
+    assert!(matches!(
+        parse(&["-Xmnfoo"], JNI_TRUE),
+        Err(VmOptionsError::InvalidValue { .. })
+    ));
+}
+
+#[test]
+fn create_java_vm_return_codes() {
+    assert_eq!(create_java_vm(&["-Xmn1m", "-Dfoo=bar"], JNI_FALSE), JNI_OK);
+    assert_eq!(create_java_vm(&["-Xfoo"], JNI_FALSE), JNI_ERR);
+    assert_eq!(create_java_vm(&["-Xfoo"], JNI_TRUE), JNI_OK);
+    assert_eq!(create_java_vm(&["-Xmnfoo"], JNI_TRUE), JNI_EINVAL);
+    assert_eq!(
+        JNI_CreateJavaVM(ptr::null_mut(), ptr::null_mut(), ptr::null_mut()),
+        JNI_EINVAL
+    );
+
+    let args = JavaVMInitArgs {
+        version: JNI_VERSION_10,
+        nOptions: 1,
+        options: ptr::null_mut(),
+        ignoreUnrecognized: JNI_FALSE,
+    };
+    assert!(matches!(
+        unsafe { VmOptions::from_init_args(&args) },
+        Err(VmOptionsError::NullInitArgs)
+    ));
+}
+
+fn thread_stack_size(value: &str) -> VmOptionsResult<Option<usize>> {
+    Ok(parse(&[&format!("-Xss{value}")], JNI_FALSE)?.thread_stack_size)
+}
+
+#[test]
+fn thread_stack_sizes() {
+    assert_eq!(parse(&[], JNI_FALSE).unwrap().thread_stack_size, None);
+    assert_eq!(thread_stack_size("0").unwrap(), Some(0));
+    assert_eq!(thread_stack_size("65536").unwrap(), Some(65536));
+    assert_eq!(thread_stack_size("512k").unwrap(), Some(512 << 10));
+    assert_eq!(thread_stack_size("512K").unwrap(), Some(512 << 10));
+    assert_eq!(thread_stack_size("2m").unwrap(), Some(2 << 20));
+    assert_eq!(thread_stack_size("2M").unwrap(), Some(2 << 20));
+    assert_eq!(
+        thread_stack_size("1g").unwrap(),
+        Some(MAX_THREAD_STACK_SIZE)
+    );
+    assert_eq!(
+        thread_stack_size("1G").unwrap(),
+        Some(MAX_THREAD_STACK_SIZE)
+    );
+}
+
+#[test]
+fn thread_stack_size_bounds() {
+    assert_eq!(thread_stack_size("1024m").unwrap(), Some(1 << 30));
+    assert_eq!(thread_stack_size("1048576k").unwrap(), Some(1 << 30));
+
+    for value in ["1025m", "1048577k", "1073741825", "2g", "1t"] {
+        assert!(
+            matches!(
+                thread_stack_size(value),
+                Err(VmOptionsError::ThreadStackSizeOutOfRange { .. })
+            ),
+            "{value}"
+        );
+    }
+}
+
+#[test]
+fn malformed_thread_stack_sizes() {
+    for value in ["", "k", "-1m", "1.5m", "1mb", "one", "99999999999999999999"] {
+        assert!(
+            matches!(
+                thread_stack_size(value),
+                Err(VmOptionsError::InvalidValue { .. })
+            ),
+            "{value:?}"
+        );
+    }
+
+    assert_eq!(create_java_vm(&["-Xss1m"], JNI_FALSE), JNI_OK);
+    assert_eq!(create_java_vm(&["-Xss2g"], JNI_FALSE), JNI_EINVAL);
+    assert_eq!(create_java_vm(&["-Xssfoo"], JNI_TRUE), JNI_EINVAL);
+}
@@ -25,3 +25,362 @@ pub struct CodeAttributeExceptionTableEntry {
     pub handler_pc: u16,
     pub catch_type: u16,
 }
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Instruction {
+    Nop,
+    AConstNull,
+    IConstM1,
+    IConst0,
+    IConst1,
+    IConst2,
+    IConst3,
+    IConst4,
+    IConst5,
+    LConst0,
+    LConst1,
+    FConst0,
+    FConst1,
+    FConst2,
+    DConst0,
+    DConst1,
+    BIPush {
+        value: i8,
+    },
+    SIPush {
+        value: i16,
+    },
+    Ldc {
+        index: u8,
+    },
+    LdcW {
+        index: u16,
+    },
+    Ldc2W {
+        index: u16,
+    },
+    ILoad {
+        index: u8,
+    },
+    LLoad {
+        index: u8,
+    },
+    FLoad {
+        index: u8,
+    },
+    DLoad {
+        index: u8,
+    },
+    ALoad {
+        index: u8,
+    },
+    ILoad0,
+    ILoad1,
+    ILoad2,
+    ILoad3,
+    LLoad0,
+    LLoad1,
+    LLoad2,
+    LLoad3,
+    FLoad0,
+    FLoad1,
+    FLoad2,
+    FLoad3,
+    DLoad0,
+    DLoad1,
+    DLoad2,
+    DLoad3,
+    ALoad0,
+    ALoad1,
+    ALoad2,
+    ALoad3,
+    IALoad,
+    LALoad,
+    FALoad,
+    DALoad,
+    AALoad,
+    BALoad,
+    CALoad,
+    SALoad,
+    IStore {
+        index: u8,
+    },
+    LStore {
+        index: u8,
+    },
+    FStore {
+        index: u8,
+    },
+    DStore {
+        index: u8,
+    },
+    AStore {
+        index: u8,
+    },
+    IStore0,
+    IStore1,
+    IStore2,
+    IStore3,
+    LStore0,
+    LStore1,
+    LStore2,
+    LStore3,
+    FStore0,
+    FStore1,
+    FStore2,
+    FStore3,
+    DStore0,
+    DStore1,
+    DStore2,
+    DStore3,
+    AStore0,
+    AStore1,
+    AStore2,
+    AStore3,
+    IAStore,
+    LAStore,
+    FAStore,
+    DAStore,
+    AAStore,
+    BAStore,
+    CAStore,
+    SAStore,
+    Pop,
+    Pop2,
+    Dup,
+    DupX1,
+    DupX2,
+    Dup2,
+    Dup2X1,
+    Dup2X2,
+    Swap,
+    IAdd,
+    LAdd,
+    FAdd,
+    DAdd,
+    ISub,
+    LSub,
+    FSub,
+    DSub,
+    IMul,
+    LMul,
+    FMul,
+    DMul,
+    IDiv,
+    LDiv,
+    FDiv,
+    DDiv,
+    IRem,
+    LRem,
+    FRem,
+    DRem,
+    INeg,
+    LNeg,
+    FNeg,
+    DNeg,
+    IShl,
+    LShl,
+    IShr,
+    LShr,
+    IUShr,
+    LUShr,
+    IAnd,
+    LAnd,
+    IOr,
+    LOr,
+    IXor,
+    LXor,
+    IInc {
+        index: u8,
+        value: i8,
+    },
+    I2L,
+    I2F,
+    I2D,
+    L2I,
+    L2F,
+    L2D,
+    F2I,
+    F2L,
+    F2D,
+    D2I,
+    D2L,
+    D2F,
+    I2B,
+    I2C,
+    I2S,
+    LCmp,
+    FCmpL,
+    FCmpG,
+    DCmpL,
+    DCmpG,
+    IfEq {
+        offset: i16,
+    },
+    IfNe {
+        offset: i16,
+    },
+    IfLt {
+        offset: i16,
+    },
+    IfGe {
+        offset: i16,
+    },
+    IfGt {
+        offset: i16,
+    },
+    IfLe {
+        offset: i16,
+    },
+    IfICmpEq {
+        offset: i16,
+    },
+    IfICmpNe {
+        offset: i16,
+    },
+    IfICmpLt {
+        offset: i16,
+    },
+    IfICmpGe {
+        offset: i16,
+    },
+    IfICmpGt {
+        offset: i16,
+    },
+    IfICmpLe {
+        offset: i16,
+    },
+    IfACmpEq {
+        offset: i16,
+    },
+    IfACmpNe {
+        offset: i16,
+    },
+    Goto {
+        offset: i16,
+    },
+    Jsr {
+        offset: i16,
+    },
+    Ret {
+        index: u8,
+    },
+    TableSwitch {
+        default: i32,
+        low: i32,
+        high: i32,
+        jump_offsets: Vec<i32>,
+    },
+    LookupSwitch {
+        default: i32,
+        npairs: i32,
+        match_offset_pairs: Vec<(i32, i32)>,
+    },
+    IReturn,
+    LReturn,
+    FReturn,
+    DReturn,
+    AReturn,
+    Return,
+    GetStatic {
+        index: u16,
+    },
+    PutStatic {
+        index: u16,
+    },
+    GetField {
+        index: u16,
+    },
+    PutField {
+        index: u16,
+    },
+    InvokeVirtual {
+        index: u16,
+    },
+    InvokeSpecial {
+        index: u16,
+    },
+    InvokeStatic {
+        index: u16,
+    },
+    InvokeInterface {
+        index: u16,
+        count: u8,
+    },
+    InvokeDynamic {
+        index: u16,
+    },
+    New {
+        index: u16,
+    },
+    NewArray {
+        atype: u8,
+    },
+    ANewArray {
+        index: u16,
+    },
+    ArrayLength,
+    AThrow,
+    CheckCast {
+        index: u16,
+    },
+    InstanceOf {
+        index: u16,
+    },
+    MonitorEnter,
+    MonitorExit,
+    MultiANewArray {
+        index: u16,
+        dimensions: u8,
+    },
+    IfNull {
+        offset: i16,
+    },
+    IfNonNull {
+        offset: i16,
+    },
+    GotoW {
+        offset: i32,
+    },
+    JsrW {
+        offset: i32,
+    },
+    // wide variants
+    ILoadW {
+        index: u16,
+    },
+    LLoadW {
+        index: u16,
+    },
+    FLoadW {
+        index: u16,
+    },
+    DLoadW {
+        index: u16,
+    },
+    ALoadW {
+        index: u16,
+    },
+    IStoreW {
+        index: u16,
+    },
+    LStoreW {
+        index: u16,
+    },
+    FStoreW {
+        index: u16,
+    },
+    DStoreW {
+        index: u16,
+    },
+    AStoreW {
+        index: u16,
+    },
+    IIncW {
+        index: u16,
+        value: i16,
+    },
+    RetW {
+        index: u16,
+    },
+}
@@ -97,11 +97,11 @@ pub enum AttributeInfoType<'clazz> {
         annotations: Vec<rvanriaattr::Annotation>,
     },
     RuntimeVisibleParameterAnnotationsAttribute {
-        num_parameters: u16,
+        num_parameters: u8,
         parameter_annotations: Vec<rvpaattr::ParameterAnnotation>,
     },
     RuntimeInvisibleParameterAnnotationsAttribute {
-        num_parameters: u16,
+        num_parameters: u8,
         parameter_annotations: Vec<rvpaattr::ParameterAnnotation>,
     },
     RuntimeVisibleTypeAnnotationsAttribute {
@@ -160,4 +160,8 @@ pub enum AttributeInfoType<'clazz> {
         number_of_classes: u16,
         classes: &'clazz [[u8; 2]],
     },
+    // attributes not defined by the specification, which are kept as they are
+    UnknownAttribute {
+        info: &'clazz [u8],
+    },
 }
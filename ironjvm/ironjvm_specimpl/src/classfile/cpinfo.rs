@@ -18,12 +18,16 @@
  * 51 Franklin Street, Fifth Floor, Boston, MA 02110-1301 USA.
  */
 
+use ironjvm_javautil::jstr::JStr;
+
 #[derive(Clone, Debug)]
 #[repr(u8)]
 pub enum CpInfoType<'clazz> {
+    // the entry following a long or double constant, which takes up two indices
+    ConstantUnusable = 0,
     ConstantUtf8 {
         length: u16,
-        bytes: &'clazz [u8],
+        string: &'clazz JStr,
     } = 1,
     ConstantInteger {
         bytes: u32,
@@ -83,3 +87,34 @@ pub enum CpInfoType<'clazz> {
         name_index: u16,
     } = 20,
 }
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum ReferenceKind {
+    GetField = 1,
+    GetStatic = 2,
+    PutField = 3,
+    PutStatic = 4,
+    InvokeVirtual = 5,
+    InvokeStatic = 6,
+    InvokeSpecial = 7,
+    NewInvokeSpecial = 8,
+    InvokeInterface = 9,
+}
+
+impl ReferenceKind {
+    pub fn from_u8(reference_kind: u8) -> Option<Self> {
+        Some(match reference_kind {
+            1 => Self::GetField,
+            2 => Self::GetStatic,
+            3 => Self::PutField,
+            4 => Self::PutStatic,
+            5 => Self::InvokeVirtual,
+            6 => Self::InvokeStatic,
+            7 => Self::InvokeSpecial,
+            8 => Self::NewInvokeSpecial,
+            9 => Self::InvokeInterface,
+            _ => return None,
+        })
+    }
+}
@@ -118,11 +118,12 @@ impl ModuleRequireFlags {
 }
 
 pub trait FlagsExt {
+    /// Returns whether every bit of `flag` is set, so `A | B` tests for both flags.
     fn flag_set(self, flag: Self) -> bool;
 }
 
 impl FlagsExt for u16 {
     fn flag_set(self, flag: Self) -> bool {
-        self & flag != 0
+        self & flag == flag
     }
 }
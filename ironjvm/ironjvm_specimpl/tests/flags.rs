@@ -0,0 +1,36 @@
+use ironjvm_specimpl::classfile::flags::ClassAccessFlags;
+use ironjvm_specimpl::classfile::flags::FieldAccessFlags;
+use ironjvm_specimpl::classfile::flags::FlagsExt;
+use ironjvm_specimpl::classfile::flags::MethodAccessFlags;
+
+#[test]
+fn single_flag() {
+    let access_flags = ClassAccessFlags::ACC_PUBLIC | ClassAccessFlags::ACC_SUPER;
+
+    assert!(access_flags.flag_set(ClassAccessFlags::ACC_PUBLIC));
+    assert!(access_flags.flag_set(ClassAccessFlags::ACC_SUPER));
+    assert!(!access_flags.flag_set(ClassAccessFlags::ACC_FINAL));
+}
+
+#[test]
+fn every_flag_of_a_mask() {
+    let public = MethodAccessFlags::ACC_PUBLIC;
+    assert!(!public.flag_set(MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_PRIVATE));
+    assert!(!public.flag_set(MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_ABSTRACT));
+
+    let public_abstract = MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_ABSTRACT;
+    assert!(
+        public_abstract.flag_set(MethodAccessFlags::ACC_PUBLIC | MethodAccessFlags::ACC_ABSTRACT)
+    );
+
+    let constant =
+        FieldAccessFlags::ACC_PUBLIC | FieldAccessFlags::ACC_STATIC | FieldAccessFlags::ACC_FINAL;
+    assert!(constant.flag_set(FieldAccessFlags::ACC_STATIC | FieldAccessFlags::ACC_FINAL));
+    assert!(!constant.flag_set(FieldAccessFlags::ACC_FINAL | FieldAccessFlags::ACC_VOLATILE));
+}
+
+#[test]
+fn empty_mask() {
+    assert!(0u16.flag_set(0));
+    assert!(ClassAccessFlags::ACC_PUBLIC.flag_set(0));
+}
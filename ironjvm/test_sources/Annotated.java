@@ -0,0 +1,44 @@
+package com.github.htgazurex1212.ironjvm.tests;
+
+import java.lang.annotation.ElementType;
+import java.lang.annotation.Retention;
+import java.lang.annotation.RetentionPolicy;
+import java.util.List;
+import java.util.concurrent.Callable;
+
+// annotations of both retentions on generic members that declare exceptions
+@Annotated.Visible(name = "class", values = {1, 2}, kind = ElementType.TYPE)
+public class Annotated<T extends Comparable<T>> implements Callable<List<T>> {
+    @Retention(RetentionPolicy.RUNTIME)
+    @interface Visible {
+        String name() default "visible";
+
+        int[] values() default {};
+
+        ElementType kind() default ElementType.FIELD;
+
+        Class<?> type() default void.class;
+    }
+
+    @Retention(RetentionPolicy.CLASS)
+    @interface Invisible {}
+
+    @Visible public static final long ANSWER = 42;
+
+    @Deprecated protected List<T> elements;
+
+    private Annotated() {}
+
+    public Annotated(List<T> elements) throws IllegalArgumentException {
+        this.elements = elements;
+    }
+
+    @Visible(type = String.class)
+    public List<T> call() throws Exception {
+        return elements;
+    }
+
+    static int sum(@Visible int a, @Invisible short b, long c) {
+        return a + b + (int) c;
+    }
+}
@@ -0,0 +1,14 @@
+package com.github.htgazurex1212.ironjvm.tests;
+
+import java.util.function.Supplier;
+
+// the invokedynamic call sites javac emits for lambdas and for string concatenation
+public class Bootstraps {
+    static Supplier<String> lambda() {
+        return () -> "lambda";
+    }
+
+    static String concat(String string, int number) {
+        return "string " + string + " and number " + number;
+    }
+}
@@ -0,0 +1,42 @@
+package com.github.htgazurex1212.ironjvm.tests;
+
+public class Branches {
+    static int run(int n) {
+        int total = 0;
+
+        try {
+            for (int i = 0; i < n; i++) {
+                if (i % 3 == 0) {
+                    total += 100 / (n - i - 1);
+                } else {
+                    total--;
+                }
+            }
+
+            switch (n) {
+                case 1: total += 10; break;
+                case 2: total += 20; break;
+                case 3: total += 30; break;
+                default: total++;
+            }
+
+            switch (n * 1000) {
+                case 5000: total += 5; break;
+                case 7000: total += 7; break;
+                default: total += new StringBuilder(n > 4 ? "big" : "small").length();
+            }
+        } catch (ArithmeticException e) {
+            total = -total;
+        } finally {
+            total *= 2;
+        }
+
+        return total;
+    }
+
+    public static void main(String[] args) {
+        for (int n = 0; n < 10; n++) {
+            System.out.println(run(n));
+        }
+    }
+}
@@ -0,0 +1,104 @@
+package com.github.htgazurex1212.ironjvm.tests;
+
+import java.util.List;
+import java.util.function.IntSupplier;
+
+public class Frames {
+    record Point(int x, int y) {
+        Point {
+            if (x < 0 || y < 0) {
+                throw new IllegalArgumentException("negative");
+            }
+        }
+
+        Point(int both) {
+            this(both > 0 ? both : -both, 0);
+        }
+    }
+
+    static int tryFinally(int n) {
+        int result = 0;
+
+        try {
+            for (int i = 0; i < n; i++) {
+                try {
+                    result += 10 / (i - 2);
+                } finally {
+                    result++;
+                }
+            }
+        } catch (ArithmeticException e) {
+            result = -result;
+        } finally {
+            result *= 3;
+        }
+
+        return result;
+    }
+
+    static String lookupSwitch(int n) {
+        switch (n) {
+            case -100: return "minus hundred";
+            case 7: return "seven";
+            case 1000: return "thousand";
+            case 123456: return "big";
+            default: return "other";
+        }
+    }
+
+    static String stringSwitch(String s) {
+        return switch (s) {
+            case "a", "b" -> "ab";
+            case "c" -> "c";
+            default -> "?";
+        };
+    }
+
+    static int lambdas(List<Integer> list, int bias) {
+        IntSupplier supplier = () -> list.stream().mapToInt(x -> x + bias).sum();
+        Runnable nothing = () -> {};
+        nothing.run();
+
+        return supplier.getAsInt();
+    }
+
+    static Number merge(boolean flag, long wide, double precise) {
+        Number number = flag ? Integer.valueOf((int) wide) : Long.valueOf(wide);
+        Object object = number;
+        if (precise > 1.5) {
+            object = "string";
+        }
+
+        return object instanceof Number n ? n : Double.valueOf(precise);
+    }
+
+    static String uninitialized(int n) {
+        return new StringBuilder(n > 0 ? "positive" : "negative").append(n).toString();
+    }
+
+    public static void main(String[] args) {
+        System.out.println(new Point(-4));
+        try {
+            new Point(-1, 2);
+        } catch (IllegalArgumentException e) {
+            System.out.println(e.getMessage());
+        }
+
+        for (int n = -1; n < 6; n++) {
+            System.out.println(tryFinally(n));
+        }
+        for (int n : new int[] { -100, 7, 1000, 123456, 5 }) {
+            System.out.println(lookupSwitch(n));
+        }
+        for (String s : new String[] { "a", "b", "c", "d" }) {
+            System.out.println(stringSwitch(s));
+        }
+
+        System.out.println(lambdas(List.of(1, 2, 3), 4));
+        System.out.println(merge(true, 3, 1.0));
+        System.out.println(merge(false, 3, 2.0));
+        System.out.println(merge(false, 3, 1.0));
+        System.out.println(uninitialized(1));
+        System.out.println(uninitialized(-1));
+    }
+}
@@ -0,0 +1,98 @@
+package com.github.htgazurex1212.ironjvm.tests;
+
+import java.util.List;
+
+public class NullPointers {
+    StringBuilder builder;
+    NullPointers next;
+    int[] ints;
+    Object[] objects;
+    String string;
+    static NullPointers instance;
+
+    interface Callback {
+        void run(StringBuilder builder, String string, Object object, List<String> list);
+    }
+
+    static String getfieldChain(NullPointers pointers) {
+        return pointers.next.next.string;
+    }
+
+    static Object aaload(NullPointers pointers) {
+        return pointers.objects[0];
+    }
+
+    static void iastore(NullPointers pointers) {
+        pointers.next.ints[1] = 2;
+    }
+
+    static int arraylength(NullPointers pointers) {
+        return pointers.ints.length;
+    }
+
+    static void monitorenter(NullPointers pointers) {
+        synchronized (pointers.string) {
+            pointers.next = null;
+        }
+    }
+
+    static void invokevirtual(NullPointers pointers) {
+        pointers.builder.append("x");
+    }
+
+    static String invokeString(String string) {
+        return string.concat("x");
+    }
+
+    static void invokeinterface(Callback callback) {
+        callback.run(null, null, null, null);
+    }
+
+    static int listSize(List<String> list) {
+        return list.size();
+    }
+
+    static int unboxing(Integer integer) {
+        return integer;
+    }
+
+    static void athrow() {
+        throw null;
+    }
+
+    static boolean getstatic() {
+        return instance.string.isEmpty();
+    }
+
+    static void putfieldLocal() {
+        NullPointers pointers = instance;
+        pointers.next = null;
+    }
+
+    // prints the messages HotSpot computes for each of the methods above
+    public static void main(String[] args) {
+        NullPointers pointers = new NullPointers();
+
+        for (int i = 0; i < 13; i++) {
+            try {
+                switch (i) {
+                    case 0: getfieldChain(pointers); break;
+                    case 1: aaload(pointers); break;
+                    case 2: iastore(pointers); break;
+                    case 3: arraylength(pointers); break;
+                    case 4: monitorenter(pointers); break;
+                    case 5: invokevirtual(pointers); break;
+                    case 6: invokeString(null); break;
+                    case 7: invokeinterface(null); break;
+                    case 8: listSize(null); break;
+                    case 9: unboxing(null); break;
+                    case 10: athrow(); break;
+                    case 11: getstatic(); break;
+                    default: putfieldLocal(); break;
+                }
+            } catch (NullPointerException e) {
+                System.out.println(e.getMessage());
+            }
+        }
+    }
+}
@@ -0,0 +1,47 @@
+package com.github.htgazurex1212.ironjvm.tests;
+
+// members of every access level, resolved from this package, from a nestmate and from a subclass
+// in another package
+public class Resolution implements Named {
+    public int publicField;
+    protected int protectedField;
+    int packageField;
+    private int privateField;
+    protected static int protectedStaticField;
+
+    public void publicMethod() {}
+
+    protected void protectedMethod() {}
+
+    void packageMethod() {}
+
+    private void privateMethod() {}
+
+    static void staticMethod() {}
+
+    static void accept(Outsider outsider) {}
+
+    interface Greeter {
+        default String greet() {
+            return "hello";
+        }
+    }
+
+    interface Polite extends Greeter {
+        default String greet() {
+            return "good day";
+        }
+    }
+
+    static class Greeting implements Greeter, Polite {}
+
+    static class Nested {
+        private int secret;
+    }
+}
+
+interface Named {
+    String NAME = "resolution";
+}
+
+class Outsider {}
@@ -0,0 +1,7 @@
+package com.github.htgazurex1212.ironjvm.tests.other;
+
+import com.github.htgazurex1212.ironjvm.tests.Resolution;
+
+public class ResolutionSubclass extends Resolution {}
+
+class Hidden {}
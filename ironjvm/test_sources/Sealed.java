@@ -0,0 +1,12 @@
+package com.github.htgazurex1212.ironjvm.tests;
+
+// a sealed hierarchy and a record, nested in Sealed so that they are its nestmates
+public class Sealed {
+    sealed interface Shape permits Point, Vehicle {}
+
+    record Point(int x, int y) implements Shape {}
+
+    static sealed class Vehicle implements Shape permits Car {}
+
+    static final class Car extends Vehicle {}
+}